@@ -0,0 +1,31 @@
+#[cfg(feature = "header")]
+extern crate cbindgen;
+
+fn main() {
+    #[cfg(feature = "header")]
+    generate_header();
+}
+
+/// Regenerates `include/indy_crypto.h` from `ErrorCode` and `#[no_mangle]` FFI functions.
+/// Header is committed, so FFI consumers don't need cbindgen; run `cargo build --features header`
+/// after changing FFI signatures and commit the result.
+#[cfg(feature = "header")]
+fn generate_header() {
+    use std::env;
+    use std::path::PathBuf;
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("Unable to read cbindgen.toml");
+
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src").join("lib.rs"))
+        .generate()
+        .expect("Unable to generate C header")
+        .write_to_file(crate_dir.join("include").join("indy_crypto.h"));
+
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src");
+}
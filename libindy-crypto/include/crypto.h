@@ -2,5 +2,6 @@
 #define crypto__included__
 
 #include "crypto_error.h"
+#include "crypto_cl.h"
 
 #endif
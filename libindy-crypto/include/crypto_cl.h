@@ -1,15 +1,7 @@
 #ifndef crypto__cl__included__
 #define crypto__cl__included__
 
-#ifdef __cplusplus
-extern "C" {
-#endif
-
-    /// TODO: FIXME: Provide list of interfaces for ffi/cl/mod.rs
+// Declarations of `cl_*` functions are generated from ffi/cl/*.rs signatures.
+#include "indy_crypto.h"
 
-#ifdef __cplusplus
-}
 #endif
-
-#endif
-
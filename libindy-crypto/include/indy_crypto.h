@@ -0,0 +1,1169 @@
+#ifndef indy_crypto__included__
+#define indy_crypto__included__
+
+// Warning: this file is generated by cbindgen from FFI functions signatures, don't modify it manually.
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+enum ErrorCode
+#ifdef __cplusplus
+  : uintptr_t
+#endif // __cplusplus
+ {
+  Success = 0,
+  CommonInvalidParam1 = 100,
+  CommonInvalidParam2 = 101,
+  CommonInvalidParam3 = 102,
+  CommonInvalidParam4 = 103,
+  CommonInvalidParam5 = 104,
+  CommonInvalidParam6 = 105,
+  CommonInvalidParam7 = 106,
+  CommonInvalidParam8 = 107,
+  CommonInvalidParam9 = 108,
+  CommonInvalidParam10 = 109,
+  CommonInvalidParam11 = 110,
+  CommonInvalidParam12 = 111,
+  CommonInvalidState = 112,
+  CommonInvalidStructure = 113,
+  CommonIOError = 114,
+  AnoncredsRevocationAccumulatorIsFull = 115,
+  AnoncredsInvalidRevocationAccumulatorIndex = 116,
+  AnoncredsCredentialRevoked = 117,
+  AnoncredsProofRejected = 118,
+  AnoncredsPredicateFailed = 119,
+  CommonOperationCancelled = 120,
+  AnoncredsCredentialSignatureInvalid = 121,
+  AnoncredsPredicateNotSatisfied = 122,
+};
+#ifndef __cplusplus
+typedef uintptr_t ErrorCode;
+#endif // __cplusplus
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Deallocates string returned by library (for example result of any `cl_*_to_json` or `cl_*_get_*_json` call).
+//
+// Note: Strings returned by library are allocated by Rust allocator so they must be released
+// by this function instead of `free` of caller's runtime.
+//
+// # Arguments
+// * `s` - Reference that contains string pointer returned by library.
+ErrorCode cl_free_string(const char *s);
+
+// Creates and returns credential schema entity builder.
+//
+// The purpose of credential schema builder is building of credential schema entity that
+// represents credential schema attributes set.
+//
+// Note: Credential schema builder instance deallocation must be performed by
+// calling cl_credential_schema_builder_finalize.
+//
+// # Arguments
+// * `credential_schema_builder_p` - Reference that will contain credentials attributes builder instance pointer.
+ErrorCode cl_credential_schema_builder_new(const void **credential_schema_builder_p);
+
+// Adds new attribute to credential schema.
+//
+// # Arguments
+// * `credential_schema_builder` - Reference that contains credential schema builder instance pointer.
+// * `attr` - Attribute to add as null terminated string.
+ErrorCode cl_credential_schema_builder_add_attr(const void *credential_schema_builder,
+                                                const char *attr);
+
+// Adds new attribute to credential schema.
+// Unlike cl_credential_schema_builder_add_attr returns error if attribute was already added.
+//
+// # Arguments
+// * `credential_schema_builder` - Reference that contains credential schema builder instance pointer.
+// * `attr` - Attribute to add as null terminated string.
+ErrorCode cl_credential_schema_builder_add_attr_checked(const void *credential_schema_builder,
+                                                        const char *attr);
+
+// Deallocates credential schema builder and returns credential schema entity instead.
+//
+// Note: Credentials schema instance deallocation must be performed by
+// calling cl_credential_schema_free.
+//
+// # Arguments
+// * `credential_schema_builder` - Reference that contains credential schema builder instance pointer
+// * `credential_schema_p` - Reference that will contain credentials schema instance pointer.
+ErrorCode cl_credential_schema_builder_finalize(const void *credential_schema_builder,
+                                                const void **credential_schema_p);
+
+// Creates and returns credential schema with attributes from json array of attribute names.
+//
+// Note that credential schema deallocation must be performed by
+// calling cl_credential_schema_free.
+//
+// # Arguments
+// * `attrs_json` - Reference that contains json array of attribute names, for example `["name","age"]`.
+// * `credential_schema_p` - Reference that will contain credentials schema instance pointer.
+ErrorCode cl_credential_schema_from_json_attrs(const char *attrs_json,
+                                               const void **credential_schema_p);
+
+// Deallocates credential schema instance.
+//
+// # Arguments
+// * `credential_schema` - Reference that contains credential schema instance pointer.
+ErrorCode cl_credential_schema_free(const void *credential_schema);
+
+// Creates and returns non credential schema builder.
+//
+// The purpose of non credential schema builder is building of non credential schema that
+// represents non credential schema attributes set. These are attributes added to schemas that are not on the ledger
+//
+// Note: Non credential schema builder instance deallocation must be performed by
+// calling cl_non_credential_schema_builder_finalize.
+//
+// # Arguments
+// * `credential_schema_builder_p` - Reference that will contain credentials attributes builder instance pointer.
+ErrorCode cl_non_credential_schema_builder_new(const void **non_credential_schema_builder_p);
+
+// Adds new attribute to non credential schema.
+//
+// # Arguments
+// * `non_credential_schema_builder` - Reference that contains non credential schema builder instance pointer.
+// * `attr` - Attribute to add as null terminated string.
+ErrorCode cl_non_credential_schema_builder_add_attr(const void *non_credential_schema_builder,
+                                                    const char *attr);
+
+// Deallocates non_credential schema builder and returns non credential schema entity instead.
+//
+// Note: Non credential schema instance deallocation must be performed by
+// calling cl_non_credential_schema_free.
+//
+// # Arguments
+// * `non_credential_schema_builder` - Reference that contains non credential schema builder instance pointer
+// * `non_credential_schema_p` - Reference that will contain non credentials schema instance pointer.
+ErrorCode cl_non_credential_schema_builder_finalize(const void *non_credential_schema_builder,
+                                                    const void **non_credential_schema_p);
+
+// Deallocates credential schema instance.
+//
+// # Arguments
+// * `non_credential_schema` - Reference that contains non credential schema instance pointer.
+ErrorCode cl_non_credential_schema_free(const void *non_credential_schema);
+
+// Creates and returns credentials values entity builder.
+//
+// The purpose of credential values builder is building of credential values entity that
+// represents credential attributes values map.
+//
+// Note: Credentials values builder instance deallocation must be performed by
+// calling cl_credential_values_builder_finalize.
+//
+// # Arguments
+// * `credential_values_builder_p` - Reference that will contain credentials values builder instance pointer.
+ErrorCode cl_credential_values_builder_new(const void **credential_values_builder_p);
+
+// Adds new known attribute dec_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `dec_value` - Credential attr dec_value. Decimal BigNum representation as null terminated string.
+ErrorCode cl_credential_values_builder_add_dec_known(const void *credential_values_builder,
+                                                     const char *attr,
+                                                     const char *dec_value);
+
+// Adds new hidden attribute dec_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `dec_value` - Credential attr dec_value. Decimal BigNum representation as null terminated string.
+ErrorCode cl_credential_values_builder_add_dec_hidden(const void *credential_values_builder,
+                                                      const char *attr,
+                                                      const char *dec_value);
+
+// Adds new hidden attribute dec_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `dec_value` - Credential attr dec_value. Decimal BigNum representation as null terminated string.
+// * `dec_blinding_factor` - Credential blinding factor. Decimal BigNum representation as null terminated string
+ErrorCode cl_credential_values_builder_add_dec_commitment(const void *credential_values_builder,
+                                                          const char *attr,
+                                                          const char *dec_value,
+                                                          const char *dec_blinding_factor);
+
+// Adds new known attribute hex_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `hex_value` - Credential attr hex_value. Big-endian hex BigNum representation (even number of digits) as null terminated string.
+ErrorCode cl_credential_values_builder_add_hex_known(const void *credential_values_builder,
+                                                     const char *attr,
+                                                     const char *hex_value);
+
+// Adds new hidden attribute hex_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `hex_value` - Credential attr hex_value. Big-endian hex BigNum representation (even number of digits) as null terminated string.
+ErrorCode cl_credential_values_builder_add_hex_hidden(const void *credential_values_builder,
+                                                      const char *attr,
+                                                      const char *hex_value);
+
+// Adds new committed attribute hex_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `hex_value` - Credential attr hex_value. Big-endian hex BigNum representation (even number of digits) as null terminated string.
+// * `hex_blinding_factor` - Credential blinding factor. Big-endian hex BigNum representation as null terminated string
+ErrorCode cl_credential_values_builder_add_hex_commitment(const void *credential_values_builder,
+                                                          const char *attr,
+                                                          const char *hex_value,
+                                                          const char *hex_blinding_factor);
+
+// Adds new known attribute base58_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `base58_value` - Credential attr base58_value. BigNum representation in base58 (Bitcoin alphabet) as null terminated string.
+ErrorCode cl_credential_values_builder_add_base58_known(const void *credential_values_builder,
+                                                        const char *attr,
+                                                        const char *base58_value);
+
+// Adds new hidden attribute base58_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `base58_value` - Credential attr base58_value. BigNum representation in base58 (Bitcoin alphabet) as null terminated string.
+ErrorCode cl_credential_values_builder_add_base58_hidden(const void *credential_values_builder,
+                                                         const char *attr,
+                                                         const char *base58_value);
+
+// Adds new committed attribute base58_value to credential values map.
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+// * `base58_value` - Credential attr base58_value. BigNum representation in base58 (Bitcoin alphabet) as null terminated string.
+// * `base58_blinding_factor` - Credential blinding factor. BigNum representation in base58 (Bitcoin alphabet) as null terminated string
+ErrorCode cl_credential_values_builder_add_base58_commitment(const void *credential_values_builder,
+                                                             const char *attr,
+                                                             const char *base58_value,
+                                                             const char *base58_blinding_factor);
+
+// Deallocates credential values builder and returns credential values entity instead.
+//
+// Note: Credentials values instance deallocation must be performed by
+// calling cl_credential_values_free.
+//
+// Returns CommonInvalidStructure if no values were added (builder is deallocated in this case too).
+//
+// # Arguments
+// * `credential_values_builder` - Reference that contains credential attribute builder instance pointer.
+// * `credential_values_p` - Reference that will contain credentials values instance pointer.
+ErrorCode cl_credential_values_builder_finalize(const void *credential_values_builder,
+                                                const void **credential_values_p);
+
+// Deallocates credential values instance.
+//
+// # Arguments
+// * `credential_values` - Credential values instance pointer
+ErrorCode cl_credential_values_free(const void *credential_values);
+
+// Creates and returns sub proof request entity builder.
+//
+// The purpose of sub proof request builder is building of sub proof request entity that
+// represents requested attributes and predicates.
+//
+// Note: sub proof request builder instance deallocation must be performed by
+// calling cl_sub_proof_request_builder_finalize.
+//
+// # Arguments
+// * `sub_proof_request_builder_p` - Reference that will contain sub proof request builder instance pointer.
+ErrorCode cl_sub_proof_request_builder_new(const void **sub_proof_request_builder_p);
+
+// Adds new revealed attribute to sub proof request.
+//
+// # Arguments
+// * `sub_proof_request_builder` - Reference that contains sub proof request builder instance pointer.
+// * `attr` - Credential attr to add as null terminated string.
+ErrorCode cl_sub_proof_request_builder_add_revealed_attr(const void *sub_proof_request_builder,
+                                                         const char *attr);
+
+// Adds predicate to sub proof request.
+//
+// # Arguments
+// * `sub_proof_request_builder` - Reference that contains sub proof request builder instance pointer.
+// * `attr_name` - Related attribute
+// * `p_type` - Predicate type (`GE`, `LE`, `GT`, `LT` or `EQ`).
+// * `value` - Requested value.
+ErrorCode cl_sub_proof_request_builder_add_predicate(const void *sub_proof_request_builder,
+                                                     const char *attr_name,
+                                                     const char *p_type,
+                                                     int32_t value);
+
+// Deallocates sub proof request builder and returns sub proof request entity instead.
+//
+// Note: Sub proof request instance deallocation must be performed by
+// calling cl_sub_proof_request_free.
+//
+// # Arguments
+// * `sub_proof_request_builder` - Reference that contains sub proof request builder instance pointer.
+// * `sub_proof_request_p` - Reference that will contain sub proof request instance pointer.
+ErrorCode cl_sub_proof_request_builder_finalize(const void *sub_proof_request_builder,
+                                                const void **sub_proof_request_p);
+
+// Deallocates sub proof request instance.
+//
+// # Arguments
+// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+ErrorCode cl_sub_proof_request_free(const void *sub_proof_request);
+
+// Returns json with names of credential attributes split into `revealed`, `predicated` and `hidden`
+// according to the sub proof request.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+// * `credential_values` - Reference that contains credential values instance pointer.
+// * `partition_json_p` - Reference that will contain partition json.
+ErrorCode cl_sub_proof_request_partition_json(const void *sub_proof_request,
+                                              const void *credential_values,
+                                              const char **partition_json_p);
+
+// Returns json array of attribute names requested to be revealed by sub proof request.
+// Names are sorted, so json is the same for equal requests.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+// * `revealed_attrs_json_p` - Reference that will contain revealed attributes json.
+ErrorCode cl_sub_proof_request_get_revealed_attrs_json(const void *sub_proof_request,
+                                                       const char **revealed_attrs_json_p);
+
+// Returns json array of predicates of sub proof request: `[{"attr_name": string, "p_type": "GE"|"LE"|"GT"|"LT"|"EQ", "value": int}]`.
+// Predicates are sorted, so json is the same for equal requests.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+// * `predicates_json_p` - Reference that will contain predicates json.
+ErrorCode cl_sub_proof_request_get_predicates_json(const void *sub_proof_request,
+                                                   const char **predicates_json_p);
+
+// Returns json representation of sub proof request.
+// Attributes and predicates are ordered, so json is the same for equal requests.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+// * `sub_proof_request_json_p` - Reference that will contain sub proof request json.
+ErrorCode cl_sub_proof_request_to_json(const void *sub_proof_request,
+                                       const char **sub_proof_request_json_p);
+
+// Creates and returns sub proof request from json.
+//
+// Note: Sub proof request instance deallocation must be performed by calling cl_sub_proof_request_free.
+//
+// # Arguments
+// * `sub_proof_request_json` - Reference that contains sub proof request json.
+// * `sub_proof_request_p` - Reference that will contain sub proof request instance pointer.
+ErrorCode cl_sub_proof_request_from_json(const char *sub_proof_request_json,
+                                         const void **sub_proof_request_p);
+
+// Creates random nonce.
+//
+// Note that nonce deallocation must be performed by calling cl_nonce_free.
+//
+// # Arguments
+// * `nonce_p` - Reference that will contain nonce instance pointer.
+ErrorCode cl_new_nonce(const void **nonce_p);
+
+// Returns json representation of nonce.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `nonce` - Reference that contains nonce instance pointer.
+// * `nonce_json_p` - Reference that will contain nonce json.
+ErrorCode cl_nonce_to_json(const void *nonce,
+                           const char **nonce_json_p);
+
+// Creates and returns nonce json.
+//
+// Note: Nonce instance deallocation must be performed by calling cl_nonce_free.
+//
+// Returns CommonInvalidStructure for zero nonce or nonce shorter than 64 bits.
+//
+// # Arguments
+// * `nonce_json` - Reference that contains nonce json.
+// * `nonce_p` - Reference that will contain nonce instance pointer.
+ErrorCode cl_nonce_from_json(const char *nonce_json,
+                             const void **nonce_p);
+
+// Deallocates nonce instance.
+//
+// # Arguments
+// * `nonce` - Reference that contains nonce instance pointer.
+ErrorCode cl_nonce_free(const void *nonce);
+
+// Returns count of sub proofs contained in proof.
+//
+// # Arguments
+// * `proof` - Reference that contains proof instance pointer.
+// * `count_p` - Reference that will contain count of sub proofs.
+ErrorCode cl_proof_get_sub_proof_count(const void *proof,
+                                       uintptr_t *count_p);
+
+// Returns json with predicates proved by inequality proofs of each sub proof (in order of sub proofs)
+// as array of arrays of `{"attr_name": .., "p_type": .., "value": ..}` objects.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `proof` - Reference that contains proof instance pointer.
+// * `predicates_json_p` - Reference that will contain predicates json.
+ErrorCode cl_proof_get_predicates_json(const void *proof,
+                                       const char **predicates_json_p);
+
+// Returns SHA-256 digest (hex encoded) of canonical serialization of the proof and the nonce.
+// Compact record of exactly what was verified.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `proof` - Reference that contains proof instance pointer.
+// * `nonce` - Reference that contains nonce instance pointer.
+// * `transcript_digest_p` - Reference that will contain hex encoded digest.
+ErrorCode cl_proof_get_transcript_digest(const void *proof,
+                                         const void *nonce,
+                                         const char **transcript_digest_p);
+
+// Returns json with revealed attributes of each sub proof (in order of sub proofs)
+// as array of objects mapping attribute name to value.
+// Values found in encoding table are replaced by original ones, others are returned as decimal strings.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `proof` - Reference that contains proof instance pointer.
+// * `encoding_table_json` - Reference that contains json object mapping encoded decimal value to original one.
+// * `revealed_attrs_json_p` - Reference that will contain revealed attributes json.
+ErrorCode cl_proof_decode_revealed_attrs(const void *proof,
+                                         const char *encoding_table_json,
+                                         const char **revealed_attrs_json_p);
+
+// Creates and returns credential definition (public and private keys, correctness proof) entities.
+//
+// Note that credential public key instances deallocation must be performed by
+// calling cl_credential_public_key_free.
+//
+// Note that credential private key instances deallocation must be performed by
+// calling cl_credential_private_key_free.
+//
+// Note that credential key correctness proof instances deallocation must be performed by
+// calling cl_credential_key_correctness_proof_free.
+//
+// # Arguments
+// * `credential_schema` - Reference that contains credential schema instance pointer.
+// * `non_credential_schema` - Reference that contains non credential schema instance pointer
+// * `credential_pub_key_p` - Reference that will contain credential public key instance pointer.
+// * `credential_priv_key_p` - Reference that will contain credential private key instance pointer.
+// * `credential_key_correctness_proof_p` - Reference that will contain credential keys correctness proof instance pointer.
+ErrorCode cl_issuer_new_credential_def(const void *credential_schema,
+                                       const void *non_credential_schema,
+                                       const void **credential_pub_key_p,
+                                       const void **credential_priv_key_p,
+                                       const void **credential_key_correctness_proof_p);
+
+// Creates credential definition and returns its public part (public key and key correctness proof)
+// as a single json bundle `{"credential_pub_key": {..}, "credential_key_correctness_proof": {..}}`.
+//
+// Credential private key is returned as a separate json to avoid storing it together with public data.
+//
+// Note: Returned credential definition bundle json must be deallocated by calling cl_free_string.
+// Credential private key json contains secret values so it must be deallocated
+// by calling cl_free_secret_str that wipes memory before releasing it.
+//
+// # Arguments
+// * `credential_schema` - Reference that contains credential schema instance pointer.
+// * `non_credential_schema` - Reference that contains non credential schema instance pointer
+// * `credential_def_bundle_json_p` - Reference that will contain credential public key and key correctness proof json.
+// * `credential_priv_key_json_p` - Reference that will contain credential private key json.
+ErrorCode cl_issuer_new_credential_def_bundle(const void *credential_schema,
+                                              const void *non_credential_schema,
+                                              const char **credential_def_bundle_json_p,
+                                              const char **credential_priv_key_json_p);
+
+// Returns json representation of credential public key.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+// * `credential_pub_key_p` - Reference that will contain credential public key json.
+ErrorCode cl_credential_public_key_to_json(const void *credential_pub_key,
+                                           const char **credential_pub_key_json_p);
+
+// Creates and returns credential public key from json.
+//
+// Note: Credential public key instance deallocation must be performed
+// by calling cl_credential_public_key_free
+//
+// # Arguments
+// * `credential_pub_key_json` - Reference that contains credential public key json.
+// * `credential_pub_key_p` - Reference that will contain credential public key instance pointer.
+ErrorCode cl_credential_public_key_from_json(const char *credential_pub_key_json,
+                                             const void **credential_pub_key_p);
+
+// Sets issuer label (for example schema id) of credential public key.
+// The tag is carried by json serialization of the key and isn't used by cryptographic operations.
+//
+// # Arguments
+// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+// * `tag` - Tag as null terminated string (optional, null removes the tag).
+ErrorCode cl_credential_public_key_set_tag(const void *credential_pub_key,
+                                           const char *tag);
+
+// Returns issuer label (for example schema id) of credential public key.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+// * `tag_p` - Reference that will contain tag as null terminated string (null if the key isn't tagged).
+ErrorCode cl_credential_public_key_get_tag(const void *credential_pub_key,
+                                           const char **tag_p);
+
+// Deallocates credential public key instance.
+//
+// # Arguments
+// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+ErrorCode cl_credential_public_key_free(const void *credential_pub_key);
+
+// Creates and returns credential primary public key from json.
+//
+// Legacy json with `rms` field is also supported.
+//
+// Note: Credential primary public key instance deallocation must be performed
+// by calling cl_credential_primary_public_key_free
+//
+// # Arguments
+// * `credential_primary_pub_key_json` - Reference that contains credential primary public key json.
+// * `credential_primary_pub_key_p` - Reference that will contain credential primary public key instance pointer.
+ErrorCode cl_credential_primary_public_key_from_json(const char *credential_primary_pub_key_json,
+                                                     const void **credential_primary_pub_key_p);
+
+// Deallocates credential primary public key instance.
+//
+// # Arguments
+// * `credential_primary_pub_key` - Reference that contains credential primary public key instance pointer.
+ErrorCode cl_credential_primary_public_key_free(const void *credential_primary_pub_key);
+
+// Returns decimal representation of RSA modulus `n` of credential primary public key.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `credential_primary_pub_key` - Reference that contains credential primary public key instance pointer.
+// * `n_p` - Reference that will contain decimal string of `n`.
+ErrorCode cl_credential_primary_public_key_get_n(const void *credential_primary_pub_key,
+                                                 const char **n_p);
+
+// Creates and returns credential public key from credential primary public key.
+//
+// Note: Credential public key instance deallocation must be performed
+// by calling cl_credential_public_key_free
+//
+// # Arguments
+// * `credential_primary_pub_key` - Reference that contains credential primary public key instance pointer.
+// * `credential_pub_key_p` - Reference that will contain credential public key instance pointer.
+ErrorCode cl_credential_public_key_build_from_parts(const void *credential_primary_pub_key,
+                                                    const void **credential_pub_key_p);
+
+// Returns json representation of credential private key.
+//
+// Note: Credential private key json contains secret values so it must be deallocated
+// by calling cl_free_secret_str that wipes memory before releasing it.
+//
+// # Arguments
+// * `credential_priv_key` - Reference that contains credential private key instance pointer.
+// * `credential_pub_key_p` - Reference that will contain credential private key json.
+ErrorCode cl_credential_private_key_to_json(const void *credential_priv_key,
+                                            const char **credential_priv_key_json_p);
+
+// Creates and returns credential private key from json.
+//
+// Note: Credential private key instance deallocation must be performed
+// by calling cl_credential_private_key_free
+//
+// # Arguments
+// * `credential_priv_key_json` - Reference that contains credential private key json.
+// * `credential_priv_key_p` - Reference that will contain credential private key instance pointer.
+ErrorCode cl_credential_private_key_from_json(const char *credential_priv_key_json,
+                                              const void **credential_priv_key_p);
+
+// Deallocates credential private key instance.
+//
+// # Arguments
+// * `credential_priv_key` - Reference that contains credential private key instance pointer.
+ErrorCode cl_credential_private_key_free(const void *credential_priv_key);
+
+// Returns json representation of credential key correctness proof.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `credential_key_correctness_proof` - Reference that contains credential key correctness proof instance pointer.
+// * `credential_key_correctness_proof_p` - Reference that will contain credential key correctness proof json.
+ErrorCode cl_credential_key_correctness_proof_to_json(const void *credential_key_correctness_proof,
+                                                      const char **credential_key_correctness_proof_json_p);
+
+// Creates and returns credential key correctness proof from json.
+//
+// Note: Credential key correctness proof instance deallocation must be performed
+// by calling cl_credential_key_correctness_proof_free
+//
+// # Arguments
+// * `credential_key_correctness_proof_json` - Reference that contains credential key correctness proof json.
+// * `credential_key_correctness_proof_p` - Reference that will contain credential key correctness proof instance pointer.
+ErrorCode cl_credential_key_correctness_proof_from_json(const char *credential_key_correctness_proof_json,
+                                                        const void **credential_key_correctness_proof_p);
+
+// Deallocates credential key correctness proof instance.
+//
+// # Arguments
+// * `credential_key_correctness_proof` - Reference that contains credential key correctness proof instance pointer.
+ErrorCode cl_credential_key_correctness_proof_free(const void *credential_key_correctness_proof);
+
+// Signs credential values with primary keys only.
+//
+// Note that credential signature instances deallocation must be performed by
+// calling cl_credential_signature_free.
+//
+// Note that credential signature correctness proof instances deallocation must be performed by
+// calling cl_signature_correctness_proof_free.
+//
+// # Arguments
+// * `prover_id` - Prover identifier.
+// * `blinded_credential_secrets` - Blinded master secret instance pointer generated by Prover.
+// * `blinded_credential_secrets_correctness_proof` - Blinded master secret correctness proof instance pointer.
+// * `credential_nonce` - Nonce instance pointer used for verification of blinded_credential_secrets_correctness_proof.
+// * `credential_issuance_nonce` - Nonce instance pointer used for creation of signature_correctness_proof.
+// * `credential_values` - Credential values to be signed instance pointer.
+// * `credential_pub_key` - Credential public key instance pointer.
+// * `credential_priv_key` - Credential private key instance pointer.
+// * `credential_signature_p` - Reference that will contain credential signature instance pointer.
+// * `credential_signature_correctness_proof_p` - Reference that will contain credential signature correctness proof instance pointer.
+ErrorCode cl_issuer_sign_credential(const char *prover_id,
+                                    const void *blinded_credential_secrets,
+                                    const void *blinded_credential_secrets_correctness_proof,
+                                    const void *credential_nonce,
+                                    const void *credential_issuance_nonce,
+                                    const void *credential_values,
+                                    const void *credential_pub_key,
+                                    const void *credential_priv_key,
+                                    const void **credential_signature_p,
+                                    const void **credential_signature_correctness_proof_p);
+
+// Returns json representation of credential signature.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `credential_signature` - Reference that contains credential signature pointer.
+// * `credential_signature_json_p` - Reference that will contain credential signature json.
+ErrorCode cl_credential_signature_to_json(const void *credential_signature,
+                                          const char **credential_signature_json_p);
+
+// Creates and returns credential signature from json.
+//
+// Note: Credential signature instance deallocation must be performed
+// by calling cl_credential_signature_free
+//
+// # Arguments
+// * `credential_signature_json` - Reference that contains credential signature json.
+// * `credential_signature_p` - Reference that will contain credential signature instance pointer.
+ErrorCode cl_credential_signature_from_json(const char *credential_signature_json,
+                                            const void **credential_signature_p);
+
+// Deallocates credential signature signature instance.
+//
+// # Arguments
+// * `credential_signature` - Reference that contains credential signature instance pointer.
+ErrorCode cl_credential_signature_free(const void *credential_signature);
+
+// Returns deterministic non-secret identifier of credential.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `credential_signature` - Reference that contains credential signature instance pointer.
+// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+// * `credential_id_p` - Reference that will contain credential identifier.
+ErrorCode cl_credential_signature_get_credential_id(const void *credential_signature,
+                                                    const void *credential_pub_key,
+                                                    const char **credential_id_p);
+
+// Returns json representation of signature correctness proof.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `signature_correctness_proof` - Reference that contains signature correctness proof instance pointer.
+// * `signature_correctness_proof_json_p` - Reference that will contain signature correctness proof json.
+ErrorCode cl_signature_correctness_proof_to_json(const void *signature_correctness_proof,
+                                                 const char **signature_correctness_proof_json_p);
+
+// Creates and returns signature correctness proof from json.
+//
+// Note: Signature correctness proof instance deallocation must be performed
+// by calling cl_signature_correctness_proof_free
+//
+// # Arguments
+// * `signature_correctness_proof_json` - Reference that contains signature correctness proof json.
+// * `signature_correctness_proof_p` - Reference that will contain signature correctness proof instance pointer.
+ErrorCode cl_signature_correctness_proof_from_json(const char *signature_correctness_proof_json,
+                                                   const void **signature_correctness_proof_p);
+
+// Deallocates signature correctness proof instance.
+//
+// # Arguments
+// * `signature_correctness_proof` - Reference that contains signature correctness proof instance pointer.
+ErrorCode cl_signature_correctness_proof_free(const void *signature_correctness_proof);
+
+// Creates a master secret.
+//
+// Note that master secret deallocation must be performed by
+// calling cl_master_secret_free.
+//
+// # Arguments
+// * `master_secret_p` - Reference that will contain master secret instance pointer.
+ErrorCode cl_prover_new_master_secret(const void **master_secret_p);
+
+// Returns json representation of master secret.
+//
+// Note: Master secret json contains secret value so it must be deallocated
+// by calling cl_free_secret_str that wipes memory before releasing it.
+//
+// # Arguments
+// * `master_secret` - Reference that contains master secret instance pointer.
+// * `master_secret_json_p` - Reference that will contain master secret json.
+ErrorCode cl_master_secret_to_json(const void *master_secret,
+                                   const char **master_secret_json_p);
+
+// Creates and returns master secret from json.
+//
+// Note: Master secret instance deallocation must be performed
+// by calling cl_master_secret_free.
+//
+// # Arguments
+// * `master_secret_json` - Reference that contains master secret json.
+// * `master_secret_p` - Reference that will contain master secret instance pointer.
+ErrorCode cl_master_secret_from_json(const char *master_secret_json,
+                                     const void **master_secret_p);
+
+// Deallocates master secret instance.
+//
+// # Arguments
+// * `master_secret` - Reference that contains master secret instance pointer.
+ErrorCode cl_master_secret_free(const void *master_secret);
+
+// Wipes and deallocates string that contains secret data (for example master secret json).
+//
+// # Arguments
+// * `secret_str` - Reference that contains string pointer returned by library.
+ErrorCode cl_free_secret_str(const char *secret_str);
+
+// Creates blinded credential secrets for given issuer key and master secret.
+//
+// Note that blinded credential secrets deallocation must be performed by
+// calling cl_blinded_credential_secrets_free.
+//
+// Note that credential secrets blinding factors deallocation must be performed by
+// calling cl_credential_secrets_blinding_factors_free.
+//
+// Note that blinded credential secrets correctness proof deallocation must be performed by
+// calling cl_blinded_credential_secrets_correctness_proof_free.
+//
+// # Arguments
+// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+// * `credential_key_correctness_proof` - Reference that contains credential key correctness proof instance pointer.
+// * `credential_values` - Reference that contains credential values pointer.
+// * `credential_nonce` - Reference that contains nonce instance pointer.
+// * `blinded_credential_secrets_p` - Reference that will contain blinded credential secrets instance pointer.
+// * `credential_secrets_blinding_factors_p` - Reference that will contain credential secrets blinding factors instance pointer.
+// * `blinded_credential_secrets_correctness_proof_p` - Reference that will contain blinded credential secrets correctness proof instance pointer.
+ErrorCode cl_prover_blind_credential_secrets(const void *credential_pub_key,
+                                             const void *credential_key_correctness_proof,
+                                             const void *credential_values,
+                                             const void *credential_nonce,
+                                             const void **blinded_credential_secrets_p,
+                                             const void **credential_secrets_blinding_factors_p,
+                                             const void **blinded_credential_secrets_correctness_proof_p);
+
+// Returns json representation of blinded credential secrets.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `blinded_credential_secrets` - Reference that contains Blinded credential secrets pointer.
+// * `blinded_credential_secrets_json_p` - Reference that will contain blinded credential secrets json.
+ErrorCode cl_blinded_credential_secrets_to_json(const void *blinded_credential_secrets,
+                                                const char **blinded_credential_secrets_json_p);
+
+// Creates and returns blinded credential secrets from json.
+//
+// Note: Blinded credential secrets instance deallocation must be performed
+// by calling cl_blinded_credential_secrets_free
+//
+// # Arguments
+// * `blinded_credential_secrets_json` - Reference that contains blinded credential secret json.
+// * `blinded_credential_secrets_p` - Reference that will contain blinded credential secret instance pointer.
+ErrorCode cl_blinded_credential_secrets_from_json(const char *blinded_credential_secrets_json,
+                                                  const void **blinded_credential_secrets_p);
+
+// Returns json object that maps committed attributes names to the commitments.
+// Allows Issuer to check that Prover committed to exactly expected set of attributes.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `blinded_credential_secrets` - Reference that contains blinded credential secrets instance pointer.
+// * `committed_attrs_json_p` - Reference that will contain committed attributes json.
+ErrorCode cl_blinded_credential_secrets_get_committed_attrs_json(const void *blinded_credential_secrets,
+                                                                 const char **committed_attrs_json_p);
+
+// Deallocates  blinded credential secrets instance.
+//
+// # Arguments
+// * `blinded_credential_secrets` - Reference that contains blinded credential secrets instance pointer.
+ErrorCode cl_blinded_credential_secrets_free(const void *blinded_credential_secrets);
+
+// Returns json representation of credential secrets blinding factors.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `credential_secrets_blinding_factors` - Reference that contains credential secrets blinding factors pointer.
+// * `credential_secrets_blinding_factors_json_p` - Reference that will contain credential secrets blinding factors json.
+ErrorCode cl_credential_secrets_blinding_factors_to_json(const void *credential_secrets_blinding_factors,
+                                                         const char **credential_secrets_blinding_factors_json_p);
+
+// Creates and returns credential secrets blinding factors json.
+//
+// Note: Credential secrets blinding factors instance deallocation must be performed
+// by calling cl_credential_secrets_blinding_factors_free.
+//
+// # Arguments
+// * `credential_secrets_blinding_factors_json` - Reference that contains credential secrets blinding factors json.
+// * `credential_secrets_blinding_factors_p` - Reference that will contain credential secrets blinding factors instance pointer.
+ErrorCode cl_credential_secrets_blinding_factors_from_json(const char *credential_secrets_blinding_factors_json,
+                                                           const void **credential_secrets_blinding_factors_p);
+
+// Deallocates credential secrets blinding factors instance.
+//
+// # Arguments
+// * `credential_secrets_blinding_factors` - Reference that contains credential secrets blinding factors instance pointer.
+ErrorCode cl_credential_secrets_blinding_factors_free(const void *credential_secrets_blinding_factors);
+
+// Returns json representation of blinded credential secrets correctness proof.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `blinded_credential_secrets_correctness_proof` - Reference that contains blinded credential secrets correctness proof pointer.
+// * `blinded_credential_secrets_correctness_proof_json_p` - Reference that will contain blinded credential secrets correctness proof json.
+ErrorCode cl_blinded_credential_secrets_correctness_proof_to_json(const void *blinded_credential_secrets_correctness_proof,
+                                                                  const char **blinded_credential_secrets_correctness_proof_json_p);
+
+// Creates and returns blinded credential secrets correctness proof json.
+//
+// Note: Blinded credential secrets correctness proof instance deallocation must be performed
+// by calling cl_blinded_credential_secrets_correctness_proof_free.
+//
+// # Arguments
+// * `blinded_credential_secrets_correctness_proof_json` - Reference that contains blinded credential secrets correctness proof json.
+// * `blinded_credential_secrets_correctness_proof_p` - Reference that will contain blinded credential secret correctness proof instance pointer.
+ErrorCode cl_blinded_credential_secrets_correctness_proof_from_json(const char *blinded_credential_secrets_correctness_proof_json,
+                                                                    const void **blinded_credential_secrets_correctness_proof_p);
+
+// Deallocates blinded credential secrets correctness proof instance.
+//
+// # Arguments
+// * `blinded_credential_secrets_correctness_proof` - Reference that contains blinded credential secrets correctness proof instance pointer.
+ErrorCode cl_blinded_credential_secrets_correctness_proof_free(const void *blinded_credential_secrets_correctness_proof);
+
+// Updates the credential signature by a credential secrets blinding factors.
+//
+// # Arguments
+// * `credential_signature` - Credential signature instance pointer generated by Issuer.
+// * `credential_values` - Credential values instance pointer.
+// * `signature_correctness_proof` - Credential signature correctness proof instance pointer.
+// * `credential_secrets_blinding_factors` - Credential secrets blinding factors instance pointer.
+// * `credential_pub_key` - Credential public key instance pointer.
+// * `nonce` -  Nonce instance pointer was used by Issuer for the creation of signature_correctness_proof.
+ErrorCode cl_prover_process_credential_signature(const void *credential_signature,
+                                                 const void *credential_values,
+                                                 const void *signature_correctness_proof,
+                                                 const void *credential_secrets_blinding_factors,
+                                                 const void *credential_pub_key,
+                                                 const void *credential_issuance_nonce);
+
+// Verifies that processed credential signature is a valid issuer signature of credential values
+// under the credential public key. Doesn't require signature correctness proof.
+//
+// # Arguments
+// * `credential_signature` - Processed credential signature instance pointer.
+// * `credential_values` - Credential values instance pointer.
+// * `credential_pub_key` - Credential public key instance pointer.
+// * `valid_p` - Reference that will be filled with true - if signature valid or false otherwise.
+ErrorCode cl_prover_verify_credential(const void *credential_signature,
+                                      const void *credential_values,
+                                      const void *credential_pub_key,
+                                      bool *valid_p);
+
+// Checks whether credential can satisfy sub proof request without building of proof.
+//
+// # Arguments
+// * `sub_proof_request` - Reference that contains requested attributes and predicates instance pointer.
+// * `credential_schema` - Reference that contains credential schema instance pointer.
+// * `credential_values` - Reference that contains credential values instance pointer.
+// * `can_satisfy_p` - Reference that will be filled with true - if credential satisfies request or false otherwise.
+ErrorCode cl_prover_can_satisfy(const void *sub_proof_request,
+                                const void *credential_schema,
+                                const void *credential_values,
+                                bool *can_satisfy_p);
+
+// Returns json array of sub proof request predicates that credential values don't satisfy.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `sub_proof_request` - Reference that contains requested attributes and predicates instance pointer.
+// * `credential_values` - Reference that contains credential values instance pointer.
+// * `unsatisfied_predicates_json_p` - Reference that will contain json array of unsatisfied predicates.
+ErrorCode cl_prover_unsatisfied_predicates(const void *sub_proof_request,
+                                           const void *credential_values,
+                                           const char **unsatisfied_predicates_json_p);
+
+// Creates and returns proof builder.
+//
+// The purpose of proof builder is building of proof entity according to the given request .
+//
+// Note that proof builder deallocation must be performed by
+// calling cl_proof_builder_finalize.
+//
+// # Arguments
+// * `proof_builder_p` - Reference that will contain proof builder instance pointer.
+ErrorCode cl_prover_new_proof_builder(const void **proof_builder_p);
+
+// Add a common attribute to the proof builder.
+//
+// Common attributes (for example master secret) must be equal across all credentials used for proof.
+//
+// # Arguments
+// * `proof_builder` - Reference that contain proof builder instance pointer.
+// * `attr_name` - Common attribute's name.
+ErrorCode cl_proof_builder_add_common_attribute(const void *proof_builder,
+                                                const char *attr_name);
+
+// Resets the proof builder so it can be reused for the next proof.
+//
+// Accumulated sub proof requests and common attributes are removed, secret intermediate values are zeroized.
+//
+// # Arguments
+// * `proof_builder` - Reference that contain proof builder instance pointer.
+ErrorCode cl_proof_builder_clear(const void *proof_builder);
+
+// Add a sub proof request to the proof builder
+//
+// # Arguments
+// * `proof_builder` - Reference that contain proof builder instance pointer.
+// * `sub_proof_request` - Reference that contain sub proof request instance pointer.
+// * `credential_schema` - Reference that contains credential schema instance pointer.
+// * `non_credential_schema` - Reference that contains non credential schema instance pointer.
+// * `credential_signature` - Reference that contains the credential signature pointer.
+// * `credential_values` - Reference that contains credential values instance pointer.
+// * `key_id` - Identifier of credential public key as null terminated string.
+// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+ErrorCode cl_proof_builder_add_sub_proof_request(const void *proof_builder,
+                                                 const void *sub_proof_request,
+                                                 const void *credential_schema,
+                                                 const void *non_credential_schema,
+                                                 const void *credential_signature,
+                                                 const void *credential_values,
+                                                 const char *key_id,
+                                                 const void *credential_pub_key);
+
+// Finalize proof.
+//
+// Note that proof deallocation must be performed by
+// calling cl_proof_free.
+//
+// # Arguments
+// * `proof_builder` - Reference that contain proof builder instance pointer.
+// * `nonce` - Reference that contain nonce instance pointer.
+// * `proof_p` - Reference that will contain proof instance pointer.
+ErrorCode cl_proof_builder_finalize(const void *proof_builder,
+                                    const void *nonce,
+                                    const void **proof_p);
+
+// Returns json representation of proof.
+//
+// Note: Returned string must be deallocated by calling cl_free_string.
+//
+// # Arguments
+// * `proof` - Reference that contains proof instance pointer.
+// * `proof_json_p` - Reference that will contain proof json.
+ErrorCode cl_proof_to_json(const void *proof,
+                           const char **proof_json_p);
+
+// Creates and returns proof json.
+//
+// Note: Proof instance deallocation must be performed by calling cl_proof_free.
+//
+// # Arguments
+// * `proof_json` - Reference that contains proof json.
+// * `proof_p` - Reference that will contain proof instance pointer.
+ErrorCode cl_proof_from_json(const char *proof_json,
+                             const void **proof_p);
+
+// Returns compact binary representation of proof.
+//
+// Note: Bytes deallocation must be performed by calling cl_proof_bytes_free.
+//
+// # Arguments
+// * `proof` - Reference that contains proof instance pointer.
+// * `bytes_p` - Reference that will contain proof bytes.
+// * `bytes_len_p` - Reference that will contain proof bytes length.
+ErrorCode cl_proof_to_bytes(const void *proof,
+                            const uint8_t **bytes_p,
+                            uintptr_t *bytes_len_p);
+
+// Deallocates proof bytes returned by cl_proof_to_bytes.
+//
+// # Arguments
+// * `bytes` - Proof bytes.
+// * `bytes_len` - Proof bytes length.
+ErrorCode cl_proof_bytes_free(const uint8_t *bytes,
+                              uintptr_t bytes_len);
+
+// Creates and returns proof from binary representation.
+//
+// Note: Proof instance deallocation must be performed by calling cl_proof_free.
+//
+// # Arguments
+// * `bytes` - Proof bytes created by cl_proof_to_bytes.
+// * `bytes_len` - Proof bytes length.
+// * `proof_p` - Reference that will contain proof instance pointer.
+ErrorCode cl_proof_from_bytes(const uint8_t *bytes,
+                              uintptr_t bytes_len,
+                              const void **proof_p);
+
+// Deallocates proof instance.
+//
+// # Arguments
+// * `proof` - Reference that contains proof instance pointer.
+ErrorCode cl_proof_free(const void *proof);
+
+// Creates and returns proof verifier.
+//
+// Note that proof verifier deallocation must be performed by
+// calling cl_proof_verifier_verify or cl_proof_verifier_free.
+//
+// # Arguments
+// * `proof_verifier_p` - Reference that will contain proof verifier instance pointer.
+ErrorCode cl_verifier_new_proof_verifier(const void **proof_verifier_p);
+
+// Add a sub proof request to the proof verifier.
+//
+// # Arguments
+// * `proof_verifier` - Reference that contain proof verifier instance pointer.
+// * `sub_proof_request` - Reference that contain sub proof request instance pointer.
+// * `credential_schema` - Reference that contains credential schema instance pointer.
+// * `non_credential_schema` - Reference that contains non credential schema instance pointer.
+// * `key_id` - Identifier of credential public key as null terminated string.
+// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+ErrorCode cl_proof_verifier_add_sub_proof_request(const void *proof_verifier,
+                                                  const void *sub_proof_request,
+                                                  const void *credential_schema,
+                                                  const void *non_credential_schema,
+                                                  const char *key_id,
+                                                  const void *credential_pub_key);
+
+// Verifies proof and deallocates proof verifier.
+//
+// # Arguments
+// * `proof_verifier` - Reference that contain proof verifier instance pointer.
+// * `proof` - Reference that contain proof instance pointer.
+// * `nonce` - Reference that contain nonce instance pointer.
+// * `valid_p` - Reference that will be filled with true - if proof valid or false otherwise.
+ErrorCode cl_proof_verifier_verify(const void *proof_verifier,
+                                   const void *proof,
+                                   const void *nonce,
+                                   bool *valid_p);
+
+// Deallocates proof verifier instance that wasn't consumed by cl_proof_verifier_verify.
+//
+// # Arguments
+// * `proof_verifier` - Reference that contains proof verifier instance pointer.
+ErrorCode cl_proof_verifier_free(const void *proof_verifier);
+
+// Set custom logger implementation.
+//
+// Allows library user to provide custom logger implementation as set of handlers.
+//
+// #Params
+// context: pointer to some logger context that will be available in logger handlers.
+// enabled: (optional) "enabled" operation handler - calls to determines if a log record would be logged. (false positive if not specified)
+// log: "log" operation handler - calls to logs a record.
+// flush: (optional) "flush" operation handler - calls to flushes buffered records (in case of crash or signal).
+//
+// #Returns
+// Error code
+//
+// Note: Handlers types are the same as `EnabledCB`, `LogCB` and `FlushCB`, but are written out in full,
+// so generated C header declares them as nullable function pointers.
+ErrorCode indy_crypto_set_logger(const void *context,
+                                 bool (*enabled)(const void *context,
+                                                 uint32_t level,
+                                                 const char *target),
+                                 void (*log)(const void *context,
+                                             uint32_t level,
+                                             const char *target,
+                                             const char *message,
+                                             const char *module_path,
+                                             const char *file,
+                                             uint32_t line),
+                                 void (*flush)(const void *context));
+
+// Set default logger implementation.
+//
+// Allows library user use `env_logger` logger as default implementation.
+// More details about `env_logger` and its customization can be found here: https://crates.io/crates/env_logger
+//
+// #Params
+// pattern: (optional) pattern that corresponds with the log messages to show.
+//
+// NOTE: You should specify either `pattern` parameter or `RUST_LOG` environment variable to init logger.
+//
+// #Returns
+// Error code
+ErrorCode indy_crypto_set_default_logger(const char *pattern);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* indy_crypto__included__ */
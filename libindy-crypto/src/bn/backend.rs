@@ -0,0 +1,249 @@
+use errors::IndyCryptoError;
+
+use std::fmt::Debug;
+
+/// Big number operations used across `cl` module.
+///
+/// Implementation is selected in `bn/mod.rs` by feature flag (`bn_openssl` is the default one,
+/// `bn_rust` is the pure Rust one) and re-exported as `bn::BigNumber`. Every backend goes to a sibling
+/// module under its own feature and must implement this trait.
+pub trait BigNumberBackend: Sized + Debug + Eq + Ord {
+    fn from_u32(n: usize) -> Result<Self, IndyCryptoError>;
+
+    fn from_dec(dec: &str) -> Result<Self, IndyCryptoError>;
+
+    fn to_dec(&self) -> Result<String, IndyCryptoError>;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, IndyCryptoError>;
+
+    fn to_bytes(&self) -> Result<Vec<u8>, IndyCryptoError>;
+
+    /// Returns random number of `size` bits.
+    fn rand(size: usize) -> Result<Self, IndyCryptoError>;
+
+    fn generate_prime(size: usize) -> Result<Self, IndyCryptoError>;
+
+    /// Returns safe prime `p` of `size + 1` bits, so `(p - 1) / 2` is prime of `size` bits.
+    fn generate_safe_prime(size: usize) -> Result<Self, IndyCryptoError>;
+
+    fn is_prime(&self) -> Result<bool, IndyCryptoError>;
+
+    fn num_bits(&self) -> Result<i32, IndyCryptoError>;
+
+    fn add(&self, a: &Self) -> Result<Self, IndyCryptoError>;
+
+    fn sub(&self, a: &Self) -> Result<Self, IndyCryptoError>;
+
+    fn mul(&self, a: &Self) -> Result<Self, IndyCryptoError>;
+
+    /// Returns non negative remainder of division by `n`.
+    fn modulus(&self, n: &Self) -> Result<Self, IndyCryptoError>;
+
+    /// Returns `self ^ a mod n`.
+    fn mod_exp(&self, a: &Self, n: &Self) -> Result<Self, IndyCryptoError>;
+
+    /// Returns `self ^ a mod n` for secret exponent `a`, modulus must be odd.
+    fn mod_exp_consttime(&self, a: &Self, n: &Self) -> Result<Self, IndyCryptoError>;
+
+    /// Returns `self ^ -1 mod n`.
+    fn inverse(&self, n: &Self) -> Result<Self, IndyCryptoError>;
+
+    /// Overwrites value with zeros.
+    fn clear(&mut self);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bn::BigNumber;
+
+    /// Minimal backend over machine words, used to check that generic code depends on the trait only.
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct StubBigNumber(i128);
+
+    impl StubBigNumber {
+        fn _value(value: i128) -> Result<StubBigNumber, IndyCryptoError> {
+            if value.abs() >= 1 << 62 {
+                return Err(IndyCryptoError::InvalidState("Stub big number overflow".to_string()));
+            }
+            Ok(StubBigNumber(value))
+        }
+
+        fn _next_prime(from: i128) -> Result<StubBigNumber, IndyCryptoError> {
+            let mut candidate = StubBigNumber::_value(from)?;
+            while !candidate.is_prime()? {
+                candidate = StubBigNumber::_value(candidate.0 + 1)?;
+            }
+            Ok(candidate)
+        }
+    }
+
+    impl BigNumberBackend for StubBigNumber {
+        fn from_u32(n: usize) -> Result<StubBigNumber, IndyCryptoError> {
+            StubBigNumber::_value(n as i128)
+        }
+
+        fn from_dec(dec: &str) -> Result<StubBigNumber, IndyCryptoError> {
+            let value = dec.parse::<i128>()
+                .map_err(|err| IndyCryptoError::InvalidStructure(format!("Invalid decimal: {}", err)))?;
+            StubBigNumber::_value(value)
+        }
+
+        fn to_dec(&self) -> Result<String, IndyCryptoError> {
+            Ok(self.0.to_string())
+        }
+
+        fn from_bytes(bytes: &[u8]) -> Result<StubBigNumber, IndyCryptoError> {
+            if bytes.len() > 8 {
+                return Err(IndyCryptoError::InvalidStructure("Stub big number overflow".to_string()));
+            }
+            StubBigNumber::_value(bytes.iter().fold(0i128, |acc, byte| (acc << 8) | *byte as i128))
+        }
+
+        fn to_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+            let bytes = (self.0.abs() as u64).to_be_bytes();
+            let first = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+            Ok(bytes[first..].to_vec())
+        }
+
+        fn rand(size: usize) -> Result<StubBigNumber, IndyCryptoError> {
+            StubBigNumber::_value((1i128 << (size - 1)) | 0x5) // deterministic is enough for the stub
+        }
+
+        fn generate_prime(size: usize) -> Result<StubBigNumber, IndyCryptoError> {
+            StubBigNumber::_next_prime(1i128 << (size - 1))
+        }
+
+        fn generate_safe_prime(size: usize) -> Result<StubBigNumber, IndyCryptoError> {
+            let mut prime = StubBigNumber::generate_prime(size)?;
+            while !StubBigNumber::_value(2 * prime.0 + 1)?.is_prime()? {
+                prime = StubBigNumber::_next_prime(prime.0 + 1)?;
+            }
+            StubBigNumber::_value(2 * prime.0 + 1)
+        }
+
+        fn is_prime(&self) -> Result<bool, IndyCryptoError> {
+            Ok(self.0 > 1 && (2..).take_while(|d| d * d <= self.0).all(|d| self.0 % d != 0))
+        }
+
+        fn num_bits(&self) -> Result<i32, IndyCryptoError> {
+            Ok(128 - self.0.abs().leading_zeros() as i32)
+        }
+
+        fn add(&self, a: &StubBigNumber) -> Result<StubBigNumber, IndyCryptoError> {
+            StubBigNumber::_value(self.0 + a.0)
+        }
+
+        fn sub(&self, a: &StubBigNumber) -> Result<StubBigNumber, IndyCryptoError> {
+            StubBigNumber::_value(self.0 - a.0)
+        }
+
+        fn mul(&self, a: &StubBigNumber) -> Result<StubBigNumber, IndyCryptoError> {
+            StubBigNumber::_value(self.0 * a.0)
+        }
+
+        fn modulus(&self, n: &StubBigNumber) -> Result<StubBigNumber, IndyCryptoError> {
+            StubBigNumber::_value(self.0.rem_euclid(n.0))
+        }
+
+        fn mod_exp(&self, a: &StubBigNumber, n: &StubBigNumber) -> Result<StubBigNumber, IndyCryptoError> {
+            let (mut base, mut exp, mut res) = (self.0.rem_euclid(n.0), a.0, 1i128);
+            while exp > 0 {
+                if exp & 1 == 1 {
+                    res = res * base % n.0;
+                }
+                base = base * base % n.0;
+                exp >>= 1;
+            }
+            StubBigNumber::_value(res)
+        }
+
+        fn mod_exp_consttime(&self, a: &StubBigNumber, n: &StubBigNumber) -> Result<StubBigNumber, IndyCryptoError> {
+            self.mod_exp(a, n)
+        }
+
+        fn inverse(&self, n: &StubBigNumber) -> Result<StubBigNumber, IndyCryptoError> {
+            let (mut r0, mut r1, mut t0, mut t1) = (n.0, self.0.rem_euclid(n.0), 0i128, 1i128);
+            while r1 != 0 {
+                let q = r0 / r1;
+                let (r, t) = (r0 - q * r1, t0 - q * t1);
+                r0 = r1;
+                r1 = r;
+                t0 = t1;
+                t1 = t;
+            }
+            if r0 != 1 {
+                return Err(IndyCryptoError::InvalidStructure("Value is not invertible".to_string()));
+            }
+            StubBigNumber::_value(t0.rem_euclid(n.0))
+        }
+
+        fn clear(&mut self) {
+            self.0 = 0;
+        }
+    }
+
+    fn _serialization_round_trip_works<B: BigNumberBackend>() {
+        let value = B::from_dec("1234567890123").unwrap();
+
+        assert_eq!("1234567890123", value.to_dec().unwrap());
+        assert_eq!(value, B::from_bytes(&value.to_bytes().unwrap()).unwrap());
+        assert_eq!(41, value.num_bits().unwrap());
+    }
+
+    fn _arithmetic_works<B: BigNumberBackend>() {
+        let a = B::from_u32(1000).unwrap();
+        let b = B::from_u32(7).unwrap();
+        let n = B::from_u32(101).unwrap();
+
+        assert_eq!(B::from_u32(1007).unwrap(), a.add(&b).unwrap());
+        assert_eq!(B::from_u32(993).unwrap(), a.sub(&b).unwrap());
+        assert_eq!(B::from_u32(7000).unwrap(), a.mul(&b).unwrap());
+        assert_eq!(B::from_u32(91).unwrap(), a.modulus(&n).unwrap());
+        assert_eq!(B::from_u32(1).unwrap(), b.mul(&b.inverse(&n).unwrap()).unwrap().modulus(&n).unwrap());
+        assert_eq!(a.mod_exp(&n, &n).unwrap(), a.modulus(&n).unwrap()); // Fermat's little theorem
+        assert_eq!(a.mod_exp(&b, &n).unwrap(), a.mod_exp_consttime(&b, &n).unwrap());
+        assert!(a > b);
+
+        let mut secret = B::from_u32(1000).unwrap();
+        secret.clear();
+        assert_eq!(B::from_u32(0).unwrap(), secret);
+    }
+
+    fn _prime_generation_works<B: BigNumberBackend>() {
+        let prime = B::generate_prime(20).unwrap();
+        let safe_prime = B::generate_safe_prime(20).unwrap();
+        let two = B::from_u32(2).unwrap();
+
+        assert!(prime.is_prime().unwrap());
+        assert!(safe_prime.is_prime().unwrap());
+        assert!(safe_prime.sub(&B::from_u32(1).unwrap()).unwrap().mul(&two.inverse(&safe_prime).unwrap()).unwrap()
+            .modulus(&safe_prime).unwrap().is_prime().unwrap());
+        assert_eq!(21, safe_prime.num_bits().unwrap());
+        assert!(!B::from_u32(91).unwrap().is_prime().unwrap());
+        assert!(B::rand(20).unwrap().num_bits().unwrap() <= 20);
+    }
+
+    #[test]
+    fn stub_backend_works() {
+        _serialization_round_trip_works::<StubBigNumber>();
+        _arithmetic_works::<StubBigNumber>();
+        _prime_generation_works::<StubBigNumber>();
+    }
+
+    #[test]
+    #[cfg(feature = "bn_openssl")]
+    fn openssl_backend_works() {
+        _serialization_round_trip_works::<BigNumber>();
+        _arithmetic_works::<BigNumber>();
+        _prime_generation_works::<BigNumber>();
+    }
+
+    #[test]
+    #[cfg(all(feature = "bn_rust", not(feature = "bn_openssl")))]
+    fn rust_backend_works() {
+        _serialization_round_trip_works::<BigNumber>();
+        _arithmetic_works::<BigNumber>();
+        _prime_generation_works::<BigNumber>();
+    }
+}
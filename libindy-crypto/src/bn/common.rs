@@ -0,0 +1,506 @@
+use errors::IndyCryptoError;
+
+use super::{BigNumber, BigNumberBackend, BigNumberContext};
+
+use std::cell::RefCell;
+
+#[cfg(feature = "serialization")]
+use serde::ser::{Serialize, Serializer, Error as SError};
+
+#[cfg(feature = "serialization")]
+use serde::de::{Deserialize, Deserializer, Visitor, Error as DError};
+
+#[cfg(feature = "serialization")]
+use std::fmt;
+
+const BASE58_ALPHABET: &'static [u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+const BN_CTX_POOL_SIZE: usize = 8;
+
+thread_local! {
+    static BN_CTX_POOL: RefCell<Vec<BigNumberContext>> = RefCell::new(Vec::new());
+}
+
+/// Thread local pool of contexts that allows to reuse BN_CTX allocations
+/// between big number operations instead of creating new one for every call.
+pub struct BnCtxPool {}
+
+impl BnCtxPool {
+    /// Takes context from the pool or creates new one if the pool is empty.
+    pub fn take() -> Result<BigNumberContext, IndyCryptoError> {
+        match BN_CTX_POOL.with(|pool| pool.borrow_mut().pop()) {
+            Some(ctx) => Ok(ctx),
+            None => BigNumber::new_context()
+        }
+    }
+
+    /// Returns context to the pool. Context is dropped if the pool is full.
+    pub fn release(ctx: BigNumberContext) {
+        BN_CTX_POOL.with(|pool| {
+            let mut pool = pool.borrow_mut();
+            if pool.len() < BN_CTX_POOL_SIZE {
+                pool.push(ctx);
+            }
+        })
+    }
+
+    /// Returns count of contexts available in the pool of current thread.
+    pub fn size() -> usize {
+        BN_CTX_POOL.with(|pool| pool.borrow().len())
+    }
+}
+
+/// Operations built on top of backend specific ones, so they behave the same for every backend.
+impl BigNumber {
+    /// Parses non negative number from base58 string (Bitcoin alphabet).
+    /// Leading `1` digits are zeros and don't change the value.
+    pub fn from_base58(base58: &str) -> Result<BigNumber, IndyCryptoError> {
+        if base58.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure("Invalid base58 value: empty string".to_string()));
+        }
+
+        let mut bytes: Vec<u8> = Vec::new();
+
+        for c in base58.bytes() {
+            let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Invalid base58 value: {:?}", base58)))?;
+
+            for byte in bytes.iter_mut().rev() {
+                carry += (*byte as usize) * 58;
+                *byte = (carry & 0xff) as u8;
+                carry >>= 8;
+            }
+
+            while carry > 0 {
+                bytes.insert(0, (carry & 0xff) as u8);
+                carry >>= 8;
+            }
+        }
+
+        BigNumber::from_bytes(&bytes)
+    }
+
+    /// Returns base58 representation (Bitcoin alphabet) of non negative number, zero is encoded as `1`.
+    pub fn to_base58(&self) -> Result<String, IndyCryptoError> {
+        if self.is_negative() {
+            return Err(IndyCryptoError::InvalidState("Negative BigNumber can't be encoded as base58".to_string()));
+        }
+
+        let mut digits: Vec<u8> = Vec::new();
+
+        for &byte in self.to_bytes()?.iter() {
+            let mut carry = byte as usize;
+
+            for digit in digits.iter_mut() {
+                carry += (*digit as usize) << 8;
+                *digit = (carry % 58) as u8;
+                carry /= 58;
+            }
+
+            while carry > 0 {
+                digits.push((carry % 58) as u8);
+                carry /= 58;
+            }
+        }
+
+        if digits.is_empty() {
+            digits.push(0);
+        }
+
+        Ok(digits.iter().rev().map(|&d| BASE58_ALPHABET[d as usize] as char).collect())
+    }
+
+    /// Returns big-endian bytes of the number left padded with zeros to `len` bytes.
+    pub fn to_bytes_padded(&self, len: usize) -> Result<Vec<u8>, IndyCryptoError> {
+        let bytes = self.to_bytes()?;
+
+        if bytes.len() > len {
+            return Err(IndyCryptoError::InvalidStructure(format!("BigNumber doesn't fit into {} bytes", len)));
+        }
+
+        let mut result = vec![0u8; len - bytes.len()];
+        result.extend_from_slice(&bytes);
+        Ok(result)
+    }
+}
+
+impl BigNumberBackend for BigNumber {
+    fn from_u32(n: usize) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::from_u32(n)
+    }
+
+    fn from_dec(dec: &str) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::from_dec(dec)
+    }
+
+    fn to_dec(&self) -> Result<String, IndyCryptoError> {
+        BigNumber::to_dec(self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::from_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        BigNumber::to_bytes(self)
+    }
+
+    fn rand(size: usize) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::rand(size)
+    }
+
+    fn generate_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::generate_prime(size)
+    }
+
+    fn generate_safe_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::generate_safe_prime(size)
+    }
+
+    fn is_prime(&self) -> Result<bool, IndyCryptoError> {
+        BigNumber::is_prime(self, None)
+    }
+
+    fn num_bits(&self) -> Result<i32, IndyCryptoError> {
+        BigNumber::num_bits(self)
+    }
+
+    fn add(&self, a: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::add(self, a)
+    }
+
+    fn sub(&self, a: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::sub(self, a)
+    }
+
+    fn mul(&self, a: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::mul(self, a, None)
+    }
+
+    fn modulus(&self, n: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::modulus(self, n, None)
+    }
+
+    fn mod_exp(&self, a: &BigNumber, n: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::mod_exp(self, a, n, None)
+    }
+
+    fn mod_exp_consttime(&self, a: &BigNumber, n: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::mod_exp_consttime(self, a, n, None)
+    }
+
+    fn inverse(&self, n: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::inverse(self, n, None)
+    }
+
+    fn clear(&mut self) {
+        BigNumber::clear(self)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl Serialize for BigNumber {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_newtype_struct("BigNumber", &self.to_dec().map_err(SError::custom)?)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl<'a> Deserialize<'a> for BigNumber {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'a> {
+        struct BigNumberVisitor;
+
+        impl<'a> Visitor<'a> for BigNumberVisitor {
+            type Value = BigNumber;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("expected BigNumber")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<BigNumber, E>
+                where E: DError
+            {
+                Ok(BigNumber::from_dec(value).map_err(DError::custom)?)
+            }
+        }
+
+        deserializer.deserialize_str(BigNumberVisitor)
+    }
+}
+
+/// Serializes big number as `0x` prefixed hex string of fixed width (`len` bytes).
+/// Used for secret values so length of serialized value doesn't depend on the value.
+/// Intermediate bytes and hex string are zeroized after serialization.
+#[cfg(feature = "serialization")]
+pub fn serialize_fixed_width<S>(bn: &BigNumber, len: usize, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+    let mut bytes = bn.to_bytes_padded(len).map_err(SError::custom)?;
+
+    let mut hex = String::with_capacity(2 + 2 * len);
+    hex.push_str("0x");
+    for byte in bytes.iter() {
+        hex.push_str(&format!("{:02x}", byte));
+    }
+
+    let res = serializer.serialize_newtype_struct("BigNumber", &hex);
+
+    _zeroize(&mut bytes);
+    _zeroize(unsafe { hex.as_mut_vec() });
+
+    res
+}
+
+/// Deserializes big number written by `serialize_fixed_width`.
+/// Decimal values written before fixed width serialization was introduced are accepted as well.
+/// Unlike `BigNumber` deserialization it accepts `0x` prefixed hex, so use it only for fields serialized with fixed width.
+/// Deserialized string is zeroized after parsing.
+#[cfg(feature = "serialization")]
+pub fn deserialize_fixed_width<'a, D>(deserializer: D) -> Result<BigNumber, D::Error> where D: Deserializer<'a> {
+    let mut value = String::deserialize(deserializer)?;
+
+    let res = if value.starts_with("0x") {
+        BigNumber::from_hex(&value[2..])
+    } else {
+        BigNumber::from_dec(&value)
+    };
+
+    _zeroize(unsafe { value.as_mut_vec() });
+
+    res.map_err(DError::custom)
+}
+
+#[cfg(feature = "serialization")]
+fn _zeroize(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        unsafe { ::std::ptr::write_volatile(byte, 0); }
+    }
+}
+
+impl Default for BigNumber {
+    fn default() -> BigNumber {
+        BigNumber::from_u32(0).unwrap()
+    }
+}
+
+// Constants that are used throughout the code, so avoiding recomputation.
+lazy_static! {
+    pub static ref BIGNUMBER_1: BigNumber = BigNumber::from_u32(1).unwrap();
+    pub static ref BIGNUMBER_2: BigNumber = BigNumber::from_u32(2).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cmp::Ordering;
+
+    #[cfg(feature = "serialization")]
+    use serde_json;
+
+    #[test]
+    fn bn_ctx_pool_works() {
+        let ctx = BnCtxPool::take().unwrap();
+        BnCtxPool::release(ctx);
+        assert!(BnCtxPool::size() >= 1);
+
+        let contexts: Vec<BigNumberContext> = (0..BN_CTX_POOL_SIZE + 1).map(|_| BnCtxPool::take().unwrap()).collect();
+        assert_eq!(0, BnCtxPool::size());
+
+        for ctx in contexts {
+            BnCtxPool::release(ctx);
+        }
+        assert_eq!(BN_CTX_POOL_SIZE, BnCtxPool::size());
+    }
+
+    #[test]
+    fn bn_ctx_pool_gives_same_results_as_new_context() {
+        let n = BigNumber::generate_prime(1024).unwrap();
+        let base = BigNumber::rand(1024).unwrap();
+
+        for _ in 0..100 {
+            let exp = BigNumber::rand(592).unwrap();
+
+            let mut ctx = BigNumber::new_context().unwrap();
+            let expected = base.mod_exp(&exp, &n, Some(&mut ctx)).unwrap()
+                .mod_mul(&base, &n, Some(&mut ctx)).unwrap()
+                .mul(&exp, Some(&mut ctx)).unwrap();
+
+            let actual = base.mod_exp(&exp, &n, None).unwrap()
+                .mod_mul(&base, &n, None).unwrap()
+                .mul(&exp, None).unwrap();
+
+            assert_eq!(expected, actual);
+        }
+
+        assert!(BnCtxPool::size() <= BN_CTX_POOL_SIZE);
+    }
+
+    #[cfg(feature = "serialization")]
+    #[derive(Serialize, Deserialize)]
+    struct Test {
+        field: BigNumber
+    }
+
+    #[test]
+    fn cmp_works_across_i32_boundary() {
+        let i32_max = BigNumber::from_dec(&i32::max_value().to_string()).unwrap();
+        let i32_min = BigNumber::from_dec(&i32::min_value().to_string()).unwrap();
+        let above_max = i32_max.increment().unwrap();
+        let below_min = i32_min.sub(&BIGNUMBER_1).unwrap();
+        let large = BigNumber::from_dec("123456789012345678901234567890").unwrap();
+
+        assert!(above_max > i32_max);
+        assert!(large > above_max);
+        assert!(below_min < i32_min);
+        assert!(below_min < i32_max);
+        assert!(large.set_negative(true).unwrap() < below_min);
+        assert_eq!(Ordering::Equal, large.cmp(&large.clone().unwrap()));
+    }
+
+    #[test]
+    fn to_bytes_padded_works() {
+        let num = BigNumber::from_u32(258).unwrap();
+        assert_eq!(vec![0, 0, 1, 2], num.to_bytes_padded(4).unwrap());
+        assert_eq!(vec![1, 2], num.to_bytes_padded(2).unwrap());
+        assert!(num.to_bytes_padded(1).is_err());
+    }
+
+    #[test]
+    fn base58_round_trip_works() {
+        let dec = "5944657099558967239210949258394887428692050081607692519917050011144233115103";
+        let num = BigNumber::from_dec(dec).unwrap();
+
+        let base58 = num.to_base58().unwrap();
+        assert_eq!(num, BigNumber::from_base58(&base58).unwrap());
+        assert_eq!(dec, BigNumber::from_base58(&base58).unwrap().to_dec().unwrap());
+
+        assert_eq!("1", BigNumber::from_u32(0).unwrap().to_base58().unwrap());
+        assert_eq!("21", BigNumber::from_u32(58).unwrap().to_base58().unwrap());
+        assert_eq!(BigNumber::from_u32(58).unwrap(), BigNumber::from_base58("1121").unwrap());
+        assert_eq!(BigNumber::from_u32(0).unwrap(), BigNumber::from_base58("11").unwrap());
+    }
+
+    #[test]
+    fn from_base58_works_for_invalid_characters() {
+        for base58 in ["", "0", "O1", "Il", "2+3", "21 "].iter() {
+            match BigNumber::from_base58(base58) {
+                Err(IndyCryptoError::InvalidStructure(_)) => {}
+                res => panic!("InvalidStructure error expected for {:?}, got {:?}", base58, res)
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serialization")]
+    fn deserialize_works_for_hex() {
+        assert!(serde_json::from_str::<Test>(r#"{"field":"0x000102"}"#).is_err());
+    }
+
+    #[cfg(feature = "serialization")]
+    #[derive(Serialize, Deserialize)]
+    struct FixedWidthTest {
+        #[serde(serialize_with = "serialize_fixed_width_3", deserialize_with = "deserialize_fixed_width")]
+        field: BigNumber
+    }
+
+    #[cfg(feature = "serialization")]
+    fn serialize_fixed_width_3<S>(bn: &BigNumber, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serialize_fixed_width(bn, 3, serializer)
+    }
+
+    #[test]
+    #[cfg(feature = "serialization")]
+    fn fixed_width_serialization_works() {
+        let s = FixedWidthTest { field: BigNumber::from_u32(258).unwrap() };
+        let serialized = serde_json::to_string(&s).unwrap();
+        assert_eq!(r#"{"field":"0x000102"}"#, serialized);
+
+        let deserialized = serde_json::from_str::<FixedWidthTest>(&serialized).unwrap();
+        assert_eq!(s.field, deserialized.field);
+
+        let legacy = serde_json::from_str::<FixedWidthTest>(r#"{"field":"258"}"#).unwrap();
+        assert_eq!(s.field, legacy.field);
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn serialize_works() {
+        let s = Test { field: BigNumber::from_dec("1").unwrap() };
+        let serialized = serde_json::to_string(&s);
+
+        assert!(serialized.is_ok());
+        assert_eq!("{\"field\":\"1\"}", serialized.unwrap());
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn deserialize_works() {
+        let s = "{\"field\":\"1\"}";
+        let bn: Result<Test, _> = serde_json::from_str(&s);
+
+        assert!(bn.is_ok());
+        assert_eq!("1", bn.unwrap().field.to_dec().unwrap());
+    }
+
+    #[test]
+    fn test_modular_exponentiation() {
+        let base = BigNumber::from_dec("12714671911903680502393098440562958150461307840092575886187217264492970515611166458444182780904860535776274190597528985988632488194981204988199325501696648896748368401254829974173258613724800116424602180755019588176641580062215499750550535543002990347313784260314641340394494547935943176226649412526659864646068220114536172189443925908781755710141006387091748541976715633668919725277837668568166444731358541327097786024076841158424402136565558677098853060675674958695935207345864359540948421232816012865873346545455513695413921957708811080877422273777355768568166638843699798663264533662595755767287970642902713301649").unwrap();
+        let exp = BigNumber::from_dec("13991423645225256679625502829143442357836305738777175327623021076136862973228390317258480888217725740262243618881809894688804251512223982403225288178492105393953431042196371492402144120299046493467608097411259757604892535967240041988260332063962457178993277482991886508015739613530825229685281072180891075265116698114782553748364913010741387964956740720544998915158970813171997488129859542399633104746793770216517872705889857552727967921847493285577238").unwrap();
+        let modulus = BigNumber::from_dec("991272771610724400277702356109350334773782112020672787325464582894874455338156617087078683660308327009158085342465983713825070967004447592080649030930737560915527173820649490032274245863850782844569456999473516497618489127293328524608584652323593452247534656999363158875176879817952982494174728640545484193154314433925648566686738628413929222467005197087738850212963801663981588243042912430590088435419451359859770426041670326127890520192033283832465411962274045956439947646966560440910244870464709982605844468449227905039953511431640780483761563845223213570597106855699997837768334871601402132694515676785338799407204529154456178837013845488372635042715003769626150545960460800980936426723680755798495767188398126674428244764038147226578038085253616108968402209263400729503458144370189359160926796812468410806201905992347006546335038212090539118675048292666041345556742530041533878341459110515497642054583635133581316796089099043782055893003258788369004899742992039315008110063759802733045648131896557338576682560236591353394201381103042167106112201578883917022695113857967398885475101031596068885337186646296664517159150904935112836318654117577507707562065113238913343761942585545093919444150946120523831367132144754209388110483749").unwrap();
+        let n = base.mod_exp(&exp, &modulus, None).unwrap();
+        assert_eq!(n, BigNumber::from_dec("156669382818249607878298589043381544147555658222157929549484054385620519150887267126359684884641035264854247223281407349108771361611707714806192334779156374961296686821846487267487447347213829476609283133961216115764596907219173912888367998704856300105745961091899745329082513615681466199188236178266479183520370119131067362815102553237342546358580424556049196548520326206809677290296313839918774603549816182657993044271509706055893922152644469350618465711055733369291523796837304622919600074130968607301641438272377350795631212741686475924538423333008944556761300787668873766797549942827958501053262330421256183088509761636226277739400954175538503984519144969688787730088704522060486181427528150632576628856946041322195818246199503927686629821338146828603690778689292695518745939007886131151503766930229761608131819298276772877945842806872426029069949874062579870088710097070526608376602732627661781899595747063793310401032556802468649888104062151213860356554306295111191704764944574687548637446778783560586599000631975868701382113259027374431129732911012887214749014288413818636520182416636289308770657630129067046301651835893708731812616847614495049523221056260334965662875649480493232265453415256612460815802528012166114764216881").unwrap());
+
+        let base = BigNumber::from_u32(6).unwrap();
+        let exp = BigNumber::from_u32(5).unwrap().set_negative(true).unwrap();
+        let modulus = BigNumber::from_u32(13).unwrap();
+        assert_eq!(BigNumber::from_u32(7).unwrap(), base.mod_exp(&exp, &modulus, None).unwrap());
+    }
+
+    #[test]
+    fn mod_exp_consttime_works() {
+        let modulus = BigNumber::generate_prime(1024).unwrap();
+
+        for _ in 0..10 {
+            let base = BigNumber::rand(1024).unwrap();
+            let exp = BigNumber::rand(600).unwrap();
+            assert_eq!(base.mod_exp(&exp, &modulus, None).unwrap(), base.mod_exp_consttime(&exp, &modulus, None).unwrap());
+        }
+
+        let base = BigNumber::from_u32(6).unwrap();
+        let exp = BigNumber::from_u32(5).unwrap().set_negative(true).unwrap();
+        let modulus = BigNumber::from_u32(13).unwrap();
+        assert_eq!(BigNumber::from_u32(7).unwrap(), base.mod_exp_consttime(&exp, &modulus, None).unwrap());
+    }
+
+    #[test]
+    fn decrement_works() {
+        let num = BigNumber::from_u32(1000).unwrap();
+        assert_eq!(num.decrement().unwrap(), num.sub(&BIGNUMBER_1).unwrap());
+    }
+
+    #[test]
+    fn increment_works() {
+        let num = BigNumber::from_u32(1000).unwrap();
+        assert_eq!(num.increment().unwrap(), num.add(&BIGNUMBER_1).unwrap());
+    }
+
+    #[test]
+    fn rshift1_works() {
+        let num = BigNumber::from_u32(1000).unwrap();
+        assert_eq!(num.rshift1().unwrap(), BigNumber::from_u32(500).unwrap());
+    }
+
+    #[test]
+    fn rshift_works() {
+        let num = BigNumber::from_u32(1024).unwrap();
+        assert_eq!(num.rshift(1).unwrap(), BigNumber::from_u32(512).unwrap());
+        assert_eq!(num.rshift(2).unwrap(), BigNumber::from_u32(256).unwrap());
+        assert_eq!(num.rshift(3).unwrap(), BigNumber::from_u32(128).unwrap());
+        assert_eq!(num.rshift(4).unwrap(), BigNumber::from_u32(64).unwrap());
+    }
+
+    #[test]
+    fn lshift1_works() {
+        let num = BigNumber::from_u32(1000).unwrap();
+        assert_eq!(num.lshift1().unwrap(), BigNumber::from_u32(2000).unwrap());
+    }
+}
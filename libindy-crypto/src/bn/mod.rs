@@ -0,0 +1,37 @@
+//! Big number implementation used by `cl` module.
+//!
+//! Backend is selected at compile time by feature flag: `bn_openssl` (default) builds `openssl` module,
+//! `bn_rust` builds pure Rust `rust` module (used if `bn_openssl` is disabled). Selected backend `BigNumber`
+//! is re-exported here, every backend implements `BigNumberBackend` and the same inherent API that `cl` uses.
+//! Code that doesn't depend on backend (base58, serialization, context pool) lives in `common` module.
+//!
+//! `cl` tests can be run on pure Rust backend with
+//! `cargo test --no-default-features --features "bn_rust serialization cl"`.
+
+mod backend;
+mod common;
+
+pub use self::backend::BigNumberBackend;
+pub use self::common::*;
+
+#[cfg(feature = "bn_openssl")]
+mod openssl;
+
+#[cfg(feature = "bn_openssl")]
+pub use self::openssl::*;
+
+#[cfg(all(feature = "bn_rust", not(feature = "bn_openssl")))]
+mod rust;
+
+#[cfg(all(feature = "bn_rust", not(feature = "bn_openssl")))]
+pub use self::rust::*;
+
+#[cfg(not(any(feature = "bn_openssl", feature = "bn_rust")))]
+compile_error!("Big number backend isn't selected, enable \"bn_openssl\" or \"bn_rust\" feature");
+
+/// Fails to compile if selected backend doesn't implement `BigNumberBackend`.
+#[allow(dead_code)]
+fn _check_backend() {
+    fn check<B: BigNumberBackend>() {}
+    check::<BigNumber>();
+}
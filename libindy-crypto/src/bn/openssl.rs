@@ -2,18 +2,13 @@ use errors::IndyCryptoError;
 
 use int_traits::IntTraits;
 
+use super::BnCtxPool;
+
 use openssl::bn::{BigNum, BigNumRef, BigNumContext, MsbOption};
 use openssl::hash::{hash, MessageDigest, Hasher};
 use openssl::error::ErrorStack;
 
-#[cfg(feature = "serialization")]
-use serde::ser::{Serialize, Serializer, Error as SError};
-
-#[cfg(feature = "serialization")]
-use serde::de::{Deserialize, Deserializer, Visitor, Error as DError};
-
 use std::error::Error;
-use std::fmt;
 use std::cmp::Ord;
 use std::cmp::Ordering;
 
@@ -21,6 +16,11 @@ pub struct BigNumberContext {
     openssl_bn_context: BigNumContext
 }
 
+/// Arbitrary precision integer backed by OpenSSL `BIGNUM`.
+///
+/// `BigNumber` is `Send` and `Sync`: it exclusively owns its `BIGNUM` and all `&self` operations
+/// pass it to OpenSSL as `const`. Scratch space (`BN_CTX`) isn't stored in it but taken from thread local
+/// `BnCtxPool`, so types built from big numbers can be shared between threads without extra locking.
 #[derive(Debug)]
 pub struct BigNumber {
     openssl_bn: BigNum
@@ -79,8 +79,10 @@ impl BigNumber {
         match ctx {
             Some(context) => Ok(self.openssl_bn.is_prime(checks, &mut context.openssl_bn_context)?),
             None => {
-                let mut ctx = BigNumber::new_context()?;
-                Ok(self.openssl_bn.is_prime(checks, &mut ctx.openssl_bn_context)?)
+                let mut ctx = BnCtxPool::take()?;
+                let res = self.openssl_bn.is_prime(checks, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
+                Ok(res)
             }
         }
     }
@@ -101,8 +103,10 @@ impl BigNumber {
                 )
             },
             None => {
-                let mut context = BigNumber::new_context()?;
-                self.is_safe_prime(Some(&mut context))
+                let mut context = BnCtxPool::take()?;
+                let res = self.is_safe_prime(Some(&mut context));
+                BnCtxPool::release(context);
+                res
             }
         }
     }
@@ -132,6 +136,11 @@ impl BigNumber {
         Ok(self)
     }
 
+    /// Overwrites value with zeros (BN_clear) so secret doesn't stay in memory after use.
+    pub fn clear(&mut self) {
+        BigNumRef::clear(&mut self.openssl_bn)
+    }
+
     pub fn from_u32(n: usize) -> Result<BigNumber, IndyCryptoError> {
         let bn = BigNum::from_u32(n as u32)?;
         Ok(BigNumber {
@@ -195,8 +204,9 @@ impl BigNumber {
         match ctx {
             Some(context) => BigNumRef::sqr(&mut bn.openssl_bn, &self.openssl_bn, &mut context.openssl_bn_context)?,
             None => {
-                let mut ctx = BigNumber::new_context()?;
+                let mut ctx = BnCtxPool::take()?;
                 BigNumRef::sqr(&mut bn.openssl_bn, &self.openssl_bn, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
             }
         }
         Ok(bn)
@@ -207,8 +217,9 @@ impl BigNumber {
         match ctx {
             Some(context) => BigNumRef::checked_mul(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &mut context.openssl_bn_context)?,
             None => {
-                let mut ctx = BigNumber::new_context()?;
+                let mut ctx = BnCtxPool::take()?;
                 BigNumRef::checked_mul(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
             }
         }
         Ok(bn)
@@ -219,8 +230,9 @@ impl BigNumber {
         match ctx {
             Some(context) => BigNumRef::mod_mul(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &n.openssl_bn, &mut context.openssl_bn_context)?,
             None => {
-                let mut ctx = BigNumber::new_context()?;
+                let mut ctx = BnCtxPool::take()?;
                 BigNumRef::mod_mul(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &n.openssl_bn, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
             }
         }
         Ok(bn)
@@ -231,8 +243,9 @@ impl BigNumber {
         match ctx {
             Some(context) => BigNumRef::mod_sub(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &n.openssl_bn, &mut context.openssl_bn_context)?,
             None => {
-                let mut ctx = BigNumber::new_context()?;
+                let mut ctx = BnCtxPool::take()?;
                 BigNumRef::mod_sub(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &n.openssl_bn, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
             }
         }
         Ok(bn)
@@ -243,8 +256,9 @@ impl BigNumber {
         match ctx {
             Some(context) => BigNumRef::checked_div(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &mut context.openssl_bn_context)?,
             None => {
-                let mut ctx = BigNumber::new_context()?;
+                let mut ctx = BnCtxPool::take()?;
                 BigNumRef::checked_div(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
             }
         }
         Ok(bn)
@@ -274,8 +288,10 @@ impl BigNumber {
         match ctx {
             Some(context) => self._mod_exp(a, b, context),
             None => {
-                let mut ctx = BigNumber::new_context()?;
-                self._mod_exp(a, b, &mut ctx)
+                let mut ctx = BnCtxPool::take()?;
+                let res = self._mod_exp(a, b, &mut ctx);
+                BnCtxPool::release(ctx);
+                res
             }
         }
     }
@@ -291,13 +307,47 @@ impl BigNumber {
         Ok(bn)
     }
 
+    /// Modular exponentiation in constant time (BN_mod_exp_mont_consttime) for secret exponents.
+    /// Slower than `mod_exp`, so exponentiations with public exponents should use `mod_exp`.
+    /// Modulus must be odd.
+    pub fn mod_exp_consttime(&self, a: &BigNumber, b: &BigNumber, ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        match ctx {
+            Some(context) => self._mod_exp_consttime(a, b, context),
+            None => {
+                let mut ctx = BnCtxPool::take()?;
+                let res = self._mod_exp_consttime(a, b, &mut ctx);
+                BnCtxPool::release(ctx);
+                res
+            }
+        }
+    }
+
+    fn _mod_exp_consttime(&self, a: &BigNumber, b: &BigNumber, ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError> {
+        let mut bn = BigNumber::new()?;
+
+        let mut base = if a.openssl_bn.is_negative() { self.inverse(b, Some(ctx))? } else { self.clone()? };
+        let mut exp = a.set_negative(false)?;
+
+        // BN_mod_exp dispatches to BN_mod_exp_mont_consttime if base or exponent has BN_FLG_CONSTTIME
+        base.openssl_bn.set_const_time();
+        exp.openssl_bn.set_const_time();
+
+        BigNumRef::mod_exp(&mut bn.openssl_bn, &base.openssl_bn, &exp.openssl_bn, &b.openssl_bn, &mut ctx.openssl_bn_context)?;
+
+        base.clear();
+        exp.clear();
+
+        Ok(bn)
+    }
+
     pub fn modulus(&self, a: &BigNumber, ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
         let mut bn = BigNumber::new()?;
         match ctx {
             Some(context) => BigNumRef::nnmod(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &mut context.openssl_bn_context)?,
             None => {
-                let mut ctx = BigNumber::new_context()?;
+                let mut ctx = BnCtxPool::take()?;
                 BigNumRef::nnmod(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
             }
         }
         Ok(bn)
@@ -308,8 +358,9 @@ impl BigNumber {
         match ctx {
             Some(context) => BigNumRef::exp(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &mut context.openssl_bn_context)?,
             None => {
-                let mut ctx = BigNumber::new_context()?;
+                let mut ctx = BnCtxPool::take()?;
                 BigNumRef::exp(&mut bn.openssl_bn, &self.openssl_bn, &a.openssl_bn, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
             }
         }
         Ok(bn)
@@ -320,8 +371,9 @@ impl BigNumber {
         match ctx {
             Some(context) => BigNumRef::mod_inverse(&mut bn.openssl_bn, &self.openssl_bn, &n.openssl_bn, &mut context.openssl_bn_context)?,
             None => {
-                let mut ctx = BigNumber::new_context()?;
+                let mut ctx = BnCtxPool::take()?;
                 BigNumRef::mod_inverse(&mut bn.openssl_bn, &self.openssl_bn, &n.openssl_bn, &mut ctx.openssl_bn_context)?;
+                BnCtxPool::release(ctx);
             }
         }
         Ok(bn)
@@ -378,8 +430,10 @@ impl BigNumber {
         match ctx {
             Some(mut context) => self._mod_div(b, p, &mut context),
             None => {
-                let mut context = BigNumber::new_context()?;
-                self._mod_div(b, p, &mut context)
+                let mut context = BnCtxPool::take()?;
+                let res = self._mod_div(b, p, &mut context);
+                BnCtxPool::release(context);
+                res
             }
         }
     }
@@ -418,6 +472,26 @@ impl BigNumber {
     }
 }
 
+/// Incremental sha256 hasher, produces the same digest as `BigNumber::hash_array`.
+pub struct BigNumberHasher {
+    hasher: Hasher
+}
+
+impl BigNumberHasher {
+    pub fn new() -> Result<BigNumberHasher, IndyCryptoError> {
+        Ok(BigNumberHasher { hasher: Hasher::new(MessageDigest::sha256())? })
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> Result<(), IndyCryptoError> {
+        Ok(self.hasher.update(data)?)
+    }
+
+    pub fn finish(&mut self) -> Result<Vec<u8>, IndyCryptoError> {
+        Ok(self.hasher.finish()?.to_vec())
+    }
+}
+
+/// Signed comparison of big numbers (`BN_cmp`).
 impl Ord for BigNumber {
     fn cmp(&self, other: &BigNumber) -> Ordering {
         self.openssl_bn.cmp(&other.openssl_bn)
@@ -438,36 +512,6 @@ impl PartialEq for BigNumber {
     }
 }
 
-#[cfg(feature = "serialization")]
-impl Serialize for BigNumber {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        serializer.serialize_newtype_struct("BigNumber", &self.to_dec().map_err(SError::custom)?)
-    }
-}
-
-#[cfg(feature = "serialization")]
-impl<'a> Deserialize<'a> for BigNumber {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'a> {
-        struct BigNumberVisitor;
-
-        impl<'a> Visitor<'a> for BigNumberVisitor {
-            type Value = BigNumber;
-
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("expected BigNumber")
-            }
-
-            fn visit_str<E>(self, value: &str) -> Result<BigNumber, E>
-                where E: DError
-            {
-                Ok(BigNumber::from_dec(value).map_err(DError::custom)?)
-            }
-        }
-
-        deserializer.deserialize_str(BigNumberVisitor)
-    }
-}
-
 impl From<ErrorStack> for IndyCryptoError {
     fn from(err: ErrorStack) -> IndyCryptoError {
         // TODO: FIXME: Analyze ErrorStack and split invalid structure errors from other errors
@@ -475,24 +519,10 @@ impl From<ErrorStack> for IndyCryptoError {
     }
 }
 
-impl Default for BigNumber {
-    fn default() -> BigNumber {
-        BigNumber::from_u32(0).unwrap()
-    }
-}
-
-// Constants that are used throughout the code, so avoiding recomputation.
-lazy_static! {
-    pub static ref BIGNUMBER_1: BigNumber = BigNumber::from_u32(1).unwrap();
-    pub static ref BIGNUMBER_2: BigNumber = BigNumber::from_u32(2).unwrap();
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use serde_json;
-
     const RANGE_LEFT: usize = 592;
     const RANGE_RIGHT: usize = 592;
 
@@ -534,20 +564,6 @@ mod tests {
         assert!(v3.is_prime(None).unwrap());
     }
 
-    #[test]
-    fn test_modular_exponentiation() {
-        let base = BigNumber::from_dec("12714671911903680502393098440562958150461307840092575886187217264492970515611166458444182780904860535776274190597528985988632488194981204988199325501696648896748368401254829974173258613724800116424602180755019588176641580062215499750550535543002990347313784260314641340394494547935943176226649412526659864646068220114536172189443925908781755710141006387091748541976715633668919725277837668568166444731358541327097786024076841158424402136565558677098853060675674958695935207345864359540948421232816012865873346545455513695413921957708811080877422273777355768568166638843699798663264533662595755767287970642902713301649").unwrap();
-        let exp = BigNumber::from_dec("13991423645225256679625502829143442357836305738777175327623021076136862973228390317258480888217725740262243618881809894688804251512223982403225288178492105393953431042196371492402144120299046493467608097411259757604892535967240041988260332063962457178993277482991886508015739613530825229685281072180891075265116698114782553748364913010741387964956740720544998915158970813171997488129859542399633104746793770216517872705889857552727967921847493285577238").unwrap();
-        let modulus = BigNumber::from_dec("991272771610724400277702356109350334773782112020672787325464582894874455338156617087078683660308327009158085342465983713825070967004447592080649030930737560915527173820649490032274245863850782844569456999473516497618489127293328524608584652323593452247534656999363158875176879817952982494174728640545484193154314433925648566686738628413929222467005197087738850212963801663981588243042912430590088435419451359859770426041670326127890520192033283832465411962274045956439947646966560440910244870464709982605844468449227905039953511431640780483761563845223213570597106855699997837768334871601402132694515676785338799407204529154456178837013845488372635042715003769626150545960460800980936426723680755798495767188398126674428244764038147226578038085253616108968402209263400729503458144370189359160926796812468410806201905992347006546335038212090539118675048292666041345556742530041533878341459110515497642054583635133581316796089099043782055893003258788369004899742992039315008110063759802733045648131896557338576682560236591353394201381103042167106112201578883917022695113857967398885475101031596068885337186646296664517159150904935112836318654117577507707562065113238913343761942585545093919444150946120523831367132144754209388110483749").unwrap();
-        let n = base.mod_exp(&exp, &modulus, None).unwrap();
-        assert_eq!(n, BigNumber::from_dec("156669382818249607878298589043381544147555658222157929549484054385620519150887267126359684884641035264854247223281407349108771361611707714806192334779156374961296686821846487267487447347213829476609283133961216115764596907219173912888367998704856300105745961091899745329082513615681466199188236178266479183520370119131067362815102553237342546358580424556049196548520326206809677290296313839918774603549816182657993044271509706055893922152644469350618465711055733369291523796837304622919600074130968607301641438272377350795631212741686475924538423333008944556761300787668873766797549942827958501053262330421256183088509761636226277739400954175538503984519144969688787730088704522060486181427528150632576628856946041322195818246199503927686629821338146828603690778689292695518745939007886131151503766930229761608131819298276772877945842806872426029069949874062579870088710097070526608376602732627661781899595747063793310401032556802468649888104062151213860356554306295111191704764944574687548637446778783560586599000631975868701382113259027374431129732911012887214749014288413818636520182416636289308770657630129067046301651835893708731812616847614495049523221056260334965662875649480493232265453415256612460815802528012166114764216881").unwrap());
-
-        let base = BigNumber::from_u32(6).unwrap();
-        let exp = BigNumber::from_u32(5).unwrap().set_negative(true).unwrap();
-        let modulus = BigNumber::from_u32(13).unwrap();
-        assert_eq!(BigNumber::from_u32(7).unwrap(), base.mod_exp(&exp, &modulus, None).unwrap());
-    }
-
     #[test]
     #[ignore]
     fn is_safe_prime_works() {
@@ -563,63 +579,4 @@ mod tests {
         let prime = BigNumber::generate_safe_prime(4096).unwrap();
         assert!(prime.is_safe_prime(None).unwrap());
     }
-
-    #[test]
-    fn decrement_works() {
-        let num = BigNumber::from_u32(1000).unwrap();
-        assert_eq!(num.decrement().unwrap(), num.sub(&BIGNUMBER_1).unwrap());
-    }
-
-    #[test]
-    fn increment_works() {
-        let num = BigNumber::from_u32(1000).unwrap();
-        assert_eq!(num.increment().unwrap(), num.add(&BIGNUMBER_1).unwrap());
-    }
-
-    #[test]
-    fn rshift1_works() {
-        let num = BigNumber::from_u32(1000).unwrap();
-        assert_eq!(num.rshift1().unwrap(), BigNumber::from_u32(500).unwrap());
-    }
-
-    #[test]
-    fn rshift_works() {
-        let num = BigNumber::from_u32(1024).unwrap();
-        assert_eq!(num.rshift(1).unwrap(), BigNumber::from_u32(512).unwrap());
-        assert_eq!(num.rshift(2).unwrap(), BigNumber::from_u32(256).unwrap());
-        assert_eq!(num.rshift(3).unwrap(), BigNumber::from_u32(128).unwrap());
-        assert_eq!(num.rshift(4).unwrap(), BigNumber::from_u32(64).unwrap());
-    }
-
-    #[test]
-    fn lshift1_works() {
-        let num = BigNumber::from_u32(1000).unwrap();
-        assert_eq!(num.lshift1().unwrap(), BigNumber::from_u32(2000).unwrap());
-    }
-
-    #[cfg(feature = "serialization")]
-    #[derive(Serialize, Deserialize)]
-    struct Test {
-        field: BigNumber
-    }
-
-    #[cfg(feature = "serialization")]
-    #[test]
-    fn serialize_works() {
-        let s = Test { field: BigNumber::from_dec("1").unwrap() };
-        let serialized = serde_json::to_string(&s);
-
-        assert!(serialized.is_ok());
-        assert_eq!("{\"field\":\"1\"}", serialized.unwrap());
-    }
-
-    #[cfg(feature = "serialization")]
-    #[test]
-    fn deserialize_works() {
-        let s = "{\"field\":\"1\"}";
-        let bn: Result<Test, _> = serde_json::from_str(&s);
-
-        assert!(bn.is_ok());
-        assert_eq!("1", bn.unwrap().field.to_dec().unwrap());
-    }
 }
@@ -0,0 +1,538 @@
+use errors::IndyCryptoError;
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use rand::{thread_rng, RngCore};
+use sha2::{Digest, Sha256};
+
+use std::cmp::Ord;
+use std::cmp::Ordering;
+
+const MILLER_RABIN_ROUNDS: usize = 20;
+
+lazy_static! {
+    static ref SMALL_PRIMES: Vec<u32> = {
+        let mut primes: Vec<u32> = Vec::new();
+        for n in 2..2048u32 {
+            if primes.iter().take_while(|p| *p * *p <= n).all(|p| n % p != 0) {
+                primes.push(n);
+            }
+        }
+        primes
+    };
+}
+
+/// Context is kept for API compatibility with `openssl` backend, pure Rust arithmetic doesn't need scratch space.
+pub struct BigNumberContext {}
+
+/// Arbitrary precision integer implemented in pure Rust (`num-bigint`).
+///
+/// Used where OpenSSL isn't available (WASM for example) and to run `cl` tests without OpenSSL.
+/// Unlike `openssl` backend operations aren't constant time and `clear` can't overwrite freed limbs,
+/// so this backend must not be used to handle secrets in production.
+#[derive(Debug)]
+pub struct BigNumber {
+    num: BigInt
+}
+
+impl BigNumber {
+    pub fn new_context() -> Result<BigNumberContext, IndyCryptoError> {
+        Ok(BigNumberContext {})
+    }
+
+    pub fn new() -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigInt::zero() })
+    }
+
+    pub fn generate_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
+        loop {
+            let candidate = BigNumber::_prime_candidate(size)?;
+            if candidate._is_probable_prime() {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    pub fn generate_safe_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
+        loop {
+            let q = BigNumber::_prime_candidate(size)?;
+            let p = BigNumber { num: (&q.num << 1) + BigInt::one() };
+
+            if !q._has_small_factor() && !p._has_small_factor() && q._is_probable_prime() && p._is_probable_prime() {
+                return Ok(p);
+            }
+        }
+    }
+
+    pub fn generate_prime_in_range(start: &BigNumber, end: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        let mut prime;
+        let mut iteration = 0;
+        let sub = end.sub(start)?;
+
+        loop {
+            prime = sub.rand_range()?;
+            prime = prime.add(start)?;
+
+            if prime.is_prime(None)? {
+                debug!("Found prime in {} iteration", iteration);
+                break;
+            }
+            iteration += 1;
+        }
+
+        Ok(prime)
+    }
+
+    pub fn is_prime(&self, _ctx: Option<&mut BigNumberContext>) -> Result<bool, IndyCryptoError> {
+        Ok(self._is_probable_prime())
+    }
+
+    pub fn is_safe_prime(&self, ctx: Option<&mut BigNumberContext>) -> Result<bool, IndyCryptoError> {
+        // according to https://eprint.iacr.org/2003/186.pdf
+        // a safe prime is congruent to 2 mod 3
+
+        // a safe prime satisfies (p-1)/2 is prime. Since a
+        // prime is odd, We just need to divide by 2
+        Ok(
+            self.modulus(&BigNumber::from_u32(3)?, None)? == BigNumber::from_u32(2)? &&
+            self.is_prime(ctx)? &&
+            self.rshift1()?.is_prime(None)?
+        )
+    }
+
+    pub fn rand(size: usize) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigInt::from_biguint(Sign::Plus, BigNumber::_random_bits(size)) })
+    }
+
+    pub fn rand_range(&self) -> Result<BigNumber, IndyCryptoError> {
+        if !self.num.is_positive() {
+            return Err(IndyCryptoError::InvalidStructure("Invalid range for random number".to_string()));
+        }
+
+        let size = self.num.bits();
+        loop {
+            let bn = BigInt::from_biguint(Sign::Plus, BigNumber::_random_bits(size));
+            if bn < self.num {
+                return Ok(BigNumber { num: bn });
+            }
+        }
+    }
+
+    pub fn num_bits(&self) -> Result<i32, IndyCryptoError> {
+        Ok(self.num.bits() as i32)
+    }
+
+    pub fn is_bit_set(&self, n: i32) -> Result<bool, IndyCryptoError> {
+        let n = n as usize;
+        let bytes = self.to_bytes()?;
+
+        if n / 8 >= bytes.len() {
+            return Ok(false);
+        }
+
+        Ok((bytes[bytes.len() - 1 - n / 8] >> (n % 8)) & 1 == 1)
+    }
+
+    pub fn set_bit(&mut self, n: i32) -> Result<&mut BigNumber, IndyCryptoError> {
+        let magnitude = self._magnitude() | (BigUint::one() << n as usize);
+        self.num = BigNumber::_signed(self.num.sign(), magnitude);
+        Ok(self)
+    }
+
+    /// Resets value to zero. `num-bigint` doesn't give access to its limbs, so old value isn't overwritten in memory.
+    pub fn clear(&mut self) {
+        self.num = BigInt::zero()
+    }
+
+    pub fn from_u32(n: usize) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigInt::from(n as u32) })
+    }
+
+    pub fn from_dec(dec: &str) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::_parse(dec, 10)
+    }
+
+    pub fn from_hex(hex: &str) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::_parse(hex, 16)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigInt::from_bytes_be(Sign::Plus, bytes) })
+    }
+
+    pub fn to_dec(&self) -> Result<String, IndyCryptoError> {
+        Ok(self.num.to_str_radix(10))
+    }
+
+    pub fn to_hex(&self) -> Result<String, IndyCryptoError> {
+        Ok(self.num.to_str_radix(16).to_uppercase())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        if self.num.is_zero() {
+            return Ok(Vec::new());
+        }
+        Ok(self.num.to_bytes_be().1)
+    }
+
+    pub fn hash(data: &[u8]) -> Result<Vec<u8>, IndyCryptoError> {
+        let mut sha256 = Sha256::default();
+        sha256.input(data);
+        Ok(sha256.result().to_vec())
+    }
+
+    pub fn add(&self, a: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: &self.num + &a.num })
+    }
+
+    pub fn sub(&self, a: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: &self.num - &a.num })
+    }
+
+    pub fn sqr(&self, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: &self.num * &self.num })
+    }
+
+    pub fn mul(&self, a: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: &self.num * &a.num })
+    }
+
+    pub fn mod_mul(&self, a: &BigNumber, n: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigNumber::_nnmod(&(&self.num * &a.num), &n.num)? })
+    }
+
+    pub fn mod_sub(&self, a: &BigNumber, n: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigNumber::_nnmod(&(&self.num - &a.num), &n.num)? })
+    }
+
+    pub fn div(&self, a: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        if a.num.is_zero() {
+            return Err(IndyCryptoError::InvalidStructure("Division by zero".to_string()));
+        }
+        Ok(BigNumber { num: &self.num / &a.num })
+    }
+
+    pub fn add_word(&mut self, w: u32) -> Result<&mut BigNumber, IndyCryptoError> {
+        self.num = &self.num + BigInt::from(w);
+        Ok(self)
+    }
+
+    pub fn sub_word(&mut self, w: u32) -> Result<&mut BigNumber, IndyCryptoError> {
+        self.num = &self.num - BigInt::from(w);
+        Ok(self)
+    }
+
+    pub fn mul_word(&mut self, w: u32) -> Result<&mut BigNumber, IndyCryptoError> {
+        self.num = &self.num * BigInt::from(w);
+        Ok(self)
+    }
+
+    pub fn div_word(&mut self, w: u32) -> Result<&mut BigNumber, IndyCryptoError> {
+        if w == 0 {
+            return Err(IndyCryptoError::InvalidStructure("Division by zero".to_string()));
+        }
+        self.num = &self.num / BigInt::from(w);
+        Ok(self)
+    }
+
+    pub fn mod_exp(&self, a: &BigNumber, b: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        let base = if a.num.is_negative() { self.inverse(b, None)? } else { self.modulus(b, None)? };
+        let modulus = b._magnitude();
+
+        Ok(BigNumber {
+            num: BigInt::from_biguint(Sign::Plus, base._magnitude().modpow(&a._magnitude(), &modulus))
+        })
+    }
+
+    /// Same as `mod_exp`: `num-bigint` has no constant time exponentiation, see `BigNumber` docs.
+    pub fn mod_exp_consttime(&self, a: &BigNumber, b: &BigNumber, ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        self.mod_exp(a, b, ctx)
+    }
+
+    pub fn modulus(&self, a: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigNumber::_nnmod(&self.num, &a.num)? })
+    }
+
+    pub fn exp(&self, a: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        let exp = a.num.to_usize()
+            .ok_or(IndyCryptoError::InvalidStructure("Invalid exponent".to_string()))?;
+        Ok(BigNumber { num: ::num_traits::pow(self.num.clone(), exp) })
+    }
+
+    pub fn inverse(&self, n: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        let modulus = BigInt::from_biguint(Sign::Plus, n._magnitude());
+        let (mut r0, mut r1) = (modulus.clone(), BigNumber::_nnmod(&self.num, &modulus)?);
+        let (mut t0, mut t1) = (BigInt::zero(), BigInt::one());
+
+        while !r1.is_zero() {
+            let q = &r0 / &r1;
+            let r = &r0 - &q * &r1;
+            let t = &t0 - &q * &t1;
+            r0 = r1;
+            r1 = r;
+            t0 = t1;
+            t1 = t;
+        }
+
+        if !r0.is_one() {
+            return Err(IndyCryptoError::InvalidStructure("Value is not invertible".to_string()));
+        }
+
+        Ok(BigNumber { num: BigNumber::_nnmod(&t0, &modulus)? })
+    }
+
+    pub fn set_negative(&self, negative: bool) -> Result<BigNumber, IndyCryptoError> {
+        let sign = if negative { Sign::Minus } else { Sign::Plus };
+        Ok(BigNumber { num: BigNumber::_signed(sign, self._magnitude()) })
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.num.is_negative()
+    }
+
+    pub fn increment(&self) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: &self.num + BigInt::one() })
+    }
+
+    pub fn decrement(&self) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: &self.num - BigInt::one() })
+    }
+
+    pub fn lshift1(&self) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigNumber::_signed(self.num.sign(), self._magnitude() << 1) })
+    }
+
+    pub fn rshift1(&self) -> Result<BigNumber, IndyCryptoError> {
+        self.rshift(1)
+    }
+
+    pub fn rshift(&self, n: i32) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: BigNumber::_signed(self.num.sign(), self._magnitude() >> n as usize) })
+    }
+
+    pub fn mod_div(&self, b: &BigNumber, p: &BigNumber, _ctx: Option<&mut BigNumberContext>) -> Result<BigNumber, IndyCryptoError> {
+        //(a * (1/b mod p) mod p)
+        self.mod_mul(&b.inverse(p, None)?, p, None)
+    }
+
+    pub fn random_qr(n: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        let qr = n
+            .rand_range()?
+            .sqr(None)?
+            .modulus(&n, None)?;
+        Ok(qr)
+    }
+
+    pub fn clone(&self) -> Result<BigNumber, IndyCryptoError> {
+        Ok(BigNumber { num: self.num.clone() })
+    }
+
+    pub fn hash_array(nums: &Vec<Vec<u8>>) -> Result<Vec<u8>, IndyCryptoError> {
+        let mut sha256 = Sha256::default();
+
+        for num in nums.iter() {
+            sha256.input(&num);
+        }
+
+        Ok(sha256.result().to_vec())
+    }
+
+    fn _parse(value: &str, radix: u32) -> Result<BigNumber, IndyCryptoError> {
+        let num = BigInt::parse_bytes(value.as_bytes(), radix)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Invalid BigNumber value: {:?}", value)))?;
+        Ok(BigNumber { num })
+    }
+
+    fn _magnitude(&self) -> BigUint {
+        BigUint::from_bytes_be(&self.num.to_bytes_be().1)
+    }
+
+    fn _signed(sign: Sign, magnitude: BigUint) -> BigInt {
+        match sign {
+            Sign::Minus => BigInt::from_biguint(Sign::Minus, magnitude),
+            _ => BigInt::from_biguint(Sign::Plus, magnitude)
+        }
+    }
+
+    /// Non negative remainder (`BN_nnmod`), sign of `n` is ignored.
+    fn _nnmod(a: &BigInt, n: &BigInt) -> Result<BigInt, IndyCryptoError> {
+        if n.is_zero() {
+            return Err(IndyCryptoError::InvalidStructure("Division by zero".to_string()));
+        }
+        Ok(a.mod_floor(&n.abs()))
+    }
+
+    fn _random_bits(size: usize) -> BigUint {
+        let mut bytes = vec![0u8; (size + 7) / 8];
+        thread_rng().fill_bytes(&mut bytes);
+
+        if size % 8 != 0 {
+            bytes[0] &= (1u8 << (size % 8)) - 1;
+        }
+
+        BigUint::from_bytes_be(&bytes)
+    }
+
+    /// Random odd number of exactly `size` bits with two top bits set, as `BN_generate_prime_ex` does.
+    fn _prime_candidate(size: usize) -> Result<BigNumber, IndyCryptoError> {
+        if size < 2 {
+            return Err(IndyCryptoError::InvalidStructure("Prime size is too small".to_string()));
+        }
+
+        let top = (BigUint::one() << (size - 1)) | (BigUint::one() << (size - 2));
+        let num = BigNumber::_random_bits(size) | top | BigUint::one();
+        Ok(BigNumber { num: BigInt::from_biguint(Sign::Plus, num) })
+    }
+
+    fn _has_small_factor(&self) -> bool {
+        SMALL_PRIMES.iter()
+            .map(|p| BigInt::from(*p))
+            .any(|p| p < self.num && self.num.is_multiple_of(&p))
+    }
+
+    /// Trial division by small primes followed by Miller-Rabin test with random bases.
+    fn _is_probable_prime(&self) -> bool {
+        let two = BigInt::from(2u32);
+
+        if self.num < two {
+            return false;
+        }
+
+        if self.num.bits() <= 11 {
+            return self.num.to_u32().map(|n| SMALL_PRIMES.contains(&n)).unwrap_or(false);
+        }
+
+        if self._has_small_factor() {
+            return false;
+        }
+
+        let n = self._magnitude();
+        let n_minus_one = &n - BigUint::one();
+        let bases_range = BigNumber { num: BigInt::from_biguint(Sign::Plus, &n - BigUint::from(3u32)) };
+
+        let mut d = n_minus_one.clone();
+        let mut s = 0;
+        while d.is_even() {
+            d = d >> 1;
+            s += 1;
+        }
+
+        'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+            let a = match bases_range.rand_range() {
+                Ok(a) => a._magnitude() + BigUint::from(2u32),
+                Err(_) => return false
+            };
+
+            let mut x = a.modpow(&d, &n);
+            if x.is_one() || x == n_minus_one {
+                continue;
+            }
+
+            for _ in 1..s {
+                x = x.modpow(&BigUint::from(2u32), &n);
+                if x == n_minus_one {
+                    continue 'witness;
+                }
+            }
+
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Incremental sha256 hasher, produces the same digest as `BigNumber::hash_array`.
+pub struct BigNumberHasher {
+    hasher: Sha256
+}
+
+impl BigNumberHasher {
+    pub fn new() -> Result<BigNumberHasher, IndyCryptoError> {
+        Ok(BigNumberHasher { hasher: Sha256::default() })
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> Result<(), IndyCryptoError> {
+        self.hasher.input(data);
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<Vec<u8>, IndyCryptoError> {
+        let hasher = ::std::mem::replace(&mut self.hasher, Sha256::default());
+        Ok(hasher.result().to_vec())
+    }
+}
+
+/// Signed comparison of big numbers.
+impl Ord for BigNumber {
+    fn cmp(&self, other: &BigNumber) -> Ordering {
+        self.num.cmp(&other.num)
+    }
+}
+
+impl Eq for BigNumber {}
+
+impl PartialOrd for BigNumber {
+    fn partial_cmp(&self, other: &BigNumber) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for BigNumber {
+    fn eq(&self, other: &BigNumber) -> bool {
+        self.num == other.num
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_prime_works() {
+        let primes: Vec<u64> = vec![2, 23, 31, 2039, 42885908609, 24473809133, 47055833459];
+        for pr in primes {
+            let num = BigNumber::from_dec(&pr.to_string()).unwrap();
+            assert!(num.is_prime(None).unwrap());
+        }
+
+        for composite in ["0", "1", "36", "2047", "4235339", "3215031751"].iter() {
+            assert!(!BigNumber::from_dec(composite).unwrap().is_prime(None).unwrap());
+        }
+    }
+
+    #[test]
+    fn generate_safe_prime_works() {
+        let prime = BigNumber::generate_safe_prime(64).unwrap();
+        assert_eq!(65, prime.num_bits().unwrap());
+        assert!(prime.is_safe_prime(None).unwrap());
+    }
+
+    #[test]
+    fn conversions_work_for_zero_and_negative() {
+        let zero = BigNumber::from_u32(0).unwrap();
+        assert!(zero.to_bytes().unwrap().is_empty());
+        assert_eq!(zero, BigNumber::from_bytes(&[]).unwrap());
+        assert_eq!("0", zero.to_dec().unwrap());
+
+        let negative = BigNumber::from_dec("-255").unwrap();
+        assert_eq!("-FF", negative.to_hex().unwrap());
+        assert_eq!(vec![255], negative.to_bytes().unwrap());
+        assert_eq!(BigNumber::from_dec("-127").unwrap(), negative.rshift1().unwrap());
+        assert_eq!(BigNumber::from_u32(2).unwrap(), negative.modulus(&BigNumber::from_u32(257).unwrap(), None).unwrap());
+    }
+
+    #[test]
+    fn hasher_works() {
+        let data = vec![vec![1u8, 2, 3], vec![4u8, 5]];
+
+        let mut hasher = BigNumberHasher::new().unwrap();
+        hasher.update(&data[0]).unwrap();
+        hasher.update(&data[1]).unwrap();
+
+        assert_eq!(BigNumber::hash_array(&data).unwrap(), hasher.finish().unwrap());
+        assert_eq!(BigNumber::hash(&[]).unwrap(), hasher.finish().unwrap());
+    }
+}
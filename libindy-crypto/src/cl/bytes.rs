@@ -0,0 +1,488 @@
+use bn::BigNumber;
+use cl::*;
+use errors::IndyCryptoError;
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Version of binary layout of `Proof`, written as the first byte. Must be bumped on every layout change.
+pub const PROOF_BYTES_LAYOUT_VERSION: u8 = 6;
+
+/// Binary layout of `Proof`. All integers are big-endian, variable length items are prefixed by `u32` length:
+///
+/// * string - length, UTF-8 bytes
+/// * big number - sign byte (0 or 1), length, magnitude bytes
+/// * map - entries count, (string key, big number value) entries sorted by key
+/// * list - items count, items
+///
+/// Proof is written as layout version byte (`PROOF_BYTES_LAYOUT_VERSION`), `ver`, `proofs` (`key_id`, `primary_proof`),
+/// `aggregated_proof` (`c_hash`, `c_list`), `common_m` and `predicate_security`.
+/// Primary proof is written as `eq_proof` (`revealed_attrs`, `a_prime`, `e`, `v`, `m`, `revealed_commitments`),
+/// `ne_proofs`, `linear_ne_proofs` (`predicate` coefficients, type and value, `ne_proof`) and `set_membership_proofs`.
+pub fn proof_to_bytes(proof: &Proof) -> Result<Vec<u8>, IndyCryptoError> {
+    let mut writer = ByteWriter::new();
+
+    writer.write_u8(PROOF_BYTES_LAYOUT_VERSION);
+    writer.write_u32(proof.ver.value());
+
+    writer.write_len(proof.proofs.len())?;
+    for sub_proof in proof.proofs.iter() {
+        writer.write_str(&sub_proof.key_id)?;
+        _write_primary_proof(&mut writer, &sub_proof.primary_proof)?;
+    }
+
+    writer.write_bn(&proof.aggregated_proof.c_hash)?;
+    writer.write_len(proof.aggregated_proof.c_list.len())?;
+    for c in proof.aggregated_proof.c_list.iter() {
+        writer.write_bytes(c)?;
+    }
+
+    writer.write_bn_map(proof.common_m.iter())?;
+    writer.write_u32(proof.predicate_security.value());
+
+    Ok(writer.bytes)
+}
+
+pub fn proof_from_bytes(bytes: &[u8]) -> Result<Proof, IndyCryptoError> {
+    let mut reader = ByteReader::new(bytes);
+
+    let layout_version = reader.read_u8()?;
+    if layout_version != PROOF_BYTES_LAYOUT_VERSION {
+        return Err(IndyCryptoError::InvalidStructure(format!("Unsupported proof bytes layout version: {}", layout_version)));
+    }
+
+    let ver = SerializationVersion::new(reader.read_u32()?)?;
+
+    let mut proofs = Vec::new();
+    for _ in 0..reader.read_u32()? {
+        let key_id = reader.read_str()?;
+        proofs.push(SubProof { key_id, primary_proof: _read_primary_proof(&mut reader)? });
+    }
+
+    let c_hash = reader.read_bn()?;
+    let mut c_list = Vec::new();
+    for _ in 0..reader.read_u32()? {
+        c_list.push(reader.read_bytes()?.to_vec());
+    }
+
+    let common_m = reader.read_bn_map::<BTreeMap<String, BigNumber>>()?;
+    let predicate_security = PredicateSecurityLevel::new(reader.read_u32()?)?;
+
+    reader.finish()?;
+
+    Ok(Proof {
+        ver,
+        proofs,
+        aggregated_proof: AggregatedProof { c_hash, c_list },
+        common_m,
+        predicate_security
+    })
+}
+
+/// Compact layout of `CredentialPrimaryPublicKey`: `n`, `s`, `z` as length-prefixed big-endian bytes,
+/// count of `r` entries and `r` entries (attr name, value) sorted by attr name.
+pub fn primary_public_key_to_compact_bytes(p_pub_key: &CredentialPrimaryPublicKey) -> Result<Vec<u8>, IndyCryptoError> {
+    let mut writer = ByteWriter::new();
+
+    writer.write_bytes(&p_pub_key.n.to_bytes()?)?;
+    writer.write_bytes(&p_pub_key.s.to_bytes()?)?;
+    writer.write_bytes(&p_pub_key.z.to_bytes()?)?;
+
+    let r = p_pub_key.r.iter().collect::<BTreeMap<&String, &BigNumber>>();
+    writer.write_len(r.len())?;
+    for (attr_name, value) in r {
+        writer.write_str(attr_name)?;
+        writer.write_bytes(&value.to_bytes()?)?;
+    }
+
+    Ok(writer.bytes)
+}
+
+pub fn primary_public_key_from_compact_bytes(bytes: &[u8]) -> Result<CredentialPrimaryPublicKey, IndyCryptoError> {
+    let mut reader = ByteReader::new(bytes);
+
+    let n = BigNumber::from_bytes(reader.read_bytes()?)?;
+    let s = BigNumber::from_bytes(reader.read_bytes()?)?;
+    let z = BigNumber::from_bytes(reader.read_bytes()?)?;
+
+    let mut r = HashMap::new();
+    for _ in 0..reader.read_u32()? {
+        let attr_name = reader.read_str()?;
+        let value = BigNumber::from_bytes(reader.read_bytes()?)?;
+        if r.insert_bn(attr_name.clone(), value) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Duplicated key: {}", attr_name)));
+        }
+    }
+
+    reader.finish()?;
+
+    let pub_key = CredentialPrimaryPublicKey { n, s, r, z };
+    pub_key.check_structure()?;
+
+    Ok(pub_key)
+}
+
+fn _write_primary_proof(writer: &mut ByteWriter, primary_proof: &PrimaryProof) -> Result<(), IndyCryptoError> {
+    let eq_proof = &primary_proof.eq_proof;
+    writer.write_bn_map(eq_proof.revealed_attrs.iter())?;
+    writer.write_bn(&eq_proof.a_prime)?;
+    writer.write_bn(&eq_proof.e)?;
+    writer.write_bn(&eq_proof.v)?;
+    writer.write_bn_map(eq_proof.m.iter())?;
+
+    writer.write_len(eq_proof.revealed_commitments.len())?;
+    for (attr_name, revealed_commitment) in eq_proof.revealed_commitments.iter() {
+        writer.write_str(attr_name)?;
+        writer.write_bn(&revealed_commitment.commitment)?;
+        writer.write_bn(&revealed_commitment.salt)?;
+    }
+
+    writer.write_len(primary_proof.ne_proofs.len())?;
+    for ne_proof in primary_proof.ne_proofs.iter() {
+        _write_ne_proof(writer, ne_proof)?;
+    }
+
+    writer.write_len(primary_proof.linear_ne_proofs.len())?;
+    for linear_ne_proof in primary_proof.linear_ne_proofs.iter() {
+        writer.write_len(linear_ne_proof.predicate.coeffs.len())?;
+        for (attr_name, coeff) in linear_ne_proof.predicate.coeffs.iter() {
+            writer.write_str(attr_name)?;
+            writer.write_u32(*coeff as u32);
+        }
+        writer.write_u8(_predicate_type_to_u8(&linear_ne_proof.predicate.p_type)?);
+        writer.write_u32(linear_ne_proof.predicate.value as u32);
+
+        _write_ne_proof(writer, &linear_ne_proof.ne_proof)?;
+    }
+
+    writer.write_len(primary_proof.set_membership_proofs.len())?;
+    for set_membership_proof in primary_proof.set_membership_proofs.iter() {
+        writer.write_str(&set_membership_proof.attr_name)?;
+        writer.write_bn(&set_membership_proof.t)?;
+        writer.write_bn(&set_membership_proof.r)?;
+        writer.write_bn_list(&set_membership_proof.c)?;
+        writer.write_bn_list(&set_membership_proof.s)?;
+    }
+
+    Ok(())
+}
+
+fn _read_primary_proof(reader: &mut ByteReader) -> Result<PrimaryProof, IndyCryptoError> {
+    let revealed_attrs = reader.read_bn_map()?;
+    let a_prime = reader.read_bn()?;
+    let e = reader.read_bn()?;
+    let v = reader.read_bn()?;
+    let m = reader.read_bn_map()?;
+
+    let mut revealed_commitments = BTreeMap::new();
+    for _ in 0..reader.read_u32()? {
+        let attr_name = reader.read_str()?;
+        let revealed_commitment = PrimaryRevealedCommitmentProof {
+            commitment: reader.read_bn()?,
+            salt: reader.read_bn()?
+        };
+        if revealed_commitments.insert(attr_name.clone(), revealed_commitment).is_some() {
+            return Err(IndyCryptoError::InvalidStructure(format!("Duplicated key: {}", attr_name)));
+        }
+    }
+
+    let eq_proof = PrimaryEqualProof { revealed_attrs, a_prime, e, v, m, revealed_commitments };
+
+    let mut ne_proofs = Vec::new();
+    for _ in 0..reader.read_u32()? {
+        ne_proofs.push(_read_ne_proof(reader)?);
+    }
+
+    let mut linear_ne_proofs = Vec::new();
+    for _ in 0..reader.read_u32()? {
+        let mut coeffs = BTreeMap::new();
+        for _ in 0..reader.read_u32()? {
+            let attr_name = reader.read_str()?;
+            let coeff = reader.read_u32()? as i32;
+            if coeffs.insert(attr_name.clone(), coeff).is_some() {
+                return Err(IndyCryptoError::InvalidStructure(format!("Duplicated key: {}", attr_name)));
+            }
+        }
+        let p_type = _predicate_type_from_u8(reader.read_u8()?)?;
+        let value = reader.read_u32()? as i32;
+
+        linear_ne_proofs.push(PrimaryLinearPredicateProof {
+            predicate: LinearPredicate { coeffs, p_type, value },
+            ne_proof: _read_ne_proof(reader)?
+        });
+    }
+
+    let mut set_membership_proofs = Vec::new();
+    for _ in 0..reader.read_u32()? {
+        set_membership_proofs.push(PrimarySetMembershipProof {
+            attr_name: reader.read_str()?,
+            t: reader.read_bn()?,
+            r: reader.read_bn()?,
+            c: reader.read_bn_list()?,
+            s: reader.read_bn_list()?
+        });
+    }
+
+    Ok(PrimaryProof { eq_proof, ne_proofs, linear_ne_proofs, set_membership_proofs })
+}
+
+fn _write_ne_proof(writer: &mut ByteWriter, ne_proof: &PrimaryPredicateInequalityProof) -> Result<(), IndyCryptoError> {
+    writer.write_bn_map(ne_proof.u.iter())?;
+    writer.write_bn_map(ne_proof.r.iter())?;
+    writer.write_bn(&ne_proof.mj)?;
+    writer.write_bn(&ne_proof.alpha)?;
+    writer.write_bn_map(ne_proof.t.iter())?;
+
+    writer.write_str(&ne_proof.predicate.attr_name)?;
+    writer.write_u8(_predicate_type_to_u8(&ne_proof.predicate.p_type)?);
+    writer.write_u32(ne_proof.predicate.value as u32);
+
+    Ok(())
+}
+
+fn _read_ne_proof(reader: &mut ByteReader) -> Result<PrimaryPredicateInequalityProof, IndyCryptoError> {
+    let u = reader.read_bn_map()?;
+    let r = reader.read_bn_map()?;
+    let mj = reader.read_bn()?;
+    let alpha = reader.read_bn()?;
+    let t = reader.read_bn_map()?;
+
+    let attr_name = reader.read_str()?;
+    let p_type = _predicate_type_from_u8(reader.read_u8()?)?;
+    let value = reader.read_u32()? as i32;
+
+    Ok(PrimaryPredicateInequalityProof { u, r, mj, alpha, t, predicate: Predicate { attr_name, p_type, value } })
+}
+
+// Only inequality predicate types are encoded: EQ predicates are proved by equality proof and never get into ne proofs
+fn _predicate_type_to_u8(p_type: &PredicateType) -> Result<u8, IndyCryptoError> {
+    match *p_type {
+        PredicateType::GE => Ok(0),
+        PredicateType::LE => Ok(1),
+        PredicateType::GT => Ok(2),
+        PredicateType::LT => Ok(3),
+        PredicateType::EQ => Err(IndyCryptoError::InvalidStructure("EQ predicate can't be proved by inequality proof".to_string()))
+    }
+}
+
+fn _predicate_type_from_u8(p_type: u8) -> Result<PredicateType, IndyCryptoError> {
+    match p_type {
+        0 => Ok(PredicateType::GE),
+        1 => Ok(PredicateType::LE),
+        2 => Ok(PredicateType::GT),
+        3 => Ok(PredicateType::LT),
+        p_type => Err(IndyCryptoError::InvalidStructure(format!("Invalid predicate type: {}", p_type)))
+    }
+}
+
+struct ByteWriter {
+    bytes: Vec<u8>
+}
+
+impl ByteWriter {
+    fn new() -> ByteWriter {
+        ByteWriter { bytes: Vec::new() }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn write_len(&mut self, len: usize) -> Result<(), IndyCryptoError> {
+        if len > u32::max_value() as usize {
+            return Err(IndyCryptoError::InvalidStructure(format!("Length {} is too big", len)));
+        }
+        self.write_u32(len as u32);
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), IndyCryptoError> {
+        self.write_len(bytes.len())?;
+        self.bytes.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<(), IndyCryptoError> {
+        self.write_bytes(value.as_bytes())
+    }
+
+    fn write_bn(&mut self, value: &BigNumber) -> Result<(), IndyCryptoError> {
+        self.write_u8(value.is_negative() as u8);
+        self.write_bytes(&value.to_bytes()?)
+    }
+
+    fn write_bn_list(&mut self, values: &[BigNumber]) -> Result<(), IndyCryptoError> {
+        self.write_len(values.len())?;
+        for value in values {
+            self.write_bn(value)?;
+        }
+        Ok(())
+    }
+
+    /// Writes entries sorted by key, so result doesn't depend on `HashMap` iteration order.
+    fn write_bn_map<'a, I>(&mut self, entries: I) -> Result<(), IndyCryptoError>
+        where I: Iterator<Item=(&'a String, &'a BigNumber)> {
+        let entries = entries.collect::<BTreeMap<&String, &BigNumber>>();
+
+        self.write_len(entries.len())?;
+        for (key, value) in entries {
+            self.write_str(key)?;
+            self.write_bn(value)?;
+        }
+        Ok(())
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8]
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { bytes }
+    }
+
+    fn _take(&mut self, len: usize) -> Result<&'a [u8], IndyCryptoError> {
+        if len > self.bytes.len() {
+            return Err(IndyCryptoError::InvalidStructure("Unexpected end of bytes".to_string()));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, IndyCryptoError> {
+        Ok(self._take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, IndyCryptoError> {
+        let mut value = [0u8; 4];
+        value.copy_from_slice(self._take(4)?);
+        Ok(u32::from_be_bytes(value))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], IndyCryptoError> {
+        let len = self.read_u32()? as usize;
+        self._take(len)
+    }
+
+    fn read_str(&mut self) -> Result<String, IndyCryptoError> {
+        String::from_utf8(self.read_bytes()?.to_vec())
+            .map_err(|err| IndyCryptoError::InvalidStructure(format!("Invalid UTF-8 string: {}", err)))
+    }
+
+    fn read_bn(&mut self) -> Result<BigNumber, IndyCryptoError> {
+        let negative = match self.read_u8()? {
+            0 => false,
+            1 => true,
+            sign => return Err(IndyCryptoError::InvalidStructure(format!("Invalid big number sign: {}", sign)))
+        };
+        BigNumber::from_bytes(self.read_bytes()?)?.set_negative(negative)
+    }
+
+    fn read_bn_list(&mut self) -> Result<Vec<BigNumber>, IndyCryptoError> {
+        let mut values = Vec::new();
+        for _ in 0..self.read_u32()? {
+            values.push(self.read_bn()?);
+        }
+        Ok(values)
+    }
+
+    fn read_bn_map<M>(&mut self) -> Result<M, IndyCryptoError> where M: BnMap {
+        let mut map = M::default();
+        for _ in 0..self.read_u32()? {
+            let key = self.read_str()?;
+            let value = self.read_bn()?;
+            if map.insert_bn(key.clone(), value) {
+                return Err(IndyCryptoError::InvalidStructure(format!("Duplicated key: {}", key)));
+            }
+        }
+        Ok(map)
+    }
+
+    fn finish(self) -> Result<(), IndyCryptoError> {
+        if !self.bytes.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure(format!("{} unexpected trailing bytes", self.bytes.len())));
+        }
+        Ok(())
+    }
+}
+
+/// Maps of big numbers used in `Proof` (both `HashMap` and `BTreeMap` are used).
+trait BnMap: Default {
+    /// Returns true if key was already present.
+    fn insert_bn(&mut self, key: String, value: BigNumber) -> bool;
+}
+
+impl BnMap for HashMap<String, BigNumber> {
+    fn insert_bn(&mut self, key: String, value: BigNumber) -> bool {
+        self.insert(key, value).is_some()
+    }
+}
+
+impl BnMap for BTreeMap<String, BigNumber> {
+    fn insert_bn(&mut self, key: String, value: BigNumber) -> bool {
+        self.insert(key, value).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _write_eq_proof_head(writer: &mut ByteWriter) {
+        writer.write_len(0).unwrap();
+        writer.write_bn(&BigNumber::from_u32(1).unwrap()).unwrap();
+        writer.write_bn(&BigNumber::from_u32(2).unwrap()).unwrap();
+        writer.write_bn(&BigNumber::from_u32(3).unwrap()).unwrap();
+        writer.write_len(0).unwrap();
+    }
+
+    #[test]
+    fn read_primary_proof_works_for_duplicated_revealed_commitment() {
+        let mut writer = ByteWriter::new();
+        _write_eq_proof_head(&mut writer);
+        writer.write_len(2).unwrap();
+        for _ in 0..2 {
+            writer.write_str("age").unwrap();
+            writer.write_bn(&BigNumber::from_u32(4).unwrap()).unwrap();
+            writer.write_bn(&BigNumber::from_u32(5).unwrap()).unwrap();
+        }
+
+        let res = _read_primary_proof(&mut ByteReader::new(&writer.bytes));
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn read_primary_proof_works_for_duplicated_linear_predicate_coeff() {
+        let mut writer = ByteWriter::new();
+        _write_eq_proof_head(&mut writer);
+        writer.write_len(0).unwrap();
+        writer.write_len(0).unwrap();
+        writer.write_len(1).unwrap();
+        writer.write_len(2).unwrap();
+        for _ in 0..2 {
+            writer.write_str("age").unwrap();
+            writer.write_u32(1);
+        }
+
+        let res = _read_primary_proof(&mut ByteReader::new(&writer.bytes));
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn read_bn_map_works_for_duplicated_key() {
+        let mut writer = ByteWriter::new();
+        writer.write_len(2).unwrap();
+        for _ in 0..2 {
+            writer.write_str("age").unwrap();
+            writer.write_bn(&BigNumber::from_u32(1).unwrap()).unwrap();
+        }
+
+        let res = ByteReader::new(&writer.bytes).read_bn_map::<BTreeMap<String, BigNumber>>();
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+}
@@ -21,3 +21,14 @@ pub fn get_pedersen_commitment(gen_1: &BigNumber, m: &BigNumber,
                  modulus, Some(ctx))?;
     Ok(commitment)
 }
+
+/// Same as `get_pedersen_commitment`, but uses constant time exponentiation.
+/// Must be used when `m` or `r` is secret (prover side).
+pub fn get_pedersen_commitment_consttime(gen_1: &BigNumber, m: &BigNumber,
+                                         gen_2: &BigNumber, r: &BigNumber,
+                                         modulus: &BigNumber, ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError> {
+    let commitment = gen_1.mod_exp_consttime(m, modulus, Some(ctx))?
+        .mod_mul(&gen_2.mod_exp_consttime(r, modulus, Some(ctx))?,
+                 modulus, Some(ctx))?;
+    Ok(commitment)
+}
@@ -4,6 +4,7 @@ pub const LARGE_MASTER_SECRET: usize = 256;
 pub const LARGE_E_START: usize = 596;
 pub const LARGE_E_END_RANGE: usize = 119;
 pub const LARGE_PRIME: usize = 1024;
+pub const MIN_MODULUS_BITS: i32 = 2 * LARGE_PRIME as i32 - 1; // n = p * q of two LARGE_PRIME bits safe primes
 pub const LARGE_VPRIME: usize = 2128;
 pub const LARGE_VPRIME_PRIME: usize = 2724;
 pub const LARGE_MVECT: usize = 592;
@@ -20,7 +21,32 @@ pub const ITERATION: usize = 4;
   FIXME sync the paper and remove this comment
 */
 pub const LARGE_NONCE: usize = 80; // number of bits
+/// Minimal bit length of nonce accepted by issuance and proof operations, rejects zero and trivial nonces.
+pub const MIN_NONCE_BITS: usize = 64;
 pub const LARGE_ALPHATILDE: usize = 2787;
+/// Extra bits of `u_tilde`, `r_tilde` and `alpha_tilde` of inequality proof for each predicate security level above the default one.
+pub const PREDICATE_SECURITY_EXTRA_BITS: usize = 128;
+pub const MAX_PREDICATE_SECURITY_LEVEL: u32 = 2;
+/// Maximal delta between attribute value and predicate value that inequality proof supports.
+/// Delta is decomposed into sum of four squares (Lagrange), so it must fit into `i32`.
+pub const MAX_PREDICATE_DELTA: i64 = ::std::i32::MAX as i64;
+/// Default maximal number of attributes of credential schema and revealed attributes of sub proof request (mirrors ledger constraint).
+pub const MAX_ATTRS_COUNT: usize = 125;
+/// Default maximal number of predicates of sub proof request.
+pub const MAX_PREDICATES_COUNT: usize = 64;
+/// Size of Fiat-Shamir challenge (SHA-256). Challenges of set membership branches are summed modulo `2^LARGE_CHALLENGE`.
+pub const LARGE_CHALLENGE: usize = 256;
+/// Blinding of set membership proof responses: statistically hides `c * r` of `LARGE_VPRIME` bits commitment randomness.
+pub const LARGE_SET_MEMBERSHIP_RTILDE: usize = LARGE_VPRIME + LARGE_CHALLENGE + LARGE_NONCE;
+/// Maximal size of salt of revealed commitment to hidden attribute.
+pub const LARGE_COMMITMENT_SALT: usize = LARGE_VPRIME;
+/// Blinding of revealed commitment salt response: statistically hides `c * salt`.
+pub const LARGE_COMMITMENT_SALT_TILDE: usize = LARGE_COMMITMENT_SALT + LARGE_CHALLENGE + LARGE_NONCE;
+/// Domain separation tag, the first input of key rotation proof challenge.
+pub const KEY_ROTATION_PROOF_TAG: &'static [u8] = b"indy-crypto:cl:key-rotation-proof";
+/// Blinding of key rotation proof responses: statistically hides `c * (n - phi(n))`,
+/// where `n - phi(n) = 2p' + 2q' + 1` has at most `LARGE_PRIME + 2` bits.
+pub const LARGE_KEY_ROTATION_TILDE: usize = LARGE_PRIME + 2 + LARGE_CHALLENGE + LARGE_NONCE;
 
 // Constants that are used throughout the CL signatures code, so avoiding recomputation.
 lazy_static! {
@@ -32,4 +58,6 @@ lazy_static! {
                 None).unwrap().add(&LARGE_E_START_VALUE).unwrap();
     pub static ref LARGE_VPRIME_PRIME_VALUE: BigNumber = BIGNUMBER_2.exp(
         &BigNumber::from_u32(LARGE_VPRIME_PRIME - 1).unwrap(), None).unwrap();
+    pub static ref LARGE_CHALLENGE_VALUE: BigNumber = BIGNUMBER_2.exp(
+        &BigNumber::from_u32(LARGE_CHALLENGE).unwrap(), None).unwrap();
 }
@@ -0,0 +1,114 @@
+use bn::{BigNumber, BigNumberContext, BIGNUMBER_1};
+use errors::IndyCryptoError;
+
+use std::fmt;
+
+// Bits of exponent covered by one row of the table, so one byte of exponent is covered by two rows
+const WINDOW_BITS: usize = 4;
+const WINDOW_SIZE: usize = 1 << WINDOW_BITS;
+
+/// Precomputed powers of a fixed base modulo n: `rows[j][d - 1] = base^(d * 2^(WINDOW_BITS * j))`.
+/// Exponentiation takes one modular multiplication per non zero window of exponent instead of a squaring per bit.
+/// It isn't constant time, so it must be used only with public exponents (verifier side).
+pub struct FixedBaseTable {
+    base: BigNumber,
+    n: BigNumber,
+    rows: Vec<Vec<BigNumber>>
+}
+
+impl FixedBaseTable {
+    /// Precomputes table for exponents up to `max_bits` bits, longer exponents fall back to `BigNumber::mod_exp`.
+    pub fn new(base: &BigNumber, n: &BigNumber, max_bits: usize, ctx: &mut BigNumberContext) -> Result<FixedBaseTable, IndyCryptoError> {
+        let rows_count = (max_bits + WINDOW_BITS - 1) / WINDOW_BITS;
+        let mut rows: Vec<Vec<BigNumber>> = Vec::with_capacity(rows_count);
+        let mut row_base = base.clone()?;
+
+        for _ in 0..rows_count {
+            let mut row: Vec<BigNumber> = Vec::with_capacity(WINDOW_SIZE - 1);
+            row.push(row_base.clone()?);
+
+            for d in 1..WINDOW_SIZE - 1 {
+                let next = row[d - 1].mod_mul(&row_base, n, Some(ctx))?;
+                row.push(next);
+            }
+
+            // base^(2^(WINDOW_BITS * (j + 1))) = base^((WINDOW_SIZE - 1) * 2^(WINDOW_BITS * j)) * base^(2^(WINDOW_BITS * j))
+            row_base = row[WINDOW_SIZE - 2].mod_mul(&row_base, n, Some(ctx))?;
+            rows.push(row);
+        }
+
+        Ok(FixedBaseTable {
+            base: base.clone()?,
+            n: n.clone()?,
+            rows
+        })
+    }
+
+    /// Returns `base^exp mod n`, same as `base.mod_exp(exp, n)` including negative exponents.
+    pub fn mod_exp(&self, exp: &BigNumber, ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError> {
+        if exp.num_bits()? as usize > self.rows.len() * WINDOW_BITS {
+            return self.base.mod_exp(exp, &self.n, Some(ctx));
+        }
+
+        let mut result: Option<BigNumber> = None;
+
+        // to_bytes returns big-endian magnitude, rows are indexed from the least significant window
+        for (i, byte) in exp.to_bytes()?.iter().rev().enumerate() {
+            for (k, digit) in [byte & 0x0f, byte >> 4].iter().enumerate() {
+                if *digit == 0 {
+                    continue;
+                }
+
+                let power = &self.rows[2 * i + k][*digit as usize - 1];
+
+                result = Some(match result {
+                    Some(result) => result.mod_mul(power, &self.n, Some(ctx))?,
+                    None => power.clone()?
+                });
+            }
+        }
+
+        let result = match result {
+            Some(result) => result,
+            None => BIGNUMBER_1.clone()?
+        };
+
+        if exp.is_negative() {
+            result.inverse(&self.n, Some(ctx))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl fmt::Debug for FixedBaseTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FixedBaseTable {{ base: {:?}, max_bits: {} }}", self.base, self.rows.len() * WINDOW_BITS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bn::BnCtxPool;
+    use cl::issuer;
+
+    #[test]
+    fn fixed_base_table_mod_exp_works() {
+        let pk = issuer::mocks::credential_primary_public_key();
+        let mut ctx = BnCtxPool::take().unwrap();
+
+        let table = FixedBaseTable::new(&pk.s, &pk.n, 600, &mut ctx).unwrap();
+
+        for exp in [
+            BigNumber::new().unwrap(),
+            BigNumber::from_u32(1).unwrap(),
+            BigNumber::from_u32(16).unwrap(),
+            BigNumber::rand(600).unwrap(),
+            BigNumber::rand(600).unwrap().set_negative(true).unwrap(),
+            BigNumber::rand(700).unwrap()
+        ].iter() {
+            assert_eq!(pk.s.mod_exp(exp, &pk.n, Some(&mut ctx)).unwrap(), table.mod_exp(exp, &mut ctx).unwrap());
+        }
+    }
+}
@@ -1,6 +1,56 @@
-use bn::BigNumber;
+use bn::{BigNumber, BigNumberHasher};
 use errors::IndyCryptoError;
 
+/// Identifier of hash algorithm of Fiat-Shamir challenge (1 is SHA-256).
+///
+/// The identifier is the first input of the challenge hash (domain separation), so proof
+/// whose challenge was computed with another algorithm identifier fails verification.
+pub const CHALLENGE_HASH_ALGORITHM_ID: u8 = 1;
+
+/// Incremental version of `get_hash_as_int`.
+/// Allows to feed values one by one without collecting them into one vector.
+pub struct HashBuilder {
+    hasher: BigNumberHasher
+}
+
+impl HashBuilder {
+    pub fn new() -> Result<HashBuilder, IndyCryptoError> {
+        Ok(HashBuilder { hasher: BigNumberHasher::new()? })
+    }
+
+    /// Creates builder for Fiat-Shamir challenge prefixed with `CHALLENGE_HASH_ALGORITHM_ID`.
+    pub fn new_challenge() -> Result<HashBuilder, IndyCryptoError> {
+        HashBuilder::with_algorithm_id(CHALLENGE_HASH_ALGORITHM_ID)
+    }
+
+    pub fn with_algorithm_id(algorithm_id: u8) -> Result<HashBuilder, IndyCryptoError> {
+        let mut hash_builder = HashBuilder::new()?;
+        hash_builder.update(&[algorithm_id])?;
+        Ok(hash_builder)
+    }
+
+    pub fn update(&mut self, num: &[u8]) -> Result<(), IndyCryptoError> {
+        self.hasher.update(num)
+    }
+
+    pub fn update_all(&mut self, nums: &[Vec<u8>]) -> Result<(), IndyCryptoError> {
+        for num in nums.iter() {
+            self.hasher.update(num)?;
+        }
+        Ok(())
+    }
+
+    pub fn finalize_as_int(mut self) -> Result<BigNumber, IndyCryptoError> {
+        trace!("HashBuilder::finalize_as_int: >>>");
+
+        let hash = BigNumber::from_bytes(&self.hasher.finish()?);
+
+        trace!("HashBuilder::finalize_as_int: <<< hash: {:?}", hash);
+
+        hash
+    }
+}
+
 pub fn get_hash_as_int(nums: &Vec<Vec<u8>>) -> Result<BigNumber, IndyCryptoError> {
     trace!("Helpers::get_hash_as_int: >>> nums: {:?}", nums);
 
@@ -26,4 +76,33 @@ mod tests {
         assert!(res.is_ok());
         assert_eq!("2C2566C22E04AB3F18B3BA693823175002F10F400811363D26BBB33633AC8BAD", res.unwrap().to_hex().unwrap());
     }
+
+    #[test]
+    fn hash_builder_works() {
+        let nums = vec![
+            BigNumber::from_hex("ff9d2eedfee9cffd9ef6dbffedff3fcbef4caecb9bffe79bfa94d3fdf6abfbff").unwrap().to_bytes().unwrap(),
+            BigNumber::from_hex("ff9d2eedfee9cffd9ef6dbffedff3fcbef4caecb9bffe79bfa9168615ccbc546").unwrap().to_bytes().unwrap(),
+            BigNumber::from_u32(42).unwrap().to_bytes().unwrap()
+        ];
+
+        let mut hash_builder = HashBuilder::new().unwrap();
+        hash_builder.update(&nums[0]).unwrap();
+        hash_builder.update_all(&nums[1..]).unwrap();
+
+        assert_eq!(get_hash_as_int(&nums).unwrap(), hash_builder.finalize_as_int().unwrap());
+    }
+
+    #[test]
+    fn new_challenge_works() {
+        let num = BigNumber::from_u32(42).unwrap().to_bytes().unwrap();
+
+        let mut hash_builder = HashBuilder::new_challenge().unwrap();
+        hash_builder.update(&num).unwrap();
+
+        let mut other_hash_builder = HashBuilder::with_algorithm_id(CHALLENGE_HASH_ALGORITHM_ID + 1).unwrap();
+        other_hash_builder.update(&num).unwrap();
+
+        assert_eq!(get_hash_as_int(&vec![vec![CHALLENGE_HASH_ALGORITHM_ID], num.clone()]).unwrap(), hash_builder.finalize_as_int().unwrap());
+        assert_ne!(get_hash_as_int(&vec![vec![CHALLENGE_HASH_ALGORITHM_ID], num]).unwrap(), other_hash_builder.finalize_as_int().unwrap());
+    }
 }
@@ -1,10 +1,16 @@
-use bn::{BigNumber, BIGNUMBER_1};
+use bn::{BigNumber, BigNumberContext, BigNumberHasher, BnCtxPool, BIGNUMBER_1};
 use cl::*;
+use cl::fixed_base::FixedBaseTable;
+use cl::hash::get_hash_as_int;
 use errors::IndyCryptoError;
 use super::constants::*;
 
 use std::cmp::max;
 use std::collections::{HashMap, HashSet};
+#[cfg(any(feature = "blinding_factors_reuse_check", feature = "proof_builder_snapshot"))]
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 #[cfg(test)]
 use std::cell::RefCell;
@@ -179,6 +185,82 @@ pub fn _generate_safe_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
     Ok(safe_prime)
 }
 
+// 可取消的safe素数生成：与SeededRandom::safe_prime相同的筛法搜索，随机数来自BigNumber::rand
+// 每次素性测试之前检查取消标志，因此等待时间以单次素性测试为上限
+pub fn generate_safe_prime_cancellable(size: usize, cancel: &AtomicBool) -> Result<BigNumber, IndyCryptoError> {
+    trace!("Helpers::generate_safe_prime_cancellable: >>> size: {:?}", size);
+
+    let start = Instant::now();
+
+    let (safe_prime, tests) = _sieved_safe_prime(size, |bits| BigNumber::rand(bits), Some(cancel))?;
+
+    let elapsed = start.elapsed();
+    debug!("Found safe prime in {} primality tests, {}.{:03}s", tests, elapsed.as_secs(), elapsed.subsec_nanos() / 1_000_000);
+
+    trace!("Helpers::generate_safe_prime_cancellable: <<< safe_prime: {:?}", secret!(&safe_prime));
+
+    Ok(safe_prime)
+}
+
+// 生成size + 1位的safe素数2q + 1，其中q为size位素数，返回素数和素性测试次数
+// 从随机起点按步长2搜索q，先用小素数筛掉q或2q + 1可被整除的候选，再做素性测试
+fn _sieved_safe_prime<R>(size: usize, mut rand: R, cancel: Option<&AtomicBool>) -> Result<(BigNumber, usize), IndyCryptoError>
+    where R: FnMut(usize) -> Result<BigNumber, IndyCryptoError> {
+    const SAFE_PRIME_SIEVE_BOUND: u64 = 2048;
+    const SAFE_PRIME_SEARCH_STEPS: u64 = 1 << 20;
+
+    let small_primes = small_primes(SAFE_PRIME_SIEVE_BOUND);
+    let mut ctx = BigNumber::new_context()?;
+    let mut tests = 0;
+
+    loop {
+        let mut start = rand(size)?;
+        start.set_bit(size as i32 - 1)?;
+        start.set_bit(0)?;
+
+        let mut remainders = Vec::with_capacity(small_primes.len());
+        for &small_prime in small_primes.iter() {
+            remainders.push(bn_to_u64(&start.modulus(&BigNumber::from_u32(small_prime as usize)?, Some(&mut ctx))?)?);
+        }
+
+        for step in 0..SAFE_PRIME_SEARCH_STEPS {
+            let offset = 2 * step;
+
+            let sieved = small_primes.iter().zip(remainders.iter())
+                .any(|(&small_prime, &remainder)| {
+                    let q_rem = (remainder + offset) % small_prime;
+                    q_rem == 0 || (2 * q_rem + 1) % small_prime == 0
+                });
+
+            if sieved {
+                continue;
+            }
+
+            if let Some(cancel) = cancel {
+                if cancel.load(Ordering::SeqCst) {
+                    return Err(IndyCryptoError::OperationCancelled(
+                        format!("Safe prime generation cancelled after {} primality tests", tests)));
+                }
+            }
+
+            let q = start.add(&BigNumber::from_dec(&offset.to_string())?)?;
+
+            if q.num_bits()? as usize != size {
+                break;
+            }
+
+            tests += 1;
+
+            if q.is_prime(Some(&mut ctx))? {
+                let p = q.lshift1()?.add(&BIGNUMBER_1)?;
+                if p.is_prime(Some(&mut ctx))? {
+                    return Ok((p, tests));
+                }
+            }
+        }
+    }
+}
+
 // 在 [2, p'q'-1] 的范围内随机生成一个数
 #[cfg(test)]
 pub fn gen_x(p: &BigNumber, q: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
@@ -232,6 +314,92 @@ pub fn _random_qr(n: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
     Ok(qr)
 }
 
+/// 由种子确定性生成随机数（SHA-256计数器模式），使相同种子得到相同的凭证定义
+/// 只用于生成可复现的测试向量，输出可由种子完全重建，不能用于生产环境
+#[cfg_attr(not(feature = "seeded_credential_def"), allow(dead_code))]
+pub struct SeededRandom {
+    seed: Vec<u8>,
+    counter: u64
+}
+
+#[cfg_attr(not(feature = "seeded_credential_def"), allow(dead_code))]
+impl SeededRandom {
+    pub fn new(seed: &[u8]) -> SeededRandom {
+        SeededRandom { seed: seed.to_vec(), counter: 0 }
+    }
+
+    fn next_bytes(&mut self, len: usize) -> Result<Vec<u8>, IndyCryptoError> {
+        let mut bytes = Vec::with_capacity(len + 32);
+
+        while bytes.len() < len {
+            let mut block = self.seed.clone();
+            block.extend_from_slice(&u64_to_be_bytes(self.counter));
+            bytes.extend_from_slice(&BigNumber::hash(&block)?);
+            self.counter += 1;
+        }
+
+        bytes.truncate(len);
+        Ok(bytes)
+    }
+
+    /// 生成size位以内的随机数（与BigNumber::rand相同）
+    pub fn rand(&mut self, size: usize) -> Result<BigNumber, IndyCryptoError> {
+        let mut bytes = self.next_bytes((size + 7) / 8)?;
+
+        if size % 8 != 0 {
+            bytes[0] &= (1u8 << (size % 8)) - 1;
+        }
+
+        BigNumber::from_bytes(&bytes)
+    }
+
+    /// 生成[0, bound)范围内的随机数（多取64位以减小取模偏差）
+    pub fn rand_range(&mut self, bound: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        self.rand(bound.num_bits()? as usize + 64)?.modulus(bound, None)
+    }
+
+    /// 与gen_x相同：生成[2, p*q - 1)范围内的随机数
+    pub fn gen_x(&mut self, p: &BigNumber, q: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        let mut x = self.rand_range(&p.mul(&q, None)?.sub_word(3)?)?;
+        x.add_word(2)?;
+        Ok(x)
+    }
+
+    /// 与random_qr相同：生成模n的随机二次剩余
+    pub fn random_qr(&mut self, n: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        self.rand_range(n)?.sqr(None)?.modulus(n, None)
+    }
+
+    /// 与generate_safe_prime相同：生成size + 1位的safe素数2q + 1，其中q为size位素数
+    pub fn safe_prime(&mut self, size: usize) -> Result<BigNumber, IndyCryptoError> {
+        let (safe_prime, _) = _sieved_safe_prime(size, |bits| self.rand(bits), None)?;
+        Ok(safe_prime)
+    }
+}
+
+fn small_primes(bound: u64) -> Vec<u64> {
+    (3..bound).filter(|n| (2..*n).take_while(|d| d * d <= *n).all(|d| n % d != 0)).collect()
+}
+
+fn bn_to_u64(n: &BigNumber) -> Result<u64, IndyCryptoError> {
+    let bytes = n.to_bytes()?;
+
+    if bytes.len() > 8 {
+        return Err(IndyCryptoError::InvalidState(format!("Value doesn't fit into u64")));
+    }
+
+    Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+}
+
+#[cfg_attr(not(feature = "seeded_credential_def"), allow(dead_code))]
+fn u64_to_be_bytes(n: u64) -> [u8; 8] {
+    let mut bytes = [0u8; 8];
+    for i in 0..8 {
+        bytes[i] = (n >> (8 * (7 - i))) as u8;
+    }
+    bytes
+}
+
 // 生成一个2724bit数，第一位一定是1，主要用作生成v''
 //TODO: FIXME very inefficient code
 pub fn bitwise_or_big_int(a: &BigNumber, b: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
@@ -250,6 +418,49 @@ pub fn bitwise_or_big_int(a: &BigNumber, b: &BigNumber) -> Result<BigNumber, Ind
     Ok(result)
 }
 
+// Jacobi symbol (a/n) for odd positive n.
+// (a/n) == 1 is necessary (but not sufficient) condition for a to be quadratic residue mod n.
+pub fn jacobi_symbol(a: &BigNumber, n: &BigNumber) -> Result<i32, IndyCryptoError> {
+    trace!("Helpers::jacobi_symbol: >>> a: {:?}, n: {:?}", a, n);
+
+    if n.is_negative() || !n.is_bit_set(0)? {
+        return Err(IndyCryptoError::InvalidStructure(format!("Jacobi symbol is defined for odd positive modulus only {:?}", n)));
+    }
+
+    let mut ctx = BnCtxPool::take()?;
+
+    let mut a = a.modulus(n, Some(&mut ctx))?;
+    let mut n = n.clone()?;
+    let mut res = 1;
+
+    while a.num_bits()? != 0 {
+        while !a.is_bit_set(0)? {
+            a = a.rshift1()?;
+            // n mod 8 is 3 or 5
+            if n.is_bit_set(1)? != n.is_bit_set(2)? {
+                res = -res;
+            }
+        }
+
+        ::std::mem::swap(&mut a, &mut n);
+
+        // a mod 4 == 3 and n mod 4 == 3
+        if a.is_bit_set(1)? && n.is_bit_set(1)? {
+            res = -res;
+        }
+
+        a = a.modulus(&n, Some(&mut ctx))?;
+    }
+
+    let res = if n == *BIGNUMBER_1 { res } else { 0 };
+
+    BnCtxPool::release(ctx);
+
+    trace!("Helpers::jacobi_symbol: <<< res: {:?}", res);
+
+    Ok(res)
+}
+
 // 生成 \tilde{m_i} ，在翻译论文4.2节，目的是生成unrevealed属性盲化参数 
 pub fn get_mtilde(unrevealed_attrs: &HashSet<String>, mtilde: &mut HashMap<String, BigNumber>) -> Result<(), IndyCryptoError> {
     trace!("Helpers::get_mtilde: >>> unrevealed_attrs: {:?}", unrevealed_attrs);
@@ -292,18 +503,39 @@ pub fn get_mtilde(unrevealed_attrs: &HashSet<String>, mtilde: &mut HashMap<Strin
     /// 
     /// 输出：
     /// \hat{T}: BigNum
-pub fn calc_teq(p_pub_key: &CredentialPrimaryPublicKey,
+pub fn calc_teq(pub_key: &PreparedPublicKey,
                 a_prime: &BigNumber,
                 e: &BigNumber,
                 v: &BigNumber,
                 m_tilde: &HashMap<String, BigNumber>,
                 unrevealed_attrs: &HashSet<String>) -> Result<BigNumber, IndyCryptoError> {
+    _calc_teq(&pub_key.p_key, pub_key.tables.as_ref().map(|tables| &**tables), a_prime, e, v, m_tilde, unrevealed_attrs, false)
+}
+
+/// Same as `calc_teq`, but uses constant time exponentiation.
+/// Prover must use it, because `e_tilde`, `v_tilde` and `m_tilde` are secret.
+pub fn calc_teq_consttime(p_pub_key: &CredentialPrimaryPublicKey,
+                          a_prime: &BigNumber,
+                          e: &BigNumber,
+                          v: &BigNumber,
+                          m_tilde: &HashMap<String, BigNumber>,
+                          unrevealed_attrs: &HashSet<String>) -> Result<BigNumber, IndyCryptoError> {
+    _calc_teq(p_pub_key, None, a_prime, e, v, m_tilde, unrevealed_attrs, true)
+}
+
+fn _calc_teq(p_pub_key: &CredentialPrimaryPublicKey,
+             tables: Option<&PreparedKeyTables>,
+             a_prime: &BigNumber,
+             e: &BigNumber,
+             v: &BigNumber,
+             m_tilde: &HashMap<String, BigNumber>,
+             unrevealed_attrs: &HashSet<String>,
+             consttime: bool) -> Result<BigNumber, IndyCryptoError> {
     trace!("Helpers::calc_teq: >>> p_pub_key: {:?}, p_pub_key: {:?}, e: {:?}, v: {:?}, m_tilde: {:?}, \
-    unrevealed_attrs: {:?}", p_pub_key, a_prime, e, v, m_tilde, unrevealed_attrs);
+    unrevealed_attrs: {:?}, consttime: {:?}", p_pub_key, a_prime, e, v, m_tilde, unrevealed_attrs, consttime);
 
-    let mut ctx = BigNumber::new_context()?;
-    let mut result: BigNumber = a_prime
-        .mod_exp(&e, &p_pub_key.n, Some(&mut ctx))?;
+    let mut ctx = BnCtxPool::take()?;
+    let mut result: BigNumber = _mod_exp(a_prime, e, &p_pub_key.n, consttime, &mut ctx)?;
 
     for k in unrevealed_attrs.iter() {
         let cur_r = p_pub_key.r.get(k)
@@ -311,34 +543,84 @@ pub fn calc_teq(p_pub_key: &CredentialPrimaryPublicKey,
         let cur_m = m_tilde.get(k)
             .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in m_tilde", k)))?;
 
-        result = cur_r
-            .mod_exp(&cur_m, &p_pub_key.n, Some(&mut ctx))?
+        result = _key_mod_exp(cur_r, tables.and_then(|tables| tables.r.get(k)), cur_m, &p_pub_key.n, consttime, &mut ctx)?
             .mod_mul(&result, &p_pub_key.n, Some(&mut ctx))?;
     }
 
-    result = p_pub_key.s
-        .mod_exp(&v, &p_pub_key.n, Some(&mut ctx))?
+    result = _key_mod_exp(&p_pub_key.s, tables.map(|tables| &tables.s), v, &p_pub_key.n, consttime, &mut ctx)?
         .mod_mul(&result, &p_pub_key.n, Some(&mut ctx))?;
 
+    BnCtxPool::release(ctx);
+
     trace!("Helpers::calc_teq: <<< t: {:?}", result);
 
     Ok(result)
 }
 
+fn _mod_exp(base: &BigNumber,
+            exp: &BigNumber,
+            modulus: &BigNumber,
+            consttime: bool,
+            ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError> {
+    if consttime {
+        base.mod_exp_consttime(exp, modulus, Some(ctx))
+    } else {
+        base.mod_exp(exp, modulus, Some(ctx))
+    }
+}
+
+/// 公钥底数（S、Z、R_i）的幂运算，验证者有预先计算的表时使用表
+fn _key_mod_exp(base: &BigNumber,
+                table: Option<&FixedBaseTable>,
+                exp: &BigNumber,
+                modulus: &BigNumber,
+                consttime: bool,
+                ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError> {
+    match table {
+        Some(table) => table.mod_exp(exp, ctx),
+        None => _mod_exp(base, exp, modulus, consttime, ctx)
+    }
+}
+
 /// Prover和Verifier都调用这个函数，生成Tau集合中 T_i, T_\Delta, Q
     /// 
     ///  
-pub fn calc_tne(p_pub_key: &CredentialPrimaryPublicKey,
+pub fn calc_tne(pub_key: &PreparedPublicKey,
                 u: &HashMap<String, BigNumber>,
                 r: &HashMap<String, BigNumber>,
                 mj: &BigNumber,
                 alpha: &BigNumber,
                 t: &HashMap<String, BigNumber>,
                 is_less: bool) -> Result<Vec<BigNumber>, IndyCryptoError> {
-    trace!("Helpers::calc_tge: >>> p_pub_key: {:?}, u: {:?}, r: {:?}, mj: {:?}, alpha: {:?}, t: {:?}", p_pub_key, u, r, mj, alpha, t);
+    _calc_tne(&pub_key.p_key, pub_key.tables.as_ref().map(|tables| &**tables), u, r, mj, alpha, t, is_less, false)
+}
+
+/// Same as `calc_tne`, but uses constant time exponentiation.
+/// Prover must use it, because `u_tilde`, `r_tilde`, `m_tilde` and `alpha_tilde` are secret.
+pub fn calc_tne_consttime(p_pub_key: &CredentialPrimaryPublicKey,
+                          u: &HashMap<String, BigNumber>,
+                          r: &HashMap<String, BigNumber>,
+                          mj: &BigNumber,
+                          alpha: &BigNumber,
+                          t: &HashMap<String, BigNumber>,
+                          is_less: bool) -> Result<Vec<BigNumber>, IndyCryptoError> {
+    _calc_tne(p_pub_key, None, u, r, mj, alpha, t, is_less, true)
+}
+
+fn _calc_tne(p_pub_key: &CredentialPrimaryPublicKey,
+             tables: Option<&PreparedKeyTables>,
+             u: &HashMap<String, BigNumber>,
+             r: &HashMap<String, BigNumber>,
+             mj: &BigNumber,
+             alpha: &BigNumber,
+             t: &HashMap<String, BigNumber>,
+             is_less: bool,
+             consttime: bool) -> Result<Vec<BigNumber>, IndyCryptoError> {
+    trace!("Helpers::calc_tge: >>> p_pub_key: {:?}, u: {:?}, r: {:?}, mj: {:?}, alpha: {:?}, t: {:?}, consttime: {:?}",
+           p_pub_key, u, r, mj, alpha, t, consttime);
 
     let mut tau_list: Vec<BigNumber> = Vec::new();
-    let mut ctx = BigNumber::new_context()?;
+    let mut ctx = BnCtxPool::take()?;
 
     for i in 0..ITERATION {
         let cur_u = u.get(&i.to_string())
@@ -346,10 +628,9 @@ pub fn calc_tne(p_pub_key: &CredentialPrimaryPublicKey,
         let cur_r = r.get(&i.to_string())
             .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in r", i)))?;
 
-        let t_tau = p_pub_key.z
-            .mod_exp(&cur_u, &p_pub_key.n, Some(&mut ctx))?
+        let t_tau = _key_mod_exp(&p_pub_key.z, tables.map(|tables| &tables.z), cur_u, &p_pub_key.n, consttime, &mut ctx)?
             .mod_mul(
-                &p_pub_key.s.mod_exp(&cur_r, &p_pub_key.n, Some(&mut ctx))?,
+                &_key_mod_exp(&p_pub_key.s, tables.map(|tables| &tables.s), cur_r, &p_pub_key.n, consttime, &mut ctx)?,
                 &p_pub_key.n, Some(&mut ctx)
             )?;
 
@@ -364,10 +645,9 @@ pub fn calc_tne(p_pub_key: &CredentialPrimaryPublicKey,
         delta.clone()?
     };
 
-    let t_tau = p_pub_key.z
-        .mod_exp(&mj, &p_pub_key.n, Some(&mut ctx))?
+    let t_tau = _key_mod_exp(&p_pub_key.z, tables.map(|tables| &tables.z), mj, &p_pub_key.n, consttime, &mut ctx)?
         .mod_mul(
-            &p_pub_key.s.mod_exp(&delta_predicate, &p_pub_key.n, Some(&mut ctx))?,
+            &_key_mod_exp(&p_pub_key.s, tables.map(|tables| &tables.s), &delta_predicate, &p_pub_key.n, consttime, &mut ctx)?,
             &p_pub_key.n, Some(&mut ctx)
         )?;
 
@@ -381,37 +661,146 @@ pub fn calc_tne(p_pub_key: &CredentialPrimaryPublicKey,
         let cur_u = u.get(&i.to_string())
             .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in u", i)))?;
 
-        q = cur_t
-            .mod_exp(&cur_u, &p_pub_key.n, Some(&mut ctx))?
+        q = _mod_exp(cur_t, cur_u, &p_pub_key.n, consttime, &mut ctx)?
             .mul(&q, Some(&mut ctx))?;
     }
 
-    q = p_pub_key.s
-        .mod_exp(&alpha, &p_pub_key.n, Some(&mut ctx))?
+    q = _key_mod_exp(&p_pub_key.s, tables.map(|tables| &tables.s), alpha, &p_pub_key.n, consttime, &mut ctx)?
         .mod_mul(&q, &p_pub_key.n, Some(&mut ctx))?;
 
     tau_list.push(q);
 
+    BnCtxPool::release(ctx);
+
     trace!("Helpers::calc_tne: <<< tau_list: {:?}", tau_list);
 
     Ok(tau_list)
 }
 
+/// Prover和Verifier都调用这个函数，生成集合成员证明中一个分支的Tau：S^s * (T * Z^{-value})^{-c}
+/// 对于真实分支 T * Z^{-value} = S^r，s = rho + c * r，结果等于 S^rho
+pub fn calc_tset(p_pub_key: &CredentialPrimaryPublicKey,
+                 t: &BigNumber,
+                 value: &BigNumber,
+                 c: &BigNumber,
+                 s: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+    trace!("Helpers::calc_tset: >>> p_pub_key: {:?}, t: {:?}, value: {:?}, c: {:?}, s: {:?}", p_pub_key, t, value, c, s);
+
+    let mut ctx = BnCtxPool::take()?;
+
+    let d = p_pub_key.z
+        .mod_exp(&value.set_negative(true)?, &p_pub_key.n, Some(&mut ctx))?
+        .mod_mul(&t, &p_pub_key.n, Some(&mut ctx))?;
+
+    let tau = d
+        .mod_exp(&c.set_negative(true)?, &p_pub_key.n, Some(&mut ctx))?
+        .mod_mul(
+            &p_pub_key.s.mod_exp(&s, &p_pub_key.n, Some(&mut ctx))?,
+            &p_pub_key.n, Some(&mut ctx)
+        )?;
+
+    BnCtxPool::release(ctx);
+
+    trace!("Helpers::calc_tset: <<< tau: {:?}", tau);
+
+    Ok(tau)
+}
+
+/// Builds Fiat-Shamir challenge of key rotation proof over domain tag, both keys and both commitments `S^x_tilde`.
+/// Every value is prefixed with its length (4 bytes, big-endian), so values can't be shifted between fields.
+pub fn get_key_rotation_challenge(old_p_pub_key: &CredentialPrimaryPublicKey,
+                                  new_p_pub_key: &CredentialPrimaryPublicKey,
+                                  old_t: &BigNumber,
+                                  new_t: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+    trace!("Helpers::get_key_rotation_challenge: >>> old_p_pub_key: {:?}, new_p_pub_key: {:?}, old_t: {:?}, new_t: {:?}",
+           old_p_pub_key, new_p_pub_key, old_t, new_t);
+
+    let values = vec![
+        KEY_ROTATION_PROOF_TAG.to_vec(),
+        old_p_pub_key.n.to_bytes()?,
+        old_p_pub_key.s.to_bytes()?,
+        old_p_pub_key.z.to_bytes()?,
+        new_p_pub_key.n.to_bytes()?,
+        new_p_pub_key.s.to_bytes()?,
+        new_p_pub_key.z.to_bytes()?,
+        old_t.to_bytes()?,
+        new_t.to_bytes()?
+    ];
+
+    let mut prefixed_values: Vec<Vec<u8>> = Vec::new();
+    for value in values {
+        prefixed_values.push((value.len() as u32).to_be_bytes().to_vec());
+        prefixed_values.push(value);
+    }
+
+    let challenge = get_hash_as_int(&prefixed_values)?;
+
+    trace!("Helpers::get_key_rotation_challenge: <<< challenge: {:?}", challenge);
+
+    Ok(challenge)
+}
+
+/// Builds SHA-256 digest of credential offer: canonical json of credential public key and its correctness proof.
+pub fn get_credential_offer_digest(cred_pub_key: &CredentialPublicKey,
+                                   key_correctness_proof: &CredentialKeyCorrectnessProof) -> Result<[u8; 32], IndyCryptoError> {
+    trace!("Helpers::get_credential_offer_digest: >>> cred_pub_key: {:?}, key_correctness_proof: {:?}", cred_pub_key, key_correctness_proof);
+
+    let mut hasher = BigNumberHasher::new()?;
+    hasher.update(cred_pub_key.to_canonical_json()?.as_bytes())?;
+    hasher.update(super::to_canonical_json(key_correctness_proof)?.as_bytes())?;
+
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&hasher.finish()?);
+
+    trace!("Helpers::get_credential_offer_digest: <<< digest: {:?}", digest);
+
+    Ok(digest)
+}
+
 fn largest_square_less_than(delta: usize) -> usize {
     (delta as f64).sqrt().floor() as usize
 }
 
 // 找出四个 u_i
-//Express the natural number `delta` as a sum of four integer squares,
-// i.e `delta = a^2 + b^2 + c^2 + d^2` using Lagrange's four-square theorem
-pub fn four_squares(delta: i32) -> Result<HashMap<String, BigNumber>, IndyCryptoError> {
-    trace!("Helpers::four_squares: >>> delta: {:?}", delta);
+//Express the natural number `n` as a sum of four integer squares,
+// i.e `n = a^2 + b^2 + c^2 + d^2` using Lagrange's four-square theorem
+pub fn four_square_decomposition(n: &BigNumber) -> Result<[BigNumber; 4], IndyCryptoError> {
+    trace!("Helpers::four_square_decomposition: >>> n: {:?}", n);
+
+    if n.is_negative() {
+        return Err(IndyCryptoError::InvalidStructure(format!("Cannot express a negative number as sum of four squares {:?} ", n)));
+    }
+
+    let d = n.to_dec()?
+        .parse::<i64>()
+        .ok()
+        .filter(|d| *d <= MAX_PREDICATE_DELTA)
+        .ok_or(IndyCryptoError::InvalidStructure(format!("Number {:?} exceeds max supported value {}", n, MAX_PREDICATE_DELTA)))? as usize;
 
-    if delta < 0 {
-        return Err(IndyCryptoError::InvalidStructure(format!("Cannot express a negative number as sum of four squares {} ", delta)));
+    let roots = _four_square_roots(d);
+
+    let res = [
+        BigNumber::from_dec(&roots[0].to_string()[..])?,
+        BigNumber::from_dec(&roots[1].to_string()[..])?,
+        BigNumber::from_dec(&roots[2].to_string()[..])?,
+        BigNumber::from_dec(&roots[3].to_string()[..])?
+    ];
+
+    let mut sum = BigNumber::new()?;
+    for root in res.iter() {
+        sum = sum.add(&root.sqr(None)?)?;
     }
 
-    let d = delta as usize;
+    if sum != *n {
+        return Err(IndyCryptoError::InvalidStructure(format!("Four squares decomposition of {:?} is invalid", n)));
+    }
+
+    trace!("Helpers::four_square_decomposition: <<< res: {:?}", res);
+
+    Ok(res)
+}
+
+fn _four_square_roots(d: usize) -> [usize; 4] {
     let mut roots: [usize; 4] = [largest_square_less_than(d), 0, 0, 0];
 
     'outer: for i in (1..roots[0] + 1).rev() {
@@ -445,16 +834,44 @@ pub fn four_squares(delta: i32) -> Result<HashMap<String, BigNumber>, IndyCrypto
         }
     }
 
-    let res = hashmap![
-        "0".to_string() => BigNumber::from_dec(&roots[0].to_string()[..])?,
-        "1".to_string() => BigNumber::from_dec(&roots[1].to_string()[..])?,
-        "2".to_string() => BigNumber::from_dec(&roots[2].to_string()[..])?,
-        "3".to_string() => BigNumber::from_dec(&roots[3].to_string()[..])?
-    ];
+    roots
+}
+
+/// Set of fingerprints that remembers only the last `capacity` inserted ones, the oldest are forgotten first.
+/// Used by process-wide registries of values that must be used once, so they don't grow without bound.
+#[cfg(any(feature = "blinding_factors_reuse_check", feature = "proof_builder_snapshot"))]
+pub struct BoundedSet {
+    items: HashSet<Vec<u8>>,
+    order: VecDeque<Vec<u8>>,
+    capacity: usize
+}
 
-    trace!("Helpers::four_squares: <<< res: {:?}", res);
+#[cfg(any(feature = "blinding_factors_reuse_check", feature = "proof_builder_snapshot"))]
+impl BoundedSet {
+    pub fn new(capacity: usize) -> BoundedSet {
+        BoundedSet { items: HashSet::new(), order: VecDeque::new(), capacity }
+    }
 
-    Ok(res)
+    pub fn contains(&self, item: &Vec<u8>) -> bool {
+        self.items.contains(item)
+    }
+
+    /// Returns false if item is already in the set.
+    pub fn insert(&mut self, item: Vec<u8>) -> bool {
+        if !self.items.insert(item.clone()) {
+            return false;
+        }
+
+        self.order.push_back(item);
+
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.items.remove(&oldest);
+            }
+        }
+
+        true
+    }
 }
 
 #[cfg(test)]
@@ -462,6 +879,21 @@ mod tests {
     use super::*;
     use cl::{issuer, prover};
 
+    #[cfg(any(feature = "blinding_factors_reuse_check", feature = "proof_builder_snapshot"))]
+    #[test]
+    fn bounded_set_forgets_oldest() {
+        let mut set = BoundedSet::new(3);
+
+        for i in 0..4 {
+            assert!(set.insert(vec![i]));
+        }
+
+        assert_eq!(3, set.items.len());
+        assert!(!set.contains(&vec![0]));
+        assert!(set.contains(&vec![3]));
+        assert!(!set.insert(vec![3]));
+    }
+
     #[test]
     fn generate_v_prime_prime_works() {
         MockHelper::inject();
@@ -470,6 +902,41 @@ mod tests {
         assert_eq!(generate_v_prime_prime().unwrap(), result);
     }
 
+    #[test]
+    fn jacobi_symbol_works() {
+        let table = vec![(1, 1, 1), (2, 15, 1), (7, 15, -1), (5, 15, 0), (1001, 9907, -1), (19, 45, 1), (30, 59, -1)];
+
+        for (a, n, expected) in table {
+            assert_eq!(expected, jacobi_symbol(&BigNumber::from_u32(a).unwrap(), &BigNumber::from_u32(n).unwrap()).unwrap());
+        }
+
+        assert!(jacobi_symbol(&BigNumber::from_u32(1).unwrap(), &BigNumber::from_u32(4).unwrap()).is_err());
+    }
+
+    #[test]
+    fn seeded_random_works() {
+        let mut rng = SeededRandom::new(b"seed");
+        let mut same_rng = SeededRandom::new(b"seed");
+        let mut other_rng = SeededRandom::new(b"other seed");
+
+        let value = rng.rand(300).unwrap();
+        assert!(value.num_bits().unwrap() <= 300);
+        assert_eq!(value, same_rng.rand(300).unwrap());
+        assert_ne!(value, other_rng.rand(300).unwrap());
+
+        let safe_prime = rng.safe_prime(128).unwrap();
+        assert_eq!(129, safe_prime.num_bits().unwrap());
+        assert!(safe_prime.is_safe_prime(None).unwrap());
+        assert_eq!(safe_prime, same_rng.safe_prime(128).unwrap());
+    }
+
+    #[test]
+    fn generate_safe_prime_cancellable_works() {
+        let safe_prime = generate_safe_prime_cancellable(128, &AtomicBool::new(false)).unwrap();
+        assert_eq!(129, safe_prime.num_bits().unwrap());
+        assert!(safe_prime.is_safe_prime(None).unwrap());
+    }
+
     #[test]
     fn bitwise_or_big_int_works() {
         let a = BigNumber::from_dec("778378032744961463933002553964902776831187587689736807008034459507677878432383414623740074");
@@ -479,46 +946,47 @@ mod tests {
     }
 
     #[test]
-    fn four_squares_works() {
-        let res = four_squares(107 as i32);
-        let res_data = res.unwrap();
-
-        assert_eq!("9".to_string(), res_data.get("0").unwrap().to_dec().unwrap());
-        assert_eq!("5".to_string(), res_data.get("1").unwrap().to_dec().unwrap());
-        assert_eq!("1".to_string(), res_data.get("2").unwrap().to_dec().unwrap());
-        assert_eq!("0".to_string(), res_data.get("3").unwrap().to_dec().unwrap());
-
-        let res = four_squares(112 as i32);
-        let res_data = res.unwrap();
-
-        assert_eq!("10".to_string(), res_data.get("0").unwrap().to_dec().unwrap());
-        assert_eq!("2".to_string(), res_data.get("1").unwrap().to_dec().unwrap());
-        assert_eq!("2".to_string(), res_data.get("2").unwrap().to_dec().unwrap());
-        assert_eq!("2".to_string(), res_data.get("3").unwrap().to_dec().unwrap());
-
-
-        let res = four_squares(253 as i32);
-        let res_data = res.unwrap();
-
-        assert_eq!("14".to_string(), res_data.get("0").unwrap().to_dec().unwrap());
-        assert_eq!("7".to_string(), res_data.get("1").unwrap().to_dec().unwrap());
-        assert_eq!("2".to_string(), res_data.get("2").unwrap().to_dec().unwrap());
-        assert_eq!("2".to_string(), res_data.get("3").unwrap().to_dec().unwrap());
-
-        let res = four_squares(1506099439 as i32);
-        let res_data = res.unwrap();
+    fn four_square_decomposition_works() {
+        let expected = vec![
+            (0, ["0", "0", "0", "0"]),
+            (107, ["9", "5", "1", "0"]),
+            (112, ["10", "2", "2", "2"]),
+            (253, ["14", "7", "2", "2"]),
+            (1506099439, ["38807", "337", "50", "11"])
+        ];
+
+        for (n, roots) in expected {
+            let res = four_square_decomposition(&BigNumber::from_u32(n).unwrap()).unwrap();
+            for i in 0..4 {
+                assert_eq!(roots[i].to_string(), res[i].to_dec().unwrap());
+            }
+        }
+    }
 
-        assert_eq!("38807".to_string(), res_data.get("0").unwrap().to_dec().unwrap());
-        assert_eq!("337".to_string(), res_data.get("1").unwrap().to_dec().unwrap());
-        assert_eq!("50".to_string(), res_data.get("2").unwrap().to_dec().unwrap());
-        assert_eq!("11".to_string(), res_data.get("3").unwrap().to_dec().unwrap());
+    #[test]
+    fn four_square_decomposition_works_for_sum_invariant() {
+        for n in &["1", "2", "3", "7", "15", "1000000", "2147483647"] {
+            let n = BigNumber::from_dec(n).unwrap();
+            let res = four_square_decomposition(&n).unwrap();
+
+            let mut sum = BigNumber::new().unwrap();
+            for root in res.iter() {
+                sum = sum.add(&root.sqr(None).unwrap()).unwrap();
+            }
+            assert_eq!(n, sum);
+        }
     }
 
+    #[test]
+    fn four_square_decomposition_works_for_invalid_number() {
+        assert!(four_square_decomposition(&BigNumber::from_dec("-1").unwrap()).is_err());
+        assert!(four_square_decomposition(&BigNumber::from_dec("2147483648").unwrap()).is_err());
+    }
 
     #[test]
     fn calc_tne_works() {
         let proof = prover::mocks::ne_proof();
-        let pk = issuer::mocks::credential_primary_public_key();
+        let pk = PreparedPublicKey::new(&issuer::mocks::credential_public_key()).unwrap();
 
         let res = calc_tne(&pk, &proof.u, &proof.r, &proof.mj, &proof.alpha, &proof.t, proof.predicate.is_less());
 
@@ -550,4 +1018,14 @@ mod tests {
         31839937087680336690781266325043663083854158029559611517721678702314741440737728022831823751\
         45256219426454149503998537986414519426715148839164974816475472185621648644891", res_data[5].to_dec().unwrap());
     }
+
+    #[test]
+    fn calc_tne_consttime_works() {
+        let proof = prover::mocks::ne_proof();
+        let pk = issuer::mocks::credential_public_key();
+
+        let res = calc_tne_consttime(&pk.p_key, &proof.u, &proof.r, &proof.mj, &proof.alpha, &proof.t, proof.predicate.is_less()).unwrap();
+
+        assert_eq!(calc_tne(&PreparedPublicKey::new(&pk).unwrap(), &proof.u, &proof.r, &proof.mj, &proof.alpha, &proof.t, proof.predicate.is_less()).unwrap(), res);
+    }
 }
@@ -6,7 +6,9 @@ use cl::helpers::*;
 use cl::commitment::get_pedersen_commitment;
 use cl::hash::get_hash_as_int;
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 
 /// Trust source that provides credentials to prover.
 pub struct Issuer {}
@@ -63,14 +65,10 @@ impl Issuer {
         trace!("Issuer::new_credential_def: >>> credential_schema: {:?}", credential_schema);
 
         let (p_pub_key, p_priv_key, p_key_meta) =
-            Issuer::_new_credential_primary_keys(credential_schema, non_credential_schema)?;
+            Issuer::_new_credential_primary_keys(credential_schema, non_credential_schema, None)?;
 
-        let cred_pub_key = CredentialPublicKey { p_key: p_pub_key};
-        let cred_priv_key = CredentialPrivateKey { p_key: p_priv_key};
-        let cred_key_correctness_proof =
-            Issuer::_new_credential_key_correctness_proof(&cred_pub_key.p_key,
-                                                          &cred_priv_key.p_key,
-                                                          &p_key_meta)?;
+        let (cred_pub_key, cred_priv_key, cred_key_correctness_proof) =
+            Issuer::_new_credential_def(p_pub_key, p_priv_key, p_key_meta, &mut None)?;
 
         trace!("Issuer::new_credential_def: <<< cred_pub_key: {:?}, cred_priv_key: {:?}, cred_key_correctness_proof: {:?}",
                cred_pub_key, secret!(&cred_priv_key), cred_key_correctness_proof);
@@ -78,6 +76,233 @@ impl Issuer {
         Ok((cred_pub_key, cred_priv_key, cred_key_correctness_proof))
     }
 
+    /// Same as `new_credential_def`, but safe primes generation can be aborted from another thread.
+    /// The token is checked between prime search iterations, so generation stops shortly after it is set
+    /// and `OperationCancelled` error is returned.
+    ///
+    /// # Arguments
+    /// * `credential_schema` - Credential schema entity.
+    /// * `non_credential_schema` - Non credential schema entity.
+    /// * `cancel` - Cancellation token, set it to `true` to abort generation.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("name").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// cancel.store(true, Ordering::SeqCst);
+    ///
+    /// assert!(Issuer::new_credential_def_cancellable(&credential_schema, &non_credential_schema, cancel).is_err());
+    /// ```
+    pub fn new_credential_def_cancellable(credential_schema: &CredentialSchema,
+                                          non_credential_schema: &NonCredentialSchema,
+                                          cancel: Arc<AtomicBool>) -> Result<(CredentialPublicKey,
+                                                                              CredentialPrivateKey,
+                                                                              CredentialKeyCorrectnessProof), IndyCryptoError> {
+        trace!("Issuer::new_credential_def_cancellable: >>> credential_schema: {:?}, non_credential_schema: {:?}, cancel: {:?}",
+               credential_schema, non_credential_schema, cancel);
+
+        let (p_pub_key, p_priv_key, p_key_meta) =
+            Issuer::_new_credential_primary_keys(credential_schema, non_credential_schema, Some(&*cancel))?;
+
+        let (cred_pub_key, cred_priv_key, cred_key_correctness_proof) =
+            Issuer::_new_credential_def(p_pub_key, p_priv_key, p_key_meta, &mut None)?;
+
+        trace!("Issuer::new_credential_def_cancellable: <<< cred_pub_key: {:?}, cred_priv_key: {:?}, cred_key_correctness_proof: {:?}",
+               cred_pub_key, secret!(&cred_priv_key), cred_key_correctness_proof);
+
+        Ok((cred_pub_key, cred_priv_key, cred_key_correctness_proof))
+    }
+
+    /// Creates and returns credential definition (public and private keys, correctness proof) entities
+    /// from pre-generated safe primes. Allows to move expensive safe primes generation offline.
+    ///
+    /// # Arguments
+    /// * `credential_schema` - Credential schema entity.
+    /// * `non_credential_schema` - Non credential schema entity.
+    /// * `p_safe` - Safe prime generated by `BigNumber::generate_safe_prime(1024)`.
+    /// * `q_safe` - Another safe prime generated by `BigNumber::generate_safe_prime(1024)`.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::bn::BigNumber;
+    /// use indy_crypto::cl::issuer::Issuer;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("name").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let p_safe = BigNumber::generate_safe_prime(1024).unwrap();
+    /// let q_safe = BigNumber::generate_safe_prime(1024).unwrap();
+    ///
+    /// let (_cred_pub_key, _cred_priv_key, _cred_key_correctness_proof) =
+    ///     Issuer::new_credential_def_from_primes(&credential_schema, &non_credential_schema, &p_safe, &q_safe).unwrap();
+    /// ```
+    pub fn new_credential_def_from_primes(credential_schema: &CredentialSchema,
+                                          non_credential_schema: &NonCredentialSchema,
+                                          p_safe: &BigNumber,
+                                          q_safe: &BigNumber) -> Result<(CredentialPublicKey,
+                                                                         CredentialPrivateKey,
+                                                                         CredentialKeyCorrectnessProof), IndyCryptoError> {
+        trace!("Issuer::new_credential_def_from_primes: >>> credential_schema: {:?}, non_credential_schema: {:?}, p_safe: {:?}, q_safe: {:?}",
+               credential_schema, non_credential_schema, secret!(p_safe), secret!(q_safe));
+
+        if credential_schema.attrs.len() == 0 {
+            return Err(IndyCryptoError::InvalidStructure(format!("List of attributes is empty")));
+        }
+
+        Issuer::_check_safe_primes(p_safe, q_safe)?;
+
+        let (p_pub_key, p_priv_key, p_key_meta) =
+            Issuer::_new_credential_primary_keys_from_primes(credential_schema, non_credential_schema, p_safe, q_safe, &mut None)?;
+
+        let (cred_pub_key, cred_priv_key, cred_key_correctness_proof) =
+            Issuer::_new_credential_def(p_pub_key, p_priv_key, p_key_meta, &mut None)?;
+
+        trace!("Issuer::new_credential_def_from_primes: <<< cred_pub_key: {:?}, cred_priv_key: {:?}, cred_key_correctness_proof: {:?}",
+               cred_pub_key, secret!(&cred_priv_key), cred_key_correctness_proof);
+
+        Ok((cred_pub_key, cred_priv_key, cred_key_correctness_proof))
+    }
+
+    /// Creates credential definition deterministically derived from the seed:
+    /// the same seed and schemas always give byte-identical keys and correctness proof.
+    ///
+    /// INSECURE: private key is fully recoverable from the seed. Intended only for reproducible
+    /// test vectors, so it is available with `seeded_credential_def` feature only.
+    /// Never use it for production credential definitions.
+    ///
+    /// # Arguments
+    /// * `credential_schema` - Credential schema entity.
+    /// * `non_credential_schema` - Non credential schema entity.
+    /// * `seed` - Seed all safe primes and random values are derived from.
+    #[cfg(feature = "seeded_credential_def")]
+    pub fn new_credential_def_from_seed(credential_schema: &CredentialSchema,
+                                        non_credential_schema: &NonCredentialSchema,
+                                        seed: &[u8]) -> Result<(CredentialPublicKey,
+                                                                CredentialPrivateKey,
+                                                                CredentialKeyCorrectnessProof), IndyCryptoError> {
+        trace!("Issuer::new_credential_def_from_seed: >>> credential_schema: {:?}, non_credential_schema: {:?}, seed: {:?}",
+               credential_schema, non_credential_schema, secret!(seed));
+
+        if credential_schema.attrs.len() == 0 {
+            return Err(IndyCryptoError::InvalidStructure(format!("List of attributes is empty")));
+        }
+
+        let mut seeded_random = SeededRandom::new(seed);
+
+        let p_safe = seeded_random.safe_prime(LARGE_PRIME)?;
+        let q_safe = seeded_random.safe_prime(LARGE_PRIME)?;
+
+        Issuer::_check_safe_primes(&p_safe, &q_safe)?;
+
+        let mut rng = Some(seeded_random);
+
+        let (p_pub_key, p_priv_key, p_key_meta) =
+            Issuer::_new_credential_primary_keys_from_primes(credential_schema, non_credential_schema, &p_safe, &q_safe, &mut rng)?;
+
+        let (cred_pub_key, cred_priv_key, cred_key_correctness_proof) =
+            Issuer::_new_credential_def(p_pub_key, p_priv_key, p_key_meta, &mut rng)?;
+
+        trace!("Issuer::new_credential_def_from_seed: <<< cred_pub_key: {:?}, cred_priv_key: {:?}, cred_key_correctness_proof: {:?}",
+               cred_pub_key, secret!(&cred_priv_key), cred_key_correctness_proof);
+
+        Ok((cred_pub_key, cred_priv_key, cred_key_correctness_proof))
+    }
+
+    /// Creates new credential definition that replaces the old one and proof that links old and new keys.
+    ///
+    /// # Arguments
+    /// * `old_credential_priv_key` - Private key of credential definition to be rotated.
+    /// * `old_credential_pub_key` - Public key of credential definition to be rotated.
+    /// * `credential_schema` - Credential schema entity.
+    /// * `non_credential_schema` - Non credential schema entity.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    /// use indy_crypto::cl::verifier::Verifier;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("name").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let (cred_pub_key, cred_priv_key, _cred_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema).unwrap();
+    ///
+    /// let (new_cred_pub_key, _new_cred_priv_key, _new_cred_key_correctness_proof, key_rotation_proof) =
+    ///     Issuer::rotate_credential_def(&cred_priv_key, &cred_pub_key, &credential_schema, &non_credential_schema).unwrap();
+    ///
+    /// assert!(Verifier::verify_key_rotation(&cred_pub_key, &new_cred_pub_key, &key_rotation_proof).unwrap());
+    /// ```
+    pub fn rotate_credential_def(old_credential_priv_key: &CredentialPrivateKey,
+                                 old_credential_pub_key: &CredentialPublicKey,
+                                 credential_schema: &CredentialSchema,
+                                 non_credential_schema: &NonCredentialSchema) -> Result<(CredentialPublicKey,
+                                                                                         CredentialPrivateKey,
+                                                                                         CredentialKeyCorrectnessProof,
+                                                                                         KeyRotationProof), IndyCryptoError> {
+        trace!("Issuer::rotate_credential_def: >>> old_credential_priv_key: {:?}, old_credential_pub_key: {:?}, credential_schema: {:?}, \
+               non_credential_schema: {:?}", secret!(old_credential_priv_key), old_credential_pub_key, credential_schema, non_credential_schema);
+
+        let (cred_pub_key, cred_priv_key, cred_key_correctness_proof) =
+            Issuer::new_credential_def(credential_schema, non_credential_schema)?;
+
+        let key_rotation_proof = Issuer::_new_key_rotation_proof(&old_credential_pub_key.p_key, &old_credential_priv_key.p_key,
+                                                                 &cred_pub_key.p_key, &cred_priv_key.p_key)?;
+
+        trace!("Issuer::rotate_credential_def: <<< cred_pub_key: {:?}, cred_priv_key: {:?}, cred_key_correctness_proof: {:?}, key_rotation_proof: {:?}",
+               cred_pub_key, secret!(&cred_priv_key), cred_key_correctness_proof, key_rotation_proof);
+
+        Ok((cred_pub_key, cred_priv_key, cred_key_correctness_proof, key_rotation_proof))
+    }
+
+    /// Returns SHA-256 digest of credential offer (credential public key and its correctness proof).
+    /// Issuer publishes the digest as a fingerprint, so prover can check integrity of received offer
+    /// with `Prover::verify_credential_offer` before blinding of credential secrets.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key.
+    /// * `credential_key_correctness_proof` - Credential key correctness proof.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("sex").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let (cred_pub_key, _cred_priv_key, cred_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema).unwrap();
+    ///
+    /// let _digest = Issuer::credential_offer_digest(&cred_pub_key, &cred_key_correctness_proof).unwrap();
+    /// ```
+    pub fn credential_offer_digest(credential_pub_key: &CredentialPublicKey,
+                                   credential_key_correctness_proof: &CredentialKeyCorrectnessProof) -> Result<[u8; 32], IndyCryptoError> {
+        get_credential_offer_digest(credential_pub_key, credential_key_correctness_proof)
+    }
+
     /// Creates and returns credential values entity builder.
     ///
     /// The purpose of credential values builder is building of credential values entity that
@@ -104,7 +329,7 @@ impl Issuer {
     /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
     /// * `blinded_credential_secrets_correctness_proof` - Blinded credential secrets correctness proof.
     /// * `credential_nonce` - Nonce used for verification of blinded_credential_secrets_correctness_proof.
-    /// * `credential_issuance_nonce` - Nonce used for creation of signature_correctness_proof.
+    /// * `credential_issuance_nonce` - Nonce used for creation of signature_correctness_proof. Must differ from `credential_nonce`.
     /// * `credential_values` - Credential values to be signed.
     /// * `credential_pub_key` - Credential public key.
     /// * `credential_priv_key` - Credential private key.
@@ -173,6 +398,10 @@ impl Issuer {
                                             credential_pub_key,
                                             secret!(credential_priv_key));
 
+        check_nonce(credential_nonce)?;
+        check_nonce(credential_issuance_nonce)?;
+        Issuer::_check_nonces_differ(credential_nonce, credential_issuance_nonce)?;
+
         Issuer::_check_blinded_credential_secrets_correctness_proof(blinded_credential_secrets,
                                                                blinded_credential_secrets_correctness_proof,
                                                                credential_nonce,
@@ -183,7 +412,7 @@ impl Issuer {
                                                           blinded_credential_secrets,
                                                           credential_values)?;
 
-        let cred_signature = CredentialSignature { p_credential: p_cred};
+        let cred_signature = CredentialSignature { ver: SerializationVersion::current(), p_credential: p_cred };
 
         let signature_correctness_proof = Issuer::_new_signature_correctness_proof(&credential_pub_key.p_key,
                                                                                    &credential_priv_key.p_key,
@@ -202,41 +431,69 @@ impl Issuer {
     /// 输入：
     ///     CredentialSchema                    Credential模板
     ///     NonCredentialSchema                 NonCredential模板
+    ///     cancel                              取消标志（可选）
     /// 输出：
     ///     CredentialPrimaryPublicKey          Primary凭证公钥
     ///     CredentialPrimaryPrivateKey         Primary凭证私钥
     ///     CredentialPrimaryPublicKeyMetadata  Primary凭证元素
     /// 对应论文中 1.2.1-1.3.1
     fn _new_credential_primary_keys(credential_schema: &CredentialSchema,
-                                    non_credential_schema: &NonCredentialSchema) ->
+                                    non_credential_schema: &NonCredentialSchema,
+                                    cancel: Option<&AtomicBool>) ->
                                                                           Result<(CredentialPrimaryPublicKey,
                                                                                   CredentialPrimaryPrivateKey,
                                                                                   CredentialPrimaryPublicKeyMetadata), IndyCryptoError> {
         trace!("Issuer::_new_credential_primary_keys: >>> credential_schema: {:?}", credential_schema);
 
-        let mut ctx = BigNumber::new_context()?;
-
         if credential_schema.attrs.len() == 0 {
             return Err(IndyCryptoError::InvalidStructure(format!("List of attributes is empty")));
         }
 
-        let p_safe = generate_safe_prime(LARGE_PRIME)?;
-        let q_safe = generate_safe_prime(LARGE_PRIME)?;
+        let (p_safe, q_safe) = match cancel {
+            Some(cancel) => (generate_safe_prime_cancellable(LARGE_PRIME, cancel)?, generate_safe_prime_cancellable(LARGE_PRIME, cancel)?),
+            None => (generate_safe_prime(LARGE_PRIME)?, generate_safe_prime(LARGE_PRIME)?)
+        };
+
+        let (cred_pr_pub_key, cred_pr_priv_key, cred_pr_pub_key_metadata) =
+            Issuer::_new_credential_primary_keys_from_primes(credential_schema, non_credential_schema, &p_safe, &q_safe, &mut None)?;
+
+        trace!("Issuer::_new_credential_primary_keys: <<< cred_pr_pub_key: {:?}, cred_pr_priv_key: {:?}, cred_pr_pub_key_metadata: {:?}",
+               cred_pr_pub_key, secret!(&cred_pr_priv_key), cred_pr_pub_key_metadata);
+
+        Ok((cred_pr_pub_key, cred_pr_priv_key, cred_pr_pub_key_metadata))
+    }
+
+    /// 使用给定的safe素数生成Primary凭证的公私钥对
+    /// rng不为空时随机数由种子确定性生成（只用于测试向量）
+    fn _new_credential_primary_keys_from_primes(credential_schema: &CredentialSchema,
+                                                non_credential_schema: &NonCredentialSchema,
+                                                p_safe: &BigNumber,
+                                                q_safe: &BigNumber,
+                                                rng: &mut Option<SeededRandom>) -> Result<(CredentialPrimaryPublicKey,
+                                                                               CredentialPrimaryPrivateKey,
+                                                                               CredentialPrimaryPublicKeyMetadata), IndyCryptoError> {
+        trace!("Issuer::_new_credential_primary_keys_from_primes: >>> credential_schema: {:?}, non_credential_schema: {:?}",
+               credential_schema, non_credential_schema);
+
+        let mut ctx = BigNumber::new_context()?;
 
         let p = p_safe.rshift1()?;
         let q = q_safe.rshift1()?;
 
         let n = p_safe.mul(&q_safe, Some(&mut ctx))?;
-        let s = random_qr(&n)?;
-        let xz = gen_x(&p, &q)?;
+        let s = match *rng {
+            Some(ref mut rng) => rng.random_qr(&n)?,
+            None => random_qr(&n)?
+        };
+        let xz = Issuer::_gen_x(&p, &q, rng)?;
 
         let mut xr = HashMap::new();
         for non_schema_element in &non_credential_schema.attrs {
-            xr.insert(non_schema_element.to_string(), gen_x(&p, &q)?);
+            xr.insert(non_schema_element.to_string(), Issuer::_gen_x(&p, &q, rng)?);
         }
 
         for attribute in &credential_schema.attrs {
-            xr.insert(attribute.to_string(), gen_x(&p, &q)?);
+            xr.insert(attribute.to_string(), Issuer::_gen_x(&p, &q, rng)?);
         }
 
         let mut r = HashMap::new();
@@ -250,12 +507,55 @@ impl Issuer {
         let cred_pr_priv_key = CredentialPrimaryPrivateKey { p, q };
         let cred_pr_pub_key_metadata = CredentialPrimaryPublicKeyMetadata { xz, xr };
 
-        trace!("Issuer::_new_credential_primary_keys: <<< cred_pr_pub_key: {:?}, cred_pr_priv_key: {:?}, cred_pr_pub_key_metadata: {:?}",
+        trace!("Issuer::_new_credential_primary_keys_from_primes: <<< cred_pr_pub_key: {:?}, cred_pr_priv_key: {:?}, cred_pr_pub_key_metadata: {:?}",
                cred_pr_pub_key, secret!(&cred_pr_priv_key), cred_pr_pub_key_metadata);
 
         Ok((cred_pr_pub_key, cred_pr_priv_key, cred_pr_pub_key_metadata))
     }
 
+    /// 生成x，rng不为空时由种子确定性生成
+    fn _gen_x(p: &BigNumber, q: &BigNumber, rng: &mut Option<SeededRandom>) -> Result<BigNumber, IndyCryptoError> {
+        match *rng {
+            Some(ref mut rng) => rng.gen_x(p, q),
+            None => gen_x(p, q)
+        }
+    }
+
+    /// 检查给定的素数是否为LARGE_PRIME + 1位的不同safe素数
+    fn _check_safe_primes(p_safe: &BigNumber, q_safe: &BigNumber) -> Result<(), IndyCryptoError> {
+        let mut ctx = BigNumber::new_context()?;
+
+        for prime in [p_safe, q_safe].iter() {
+            if prime.num_bits()? != LARGE_PRIME as i32 + 1 || !prime.is_safe_prime(Some(&mut ctx))? {
+                return Err(IndyCryptoError::InvalidStructure(format!("Value is not a safe prime of {} bits", LARGE_PRIME + 1)));
+            }
+        }
+
+        if p_safe == q_safe {
+            return Err(IndyCryptoError::InvalidStructure("Safe primes must be different".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// 由Primary凭证的公私钥对生成凭证定义及其正确性证明
+    fn _new_credential_def(p_pub_key: CredentialPrimaryPublicKey,
+                           p_priv_key: CredentialPrimaryPrivateKey,
+                           p_key_meta: CredentialPrimaryPublicKeyMetadata,
+                           rng: &mut Option<SeededRandom>) -> Result<(CredentialPublicKey,
+                                                                                      CredentialPrivateKey,
+                                                                                      CredentialKeyCorrectnessProof), IndyCryptoError> {
+        let cred_pub_key = CredentialPublicKey { ver: SerializationVersion::current(), p_key: p_pub_key, tag: None };
+        let cred_priv_key = CredentialPrivateKey { p_key: p_priv_key };
+        let cred_key_correctness_proof =
+            Issuer::_new_credential_key_correctness_proof(&cred_pub_key.p_key,
+                                                          &cred_priv_key.p_key,
+                                                          &p_key_meta,
+                                                          rng)?;
+
+        Ok((cred_pub_key, cred_priv_key, cred_key_correctness_proof))
+    }
+
     /// 生成Primary凭证密钥的正确性证明
     /// 输入：
     ///     CredentialPrimaryPublicKey          Primary凭证公钥
@@ -266,17 +566,22 @@ impl Issuer {
     /// 对应论文中1.3.1
     fn _new_credential_key_correctness_proof(cred_pr_pub_key: &CredentialPrimaryPublicKey,
                                              cred_pr_priv_key: &CredentialPrimaryPrivateKey,
-                                             cred_pr_pub_key_meta: &CredentialPrimaryPublicKeyMetadata) -> Result<CredentialKeyCorrectnessProof, IndyCryptoError> {
+                                             cred_pr_pub_key_meta: &CredentialPrimaryPublicKeyMetadata,
+                                             rng: &mut Option<SeededRandom>) -> Result<CredentialKeyCorrectnessProof, IndyCryptoError> {
         trace!("Issuer::_new_credential_key_correctness_proof: >>> cred_pr_pub_key: {:?}, cred_pr_priv_key: {:?}, cred_pr_pub_key_meta: {:?}",
                cred_pr_pub_key, secret!(cred_pr_priv_key), cred_pr_pub_key_meta);
 
         let mut ctx = BigNumber::new_context()?;
 
-        let xz_tilda = gen_x(&cred_pr_priv_key.p, &cred_pr_priv_key.q)?;
+        // 按属性名排序，使xr_cap的顺序（以及种子生成时的结果）不依赖HashMap的遍历顺序
+        let mut ordered_attrs: Vec<String> = cred_pr_pub_key.r.keys().cloned().collect();
+        ordered_attrs.sort();
+
+        let xz_tilda = Issuer::_gen_x(&cred_pr_priv_key.p, &cred_pr_priv_key.q, rng)?;
 
         let mut xr_tilda = HashMap::new();
-        for key in cred_pr_pub_key.r.keys() {
-            xr_tilda.insert(key.to_string(), gen_x(&cred_pr_priv_key.p, &cred_pr_priv_key.q)?);
+        for key in ordered_attrs.iter() {
+            xr_tilda.insert(key.to_string(), Issuer::_gen_x(&cred_pr_priv_key.p, &cred_pr_priv_key.q, rng)?);
         }
 
         let z_tilda = cred_pr_pub_key.s.mod_exp(&xz_tilda, &cred_pr_pub_key.n, Some(&mut ctx))?;
@@ -287,11 +592,9 @@ impl Issuer {
         }
 
         let mut values: Vec<u8> = Vec::new();
-        let mut ordered_attrs: Vec<String> = Vec::new();
         values.extend_from_slice(&cred_pr_pub_key.z.to_bytes()?);
-        for (key, val) in cred_pr_pub_key.r.iter() {
-            values.extend_from_slice(&val.to_bytes()?);
-            ordered_attrs.push(key.to_owned());
+        for key in ordered_attrs.iter() {
+            values.extend_from_slice(&cred_pr_pub_key.r[key].to_bytes()?);
         }
         values.extend_from_slice(&z_tilda.to_bytes()?);
         for attr in &ordered_attrs {
@@ -321,6 +624,102 @@ impl Issuer {
         Ok(key_correctness_proof)
     }
 
+    /// 生成密钥轮换证明
+    /// 对新旧两个密钥同时证明知道 n - phi(n)（即知道n的分解），两个证明使用同一个挑战
+    /// 挑战绑定领域标签、新旧公钥和两个承诺 S^x_tilde
+    /// 输入：
+    ///     old_cred_pr_pub_key                 旧Primary凭证公钥
+    ///     old_cred_pr_priv_key                旧Primary凭证私钥
+    ///     new_cred_pr_pub_key                 新Primary凭证公钥
+    ///     new_cred_pr_priv_key                新Primary凭证私钥
+    /// 输出：
+    ///     KeyRotationProof                    密钥轮换证明 (c, x_cap = x_tilde + c * (n - phi(n)))
+    fn _new_key_rotation_proof(old_cred_pr_pub_key: &CredentialPrimaryPublicKey,
+                               old_cred_pr_priv_key: &CredentialPrimaryPrivateKey,
+                               new_cred_pr_pub_key: &CredentialPrimaryPublicKey,
+                               new_cred_pr_priv_key: &CredentialPrimaryPrivateKey) -> Result<KeyRotationProof, IndyCryptoError> {
+        trace!("Issuer::_new_key_rotation_proof: >>> old_cred_pr_pub_key: {:?}, old_cred_pr_priv_key: {:?}, new_cred_pr_pub_key: {:?}, new_cred_pr_priv_key: {:?}",
+               old_cred_pr_pub_key, secret!(old_cred_pr_priv_key), new_cred_pr_pub_key, secret!(new_cred_pr_priv_key));
+
+        let mut ctx = BigNumber::new_context()?;
+
+        let mut old_x = Issuer::_key_rotation_secret(old_cred_pr_pub_key, old_cred_pr_priv_key)?;
+        let mut new_x = Issuer::_key_rotation_secret(new_cred_pr_pub_key, new_cred_pr_priv_key)?;
+
+        let mut old_x_tilde = bn_rand(LARGE_KEY_ROTATION_TILDE)?;
+        let mut new_x_tilde = bn_rand(LARGE_KEY_ROTATION_TILDE)?;
+
+        let old_t = old_cred_pr_pub_key.s.mod_exp_consttime(&old_x_tilde, &old_cred_pr_pub_key.n, Some(&mut ctx))?;
+        let new_t = new_cred_pr_pub_key.s.mod_exp_consttime(&new_x_tilde, &new_cred_pr_pub_key.n, Some(&mut ctx))?;
+
+        let c = get_key_rotation_challenge(old_cred_pr_pub_key, new_cred_pr_pub_key, &old_t, &new_t)?;
+
+        let old_x_cap = c.mul(&old_x, Some(&mut ctx))?.add(&old_x_tilde)?;
+        let new_x_cap = c.mul(&new_x, Some(&mut ctx))?.add(&new_x_tilde)?;
+
+        old_x.clear();
+        new_x.clear();
+        old_x_tilde.clear();
+        new_x_tilde.clear();
+
+        let key_rotation_proof = KeyRotationProof { c, old_x_cap, new_x_cap };
+
+        trace!("Issuer::_new_key_rotation_proof: <<< key_rotation_proof: {:?}", key_rotation_proof);
+
+        Ok(key_rotation_proof)
+    }
+
+    /// 检查私钥与公钥对应，并计算密钥轮换证明的秘密值
+    /// n = (2p' + 1)(2q' + 1), phi(n) = 4p'q'
+    /// 输出：
+    ///     x                                   n - phi(n) = 2p' + 2q' + 1
+    fn _key_rotation_secret(cred_pr_pub_key: &CredentialPrimaryPublicKey,
+                            cred_pr_priv_key: &CredentialPrimaryPrivateKey) -> Result<BigNumber, IndyCryptoError> {
+        let mut p_safe = cred_pr_priv_key.p.lshift1()?.increment()?;
+        let mut q_safe = cred_pr_priv_key.q.lshift1()?.increment()?;
+
+        let res = if p_safe.mul(&q_safe, None)? != cred_pr_pub_key.n {
+            Err(IndyCryptoError::InvalidStructure(format!("Credential private key doesn't correspond to credential public key")))
+        } else {
+            p_safe.add(&q_safe)?.decrement()
+        };
+
+        p_safe.clear();
+        q_safe.clear();
+
+        res
+    }
+
+    /// 检查盲化阶段和签发阶段的nonce不同
+    /// 相同的nonce会使两个正确性证明的挑战绑定到同一个值上
+    fn _check_nonces_differ(credential_nonce: &Nonce,
+                            credential_issuance_nonce: &Nonce) -> Result<(), IndyCryptoError> {
+        if credential_nonce == credential_issuance_nonce {
+            return Err(IndyCryptoError::InvalidStructure("Credential nonce and credential issuance nonce must differ".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 检查盲化凭证正确性证明的结构：每个隐藏属性和承诺属性都有m_cap，每个承诺属性都有r_cap
+    fn _check_blinded_credential_secrets_correctness_proof_structure(blinded_cred_secrets: &BlindedCredentialSecrets,
+                                                                     blinded_cred_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof) -> Result<(), IndyCryptoError> {
+        let expected_m_caps = blinded_cred_secrets.hidden_attributes.iter()
+            .chain(blinded_cred_secrets.committed_attributes.keys())
+            .collect::<BTreeSet<&String>>();
+
+        if blinded_cred_secrets_correctness_proof.m_caps.keys().collect::<BTreeSet<&String>>() != expected_m_caps {
+            return Err(IndyCryptoError::InvalidStructure(
+                "Invalid BlindedCredentialSecrets correctness proof: m_caps don't correspond to hidden and committed attributes".to_string()));
+        }
+
+        if !blinded_cred_secrets_correctness_proof.r_caps.keys().eq(blinded_cred_secrets.committed_attributes.keys()) {
+            return Err(IndyCryptoError::InvalidStructure(
+                "Invalid BlindedCredentialSecrets correctness proof: r_caps don't correspond to committed attributes".to_string()));
+        }
+
+        Ok(())
+    }
+
     /// 检查Prover发来的盲化凭证的正确性
     /// 
     /// 输入：
@@ -340,6 +739,9 @@ impl Issuer {
         trace!("Issuer::_check_blinded_credential_secrets_correctness_proof: >>> blinded_cred_secrets: {:?}, blinded_cred_secrets_correctness_proof: {:?},\
          nonce: {:?}, cred_pr_pub_key: {:?}", blinded_cred_secrets, blinded_cred_secrets_correctness_proof, nonce, cred_pr_pub_key);
 
+        Issuer::_check_blinded_credential_secrets_correctness_proof_structure(blinded_cred_secrets,
+                                                                              blinded_cred_secrets_correctness_proof)?;
+
         let mut values: Vec<u8> = Vec::new();
         let mut ctx = BigNumber::new_context()?;
         // 论文2.3.1步骤
@@ -385,7 +787,8 @@ impl Issuer {
         let valid = blinded_cred_secrets_correctness_proof.c.eq(&c);
 
         if !valid {
-            return Err(IndyCryptoError::InvalidStructure(format!("Invalid BlindedCredentialSecrets correctness proof")));
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Invalid BlindedCredentialSecrets correctness proof: hidden or committed attributes don't match the proof")));
         }
 
         trace!("Issuer::_check_blinded_credential_secrets_correctness_proof: <<<");
@@ -547,6 +950,10 @@ mod tests {
     use cl::helpers::MockHelper;
     use self::prover::mocks as prover_mocks;
     use self::prover::Prover;
+    use self::verifier::Verifier;
+    use std::sync::atomic::Ordering;
+    use std::thread;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn credential_schema_builder_works() {
@@ -595,12 +1002,115 @@ mod tests {
 
     #[test]
     fn issuer_new_credential_works_for_empty_attributes() {
-        let cred_attrs = CredentialSchema { attrs: BTreeSet::new() };
+        let cred_attrs = CredentialSchema { attrs: BTreeSet::new(), public_attrs: BTreeSet::new() };
         let non_cred_attrs = NonCredentialSchema { attrs: BTreeSet::new() };
         let res = Issuer::new_credential_def(&cred_attrs, &non_cred_attrs);
         assert!(res.is_err())
     }
 
+    #[test]
+    fn issuer_new_credential_def_from_primes_works_for_invalid_primes() {
+        MockHelper::inject();
+
+        let p_safe = generate_safe_prime(LARGE_PRIME).unwrap();
+        let not_safe_prime = p_safe.sub(&BigNumber::from_u32(2).unwrap()).unwrap();
+        let small_safe_prime = BigNumber::from_u32(23).unwrap();
+
+        let table = vec![
+            (&p_safe, &not_safe_prime),
+            (&small_safe_prime, &p_safe),
+            (&p_safe, &p_safe),
+        ];
+
+        for (p, q) in table {
+            let res = Issuer::new_credential_def_from_primes(&mocks::credential_schema(), &mocks::non_credential_schema(), p, q);
+            assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+        }
+    }
+
+    #[test]
+    fn issuer_new_credential_def_cancellable_works_for_cancelled_token() {
+        let cancel = Arc::new(AtomicBool::new(true));
+
+        let res = Issuer::new_credential_def_cancellable(&mocks::credential_schema(), &mocks::non_credential_schema(), cancel);
+        assert!(match res { Err(IndyCryptoError::OperationCancelled(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn issuer_new_credential_def_cancellable_works_for_token_set_during_generation() {
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let cancel_setter = cancel.clone();
+        let setter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            cancel_setter.store(true, Ordering::SeqCst);
+        });
+
+        let start = Instant::now();
+        let res = Issuer::new_credential_def_cancellable(&mocks::credential_schema(), &mocks::non_credential_schema(), cancel);
+        let elapsed = start.elapsed();
+        setter.join().unwrap();
+
+        assert!(match res { Err(IndyCryptoError::OperationCancelled(_)) => true, _ => false });
+        assert!(elapsed < Duration::from_secs(5));
+    }
+
+    fn _small_credential_keys() -> (CredentialPublicKey, CredentialPrivateKey) {
+        // n = 23 * 47, p' = 11, q' = 23
+        let pub_key = CredentialPublicKey {
+            ver: SerializationVersion::current(),
+            p_key: CredentialPrimaryPublicKey {
+                n: BigNumber::from_u32(1081).unwrap(),
+                s: BigNumber::from_u32(4).unwrap(),
+                r: hashmap!["name".to_string() => BigNumber::from_u32(64).unwrap()],
+                z: BigNumber::from_u32(1024).unwrap()
+            },
+            tag: None
+        };
+        let priv_key = CredentialPrivateKey {
+            p_key: CredentialPrimaryPrivateKey {
+                p: BigNumber::from_u32(11).unwrap(),
+                q: BigNumber::from_u32(23).unwrap()
+            }
+        };
+        (pub_key, priv_key)
+    }
+
+    #[test]
+    fn new_key_rotation_proof_works() {
+        let (old_pub_key, old_priv_key) = (mocks::credential_public_key(), mocks::credential_private_key());
+        let (new_pub_key, new_priv_key) = _small_credential_keys();
+
+        let key_rotation_proof = Issuer::_new_key_rotation_proof(&old_pub_key.p_key, &old_priv_key.p_key,
+                                                                 &new_pub_key.p_key, &new_priv_key.p_key).unwrap();
+
+        assert!(Verifier::verify_key_rotation(&old_pub_key, &new_pub_key, &key_rotation_proof).unwrap());
+        assert!(!Verifier::verify_key_rotation(&new_pub_key, &old_pub_key, &key_rotation_proof).unwrap());
+        assert!(!Verifier::verify_key_rotation(&old_pub_key, &old_pub_key, &key_rotation_proof).unwrap());
+    }
+
+    #[test]
+    fn new_key_rotation_proof_works_for_proof_without_old_private_key() {
+        let old_pub_key = mocks::credential_public_key();
+        let (new_pub_key, new_priv_key) = _small_credential_keys();
+
+        // proof made with new key only (for rotation from new key to itself) doesn't prove ownership of old key
+        let key_rotation_proof = Issuer::_new_key_rotation_proof(&new_pub_key.p_key, &new_priv_key.p_key,
+                                                                 &new_pub_key.p_key, &new_priv_key.p_key).unwrap();
+
+        assert!(Verifier::verify_key_rotation(&new_pub_key, &new_pub_key, &key_rotation_proof).unwrap());
+        assert!(!Verifier::verify_key_rotation(&old_pub_key, &new_pub_key, &key_rotation_proof).unwrap());
+    }
+
+    #[test]
+    fn new_key_rotation_proof_works_for_private_key_not_correspond_to_public_key() {
+        let old_pub_key = mocks::credential_public_key();
+        let (new_pub_key, new_priv_key) = _small_credential_keys();
+
+        let res = Issuer::_new_key_rotation_proof(&old_pub_key.p_key, &new_priv_key.p_key, &new_pub_key.p_key, &new_priv_key.p_key);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
     #[test]
     fn sign_primary_credential_works() {
         MockHelper::inject();
@@ -650,6 +1160,99 @@ mod tests {
         assert_eq!(expected_signature_correctness_proof, signature_correctness_proof);
     }
 
+    #[test]
+    fn sign_credential_signature_fails_for_same_nonces() {
+        MockHelper::inject();
+
+        let (pub_key, priv_key) = (mocks::credential_public_key(), mocks::credential_private_key());
+        let nonce = mocks::credential_nonce();
+        let (blinded_credential_secrets, blinded_credential_secrets_correctness_proof) =
+            (prover::mocks::blinded_credential_secrets(), prover::mocks::blinded_credential_secrets_correctness_proof());
+
+        let res = Issuer::sign_credential(prover_mocks::PROVER_DID,
+                                          &blinded_credential_secrets,
+                                          &blinded_credential_secrets_correctness_proof,
+                                          &nonce,
+                                          &nonce,
+                                          &mocks::credential_values(),
+                                          &pub_key,
+                                          &priv_key);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    fn _blinded_credential_secrets_with_committed_attr() -> (CredentialValues, BlindedCredentialSecrets, BlindedCredentialSecretsCorrectnessProof) {
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &prover_mocks::master_secret().value().unwrap()).unwrap();
+        credential_values_builder.add_value_commitment("name", &BigNumber::from_u32(1).unwrap(), &BigNumber::from_u32(2).unwrap()).unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("age", "25").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let (blinded_credential_secrets, _, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&mocks::credential_public_key(),
+                                             &mocks::credential_key_correctness_proof(),
+                                             &credential_values,
+                                             &mocks::credential_nonce()).unwrap();
+
+        (credential_values, blinded_credential_secrets, blinded_credential_secrets_correctness_proof)
+    }
+
+    fn _sign_credential(credential_values: &CredentialValues,
+                        blinded_credential_secrets: &BlindedCredentialSecrets,
+                        blinded_credential_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof) -> Result<(CredentialSignature, SignatureCorrectnessProof), IndyCryptoError> {
+        Issuer::sign_credential(prover_mocks::PROVER_DID,
+                                blinded_credential_secrets,
+                                blinded_credential_secrets_correctness_proof,
+                                &mocks::credential_nonce(),
+                                &mocks::credential_issuance_nonce(),
+                                credential_values,
+                                &mocks::credential_public_key(),
+                                &mocks::credential_private_key())
+    }
+
+    #[test]
+    fn sign_credential_works_for_committed_attr() {
+        let (credential_values, blinded_credential_secrets, blinded_credential_secrets_correctness_proof) =
+            _blinded_credential_secrets_with_committed_attr();
+
+        assert!(_sign_credential(&credential_values, &blinded_credential_secrets, &blinded_credential_secrets_correctness_proof).is_ok());
+    }
+
+    #[test]
+    fn sign_credential_fails_for_tampered_v_dash_cap() {
+        let (credential_values, blinded_credential_secrets, mut blinded_credential_secrets_correctness_proof) =
+            _blinded_credential_secrets_with_committed_attr();
+
+        blinded_credential_secrets_correctness_proof.v_dash_cap = blinded_credential_secrets_correctness_proof.v_dash_cap.increment().unwrap();
+
+        let res = _sign_credential(&credential_values, &blinded_credential_secrets, &blinded_credential_secrets_correctness_proof);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn sign_credential_fails_for_tampered_committed_attr() {
+        let (credential_values, mut blinded_credential_secrets, blinded_credential_secrets_correctness_proof) =
+            _blinded_credential_secrets_with_committed_attr();
+
+        let commitment = blinded_credential_secrets.committed_attributes["name"].increment().unwrap();
+        blinded_credential_secrets.committed_attributes.insert("name".to_string(), commitment);
+
+        let res = _sign_credential(&credential_values, &blinded_credential_secrets, &blinded_credential_secrets_correctness_proof);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn sign_credential_fails_for_missed_r_cap() {
+        let (credential_values, blinded_credential_secrets, mut blinded_credential_secrets_correctness_proof) =
+            _blinded_credential_secrets_with_committed_attr();
+
+        blinded_credential_secrets_correctness_proof.r_caps.remove("name");
+
+        let res = _sign_credential(&credential_values, &blinded_credential_secrets, &blinded_credential_secrets_correctness_proof);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
     #[test]
     #[ignore]
     fn generate_mocks() {
@@ -733,9 +1336,13 @@ pub mod mocks {
     use super::*;
     use self::prover::mocks as prover_mocks;
 
+    pub const CREDENTIAL_KEY_ID: &'static str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW:3:CL:1";
+
     pub fn credential_public_key() -> CredentialPublicKey {
         CredentialPublicKey {
-            p_key: credential_primary_public_key()
+            ver: SerializationVersion::current(),
+            p_key: credential_primary_public_key(),
+            tag: None
         }
     }
 
@@ -789,7 +1396,8 @@ pub mod mocks {
 
     pub fn credential_schema() -> CredentialSchema {
         CredentialSchema {
-            attrs: btreeset!["name".to_string(), "age".to_string(), "height".to_string(), "sex".to_string()]
+            attrs: btreeset!["name".to_string(), "age".to_string(), "height".to_string(), "sex".to_string()],
+            public_attrs: BTreeSet::new()
         }
     }
 
@@ -813,6 +1421,7 @@ pub mod mocks {
 
     pub fn credential() -> CredentialSignature {
         CredentialSignature {
+            ver: SerializationVersion::current(),
             p_credential: primary_credential()
         }
     }
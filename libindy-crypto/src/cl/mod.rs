@@ -1,9 +1,11 @@
 #[macro_use]
 pub mod logger;
+mod bytes;
 mod commitment;
 mod constants;
 #[macro_use]
 mod datastructures;
+mod fixed_base;
 #[macro_use]
 mod helpers;
 mod hash;
@@ -11,11 +13,15 @@ pub mod issuer;
 pub mod prover;
 pub mod verifier;
 
-use bn::BigNumber;
+use bn::{BigNumber, BigNumberHasher, BnCtxPool, BIGNUMBER_1, serialize_fixed_width, deserialize_fixed_width};
 use errors::IndyCryptoError;
+use self::fixed_base::FixedBaseTable;
+use self::hash::HashBuilder;
 
 use std::collections::{HashMap, HashSet, BTreeSet, BTreeMap};
 use std::hash::Hash;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
 
 /// Creates random nonce
 ///
@@ -26,37 +32,150 @@ use std::hash::Hash;
 /// let _nonce = new_nonce().unwrap();
 /// ```
 pub fn new_nonce() -> Result<Nonce, IndyCryptoError> {
-    Ok(helpers::bn_rand(constants::LARGE_NONCE)?)
+    loop {
+        let nonce = helpers::bn_rand(constants::LARGE_NONCE)?;
+
+        if check_nonce(&nonce).is_ok() {
+            return Ok(nonce);
+        }
+    }
+}
+
+/// Checks that nonce received from other party is nonzero and has at least `MIN_NONCE_BITS` bits,
+/// so zero or tiny nonces can't undermine freshness of proofs.
+pub fn check_nonce(nonce: &Nonce) -> Result<(), IndyCryptoError> {
+    if nonce.is_negative() || nonce.num_bits()? == 0 {
+        return Err(IndyCryptoError::InvalidStructure("Nonce must be positive".to_string()));
+    }
+
+    if (nonce.num_bits()? as usize) < constants::MIN_NONCE_BITS {
+        return Err(IndyCryptoError::InvalidStructure(
+            format!("Nonce has {} bits, at least {} bits expected", nonce.num_bits()?, constants::MIN_NONCE_BITS)));
+    }
+
+    Ok(())
+}
+
+/// Returns attribute name in Unicode Normalization Form C,
+/// so canonically equivalent names (for example NFC and NFD forms of "café") map to the same attribute.
+pub fn normalize_attr_name(attr: &str) -> String {
+    attr.nfc().collect()
 }
 
+/// Attribute names reserved for hidden (non-credential) attributes.
+/// They can't be used as credential schema attributes.
+pub const RESERVED_ATTR_NAMES: [&'static str; 1] = ["master_secret"];
+
 /// A list of attributes a Credential is based on.
-#[derive(Debug, Clone)]
+///
+/// Public attributes are always revealed: they can't be hidden or used in predicates.
+/// Schema is `Send` and `Sync`, so it can be shared between threads by `Arc`.
+#[derive(Debug, Clone, Serialize)]
 pub struct CredentialSchema {
     attrs: BTreeSet<String>, /* attr names */
+    #[serde(default)]
+    public_attrs: BTreeSet<String>, /* attr names that must be always revealed */
+}
+
+impl CredentialSchema {
+    /// Builds schema with given attributes, the same as adding them one by one by `CredentialSchemaBuilder::add_attr`.
+    pub fn from_attrs(attrs: &[&str]) -> Result<CredentialSchema, IndyCryptoError> {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new()?;
+
+        for attr in attrs {
+            credential_schema_builder.add_attr(attr)?;
+        }
+
+        credential_schema_builder.finalize()
+    }
+}
+
+/// Deserializes schema through `CredentialSchemaBuilder`, so attribute names are normalized (NFC)
+/// and checked the same way as for schema built by the builder.
+impl <'a> ::serde::de::Deserialize<'a> for CredentialSchema {
+    fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        struct CredentialSchemaV1 {
+            attrs: BTreeSet<String>,
+            #[serde(default)]
+            public_attrs: BTreeSet<String>
+        }
+
+        let helper = CredentialSchemaV1::deserialize(deserializer)?;
+
+        let build = || -> Result<CredentialSchema, IndyCryptoError> {
+            let mut credential_schema_builder = CredentialSchemaBuilder::new()?;
+
+            for attr in helper.attrs.iter() {
+                credential_schema_builder.add_attr(attr)?;
+            }
+
+            for attr in helper.public_attrs.iter() {
+                credential_schema_builder.add_public_attr(attr)?;
+            }
+
+            credential_schema_builder.finalize()
+        };
+
+        build().map_err(|err| D::Error::custom(format!("{:?}", err)))
+    }
 }
 
 /// A Builder of `Credential Schema`.
 #[derive(Debug)]
 pub struct CredentialSchemaBuilder {
     attrs: BTreeSet<String>, /* attr names */
+    public_attrs: BTreeSet<String>, /* attr names that must be always revealed */
+    limits: SizeLimits,
 }
 
 impl CredentialSchemaBuilder {
     pub fn new() -> Result<CredentialSchemaBuilder, IndyCryptoError> {
-        Ok(CredentialSchemaBuilder { attrs: BTreeSet::new() })
+        Ok(CredentialSchemaBuilder { attrs: BTreeSet::new(), public_attrs: BTreeSet::new(), limits: SizeLimits::DEFAULT })
+    }
+
+    /// Overrides default limits (`SizeLimits::DEFAULT`) checked by `finalize`.
+    pub fn with_limits(mut self, limits: SizeLimits) -> Result<CredentialSchemaBuilder, IndyCryptoError> {
+        self.limits = limits;
+        Ok(self)
     }
 
     pub fn add_attr(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
-        self.attrs.insert(attr.to_owned());
+        self.attrs.insert(normalize_attr_name(attr));
+        Ok(())
+    }
+
+    /// Adds attribute as `add_attr` does, but returns error if attribute was already added.
+    pub fn add_attr_checked(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
+        if !self.attrs.insert(normalize_attr_name(attr)) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Attribute {} is already added to credential schema", attr)));
+        }
+        Ok(())
+    }
+
+    /// Adds attribute that must be always revealed in proofs.
+    pub fn add_public_attr(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
+        self.attrs.insert(normalize_attr_name(attr));
+        self.public_attrs.insert(normalize_attr_name(attr));
         Ok(())
     }
 
     pub fn finalize(self) -> Result<CredentialSchema, IndyCryptoError> {
-        Ok(CredentialSchema { attrs: self.attrs })
+        if let Some(attr) = self.attrs.iter().find(|attr| RESERVED_ATTR_NAMES.contains(&attr.as_str())) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Attribute name {} is reserved and can't be used in credential schema", attr)));
+        }
+
+        let credential_schema = CredentialSchema { attrs: self.attrs, public_attrs: self.public_attrs };
+        self.limits.check_credential_schema(&credential_schema)?;
+
+        Ok(credential_schema)
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize, Deserialize))]
 pub struct NonCredentialSchema {
     attrs: BTreeSet<String>,
 }
@@ -74,11 +193,15 @@ impl NonCredentialSchemaBuilder {
     }
 
     pub fn add_attr(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
-        self.attrs.insert(attr.to_owned());
+        self.attrs.insert(normalize_attr_name(attr));
         Ok(())
     }
 
     pub fn finalize(self) -> Result<NonCredentialSchema, IndyCryptoError> {
+        for attr in self.attrs.iter().filter(|attr| !RESERVED_ATTR_NAMES.contains(&attr.as_str())) {
+            warn!("Attribute {} is not an intended hidden attribute but added to non credential schema", attr);
+        }
+
         Ok(NonCredentialSchema { attrs: self.attrs })
     }
 }
@@ -146,6 +269,7 @@ impl CredentialValue {
 
 /// Values of attributes from `Claim Schema` (must be integers).
 #[derive(Debug)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize))]
 pub struct CredentialValues {
     attrs_values: BTreeMap<String, CredentialValue>,
 }
@@ -156,6 +280,69 @@ impl CredentialValues {
             attrs_values: clone_credential_value_map(&self.attrs_values)?
         })
     }
+
+    /// Returns value of attribute or `None` if credential values don't contain it.
+    pub fn get(&self, attr: &str) -> Option<&CredentialValue> {
+        self.attrs_values.get(&normalize_attr_name(attr))
+    }
+
+    /// Returns names of attributes in sorted order.
+    pub fn attrs(&self) -> impl Iterator<Item=&String> {
+        self.attrs_values.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.attrs_values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attrs_values.is_empty()
+    }
+
+    fn clear(&mut self) {
+        for value in self.attrs_values.values_mut() {
+            match *value {
+                CredentialValue::Known { ref mut value } | CredentialValue::Hidden { ref mut value } => value.clear(),
+                CredentialValue::Commitment { ref mut value, ref mut blinding_factor } => {
+                    value.clear();
+                    blinding_factor.clear();
+                }
+            }
+        }
+    }
+}
+
+/// Deserializes values through `CredentialValuesBuilder`, so attribute names are normalized (NFC)
+/// the same way as for values built by the builder.
+#[cfg(feature = "proof_builder_snapshot")]
+impl <'a> ::serde::de::Deserialize<'a> for CredentialValues {
+    fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        struct CredentialValuesV1 {
+            attrs_values: BTreeMap<String, CredentialValue>
+        }
+
+        let helper = CredentialValuesV1::deserialize(deserializer)?;
+
+        let build = || -> Result<CredentialValues, IndyCryptoError> {
+            let mut credential_values_builder = CredentialValuesBuilder::new()?;
+
+            for (attr, value) in helper.attrs_values.iter() {
+                match *value {
+                    CredentialValue::Known { ref value } => credential_values_builder.add_value_known(attr, value)?,
+                    CredentialValue::Hidden { ref value } => credential_values_builder.add_value_hidden(attr, value)?,
+                    CredentialValue::Commitment { ref value, ref blinding_factor } =>
+                        credential_values_builder.add_value_commitment(attr, value, blinding_factor)?
+                }
+            }
+
+            credential_values_builder.finalize()
+        };
+
+        build().map_err(|err| D::Error::custom(format!("{:?}", err)))
+    }
 }
 
 /// A Builder of `Credential Values`.
@@ -171,7 +358,7 @@ impl CredentialValuesBuilder {
 
     pub fn add_dec_known(&mut self, attr: &str, value: &str) -> Result<(), IndyCryptoError> {
         self.attrs_values.insert(
-            attr.to_owned(),
+            normalize_attr_name(attr),
             CredentialValue::Known { value: BigNumber::from_dec(value)? },
         );
         Ok(())
@@ -179,7 +366,7 @@ impl CredentialValuesBuilder {
 
     pub fn add_dec_hidden(&mut self, attr: &str, value: &str) -> Result<(), IndyCryptoError> {
         self.attrs_values.insert(
-            attr.to_owned(),
+            normalize_attr_name(attr),
             CredentialValue::Hidden { value: BigNumber::from_dec(value)? },
         );
         Ok(())
@@ -192,7 +379,7 @@ impl CredentialValuesBuilder {
         blinding_factor: &str,
     ) -> Result<(), IndyCryptoError> {
         self.attrs_values.insert(
-            attr.to_owned(),
+            normalize_attr_name(attr),
             CredentialValue::Commitment {
                 value: BigNumber::from_dec(value)?,
                 blinding_factor: BigNumber::from_dec(blinding_factor)?,
@@ -201,13 +388,87 @@ impl CredentialValuesBuilder {
         Ok(())
     }
 
+    /// Adds known attribute given as big-endian hex string (even number of hex digits, without `0x` prefix).
+    pub fn add_hex_known(&mut self, attr: &str, value: &str) -> Result<(), IndyCryptoError> {
+        self.attrs_values.insert(
+            normalize_attr_name(attr),
+            CredentialValue::Known { value: CredentialValuesBuilder::_bn_from_hex(value)? },
+        );
+        Ok(())
+    }
+
+    pub fn add_hex_hidden(&mut self, attr: &str, value: &str) -> Result<(), IndyCryptoError> {
+        self.attrs_values.insert(
+            normalize_attr_name(attr),
+            CredentialValue::Hidden { value: CredentialValuesBuilder::_bn_from_hex(value)? },
+        );
+        Ok(())
+    }
+
+    pub fn add_hex_commitment(
+        &mut self,
+        attr: &str,
+        value: &str,
+        blinding_factor: &str,
+    ) -> Result<(), IndyCryptoError> {
+        self.attrs_values.insert(
+            normalize_attr_name(attr),
+            CredentialValue::Commitment {
+                value: CredentialValuesBuilder::_bn_from_hex(value)?,
+                blinding_factor: CredentialValuesBuilder::_bn_from_hex(blinding_factor)?,
+            },
+        );
+        Ok(())
+    }
+
+    /// Adds known attribute given as base58 string (Bitcoin alphabet).
+    pub fn add_base58_known(&mut self, attr: &str, value: &str) -> Result<(), IndyCryptoError> {
+        self.attrs_values.insert(
+            normalize_attr_name(attr),
+            CredentialValue::Known { value: BigNumber::from_base58(value)? },
+        );
+        Ok(())
+    }
+
+    pub fn add_base58_hidden(&mut self, attr: &str, value: &str) -> Result<(), IndyCryptoError> {
+        self.attrs_values.insert(
+            normalize_attr_name(attr),
+            CredentialValue::Hidden { value: BigNumber::from_base58(value)? },
+        );
+        Ok(())
+    }
+
+    pub fn add_base58_commitment(
+        &mut self,
+        attr: &str,
+        value: &str,
+        blinding_factor: &str,
+    ) -> Result<(), IndyCryptoError> {
+        self.attrs_values.insert(
+            normalize_attr_name(attr),
+            CredentialValue::Commitment {
+                value: BigNumber::from_base58(value)?,
+                blinding_factor: BigNumber::from_base58(blinding_factor)?,
+            },
+        );
+        Ok(())
+    }
+
+    fn _bn_from_hex(hex: &str) -> Result<BigNumber, IndyCryptoError> {
+        if hex.is_empty() || hex.len() % 2 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Invalid hex value: {:?}", hex)));
+        }
+
+        BigNumber::from_hex(hex)
+    }
+
     pub fn add_value_known(
         &mut self,
         attr: &str,
         value: &BigNumber,
     ) -> Result<(), IndyCryptoError> {
         self.attrs_values.insert(
-            attr.to_owned(),
+            normalize_attr_name(attr),
             CredentialValue::Known { value: value.clone()? },
         );
         Ok(())
@@ -219,7 +480,7 @@ impl CredentialValuesBuilder {
         value: &BigNumber,
     ) -> Result<(), IndyCryptoError> {
         self.attrs_values.insert(
-            attr.to_owned(),
+            normalize_attr_name(attr),
             CredentialValue::Hidden { value: value.clone()? },
         );
         Ok(())
@@ -232,7 +493,7 @@ impl CredentialValuesBuilder {
         blinding_factor: &BigNumber,
     ) -> Result<(), IndyCryptoError> {
         self.attrs_values.insert(
-            attr.to_owned(),
+            normalize_attr_name(attr),
             CredentialValue::Commitment {
                 value: value.clone()?,
                 blinding_factor: blinding_factor.clone()?,
@@ -241,38 +502,217 @@ impl CredentialValuesBuilder {
         Ok(())
     }
 
+    /// Fails if no values were added: empty credential values can't be blinded or signed.
     pub fn finalize(self) -> Result<CredentialValues, IndyCryptoError> {
+        if self.attrs_values.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure("Credential values are empty".to_string()));
+        }
+
         Ok(CredentialValues { attrs_values: self.attrs_values })
     }
 }
 
+/// Version of serialization format of top level entities (`CredentialPublicKey`, `CredentialSignature`, `Proof`).
+///
+/// Serialized as `ver` field. Entities serialized before the field was introduced don't contain it
+/// and are treated as version 1. Fields renamed inside version 1 (`rms`, `m1`) are migrated by
+/// custom deserializers of nested entities. Deserialization of unknown version fails, so all future
+/// format changes must bump `CURRENT` and add migration from previous versions here.
+///
+/// Versions:
+/// * 1 - initial format
+/// * 2 - Fiat-Shamir challenge of `Proof` starts with hash algorithm identifier (`hash::CHALLENGE_HASH_ALGORITHM_ID`),
+///   version 1 proofs are verified only if allowed by `ProofVerifier::allow_legacy_challenge`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SerializationVersion(u32);
+
+impl SerializationVersion {
+    pub const LEGACY: SerializationVersion = SerializationVersion(1);
+    pub const CURRENT: SerializationVersion = SerializationVersion(2);
+
+    pub fn legacy() -> SerializationVersion {
+        SerializationVersion::LEGACY
+    }
+
+    pub fn current() -> SerializationVersion {
+        SerializationVersion::CURRENT
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    fn new(ver: u32) -> Result<SerializationVersion, IndyCryptoError> {
+        match ver {
+            1 => Ok(SerializationVersion(1)),
+            2 => Ok(SerializationVersion(2)),
+            ver => Err(IndyCryptoError::InvalidStructure(format!("Unsupported serialization version: {}", ver)))
+        }
+    }
+}
+
+impl <'a> ::serde::de::Deserialize<'a> for SerializationVersion {
+    fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        SerializationVersion::new(u32::deserialize(deserializer)?)
+            .map_err(|err| D::Error::custom(err.to_string()))
+    }
+}
+
 /// `Issuer Public Key` contains 2 internal parts.
 /// One for signing primary credentials and second for signing non-revocation credentials.
 /// These keys are used to proof that credential was issued and doesn’t revoked by this issuer.
 /// Issuer keys have global identifier that must be known to all parties.
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+///
+/// Key is `Send` and `Sync`: verifier services can load it once and share it between threads by `Arc`
+/// instead of cloning it for every thread (`clone` copies all big numbers of the key).
+#[derive(Debug, Deserialize, Serialize)]
 pub struct CredentialPublicKey {
-    p_key: CredentialPrimaryPublicKey
+    #[serde(default = "SerializationVersion::legacy")]
+    ver: SerializationVersion,
+    p_key: CredentialPrimaryPublicKey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tag: Option<String>
 }
 
 impl CredentialPublicKey {
     pub fn clone(&self) -> Result<CredentialPublicKey, IndyCryptoError> {
         Ok(CredentialPublicKey {
-            p_key: self.p_key.clone()?
+            ver: self.ver,
+            p_key: self.p_key.clone()?,
+            tag: self.tag.clone()
         })
     }
 
+    /// Returns issuer label of the key (for example schema id), see `set_tag`.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_ref().map(String::as_str)
+    }
+
+    /// Labels the key (for example by schema id), so downstream code can match proofs to keys
+    /// without external mapping. The tag is only carried by serialization and isn't used by cryptographic operations.
+    pub fn set_tag(&mut self, tag: Option<&str>) {
+        self.tag = tag.map(str::to_string);
+    }
+
     pub fn get_primary_key(&self) -> Result<CredentialPrimaryPublicKey, IndyCryptoError> {
         Ok(self.p_key.clone()?)
     }
 
+    /// Checks structure of the key, but not primality of `n`: use `CredentialPrimaryPublicKey::validate` for it.
     pub fn build_from_parts(p_key: &CredentialPrimaryPublicKey) -> Result<CredentialPublicKey, IndyCryptoError> {
+        p_key.check_structure()?;
+
         Ok(CredentialPublicKey {
-            p_key: p_key.clone()?
+            ver: SerializationVersion::current(),
+            p_key: p_key.clone()?,
+            tag: None
+        })
+    }
+
+    /// Serializes public key to JSON with sorted object keys.
+    /// Unlike `serde_json::to_string` result doesn't depend on `HashMap` iteration order of attribute keys.
+    /// Tag isn't included, so it doesn't affect values derived from canonical form (credential offer digest).
+    pub fn to_canonical_json(&self) -> Result<String, IndyCryptoError> {
+        let mut value = ::serde_json::to_value(self)?;
+        if let Some(object) = value.as_object_mut() {
+            object.remove("tag");
+        }
+        Ok(::serde_json::to_string(&value)?)
+    }
+}
+
+/// Keys are equal if their key material is equal, tag is ignored.
+impl PartialEq for CredentialPublicKey {
+    fn eq(&self, other: &CredentialPublicKey) -> bool {
+        self.ver == other.ver && self.p_key == other.p_key
+    }
+}
+
+/// Credential public key prepared for proof verification.
+/// Contains values derived from the key that otherwise are recomputed on each `ProofVerifier::verify` call:
+/// inverse of Z and fixed-base exponentiation tables for S, Z and R_i.
+/// Create it once per issuer key and pass it to `ProofVerifier::add_sub_proof_request` for all verifications with this key.
+/// Tables are shared between clones, so passing prepared key to many verifiers doesn't copy them.
+/// It is `Send` and `Sync`, so one prepared key can serve verifications running in different threads.
+#[derive(Debug)]
+pub struct PreparedPublicKey {
+    p_key: CredentialPrimaryPublicKey,
+    z_inverse: BigNumber,
+    tables: Option<Arc<PreparedKeyTables>>
+}
+
+#[derive(Debug)]
+struct PreparedKeyTables {
+    s: FixedBaseTable,
+    z: FixedBaseTable,
+    r: HashMap<String, FixedBaseTable>
+}
+
+impl PreparedPublicKey {
+    pub fn new(credential_pub_key: &CredentialPublicKey) -> Result<PreparedPublicKey, IndyCryptoError> {
+        let mut prepared_pub_key = PreparedPublicKey::_without_tables(credential_pub_key)?;
+
+        let tables = {
+            let p_key = &prepared_pub_key.p_key;
+            let mut ctx = BnCtxPool::take()?;
+
+            // Max sizes of verifier exponents: v_hat and alpha for S, u_hat for Z, m_hat for R_i
+            let s = FixedBaseTable::new(&p_key.s, &p_key.n, constants::LARGE_VTILDE + 1, &mut ctx)?;
+            let z = FixedBaseTable::new(&p_key.z, &p_key.n,
+                                        constants::LARGE_UTILDE + constants::MAX_PREDICATE_SECURITY_LEVEL as usize * constants::PREDICATE_SECURITY_EXTRA_BITS + 1,
+                                        &mut ctx)?;
+
+            let mut r = HashMap::new();
+            for (attr, r_i) in p_key.r.iter() {
+                r.insert(attr.clone(), FixedBaseTable::new(r_i, &p_key.n, constants::LARGE_MTILDE + 1, &mut ctx)?);
+            }
+
+            BnCtxPool::release(ctx);
+
+            PreparedKeyTables { s, z, r }
+        };
+
+        prepared_pub_key.tables = Some(Arc::new(tables));
+
+        Ok(prepared_pub_key)
+    }
+
+    // Raw key passed to a single verifier isn't worth tables precomputation
+    fn _without_tables(credential_pub_key: &CredentialPublicKey) -> Result<PreparedPublicKey, IndyCryptoError> {
+        let p_key = credential_pub_key.p_key.clone()?;
+        let z_inverse = p_key.z.inverse(&p_key.n, None)?;
+
+        Ok(PreparedPublicKey { p_key, z_inverse, tables: None })
+    }
+
+    pub fn clone(&self) -> Result<PreparedPublicKey, IndyCryptoError> {
+        Ok(PreparedPublicKey {
+            p_key: self.p_key.clone()?,
+            z_inverse: self.z_inverse.clone()?,
+            tables: self.tables.clone()
         })
     }
 }
 
+/// Public key accepted by `ProofVerifier::add_sub_proof_request`: `CredentialPublicKey` or `PreparedPublicKey`.
+pub trait VerifierPublicKey {
+    fn prepare(&self) -> Result<PreparedPublicKey, IndyCryptoError>;
+}
+
+impl VerifierPublicKey for CredentialPublicKey {
+    fn prepare(&self) -> Result<PreparedPublicKey, IndyCryptoError> {
+        PreparedPublicKey::_without_tables(self)
+    }
+}
+
+impl VerifierPublicKey for PreparedPublicKey {
+    fn prepare(&self) -> Result<PreparedPublicKey, IndyCryptoError> {
+        self.clone()
+    }
+}
+
 /// `Issuer Private Key`: contains 2 internal parts.
 /// One for signing primary credentials and second for signing non-revocation credentials.
 #[derive(Debug, Deserialize, Serialize)]
@@ -298,10 +738,84 @@ impl CredentialPrimaryPublicKey {
             z: self.z.clone()?
         })
     }
+
+    /// Returns RSA modulus `n` of the key.
+    pub fn n(&self) -> &BigNumber {
+        &self.n
+    }
+
+    /// Checks that the key is well-formed: structure checks done on key construction
+    /// (see `CredentialPublicKey::build_from_parts`) and additionally that `n` isn't prime.
+    /// Primality test is expensive, so it isn't done on parsing: call it once for key obtained from untrusted source.
+    pub fn validate(&self) -> Result<(), IndyCryptoError> {
+        self.check_structure()?;
+
+        if self.n.is_prime(None)? {
+            return Err(IndyCryptoError::InvalidStructure("Invalid public key modulus n".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Cheap structure checks done whenever the key is constructed (deserialization, compact bytes, `build_from_parts`):
+    /// `n` has expected size and is odd, `s` passes quadratic residuosity check (Jacobi symbol)
+    /// and `s`, `z`, `r` values are in range `(1, n)`.
+    fn check_structure(&self) -> Result<(), IndyCryptoError> {
+        if self.n.num_bits()? < constants::MIN_MODULUS_BITS || !self.n.is_bit_set(0)? {
+            return Err(IndyCryptoError::InvalidStructure("Invalid public key modulus n".to_string()));
+        }
+
+        let values = vec![("s", &self.s), ("z", &self.z)].into_iter()
+            .chain(self.r.iter().map(|(attr, value)| (attr.as_str(), value)));
+
+        for (name, value) in values {
+            if *value <= *BIGNUMBER_1 || *value >= self.n {
+                return Err(IndyCryptoError::InvalidStructure(format!("Public key value {} is out of range", name)));
+            }
+        }
+
+        if helpers::jacobi_symbol(&self.s, &self.n)? != 1 {
+            return Err(IndyCryptoError::InvalidStructure("Public key value s is not quadratic residue".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the key was generated for the given schemas: `r` must contain exactly
+    /// attributes of credential schema and non credential schema.
+    fn check_schemas(&self,
+                     credential_schema: &CredentialSchema,
+                     non_credential_schema: &NonCredentialSchema) -> Result<(), IndyCryptoError> {
+        let key_attrs: BTreeSet<&String> = self.r.keys().collect();
+        let schema_attrs: BTreeSet<&String> = credential_schema.attrs.iter()
+            .chain(non_credential_schema.attrs.iter())
+            .collect();
+
+        if key_attrs != schema_attrs {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Credential public key attributes {:?} don't correspond to schema attributes {:?}", key_attrs, schema_attrs)));
+        }
+
+        Ok(())
+    }
+
+    /// Returns compact binary representation of the key for on-ledger storage.
+    /// Each value is stored as big-endian 4 bytes length followed by the value itself:
+    /// `n`, `s`, `z`, count of `r` entries and then `r` entries (name, value) sorted by attr name.
+    pub fn to_compact_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        bytes::primary_public_key_to_compact_bytes(self)
+    }
+
+    /// Restores the key from representation created by `to_compact_bytes`.
+    pub fn from_compact_bytes(bytes: &[u8]) -> Result<CredentialPrimaryPublicKey, IndyCryptoError> {
+        bytes::primary_public_key_from_compact_bytes(bytes)
+    }
 }
 
 impl <'a> ::serde::de::Deserialize<'a> for CredentialPrimaryPublicKey {
     fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
         #[derive(Deserialize)]
         struct CredentialPrimaryPublicKeyV1 {
             n: BigNumber,
@@ -316,19 +830,26 @@ impl <'a> ::serde::de::Deserialize<'a> for CredentialPrimaryPublicKey {
         if helper.rms != BigNumber::default() {
             helper.r.insert("master_secret".to_string(), helper.rms);
         }
-        Ok(CredentialPrimaryPublicKey {
+
+        let pub_key = CredentialPrimaryPublicKey {
             n: helper.n,
             s: helper.s,
             z: helper.z,
             r: helper.r
-        })
+        };
+
+        pub_key.check_structure().map_err(|err| D::Error::custom(format!("{:?}", err)))?;
+
+        Ok(pub_key)
     }
 }
 
 /// Issuer's "Private Key" used for signing Credential's attributes' values (primary credential)
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct CredentialPrimaryPrivateKey {
+    #[serde(serialize_with = "serialize_prime_secret", deserialize_with = "deserialize_fixed_width")]
     p: BigNumber,
+    #[serde(serialize_with = "serialize_prime_secret", deserialize_with = "deserialize_fixed_width")]
     q: BigNumber
 }
 
@@ -339,6 +860,14 @@ pub struct CredentialPrimaryPublicKeyMetadata {
     xr: HashMap<String, BigNumber>
 }
 
+fn serialize_prime_secret<S: ::serde::Serializer>(bn: &BigNumber, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize_fixed_width(bn, constants::LARGE_PRIME / 8, serializer)
+}
+
+fn serialize_master_secret<S: ::serde::Serializer>(bn: &BigNumber, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize_fixed_width(bn, constants::LARGE_MASTER_SECRET / 8, serializer)
+}
+
 /// Proof of `Issuer Public Key` correctness
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct CredentialKeyCorrectnessProof {
@@ -347,12 +876,40 @@ pub struct CredentialKeyCorrectnessProof {
     xr_cap: Vec<(String, BigNumber)>,
 }
 
+/// Proof that new `Issuer Public Key` succeeds the old one.
+/// Fiat-Shamir proof of knowledge of `n - phi(n)` (so of factorization of `n`) of both keys
+/// with one challenge bound to both keys, so only holder of both private keys can create it.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct KeyRotationProof {
+    c: BigNumber,
+    old_x_cap: BigNumber,
+    new_x_cap: BigNumber
+}
+
 /// Issuer's signature over Credential attribute values.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CredentialSignature {
+    #[serde(default = "SerializationVersion::legacy")]
+    ver: SerializationVersion,
     p_credential: PrimaryCredentialSignature,
 }
 
+impl CredentialSignature {
+    /// Returns deterministic non-secret credential identifier: hex encoded sha256 hash of
+    /// signature `a` value and public key modulus `n`, each prefixed by its `u32` big-endian length.
+    pub fn credential_id(&self, pub_key: &CredentialPublicKey) -> Result<String, IndyCryptoError> {
+        let mut values: Vec<Vec<u8>> = Vec::new();
+        for value in vec![self.p_credential.a.to_bytes()?, pub_key.p_key.n.to_bytes()?] {
+            values.push((value.len() as u32).to_be_bytes().to_vec());
+            values.push(value);
+        }
+
+        let hash = BigNumber::hash_array(&values)?;
+
+        Ok(hash.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PrimaryCredentialSignature {
     a: BigNumber,
@@ -373,6 +930,7 @@ pub struct SignatureCorrectnessProof {
 /// The blinding factors are used by Prover for post processing of issued credentials.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MasterSecret {
+    #[serde(serialize_with = "serialize_master_secret", deserialize_with = "deserialize_fixed_width")]
     ms: BigNumber,
 }
 
@@ -394,6 +952,18 @@ pub struct BlindedCredentialSecrets {
     committed_attributes: BTreeMap<String, BigNumber>
 }
 
+impl BlindedCredentialSecrets {
+    /// Returns names of hidden attributes (like `master_secret`) blinded by Prover.
+    pub fn hidden_attributes(&self) -> impl Iterator<Item=&String> {
+        self.hidden_attributes.iter()
+    }
+
+    /// Returns names of committed attributes with the commitments in sorted order.
+    pub fn committed_attributes(&self) -> impl Iterator<Item=(&String, &BigNumber)> {
+        self.committed_attributes.iter()
+    }
+}
+
 /// `CredentialSecretsBlindingFactors` used by Prover for post processing of credentials received from Issuer.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CredentialSecretsBlindingFactors {
@@ -418,53 +988,368 @@ pub struct BlindedCredentialSecretsCorrectnessProof {
 
 /// “Sub Proof Request” - input to create a Proof for a credential;
 /// Contains attributes to be revealed and predicates.
-#[derive(Debug, Clone)]
+/// Request without revealed attributes and predicates proves possession of credential only.
+/// Request is `Send` and `Sync`.
+/// Deserialization goes through `SubProofRequestBuilder`, so deserialized request passes the same checks as built one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub struct SubProofRequest {
     revealed_attrs: BTreeSet<String>,
     predicates: BTreeSet<Predicate>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    linear_predicates: BTreeSet<LinearPredicate>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    bitlength_predicates: BTreeMap<String /* attr_name */, u32 /* bitlength */>,
 }
 
-/// Builder of “Sub Proof Request”.
-#[derive(Debug)]
-pub struct SubProofRequestBuilder {
-    value: SubProofRequest
-}
+impl SubProofRequest {
+    /// Names of attributes which values are requested to be revealed, in sorted order.
+    pub fn revealed_attrs(&self) -> &BTreeSet<String> {
+        &self.revealed_attrs
+    }
 
-impl SubProofRequestBuilder {
-    pub fn new() -> Result<SubProofRequestBuilder, IndyCryptoError> {
-        Ok(SubProofRequestBuilder {
-            value: SubProofRequest {
-                revealed_attrs: BTreeSet::new(),
-                predicates: BTreeSet::new()
-            }
-        })
+    /// Requested predicates in sorted order.
+    pub fn predicates(&self) -> &BTreeSet<Predicate> {
+        &self.predicates
+    }
+
+    /// Bit lengths requested by `SubProofRequestBuilder::add_bitlength_predicate` by attribute names.
+    /// Predicates proving them are contained in `predicates` as well.
+    pub fn bitlength_predicates(&self) -> &BTreeMap<String, u32> {
+        &self.bitlength_predicates
+    }
+
+    /// Splits attributes of credential values into revealed, predicated and fully hidden ones
+    /// according to the request. Attributes requested but absent in values are skipped.
+    pub fn partition(&self, values: &CredentialValues) -> Partition {
+        let mut partition = Partition {
+            revealed: BTreeSet::new(),
+            predicated: BTreeSet::new(),
+            hidden: BTreeSet::new()
+        };
+
+        for attr in values.attrs() {
+            if self.revealed_attrs.contains(attr) {
+                partition.revealed.insert(attr.clone());
+            } else if self.predicates.iter().any(|predicate| &predicate.attr_name == attr) ||
+                self.linear_predicates.iter().any(|linear_predicate| linear_predicate.coeffs.contains_key(attr)) {
+                partition.predicated.insert(attr.clone());
+            } else {
+                partition.hidden.insert(attr.clone());
+            }
+        }
+
+        partition
+    }
+
+    /// Predicates proved by inequality proofs (all except `EQ` ones).
+    fn ne_predicates(&self) -> BTreeSet<Predicate> {
+        self.predicates.iter()
+            .filter(|predicate| predicate.p_type != PredicateType::EQ)
+            .cloned()
+            .collect()
+    }
+
+    /// Values of attributes requested in `EQ` predicates.
+    /// These attributes aren't revealed in the proof, but verifier knows their values,
+    /// so equality proof handles them the same way as revealed ones.
+    fn eq_predicates_values(&self) -> Result<BTreeMap<String, BigNumber>, IndyCryptoError> {
+        let mut values = BTreeMap::new();
+        for predicate in self.predicates.iter().filter(|predicate| predicate.p_type == PredicateType::EQ) {
+            values.insert(predicate.attr_name.clone(), predicate.get_delta_prime()?);
+        }
+        Ok(values)
+    }
+
+    /// Attributes which values are known to verifier: revealed ones and ones requested in `EQ` predicates.
+    fn known_attrs(&self) -> BTreeSet<String> {
+        let mut known_attrs = self.revealed_attrs.clone();
+        for predicate in self.predicates.iter().filter(|predicate| predicate.p_type == PredicateType::EQ) {
+            known_attrs.insert(predicate.attr_name.clone());
+        }
+        known_attrs
+    }
+}
+
+impl <'a> ::serde::de::Deserialize<'a> for SubProofRequest {
+    fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        struct SubProofRequestV1 {
+            revealed_attrs: BTreeSet<String>,
+            predicates: BTreeSet<Predicate>,
+            #[serde(default)]
+            linear_predicates: BTreeSet<LinearPredicate>,
+            #[serde(default)]
+            bitlength_predicates: BTreeMap<String, u32>
+        }
+
+        let helper = SubProofRequestV1::deserialize(deserializer)?;
+
+        let build = || -> Result<SubProofRequest, IndyCryptoError> {
+            let mut sub_proof_request_builder = SubProofRequestBuilder::new()?;
+
+            for attr in helper.revealed_attrs.iter() {
+                sub_proof_request_builder.add_revealed_attr(attr)?;
+            }
+
+            for predicate in helper.predicates.iter() {
+                sub_proof_request_builder.add_predicate(&predicate.attr_name, predicate.p_type.as_str(), predicate.value)?;
+            }
+
+            for linear_predicate in helper.linear_predicates.iter() {
+                let coeffs = linear_predicate.coeffs.iter()
+                    .map(|(attr_name, coeff)| (attr_name.as_str(), *coeff))
+                    .collect::<Vec<(&str, i32)>>();
+                sub_proof_request_builder.add_linear_predicate(&coeffs, linear_predicate.p_type.as_str(), linear_predicate.value)?;
+            }
+
+            for (attr_name, bitlength) in helper.bitlength_predicates.iter() {
+                sub_proof_request_builder.add_bitlength_predicate(attr_name, *bitlength)?;
+            }
+
+            sub_proof_request_builder.finalize()
+        };
+
+        build().map_err(|err| D::Error::custom(format!("{:?}", err)))
+    }
+}
+
+/// Names of credential attributes grouped by the way they are disclosed in a sub proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Partition {
+    revealed: BTreeSet<String>,
+    predicated: BTreeSet<String>,
+    hidden: BTreeSet<String>,
+}
+
+impl Partition {
+    /// Attributes which values are disclosed.
+    pub fn revealed(&self) -> &BTreeSet<String> {
+        &self.revealed
+    }
+
+    /// Attributes which values are hidden, but satisfy requested predicates.
+    pub fn predicated(&self) -> &BTreeSet<String> {
+        &self.predicated
+    }
+
+    /// Attributes nothing is disclosed about.
+    pub fn hidden(&self) -> &BTreeSet<String> {
+        &self.hidden
+    }
+}
+
+/// Limits of credential schema and sub proof request sizes.
+/// Protect prover and verifier from resource exhaustion by requests with huge number of attributes or predicates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    max_attrs: usize,
+    max_predicates: usize
+}
+
+impl SizeLimits {
+    pub const DEFAULT: SizeLimits = SizeLimits {
+        max_attrs: constants::MAX_ATTRS_COUNT,
+        max_predicates: constants::MAX_PREDICATES_COUNT
+    };
+
+    /// # Arguments
+    /// * `max_attrs` - Maximal number of credential schema attributes and revealed attributes of sub proof request.
+    /// * `max_predicates` - Maximal number of predicates of sub proof request.
+    pub fn new(max_attrs: usize, max_predicates: usize) -> Result<SizeLimits, IndyCryptoError> {
+        if max_attrs == 0 {
+            return Err(IndyCryptoError::InvalidStructure("Maximal number of attributes must be positive".to_string()));
+        }
+        Ok(SizeLimits { max_attrs, max_predicates })
+    }
+
+    pub fn max_attrs(&self) -> usize {
+        self.max_attrs
+    }
+
+    pub fn max_predicates(&self) -> usize {
+        self.max_predicates
+    }
+
+    fn check_credential_schema(&self, credential_schema: &CredentialSchema) -> Result<(), IndyCryptoError> {
+        if credential_schema.attrs.len() > self.max_attrs {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Credential schema contains {} attributes, but at most {} are allowed", credential_schema.attrs.len(), self.max_attrs)));
+        }
+        Ok(())
+    }
+
+    fn check_sub_proof_request(&self, sub_proof_request: &SubProofRequest) -> Result<(), IndyCryptoError> {
+        if sub_proof_request.revealed_attrs.len() > self.max_attrs {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Sub proof request contains {} revealed attributes, but at most {} are allowed",
+                        sub_proof_request.revealed_attrs.len(), self.max_attrs)));
+        }
+
+        let predicates_count = sub_proof_request.predicates.len() + sub_proof_request.linear_predicates.len();
+        if predicates_count > self.max_predicates {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Sub proof request contains {} predicates, but at most {} are allowed",
+                        predicates_count, self.max_predicates)));
+        }
+        Ok(())
+    }
+}
+
+/// Builder of “Sub Proof Request”.
+#[derive(Debug)]
+pub struct SubProofRequestBuilder {
+    value: SubProofRequest,
+    limits: SizeLimits
+}
+
+impl SubProofRequestBuilder {
+    pub fn new() -> Result<SubProofRequestBuilder, IndyCryptoError> {
+        Ok(SubProofRequestBuilder {
+            value: SubProofRequest {
+                revealed_attrs: BTreeSet::new(),
+                predicates: BTreeSet::new(),
+                linear_predicates: BTreeSet::new(),
+                bitlength_predicates: BTreeMap::new()
+            },
+            limits: SizeLimits::DEFAULT
+        })
+    }
+
+    /// Overrides default limits (`SizeLimits::DEFAULT`) checked by `finalize`.
+    pub fn with_limits(mut self, limits: SizeLimits) -> Result<SubProofRequestBuilder, IndyCryptoError> {
+        self.limits = limits;
+        Ok(self)
     }
 
     pub fn add_revealed_attr(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
-        self.value.revealed_attrs.insert(attr.to_owned());
+        self.value.revealed_attrs.insert(normalize_attr_name(attr));
         Ok(())
     }
 
     pub fn add_predicate(&mut self, attr_name: &str, p_type: &str, value: i32) -> Result<(), IndyCryptoError> {
+        let predicate = SubProofRequestBuilder::_new_predicate(attr_name, p_type, value)?;
+
+        self.value.predicates.insert(predicate);
+        Ok(())
+    }
+
+    /// Adds predicates `attr >= 0` and `attr <= 2^bitlength - 1` proving that attribute fits in `bitlength` bits.
+    /// Deltas of both predicates are bounded by `2^bitlength - 1`, so they never exceed `MAX_PREDICATE_DELTA`
+    /// and `bitlength` is limited to `1..=31` as predicate values are `i32`.
+    /// The bit length is recorded in the request (see `SubProofRequest::bitlength_predicates`),
+    /// so attribute can't be requested with different bit lengths.
+    pub fn add_bitlength_predicate(&mut self, attr_name: &str, bitlength: u32) -> Result<(), IndyCryptoError> {
+        if bitlength == 0 || bitlength > 31 {
+            return Err(IndyCryptoError::InvalidStructure(format!("Bit length {} is out of supported range 1..=31", bitlength)));
+        }
+
+        let max_value = ((1i64 << bitlength) - 1) as i32;
+
+        // Both predicates are validated before any of them is added, so failed call leaves the request unchanged
+        let ge_predicate = SubProofRequestBuilder::_new_predicate(attr_name, "GE", 0)?;
+        let le_predicate = SubProofRequestBuilder::_new_predicate(attr_name, "LE", max_value)?;
+
+        match self.value.bitlength_predicates.get(&ge_predicate.attr_name) {
+            Some(&recorded) if recorded != bitlength => return Err(IndyCryptoError::InvalidStructure(
+                format!("Attribute {:?} is already requested to fit in {} bits", attr_name, recorded))),
+            _ => {}
+        }
+
+        self.value.bitlength_predicates.insert(ge_predicate.attr_name.clone(), bitlength);
+        self.value.predicates.insert(ge_predicate);
+        self.value.predicates.insert(le_predicate);
+        Ok(())
+    }
+
+    /// Adds predicate over weighted sum of hidden attributes: `sum(coeff_i * attr_i) p_type value`.
+    /// For example `[("total_assets", 1), ("total_liabilities", -1)]`, `"GE"`, `0` requests proof of non negative net worth.
+    /// `EQ` type isn't supported as linear predicate is proved by inequality proof only.
+    pub fn add_linear_predicate(&mut self, coeffs: &[(&str, i32)], p_type: &str, value: i32) -> Result<(), IndyCryptoError> {
+        let p_type = SubProofRequestBuilder::_parse_predicate_type(p_type, value)?;
+
+        if p_type == PredicateType::EQ {
+            return Err(IndyCryptoError::InvalidStructure("EQ predicate type isn't supported for linear predicate".to_string()));
+        }
+
+        if coeffs.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure("Linear predicate must contain at least one attribute".to_string()));
+        }
+
+        let mut linear_coeffs = BTreeMap::new();
+        for &(attr_name, coeff) in coeffs {
+            if coeff == 0 {
+                return Err(IndyCryptoError::InvalidStructure(format!("Coefficient of attribute {:?} is zero", attr_name)));
+            }
+
+            if linear_coeffs.insert(normalize_attr_name(attr_name), coeff).is_some() {
+                return Err(IndyCryptoError::InvalidStructure(format!("Attribute {:?} is duplicated in linear predicate", attr_name)));
+            }
+        }
+
+        let linear_predicate = LinearPredicate {
+            coeffs: linear_coeffs,
+            p_type,
+            value
+        };
+
+        self.value.linear_predicates.insert(linear_predicate);
+        Ok(())
+    }
+
+    fn _new_predicate(attr_name: &str, p_type: &str, value: i32) -> Result<Predicate, IndyCryptoError> {
+        Ok(Predicate {
+            attr_name: normalize_attr_name(attr_name),
+            p_type: SubProofRequestBuilder::_parse_predicate_type(p_type, value)?,
+            value
+        })
+    }
+
+    fn _parse_predicate_type(p_type: &str, value: i32) -> Result<PredicateType, IndyCryptoError> {
         let p_type = match p_type {
             "GE" => PredicateType::GE,
             "LE" => PredicateType::LE,
             "GT" => PredicateType::GT,
             "LT" => PredicateType::LT,
+            "EQ" => PredicateType::EQ,
             p_type => return Err(IndyCryptoError::InvalidStructure(format!("Invalid predicate type: {:?}", p_type)))
         };
 
-        let predicate = Predicate {
-            attr_name: attr_name.to_owned(),
-            p_type,
-            value
-        };
+        match p_type {
+            PredicateType::GT if value == ::std::i32::MAX => return Err(IndyCryptoError::InvalidStructure(
+                format!("Predicate value {} is out of supported range for {:?}", value, p_type))),
+            PredicateType::LT if value == ::std::i32::MIN => return Err(IndyCryptoError::InvalidStructure(
+                format!("Predicate value {} is out of supported range for {:?}", value, p_type))),
+            _ => {}
+        }
 
-        self.value.predicates.insert(predicate);
-        Ok(())
+        Ok(p_type)
     }
 
     pub fn finalize(self) -> Result<SubProofRequest, IndyCryptoError> {
+        if let Some(predicate) = self.value.predicates.iter().find(|p| self.value.revealed_attrs.contains(&p.attr_name)) {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Attribute {:?} can't be revealed and used in predicate at the same time", predicate.attr_name)));
+        }
+
+        for eq_predicate in self.value.predicates.iter().filter(|p| p.p_type == PredicateType::EQ) {
+            if self.value.predicates.iter().any(|p| p.attr_name == eq_predicate.attr_name && p != eq_predicate) {
+                return Err(IndyCryptoError::InvalidStructure(
+                    format!("Attribute {:?} can't be used in EQ predicate and other predicate at the same time", eq_predicate.attr_name)));
+            }
+        }
+
+        let known_attrs = self.value.known_attrs();
+        for linear_predicate in self.value.linear_predicates.iter() {
+            if let Some(attr_name) = linear_predicate.coeffs.keys().find(|attr_name| known_attrs.contains(*attr_name)) {
+                return Err(IndyCryptoError::InvalidStructure(
+                    format!("Attribute {:?} of linear predicate must be hidden, but it is revealed or used in EQ predicate", attr_name)));
+            }
+        }
+
+        self.limits.check_sub_proof_request(&self.value)?;
+
         Ok(self.value)
     }
 }
@@ -478,13 +1363,41 @@ pub struct Predicate {
 }
 
 impl Predicate {
-    pub fn get_delta(&self, attr_value: i32) -> i32 {
-        match self.p_type {
-            PredicateType::GE => attr_value - self.value,
-            PredicateType::GT => attr_value - self.value - 1,
-            PredicateType::LE => self.value - attr_value,
-            PredicateType::LT => self.value - attr_value - 1
+    pub fn attr_name(&self) -> &str {
+        &self.attr_name
+    }
+
+    pub fn p_type(&self) -> &PredicateType {
+        &self.p_type
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    /// Returns delta between attribute value and predicate value.
+    /// Delta is non negative only if predicate holds: for `LE`/`LT` it is negative when attribute value
+    /// is greater than predicate value. Prover refuses to build inequality proof for negative delta.
+    /// Computed with big number arithmetic, so attribute values out of `i32` range are handled correctly.
+    /// Fails if delta exceeds `MAX_PREDICATE_DELTA` as it can't be proved.
+    /// `EQ` predicate is proved without inequality proof, so delta isn't defined for it.
+    pub fn get_delta(&self, attr_value: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        let value = BigNumber::from_dec(&self.value.to_string())?;
+
+        let delta = match self.p_type {
+            PredicateType::GE => attr_value.sub(&value)?,
+            PredicateType::GT => attr_value.sub(&value)?.sub(&BIGNUMBER_1)?,
+            PredicateType::LE => value.sub(attr_value)?,
+            PredicateType::LT => value.sub(attr_value)?.sub(&BIGNUMBER_1)?,
+            PredicateType::EQ => return Err(IndyCryptoError::InvalidStructure("Delta isn't defined for EQ predicate".to_string()))
+        };
+
+        if delta > BigNumber::from_dec(&constants::MAX_PREDICATE_DELTA.to_string())? {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Predicate delta {:?} exceeds max supported value {}", delta, constants::MAX_PREDICATE_DELTA)));
         }
+
+        Ok(delta)
     }
 
     pub fn get_delta_prime(&self) -> Result<BigNumber, IndyCryptoError> {
@@ -492,42 +1405,407 @@ impl Predicate {
             PredicateType::GE => BigNumber::from_dec(&self.value.to_string()),
             PredicateType::GT => BigNumber::from_dec(&(self.value + 1).to_string()),
             PredicateType::LE => BigNumber::from_dec(&self.value.to_string()),
-            PredicateType::LT => BigNumber::from_dec(&(self.value - 1).to_string())
+            PredicateType::LT => BigNumber::from_dec(&(self.value - 1).to_string()),
+            PredicateType::EQ => BigNumber::from_dec(&self.value.to_string())
         }
     }
 
+    /// Checks predicate against attribute value using big number comparison,
+    /// so values out of `i32` range are handled correctly.
+    pub fn is_satisfied_by(&self, attr_value: &BigNumber) -> Result<bool, IndyCryptoError> {
+        let value = BigNumber::from_dec(&self.value.to_string())?;
+
+        Ok(match self.p_type {
+            PredicateType::GE => *attr_value >= value,
+            PredicateType::GT => *attr_value > value,
+            PredicateType::LE => *attr_value <= value,
+            PredicateType::LT => *attr_value < value,
+            PredicateType::EQ => *attr_value == value
+        })
+    }
+
     pub fn is_less(&self) -> bool {
         match self.p_type {
-            PredicateType::GE | PredicateType::GT => false,
+            PredicateType::GE | PredicateType::GT | PredicateType::EQ => false,
             PredicateType::LE | PredicateType::LT => true
         }
     }
+
+    /// Returns predicate that is satisfied exactly when this one is not.
+    /// The value is kept as is, so strictness flips together with the direction
+    /// (e.g. `GE 5` negates to `LT 5`, which over integers is the same as `LE 4`).
+    /// Returns `None` for `EQ` predicate as its negation can't be expressed by single predicate.
+    pub fn negate(&self) -> Option<Predicate> {
+        self.p_type.negate().map(|p_type| Predicate {
+            attr_name: self.attr_name.clone(),
+            p_type,
+            value: self.value
+        })
+    }
+}
+
+/// Condition over weighted sum of hidden attributes: `sum(coeff_i * attr_i) p_type value`.
+///
+/// Proved by the same inequality proof as `Predicate`: `m` of the proof is the weighted sum
+/// of attributes `m` values of equality proof, so delta is derived from the weighted sum of attribute values.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+pub struct LinearPredicate {
+    coeffs: BTreeMap<String /* attr_name */, i32>,
+    p_type: PredicateType,
+    value: i32,
+}
+
+impl LinearPredicate {
+    /// Predicate over the weighted sum checked by inequality proof.
+    /// It isn't bound to attribute, so attribute name is empty.
+    fn as_predicate(&self) -> Predicate {
+        Predicate {
+            attr_name: String::new(),
+            p_type: self.p_type.clone(),
+            value: self.value
+        }
+    }
+
+    /// Returns weighted sum of values of linear predicate attributes.
+    /// Works the same way for attribute values, `m_tilde` and `m` values of equality proof.
+    fn combine<'a, F>(&self, get_value: F) -> Result<BigNumber, IndyCryptoError> where F: Fn(&str) -> Option<&'a BigNumber> {
+        let mut ctx = BnCtxPool::take()?;
+        let mut sum = BigNumber::new()?;
+
+        for (attr_name, coeff) in self.coeffs.iter() {
+            let value = get_value(attr_name)
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found", attr_name)))?;
+
+            sum = BigNumber::from_dec(&coeff.to_string())?
+                .mul(value, Some(&mut ctx))?
+                .add(&sum)?;
+        }
+
+        BnCtxPool::release(ctx);
+
+        Ok(sum)
+    }
 }
 
 /// Condition type
+///
+/// `EQ` proves that hidden attribute equals public constant via equality proof relation:
+/// the attribute isn't revealed in the proof, but verifier uses the constant in place of its value.
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
 pub enum PredicateType {
     GE,
     LE,
     GT,
-    LT
+    LT,
+    EQ
+}
+
+impl PredicateType {
+    /// Name of condition type accepted by `SubProofRequestBuilder::add_predicate`.
+    fn as_str(&self) -> &'static str {
+        match *self {
+            PredicateType::GE => "GE",
+            PredicateType::LE => "LE",
+            PredicateType::GT => "GT",
+            PredicateType::LT => "LT",
+            PredicateType::EQ => "EQ"
+        }
+    }
+
+    /// Returns inverse condition type over integers or `None` for `EQ` as there is no inverse condition type for it.
+    pub fn negate(&self) -> Option<PredicateType> {
+        match *self {
+            PredicateType::GE => Some(PredicateType::LT),
+            PredicateType::LT => Some(PredicateType::GE),
+            PredicateType::GT => Some(PredicateType::LE),
+            PredicateType::LE => Some(PredicateType::GT),
+            PredicateType::EQ => None
+        }
+    }
+}
+
+/// Security level of inequality (predicate) proofs.
+///
+/// Level 1 is the default one and uses `LARGE_UTILDE`, `LARGE_RTILDE` and `LARGE_ALPHATILDE` sizes.
+/// Each next level adds `PREDICATE_SECURITY_EXTRA_BITS` to these sizes. Level is stored in the proof
+/// (`predicate_security` field, omitted for the default level) and non-default level is hashed into the challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct PredicateSecurityLevel(u32);
+
+impl PredicateSecurityLevel {
+    pub const DEFAULT: PredicateSecurityLevel = PredicateSecurityLevel(1);
+
+    pub fn default() -> PredicateSecurityLevel {
+        PredicateSecurityLevel::DEFAULT
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == PredicateSecurityLevel::DEFAULT
+    }
+
+    pub fn new(level: u32) -> Result<PredicateSecurityLevel, IndyCryptoError> {
+        if level < PredicateSecurityLevel::DEFAULT.0 || level > constants::MAX_PREDICATE_SECURITY_LEVEL {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Unsupported predicate security level {}, supported levels are {}..{}",
+                        level, PredicateSecurityLevel::DEFAULT.0, constants::MAX_PREDICATE_SECURITY_LEVEL)));
+        }
+        Ok(PredicateSecurityLevel(level))
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    fn extra_bits(&self) -> usize {
+        (self.0 - PredicateSecurityLevel::DEFAULT.0) as usize * constants::PREDICATE_SECURITY_EXTRA_BITS
+    }
+
+    pub fn u_tilde_size(&self) -> usize {
+        constants::LARGE_UTILDE + self.extra_bits()
+    }
+
+    pub fn r_tilde_size(&self) -> usize {
+        constants::LARGE_RTILDE + self.extra_bits()
+    }
+
+    pub fn alpha_tilde_size(&self) -> usize {
+        constants::LARGE_ALPHATILDE + self.extra_bits()
+    }
+
+    /// Adds non-default level to Fiat-Shamir challenge, so level of the proof can't be changed after proof building.
+    /// Default level adds nothing, so challenges of proofs built before the level was introduced don't change.
+    fn update_challenge(&self, hash_builder: &mut HashBuilder) -> Result<(), IndyCryptoError> {
+        if !self.is_default() {
+            hash_builder.update(&BigNumber::from_u32(self.0 as usize)?.to_bytes()?)?;
+        }
+        Ok(())
+    }
+}
+
+impl <'a> ::serde::de::Deserialize<'a> for PredicateSecurityLevel {
+    fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        PredicateSecurityLevel::new(u32::deserialize(deserializer)?)
+            .map_err(|err| D::Error::custom(err.to_string()))
+    }
 }
 
 /// Proof is complex crypto structure created by prover over multiple credentials that allows to prove that prover:
 /// 1) Knows signature over credentials issued with specific issuer keys (identified by key id)
 /// 2) Credential contains attributes with specific values that prover wants to disclose
 /// 3) Credential contains attributes with valid predicates that verifier wants the prover to satisfy.
-#[derive(Debug, Deserialize, Serialize)]
+///
+/// Proof is `Send` and `Sync`, so it can be received in one thread and verified in another one.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Proof {
+    #[serde(default = "SerializationVersion::legacy")]
+    ver: SerializationVersion,
     proofs: Vec<SubProof>,
     aggregated_proof: AggregatedProof,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    common_m: BTreeMap<String /* attr_name of common attribute */, BigNumber>,
+    #[serde(default = "PredicateSecurityLevel::default", skip_serializing_if = "PredicateSecurityLevel::is_default")]
+    predicate_security: PredicateSecurityLevel,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl Proof {
+    /// Returns count of sub proofs (one per credential used for proof building).
+    pub fn sub_proofs_count(&self) -> usize {
+        self.proofs.len()
+    }
+
+    /// Returns identifiers of issuer keys of sub proofs (in order of sub proofs),
+    /// so verifier can select public keys the proof was built with.
+    pub fn key_ids(&self) -> impl Iterator<Item=&String> {
+        self.proofs.iter().map(|sub_proof| &sub_proof.key_id)
+    }
+
+    /// Returns commitment `Z^m * S^salt` to hidden attribute of the sub proof
+    /// revealed by prover with `ProofBuilder::add_revealed_commitment`.
+    /// Verifier compares it with expected one, for example computed by `Verifier::calc_revealed_commitment`.
+    pub fn revealed_commitment(&self, sub_proof_index: usize, attr_name: &str) -> Option<&BigNumber> {
+        self.proofs.get(sub_proof_index)
+            .and_then(|sub_proof| sub_proof.primary_proof.eq_proof.revealed_commitments.get(&normalize_attr_name(attr_name)))
+            .map(|revealed_commitment| &revealed_commitment.commitment)
+    }
+
+    /// Returns revealed attributes of each sub proof (in order of sub proofs) as attr name to value mapping.
+    /// `decoder` restores original value from encoded one (for example using caller's encoding table);
+    /// values it can't decode are returned as decimal strings.
+    pub fn decode_revealed_attrs<F>(&self, decoder: F) -> Result<Vec<BTreeMap<String, String>>, IndyCryptoError>
+        where F: Fn(&BigNumber) -> Option<String> {
+        self.proofs.iter()
+            .map(|sub_proof| {
+                sub_proof.primary_proof.eq_proof.revealed_attrs.iter()
+                    .map(|(attr_name, value)| {
+                        let value = match decoder(value) {
+                            Some(decoded) => decoded,
+                            None => value.to_dec()?
+                        };
+                        Ok((attr_name.clone(), value))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns attributes revealed and predicates proved by inequality proofs across all sub proofs,
+    /// so verifier can see what the proof constrains without the original request.
+    /// `EQ` predicates and linear predicates aren't recorded in the proof by attribute, so they aren't returned.
+    pub fn constrained_attrs(&self) -> Result<(BTreeSet<String>, BTreeSet<Predicate>), IndyCryptoError> {
+        let mut revealed_attrs = BTreeSet::new();
+        let mut predicates = BTreeSet::new();
+
+        for sub_proof in self.proofs.iter() {
+            revealed_attrs.extend(sub_proof.primary_proof.eq_proof.revealed_attrs.keys().cloned());
+            predicates.extend(sub_proof.primary_proof.ne_proofs.iter().map(|ne_proof| ne_proof.predicate.clone()));
+        }
+
+        Ok((revealed_attrs, predicates))
+    }
+
+    /// Returns predicates proved by inequality proofs of each sub proof (in order of sub proofs).
+    /// Unlike `constrained_attrs` predicates aren't merged, so verifier can enforce policy per sub proof.
+    /// Linear predicates aren't bound to single attribute, so they are returned by `linear_predicates` only.
+    pub fn predicates(&self) -> Vec<Vec<&Predicate>> {
+        self.proofs.iter()
+            .map(|sub_proof| sub_proof.primary_proof.ne_proofs.iter().map(|ne_proof| ne_proof.predicate()).collect())
+            .collect()
+    }
+
+    /// Returns linear predicates proved by each sub proof (in order of sub proofs).
+    pub fn linear_predicates(&self) -> Vec<Vec<&LinearPredicate>> {
+        self.proofs.iter()
+            .map(|sub_proof| sub_proof.primary_proof.linear_ne_proofs.iter().map(|linear_ne_proof| &linear_ne_proof.predicate).collect())
+            .collect()
+    }
+
+    /// Returns Fiat-Shamir challenge (`c_hash`) stored in the proof.
+    pub fn challenge(&self) -> &BigNumber {
+        &self.aggregated_proof.c_hash
+    }
+
+    /// Recomputes Fiat-Shamir challenge as hash of hash algorithm identifier (`hash::CHALLENGE_HASH_ALGORITHM_ID`),
+    /// tau list, `c_list` of the proof and nonce.
+    /// Tau list isn't a part of the proof: it is restored from the proof and credential public keys,
+    /// so use `ProofVerifier::recompute_challenge` to get the challenge for the whole proof.
+    pub fn recompute_challenge(&self, tau_list: &[Vec<u8>], nonce: &Nonce) -> Result<BigNumber, IndyCryptoError> {
+        self._recompute_challenge(HashBuilder::new_challenge()?, tau_list, nonce)
+    }
+
+    /// 用给定的hash_builder重新计算挑战（旧版本证明的挑战不包含哈希算法标识）
+    fn _recompute_challenge(&self, mut hash_builder: HashBuilder, tau_list: &[Vec<u8>], nonce: &Nonce) -> Result<BigNumber, IndyCryptoError> {
+        check_nonce(nonce)?;
+
+        hash_builder.update_all(tau_list)?;
+        hash_builder.update_all(&self.aggregated_proof.c_list)?;
+        hash_builder.update(&nonce.to_bytes()?)?;
+        self.predicate_security.update_challenge(&mut hash_builder)?;
+        hash_builder.finalize_as_int()
+    }
+
+    /// Serializes proof to JSON with sorted object keys.
+    /// Unlike `serde_json::to_string` result doesn't depend on `HashMap` iteration order of attribute keys.
+    pub fn to_canonical_json(&self) -> Result<String, IndyCryptoError> {
+        to_canonical_json(self)
+    }
+
+    /// Returns SHA-256 digest of canonical serialization of the proof and the nonce.
+    /// Compact record of exactly what was verified that can be logged instead of the whole proof.
+    pub fn to_transcript_digest(&self, nonce: &Nonce) -> Result<[u8; 32], IndyCryptoError> {
+        let canonical_proof = self.to_canonical_json()?;
+
+        let mut hasher = BigNumberHasher::new()?;
+        hasher.update(canonical_proof.as_bytes())?;
+        hasher.update(&nonce.to_bytes()?)?;
+
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&hasher.finish()?);
+        Ok(digest)
+    }
+
+    /// Serializes proof to compact length-prefixed binary form (big numbers are stored as big-endian bytes).
+    /// Binary form is much smaller than JSON one and is intended for transport only.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        bytes::proof_to_bytes(self)
+    }
+
+    /// Deserializes proof from binary form created by `Proof::to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Proof, IndyCryptoError> {
+        bytes::proof_from_bytes(bytes)
+    }
+
+    /// Returns true if `m` values of common attributes are stored once at the proof level.
+    pub fn is_compact(&self) -> bool {
+        !self.common_m.is_empty()
+    }
+
+    /// Converts proof to compact form: `m` values that are equal in all sub proofs
+    /// (common attributes like `master_secret`) are moved from sub proofs to the proof level.
+    /// Compact proof can be passed to `ProofVerifier::verify` as is.
+    pub fn compact(mut self) -> Result<Proof, IndyCryptoError> {
+        trace!("Proof::compact: >>> proof: {:?}", self);
+
+        if self.proofs.len() > 1 {
+            let common_attrs = {
+                let (first, others) = self.proofs.split_first().unwrap();
+
+                first.primary_proof.eq_proof.m
+                    .iter()
+                    .filter(|&(attr, value)|
+                        others.iter().all(|sub_proof| sub_proof.primary_proof.eq_proof.m.get(attr) == Some(value)))
+                    .map(|(attr, _)| attr.clone())
+                    .collect::<Vec<String>>()
+            };
+
+            for attr in common_attrs {
+                let mut value = None;
+
+                for sub_proof in self.proofs.iter_mut() {
+                    value = sub_proof.primary_proof.eq_proof.m.remove(&attr);
+                }
+
+                if let Some(value) = value {
+                    self.common_m.insert(attr, value);
+                }
+            }
+        }
+
+        trace!("Proof::compact: <<< proof: {:?}", self);
+
+        Ok(self)
+    }
+
+    /// Converts compact proof back to regular form: `m` values of common attributes are copied to every sub proof.
+    pub fn expand(mut self) -> Result<Proof, IndyCryptoError> {
+        trace!("Proof::expand: >>> proof: {:?}", self);
+
+        for sub_proof in self.proofs.iter_mut() {
+            sub_proof.primary_proof.eq_proof.m = sub_proof.primary_proof.eq_proof.expand_m(&self.common_m)?;
+        }
+        self.common_m.clear();
+
+        trace!("Proof::expand: <<< proof: {:?}", self);
+
+        Ok(self)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct SubProof {
+    #[serde(default)]
+    key_id: String,
     primary_proof: PrimaryProof
 }
 
+/// Challenge of the proof and values it was computed from.
+///
+/// `c_list` has canonical order which both prover and verifier follow:
+/// sub proofs in order they were added (sub proof index), and inside each sub proof
+/// `a_prime` and revealed commitments sorted by attribute name, then inequality proofs of predicates
+/// sorted as `Predicate`, then ones of linear predicates sorted as `LinearPredicate`,
+/// then set membership proofs sorted by attribute name.
 #[derive(Debug, Eq, PartialEq, Deserialize, Serialize)]
 pub struct AggregatedProof {
     c_hash: BigNumber,
@@ -537,7 +1815,19 @@ pub struct AggregatedProof {
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PrimaryProof {
     eq_proof: PrimaryEqualProof,
-    ne_proofs: Vec<PrimaryPredicateInequalityProof>
+    ne_proofs: Vec<PrimaryPredicateInequalityProof>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    linear_ne_proofs: Vec<PrimaryLinearPredicateProof>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    set_membership_proofs: Vec<PrimarySetMembershipProof>
+}
+
+impl PrimaryProof {
+    /// Inequality proofs of predicates followed by ones of linear predicates (order of c_list).
+    fn all_ne_proofs(&self) -> impl Iterator<Item=&PrimaryPredicateInequalityProof> {
+        self.ne_proofs.iter()
+            .chain(self.linear_ne_proofs.iter().map(|linear_ne_proof| &linear_ne_proof.ne_proof))
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize)]
@@ -546,7 +1836,24 @@ pub struct PrimaryEqualProof {
     a_prime: BigNumber,
     e: BigNumber,
     v: BigNumber,
-    m: HashMap<String /* attr_name of all except revealed */, BigNumber>
+    m: HashMap<String /* attr_name of all except revealed */, BigNumber>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    revealed_commitments: BTreeMap<String /* attr_name of hidden */, PrimaryRevealedCommitmentProof>
+}
+
+impl PrimaryEqualProof {
+    /// Returns `m` values of the sub proof merged with `m` values of common attributes stored at the proof level.
+    fn expand_m(&self, common_m: &BTreeMap<String, BigNumber>) -> Result<HashMap<String, BigNumber>, IndyCryptoError> {
+        let mut m = clone_bignum_map(&self.m)?;
+
+        for (attr, value) in common_m {
+            if m.insert(attr.clone(), value.clone()?).is_some() {
+                return Err(IndyCryptoError::InvalidStructure(format!("Common attribute '{}' is duplicated in sub proof", attr)));
+            }
+        }
+
+        Ok(m)
+    }
 }
 
 impl <'a> ::serde::de::Deserialize<'a> for PrimaryEqualProof {
@@ -559,7 +1866,9 @@ impl <'a> ::serde::de::Deserialize<'a> for PrimaryEqualProof {
             v: BigNumber,
             m: HashMap<String /* attr_name of all except revealed */, BigNumber>,
             #[serde(default)]
-            m1: BigNumber
+            m1: BigNumber,
+            #[serde(default)]
+            revealed_commitments: BTreeMap<String /* attr_name of hidden */, PrimaryRevealedCommitmentProof>
         }
 
         let mut helper = PrimaryEqualProofV1::deserialize(deserializer)?;
@@ -571,11 +1880,24 @@ impl <'a> ::serde::de::Deserialize<'a> for PrimaryEqualProof {
             a_prime: helper.a_prime,
             e: helper.e,
             v: helper.v,
-            m: helper.m
+            m: helper.m,
+            revealed_commitments: helper.revealed_commitments
         })
     }
 }
 
+/// Salted commitment `Z^m * S^salt` to hidden attribute that prover reveals to verifier.
+///
+/// Commitment is deterministic for the attribute value and the salt, so verifier that knows expected commitment
+/// checks equality without learning the value. Hash of the value can't be linked with the signed attribute
+/// by equality proof, so algebraic commitment is used instead: `salt` is response `salt_tilde + c * salt`
+/// and the commitment is linked to `m` of equality proof.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PrimaryRevealedCommitmentProof {
+    commitment: BigNumber,
+    salt: BigNumber
+}
+
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PrimaryPredicateInequalityProof {
     u: HashMap<String, BigNumber>,
@@ -586,8 +1908,39 @@ pub struct PrimaryPredicateInequalityProof {
     predicate: Predicate
 }
 
+impl PrimaryPredicateInequalityProof {
+    /// Predicate proved by this inequality proof.
+    pub fn predicate(&self) -> &Predicate {
+        &self.predicate
+    }
+}
+
+/// Proof of linear predicate: inequality proof over the weighted sum of attributes.
+/// `ne_proof.mj` must be equal to the weighted sum of attributes `m` values of equality proof.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PrimaryLinearPredicateProof {
+    predicate: LinearPredicate,
+    ne_proof: PrimaryPredicateInequalityProof
+}
+
+/// Proof that hidden attribute is equal to one of public allowed values without revealing which one.
+///
+/// `t` is commitment `Z^m * S^r` linked to `m` of equality proof. For every allowed value `v_i` prover
+/// shows knowledge of `r` such that `t * Z^(-v_i) = S^r`; all branches except the real one are simulated
+/// and branch challenges `c` sum up to the proof challenge modulo `2^LARGE_CHALLENGE`.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PrimarySetMembershipProof {
+    attr_name: String,
+    t: BigNumber,
+    r: BigNumber,
+    c: Vec<BigNumber>,
+    s: Vec<BigNumber>
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize, Deserialize))]
 pub struct InitProof {
+    key_id: String,
     primary_init_proof: PrimaryInitProof,
     credential_values: CredentialValues,
     sub_proof_request: SubProofRequest,
@@ -595,11 +1948,30 @@ pub struct InitProof {
     non_credential_schema: NonCredentialSchema,
 }
 
+impl InitProof {
+    /// 清零证明生成的秘密中间值（随机数、属性值），之后InitProof不可再用于生成证明
+    fn clear(&mut self) {
+        self.credential_values.clear();
+        self.primary_init_proof.eq_proof.clear();
+        for ne_proof in self.primary_init_proof.ne_proofs.iter_mut() {
+            ne_proof.clear();
+        }
+        for linear_ne_proof in self.primary_init_proof.linear_ne_proofs.iter_mut() {
+            linear_ne_proof.ne_proof.clear();
+        }
+        for set_membership_proof in self.primary_init_proof.set_membership_proofs.iter_mut() {
+            set_membership_proof.clear();
+        }
+    }
+}
 
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize, Deserialize))]
 pub struct PrimaryInitProof {
     eq_proof: PrimaryEqualInitProof,
-    ne_proofs: Vec<PrimaryPredicateInequalityInitProof>
+    ne_proofs: Vec<PrimaryPredicateInequalityInitProof>,
+    linear_ne_proofs: Vec<PrimaryLinearPredicateInitProof>,
+    set_membership_proofs: Vec<PrimarySetMembershipInitProof>
 }
 
 impl PrimaryInitProof {
@@ -608,6 +1980,12 @@ impl PrimaryInitProof {
         for ne_proof in self.ne_proofs.iter() {
             c_list.append_vec(ne_proof.as_list()?)?;
         }
+        for linear_ne_proof in self.linear_ne_proofs.iter() {
+            c_list.append_vec(linear_ne_proof.ne_proof.as_list()?)?;
+        }
+        for set_membership_proof in self.set_membership_proofs.iter() {
+            c_list.append_vec(&set_membership_proof.as_list()?)?;
+        }
         Ok(c_list)
     }
 
@@ -616,11 +1994,18 @@ impl PrimaryInitProof {
         for ne_proof in self.ne_proofs.iter() {
             tau_list.append_vec(ne_proof.as_tau_list()?)?;
         }
+        for linear_ne_proof in self.linear_ne_proofs.iter() {
+            tau_list.append_vec(linear_ne_proof.ne_proof.as_tau_list()?)?;
+        }
+        for set_membership_proof in self.set_membership_proofs.iter() {
+            tau_list.append_vec(set_membership_proof.as_tau_list()?)?;
+        }
         Ok(tau_list)
     }
 }
 
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize, Deserialize))]
 pub struct PrimaryEqualInitProof {
     a_prime: BigNumber,
     t: BigNumber,
@@ -628,20 +2013,51 @@ pub struct PrimaryEqualInitProof {
     e_prime: BigNumber,
     v_tilde: BigNumber,
     v_prime: BigNumber,
-    m_tilde: HashMap<String, BigNumber>
+    m_tilde: HashMap<String, BigNumber>,
+    revealed_commitments: BTreeMap<String, PrimaryRevealedCommitmentInitProof>
 }
 
 impl PrimaryEqualInitProof {
     pub fn as_list(&self) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
-        Ok(vec![self.a_prime.to_bytes()?])
+        let mut list = vec![self.a_prime.to_bytes()?];
+        for revealed_commitment in self.revealed_commitments.values() {
+            list.push(revealed_commitment.commitment.to_bytes()?);
+        }
+        Ok(list)
     }
 
     pub fn as_tau_list(&self) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
-        Ok(vec![self.t.to_bytes()?])
+        let mut tau_list = vec![self.t.to_bytes()?];
+        for revealed_commitment in self.revealed_commitments.values() {
+            tau_list.push(revealed_commitment.tau.to_bytes()?);
+        }
+        Ok(tau_list)
+    }
+
+    fn clear(&mut self) {
+        self.e_tilde.clear();
+        self.e_prime.clear();
+        self.v_tilde.clear();
+        self.v_prime.clear();
+        clear_bignum_map(&mut self.m_tilde);
+        for revealed_commitment in self.revealed_commitments.values_mut() {
+            revealed_commitment.salt.clear();
+            revealed_commitment.salt_tilde.clear();
+        }
     }
 }
 
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize, Deserialize))]
+pub struct PrimaryRevealedCommitmentInitProof {
+    commitment: BigNumber,
+    salt: BigNumber,
+    salt_tilde: BigNumber,
+    tau: BigNumber
+}
+
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize, Deserialize))]
 pub struct PrimaryPredicateInequalityInitProof {
     c_list: Vec<BigNumber>,
     tau_list: Vec<BigNumber>,
@@ -662,6 +2078,77 @@ impl PrimaryPredicateInequalityInitProof {
     pub fn as_tau_list(&self) -> Result<&Vec<BigNumber>, IndyCryptoError> {
         Ok(&self.tau_list)
     }
+
+    /// Predicate proved by this inequality init proof.
+    pub fn predicate(&self) -> &Predicate {
+        &self.predicate
+    }
+
+    /// Debug only, NOT for production: reconstructs predicate delta as sum of squares of four-square components `u`.
+    ///
+    /// Delta reveals how far hidden attribute value is from predicate value (for `GE 18` the value is `18 + delta`),
+    /// so it must never leave the prover. It's available on init proof only: finalized proof contains
+    /// `u_hat = u_tilde + c * u`, from which delta can't be reconstructed.
+    #[cfg(feature = "debug_delta")]
+    pub fn debug_delta(&self) -> Result<BigNumber, IndyCryptoError> {
+        let mut ctx = BigNumber::new_context()?;
+        let mut delta = BigNumber::new()?;
+
+        for i in 0..constants::ITERATION {
+            let u_i = self.u.get(&i.to_string())
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in u", i)))?;
+
+            delta = delta.add(&u_i.sqr(Some(&mut ctx))?)?;
+        }
+
+        Ok(delta)
+    }
+
+    fn clear(&mut self) {
+        clear_bignum_map(&mut self.u);
+        clear_bignum_map(&mut self.u_tilde);
+        clear_bignum_map(&mut self.r);
+        clear_bignum_map(&mut self.r_tilde);
+        self.alpha_tilde.clear();
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize, Deserialize))]
+pub struct PrimaryLinearPredicateInitProof {
+    predicate: LinearPredicate,
+    ne_proof: PrimaryPredicateInequalityInitProof
+}
+
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "proof_builder_snapshot", derive(Serialize, Deserialize))]
+pub struct PrimarySetMembershipInitProof {
+    attr_name: String,
+    member_index: usize,
+    t: BigNumber,
+    r: BigNumber,
+    r_tilde: BigNumber,
+    rho: BigNumber,
+    c: Vec<BigNumber>, // Challenges of simulated branches, zero for the real one
+    s: Vec<BigNumber>, // Responses of simulated branches, zero for the real one
+    tau_list: Vec<BigNumber>
+}
+
+impl PrimarySetMembershipInitProof {
+    pub fn as_list(&self) -> Result<Vec<BigNumber>, IndyCryptoError> {
+        Ok(vec![self.t.clone()?])
+    }
+
+    pub fn as_tau_list(&self) -> Result<&Vec<BigNumber>, IndyCryptoError> {
+        Ok(&self.tau_list)
+    }
+
+    fn clear(&mut self) {
+        self.r.clear();
+        self.r_tilde.clear();
+        self.rho.clear();
+        self.member_index = 0;
+    }
 }
 
 /// Random BigNumber that uses `Prover` for proof generation and `Verifier` for proof verification.
@@ -669,10 +2156,12 @@ pub type Nonce = BigNumber;
 
 #[derive(Debug)]
 pub struct VerifiableCredential {
-    pub_key: CredentialPublicKey,
+    key_id: String,
+    pub_key: PreparedPublicKey,
     sub_proof_request: SubProofRequest,
     credential_schema: CredentialSchema,
-    non_credential_schema: NonCredentialSchema
+    non_credential_schema: NonCredentialSchema,
+    set_memberships: Vec<(String /* attr_name */, Vec<BigNumber> /* allowed_values */)>
 }
 
 trait BytesView {
@@ -698,6 +2187,11 @@ impl AppendByteArray for Vec<Vec<u8>> {
     }
 }
 
+fn to_canonical_json<T: ::serde::Serialize>(value: &T) -> Result<String, IndyCryptoError> {
+    // serde_json::Value keeps object keys sorted, so result doesn't depend on HashMap iteration order
+    Ok(::serde_json::to_string(&::serde_json::to_value(value)?)?)
+}
+
 fn clone_bignum_map<K: Clone + Eq + Hash>(other: &HashMap<K, BigNumber>) -> Result<HashMap<K, BigNumber>, IndyCryptoError> {
     let mut res = HashMap::new();
     for (k, v) in other.iter() {
@@ -706,6 +2200,11 @@ fn clone_bignum_map<K: Clone + Eq + Hash>(other: &HashMap<K, BigNumber>) -> Resu
     Ok(res)
 }
 
+fn clear_bignum_map<K: Eq + Hash>(map: &mut HashMap<K, BigNumber>) {
+    for value in map.values_mut() {
+        value.clear();
+    }
+}
 
 fn clone_credential_value_map<K: Clone + Eq + Ord>(other: &BTreeMap<K, CredentialValue>) -> Result<BTreeMap<K, CredentialValue>, IndyCryptoError> {
     let mut res = BTreeMap::new();
@@ -722,7 +2221,290 @@ mod test {
     use self::issuer::Issuer;
     use self::prover::Prover;
     use self::verifier::Verifier;
-    
+
+    #[test]
+    fn check_nonce_works() {
+        check_nonce(&new_nonce().unwrap()).unwrap();
+        check_nonce(&BigNumber::from_dec("18446744073709551615").unwrap()).unwrap(); // 2^64 - 1
+
+        for nonce in [BigNumber::new().unwrap(), BigNumber::from_u32(12345).unwrap(), BigNumber::from_dec("9223372036854775807").unwrap()].iter() {
+            match check_nonce(nonce) {
+                Err(IndyCryptoError::InvalidStructure(_)) => {}
+                res => panic!("InvalidStructure error expected for {:?}, got {:?}", nonce, res)
+            }
+        }
+    }
+
+    #[test]
+    fn predicate_negate_works() {
+        let table = vec![
+            (PredicateType::GE, PredicateType::LT),
+            (PredicateType::GT, PredicateType::LE),
+            (PredicateType::LE, PredicateType::GT),
+            (PredicateType::LT, PredicateType::GE),
+        ];
+
+        for (p_type, expected) in table {
+            let predicate = Predicate { attr_name: "age".to_owned(), p_type: p_type.clone(), value: 5 };
+            let negated = predicate.negate().unwrap();
+
+            assert_eq!(Some(expected.clone()), p_type.negate());
+            assert_eq!(expected, negated.p_type);
+            assert_eq!(5, negated.value);
+            assert_eq!(Some(predicate.clone()), negated.negate());
+
+            for attr_value in 0..10 {
+                let attr_value = BigNumber::from_u32(attr_value).unwrap();
+                assert_ne!(predicate.get_delta(&attr_value).unwrap().is_negative(), negated.get_delta(&attr_value).unwrap().is_negative());
+            }
+        }
+
+        assert_eq!(None, PredicateType::EQ.negate());
+        assert_eq!(None, Predicate { attr_name: "age".to_owned(), p_type: PredicateType::EQ, value: 5 }.negate());
+    }
+
+    #[test]
+    fn credential_primary_public_key_compact_bytes_works() {
+        let pub_key = issuer::mocks::credential_primary_public_key();
+        assert!(pub_key.r.contains_key("master_secret"));
+
+        let bytes = pub_key.to_compact_bytes().unwrap();
+        let json = serde_json::to_string(&pub_key).unwrap();
+        assert!(bytes.len() < json.len());
+
+        let restored = CredentialPrimaryPublicKey::from_compact_bytes(&bytes).unwrap();
+        assert_eq!(pub_key, restored);
+
+        assert!(CredentialPrimaryPublicKey::from_compact_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn sub_proof_request_eq_and_hash_works() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+
+        fn _sub_proof_request(revealed_attr: &str) -> SubProofRequest {
+            let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+            sub_proof_request_builder.add_revealed_attr(revealed_attr).unwrap();
+            sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+            sub_proof_request_builder.finalize().unwrap()
+        }
+
+        fn _hash(sub_proof_request: &SubProofRequest) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            sub_proof_request.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let sub_proof_request = _sub_proof_request("name");
+        let same_sub_proof_request = _sub_proof_request("name");
+        let other_sub_proof_request = _sub_proof_request("sex");
+
+        assert_eq!(sub_proof_request, same_sub_proof_request);
+        assert_eq!(_hash(&sub_proof_request), _hash(&same_sub_proof_request));
+        assert_ne!(sub_proof_request, other_sub_proof_request);
+    }
+
+    #[test]
+    fn predicate_get_delta_works_for_max_supported_delta() {
+        let i32_max = BigNumber::from_dec(&::std::i32::MAX.to_string()).unwrap();
+
+        let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GE, value: 0 };
+        assert_eq!(i32_max, predicate.get_delta(&i32_max).unwrap());
+
+        let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GE, value: -1 };
+        assert!(predicate.get_delta(&i32_max).is_err());
+
+        let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::LE, value: ::std::i32::MAX };
+        assert_eq!(i32_max, predicate.get_delta(&BigNumber::new().unwrap()).unwrap());
+        assert!(predicate.get_delta(&BigNumber::from_dec("-1").unwrap()).is_err());
+    }
+
+    #[test]
+    fn predicate_get_delta_works_for_values_out_of_i32() {
+        let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GT, value: ::std::i32::MAX };
+        assert_eq!(BigNumber::from_u32(9).unwrap(), predicate.get_delta(&BigNumber::from_dec("2147483657").unwrap()).unwrap());
+
+        let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::LE, value: ::std::i32::MIN };
+        assert_eq!(BigNumber::from_u32(5).unwrap(), predicate.get_delta(&BigNumber::from_dec("-2147483653").unwrap()).unwrap());
+        assert!(predicate.get_delta(&BigNumber::from_dec("2147483648").unwrap()).unwrap().is_negative());
+    }
+
+    #[test]
+    fn credential_schema_from_attrs_works() {
+        let credential_schema = CredentialSchema::from_attrs(&["name", "age"]).unwrap();
+        assert_eq!(btreeset!["age".to_string(), "name".to_string()], credential_schema.attrs);
+        assert!(credential_schema.public_attrs.is_empty());
+    }
+
+    #[test]
+    fn credential_schema_from_attrs_works_for_reserved_attr() {
+        let res = CredentialSchema::from_attrs(&["name", "master_secret"]);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn sub_proof_request_json_works() {
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_revealed_attr("sex").unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("height", "LT", 200).unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let sub_proof_request_json = serde_json::to_string(&sub_proof_request).unwrap();
+        assert_eq!(r#"{"revealed_attrs":["name","sex"],"predicates":[{"attr_name":"age","p_type":"GE","value":18},{"attr_name":"height","p_type":"LT","value":200}]}"#,
+                   sub_proof_request_json);
+
+        let restored_sub_proof_request = serde_json::from_str::<SubProofRequest>(&sub_proof_request_json).unwrap();
+        assert_eq!(sub_proof_request, restored_sub_proof_request);
+    }
+
+    #[test]
+    fn sub_proof_request_json_works_for_not_normalized_attr_names() {
+        let sub_proof_request_json = json!({
+            "revealed_attrs": ["cafe\u{301}"],
+            "predicates": [{"attr_name": "age\u{301}", "p_type": "GE", "value": 18}]
+        });
+
+        let sub_proof_request = serde_json::from_value::<SubProofRequest>(sub_proof_request_json).unwrap();
+
+        assert_eq!(&btreeset![normalize_attr_name("cafe\u{301}")], sub_proof_request.revealed_attrs());
+        assert_eq!("ag\u{e9}", sub_proof_request.predicates().iter().next().unwrap().attr_name());
+    }
+
+    #[test]
+    fn credential_schema_json_works_for_not_normalized_attr_names() {
+        let credential_schema_json = json!({
+            "attrs": ["cafe\u{301}", "caf\u{e9}", "age"],
+            "public_attrs": ["age\u{301}"]
+        });
+
+        let credential_schema = serde_json::from_value::<CredentialSchema>(credential_schema_json).unwrap();
+
+        assert_eq!(btreeset!["age".to_string(), "ag\u{e9}".to_string(), "caf\u{e9}".to_string()], credential_schema.attrs);
+        assert_eq!(btreeset!["ag\u{e9}".to_string()], credential_schema.public_attrs);
+    }
+
+    #[test]
+    fn credential_schema_json_works_for_reserved_attr_name() {
+        let credential_schema_json = json!({"attrs": ["name", "master_secret"]});
+        assert!(serde_json::from_value::<CredentialSchema>(credential_schema_json).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "proof_builder_snapshot")]
+    fn credential_values_json_works_for_not_normalized_attr_names() {
+        let credential_values_json = json!({
+            "attrs_values": {
+                "cafe\u{301}": {"Known": {"value": "1"}},
+                "master_secret": {"Hidden": {"value": "2"}}
+            }
+        });
+
+        let credential_values = serde_json::from_value::<CredentialValues>(credential_values_json).unwrap();
+
+        assert!(credential_values.attrs_values.contains_key("caf\u{e9}"));
+        assert!(!credential_values.attrs_values.contains_key("cafe\u{301}"));
+        assert!(credential_values.get("master_secret").unwrap().is_hidden());
+    }
+
+    #[test]
+    fn sub_proof_request_json_works_for_invalid_request() {
+        let invalid_requests = vec![
+            json!({"revealed_attrs": ["age"], "predicates": [{"attr_name": "age", "p_type": "GE", "value": 18}]}),
+            json!({"revealed_attrs": [], "predicates": [{"attr_name": "age", "p_type": "GT", "value": ::std::i32::MAX}]}),
+            json!({"revealed_attrs": [], "predicates": [], "bitlength_predicates": {"age": 32}}),
+        ];
+
+        for sub_proof_request_json in invalid_requests {
+            assert!(serde_json::from_value::<SubProofRequest>(sub_proof_request_json).is_err());
+        }
+    }
+
+    #[test]
+    fn sub_proof_request_builder_add_predicate_works_for_value_out_of_range() {
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        assert!(sub_proof_request_builder.add_predicate("age", "GT", ::std::i32::MAX - 1).is_ok());
+        assert!(sub_proof_request_builder.add_predicate("age", "GT", ::std::i32::MAX).is_err());
+        assert!(sub_proof_request_builder.add_predicate("age", "LT", ::std::i32::MIN + 1).is_ok());
+        assert!(sub_proof_request_builder.add_predicate("age", "LT", ::std::i32::MIN).is_err());
+    }
+
+    #[test]
+    fn sub_proof_request_builder_add_bitlength_predicate_works() {
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_bitlength_predicate("age", 8).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let expected_predicates = btreeset![
+            Predicate { attr_name: "age".to_string(), p_type: PredicateType::GE, value: 0 },
+            Predicate { attr_name: "age".to_string(), p_type: PredicateType::LE, value: 255 }
+        ];
+        assert_eq!(expected_predicates, sub_proof_request.predicates);
+        assert_eq!(&btreemap!["age".to_string() => 8], sub_proof_request.bitlength_predicates());
+
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        assert!(sub_proof_request_builder.add_bitlength_predicate("age", 31).is_ok());
+        assert!(sub_proof_request_builder.add_bitlength_predicate("age", 31).is_ok());
+        assert!(sub_proof_request_builder.add_bitlength_predicate("age", 0).is_err());
+        assert!(sub_proof_request_builder.add_bitlength_predicate("age", 32).is_err());
+        assert!(sub_proof_request_builder.add_bitlength_predicate("age", 8).is_err());
+        assert_eq!(2, sub_proof_request_builder.value.predicates.len());
+    }
+
+    #[test]
+    fn sub_proof_request_builder_finalize_works_for_eq_and_other_predicate_on_same_attr() {
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_predicate("country", "EQ", 840).unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        assert_eq!(btreeset!["country".to_string()], sub_proof_request.known_attrs());
+        assert_eq!(1, sub_proof_request.ne_predicates().len());
+
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_predicate("age", "EQ", 28).unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+
+        let res = sub_proof_request_builder.finalize();
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn predicate_is_satisfied_by_works_for_values_out_of_i32() {
+        let large = BigNumber::from_dec("3000000000").unwrap();
+        let negative = BigNumber::from_dec("-3000000000").unwrap();
+
+        let ge = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GE, value: i32::max_value() };
+        assert!(ge.is_satisfied_by(&large).unwrap());
+        assert!(ge.is_satisfied_by(&BigNumber::from_dec(&i32::max_value().to_string()).unwrap()).unwrap());
+        assert!(!ge.is_satisfied_by(&negative).unwrap());
+
+        let lt = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::LT, value: i32::min_value() };
+        assert!(lt.is_satisfied_by(&negative).unwrap());
+        assert!(!lt.is_satisfied_by(&BigNumber::from_dec(&i32::min_value().to_string()).unwrap()).unwrap());
+        assert!(!lt.is_satisfied_by(&large).unwrap());
+    }
+
+    fn assert_send<T: Send>() {}
+
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn public_types_are_send_and_sync() {
+        assert_send::<CredentialPublicKey>();
+        assert_sync::<CredentialPublicKey>();
+        assert_send::<PreparedPublicKey>();
+        assert_sync::<PreparedPublicKey>();
+        assert_send::<CredentialSchema>();
+        assert_sync::<CredentialSchema>();
+        assert_send::<SubProofRequest>();
+        assert_sync::<SubProofRequest>();
+        assert_send::<Proof>();
+        assert_sync::<Proof>();
+    }
+
     #[test]
     fn multiple_predicates() {
         let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
@@ -790,40 +2572,206 @@ mod test {
                                              &cred_issuance_nonce).unwrap();
 
         let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
-        sub_proof_request_builder.add_revealed_attr("total_liabilities").unwrap();
+        sub_proof_request_builder.add_revealed_attr("total_liabilities").unwrap();
+
+        sub_proof_request_builder.add_predicate("funds_sold_and_securities_purchased", "LT", 100).unwrap();
+        sub_proof_request_builder.add_predicate("funds_sold_and_securities_purchased", "GT", 0).unwrap();
+        sub_proof_request_builder.add_predicate("other_earning_assets", "LT", 100).unwrap();
+        sub_proof_request_builder.add_predicate("cash", "LT", 100).unwrap();
+        sub_proof_request_builder.add_predicate("allowance", "LT", 100).unwrap();
+        sub_proof_request_builder.add_predicate("total_assets", "GT", 100).unwrap();
+
+        sub_proof_request_builder.add_predicate("domestic_interest_bearing_deposits", "LE", 100).unwrap();
+        sub_proof_request_builder.add_predicate("funds_purchased", "LE", 100).unwrap();
+        sub_proof_request_builder.add_predicate("long_term_debt", "LE", 100).unwrap();
+        sub_proof_request_builder.add_predicate("non_interest_bearing_liabilities", "LE", 100).unwrap();
+        sub_proof_request_builder.add_predicate("shareholder_equity", "LE", 100).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &cred_signature,
+                                            &cred_values,
+                                            issuer::mocks::CREDENTIAL_KEY_ID,
+                                            &cred_pub_key).unwrap();
+
+        let proof_request_nonce = new_nonce().unwrap();
+        let proof = proof_builder.finalize(&proof_request_nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             issuer::mocks::CREDENTIAL_KEY_ID,
+                                             &cred_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &proof_request_nonce).unwrap());
+    }
+
+    fn _financial_credential(total_assets: &str, total_liabilities: &str)
+                             -> (CredentialSchema, NonCredentialSchema, CredentialPublicKey, CredentialSignature, CredentialValues) {
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr("funds_sold_and_securities_purchased").unwrap();
+        credential_schema_builder.add_attr("other_earning_assets").unwrap();
+        credential_schema_builder.add_attr("cash").unwrap();
+        credential_schema_builder.add_attr("allowance").unwrap();
+        credential_schema_builder.add_attr("total_assets").unwrap();
+        credential_schema_builder.add_attr("domestic_interest_bearing_deposits").unwrap();
+        credential_schema_builder.add_attr("funds_purchased").unwrap();
+        credential_schema_builder.add_attr("long_term_debt").unwrap();
+        credential_schema_builder.add_attr("non_interest_bearing_liabilities").unwrap();
+        credential_schema_builder.add_attr("shareholder_equity").unwrap();
+        credential_schema_builder.add_attr("total_liabilities").unwrap();
+
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let mut non_credential_schema_builder = NonCredentialSchemaBuilder::new().unwrap();
+        non_credential_schema_builder.add_attr("master_secret").unwrap();
+        let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+
+        let (cred_pub_key, cred_priv_key, cred_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema).unwrap();
+
+        let master_secret = Prover::new_master_secret().unwrap();
+        let credential_nonce = new_nonce().unwrap();
+
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_dec_known("funds_sold_and_securities_purchased", "50").unwrap();
+        credential_values_builder.add_dec_known("other_earning_assets", "60").unwrap();
+        credential_values_builder.add_dec_known("cash", "70").unwrap();
+        credential_values_builder.add_dec_known("allowance", "80").unwrap();
+        credential_values_builder.add_dec_known("total_assets", total_assets).unwrap();
+
+        credential_values_builder.add_dec_known("domestic_interest_bearing_deposits", "10").unwrap();
+        credential_values_builder.add_dec_known("funds_purchased", "20").unwrap();
+        credential_values_builder.add_dec_known("long_term_debt", "30").unwrap();
+        credential_values_builder.add_dec_known("non_interest_bearing_liabilities", "40").unwrap();
+        credential_values_builder.add_dec_known("shareholder_equity", "50").unwrap();
+        credential_values_builder.add_dec_known("total_liabilities", total_liabilities).unwrap();
+        let cred_values = credential_values_builder.finalize().unwrap();
+
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&cred_pub_key,
+                                        &cred_key_correctness_proof,
+                                        &cred_values,
+                                        &credential_nonce).unwrap();
+
+        let cred_issuance_nonce = new_nonce().unwrap();
+
+        let (mut cred_signature, signature_correctness_proof) = Issuer::sign_credential("b977afe22b5b446109797ad925d9f133fc33c1914081071295d2ac1ddce3385d",
+                                                                                        &blinded_credential_secrets,
+                                                                                        &blinded_credential_secrets_correctness_proof,
+                                                                                        &credential_nonce,
+                                                                                        &cred_issuance_nonce,
+                                                                                        &cred_values,
+                                                                                        &cred_pub_key,
+                                                                                        &cred_priv_key).unwrap();
+
+        Prover::process_credential_signature(&mut cred_signature,
+                                             &cred_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &cred_pub_key,
+                                             &cred_issuance_nonce).unwrap();
+
+        (credential_schema, non_credential_schema, cred_pub_key, cred_signature, cred_values)
+    }
+
+    fn _prove_financial_credential(sub_proof_request: &SubProofRequest,
+                                   credential: &(CredentialSchema, NonCredentialSchema, CredentialPublicKey, CredentialSignature, CredentialValues),
+                                   proof_request_nonce: &Nonce) -> Result<Proof, IndyCryptoError> {
+        let &(ref credential_schema, ref non_credential_schema, ref cred_pub_key, ref cred_signature, ref cred_values) = credential;
+
+        let mut proof_builder = Prover::new_proof_builder()?;
+        proof_builder.add_common_attribute("master_secret")?;
+        proof_builder.add_sub_proof_request(sub_proof_request,
+                                            credential_schema,
+                                            non_credential_schema,
+                                            cred_signature,
+                                            cred_values,
+                                            issuer::mocks::CREDENTIAL_KEY_ID,
+                                            cred_pub_key)?;
+        proof_builder.finalize(proof_request_nonce)
+    }
+
+    fn _prove_and_verify_financial_credential(sub_proof_request: &SubProofRequest,
+                                              credential: &(CredentialSchema, NonCredentialSchema, CredentialPublicKey, CredentialSignature, CredentialValues))
+                                              -> Result<bool, IndyCryptoError> {
+        let &(ref credential_schema, ref non_credential_schema, ref cred_pub_key, _, _) = credential;
+
+        let proof_request_nonce = new_nonce()?;
+        let proof = _prove_financial_credential(sub_proof_request, credential, &proof_request_nonce)?;
+
+        let mut proof_verifier = Verifier::new_proof_verifier()?;
+        proof_verifier.add_sub_proof_request(sub_proof_request,
+                                             credential_schema,
+                                             non_credential_schema,
+                                             issuer::mocks::CREDENTIAL_KEY_ID,
+                                             cred_pub_key)?;
+        proof_verifier.verify(&proof, &proof_request_nonce)
+    }
+
+    #[test]
+    fn linear_predicate_works_for_net_worth() {
+        let credential = _financial_credential("260", "150");
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_linear_predicate(&[("total_assets", 1), ("total_liabilities", -1)], "GE", 0).unwrap();
+        sub_proof_request_builder.add_linear_predicate(&[("total_assets", 1), ("total_liabilities", -2)], "LT", 0).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        assert!(_prove_and_verify_financial_credential(&sub_proof_request, &credential).unwrap());
+    }
+
+    #[test]
+    fn proof_predicates_works_for_linear_predicate() {
+        let credential = _financial_credential("260", "150");
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("cash", "GE", 50).unwrap();
+        sub_proof_request_builder.add_linear_predicate(&[("total_assets", 1), ("total_liabilities", -1)], "GE", 0).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
 
-        sub_proof_request_builder.add_predicate("funds_sold_and_securities_purchased", "LT", 100).unwrap();
-        sub_proof_request_builder.add_predicate("funds_sold_and_securities_purchased", "GT", 0).unwrap();
-        sub_proof_request_builder.add_predicate("other_earning_assets", "LT", 100).unwrap();
-        sub_proof_request_builder.add_predicate("cash", "LT", 100).unwrap();
-        sub_proof_request_builder.add_predicate("allowance", "LT", 100).unwrap();
-        sub_proof_request_builder.add_predicate("total_assets", "GT", 100).unwrap();
+        let proof = _prove_financial_credential(&sub_proof_request, &credential, &new_nonce().unwrap()).unwrap();
 
-        sub_proof_request_builder.add_predicate("domestic_interest_bearing_deposits", "LE", 100).unwrap();
-        sub_proof_request_builder.add_predicate("funds_purchased", "LE", 100).unwrap();
-        sub_proof_request_builder.add_predicate("long_term_debt", "LE", 100).unwrap();
-        sub_proof_request_builder.add_predicate("non_interest_bearing_liabilities", "LE", 100).unwrap();
-        sub_proof_request_builder.add_predicate("shareholder_equity", "LE", 100).unwrap();
+        let expected_predicate = Predicate { attr_name: "cash".to_string(), p_type: PredicateType::GE, value: 50 };
+        let expected_linear_predicate = LinearPredicate {
+            coeffs: btreemap!["total_assets".to_string() => 1, "total_liabilities".to_string() => -1],
+            p_type: PredicateType::GE,
+            value: 0
+        };
+        assert_eq!(vec![vec![&expected_predicate]], proof.predicates());
+        assert_eq!(vec![vec![&expected_linear_predicate]], proof.linear_predicates());
+        assert_eq!(btreeset![expected_predicate.clone()], proof.constrained_attrs().unwrap().1);
+    }
+
+    #[test]
+    fn linear_predicate_works_for_negative_net_worth() {
+        let credential = _financial_credential("100", "150");
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_linear_predicate(&[("total_assets", 1), ("total_liabilities", -1)], "GE", 0).unwrap();
         let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
 
-        let mut proof_builder = Prover::new_proof_builder().unwrap();
-        proof_builder.add_common_attribute("master_secret").unwrap();
-        proof_builder.add_sub_proof_request(&sub_proof_request,
-                                            &credential_schema,
-                                            &non_credential_schema,
-                                            &cred_signature,
-                                            &cred_values,
-                                            &cred_pub_key).unwrap();
+        match _prove_and_verify_financial_credential(&sub_proof_request, &credential) {
+            Err(IndyCryptoError::AnoncredsPredicateNotSatisfied(ref msg)) => assert_eq!("Predicate is not satisfied", msg),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
 
-        let proof_request_nonce = new_nonce().unwrap();
-        let proof = proof_builder.finalize(&proof_request_nonce).unwrap();
+    #[test]
+    fn sub_proof_request_builder_add_linear_predicate_works_for_invalid_params() {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        assert!(sub_proof_request_builder.add_linear_predicate(&[], "GE", 0).is_err());
+        assert!(sub_proof_request_builder.add_linear_predicate(&[("total_assets", 0)], "GE", 0).is_err());
+        assert!(sub_proof_request_builder.add_linear_predicate(&[("total_assets", 1), ("total_assets", -1)], "GE", 0).is_err());
+        assert!(sub_proof_request_builder.add_linear_predicate(&[("total_assets", 1), ("total_liabilities", -1)], "EQ", 0).is_err());
 
-        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
-        proof_verifier.add_sub_proof_request(&sub_proof_request,
-                                             &credential_schema,
-                                             &non_credential_schema,
-                                             &cred_pub_key).unwrap();
-        assert!(proof_verifier.verify(&proof, &proof_request_nonce).unwrap());
+        sub_proof_request_builder.add_revealed_attr("total_liabilities").unwrap();
+        sub_proof_request_builder.add_linear_predicate(&[("total_assets", 1), ("total_liabilities", -1)], "GE", 0).unwrap();
+        assert!(sub_proof_request_builder.finalize().is_err());
     }
 
     #[test]
@@ -862,6 +2810,421 @@ mod test {
         assert_eq!(two, one);
     }
 
+    #[test]
+    fn proof_serialization_round_trip_works() {
+        let proof = prover::mocks::proof();
+
+        let proof_json = serde_json::to_string(&proof).unwrap();
+        let restored = serde_json::from_str::<Proof>(&proof_json).unwrap();
+
+        assert_eq!(proof, restored);
+    }
+
+    #[test]
+    fn proof_serialization_version_works() {
+        let proof = prover::mocks::proof();
+
+        let proof_json = serde_json::to_value(&proof).unwrap();
+        assert_eq!(SerializationVersion::CURRENT.value(), proof_json["ver"].as_u64().unwrap() as u32);
+
+        let versioned = serde_json::from_value::<Proof>(proof_json.clone()).unwrap();
+        assert_eq!(SerializationVersion::CURRENT, versioned.ver);
+
+        let mut legacy_json = proof_json.clone();
+        legacy_json.as_object_mut().unwrap().remove("ver");
+        let legacy = serde_json::from_value::<Proof>(legacy_json).unwrap();
+        assert_eq!(SerializationVersion::LEGACY, legacy.ver);
+        assert_eq!(proof.aggregated_proof, legacy.aggregated_proof);
+        assert_eq!(proof.proofs[0].primary_proof, legacy.proofs[0].primary_proof);
+
+        let mut unknown_json = proof_json.clone();
+        unknown_json["ver"] = json!(100);
+        assert!(serde_json::from_value::<Proof>(unknown_json).is_err());
+    }
+
+    #[test]
+    fn proof_recompute_challenge_works_for_legacy_proof() {
+        let mut proof = prover::mocks::proof();
+        let nonce = prover::mocks::proof_request_nonce();
+        let tau_list = vec![vec![1, 2, 3]];
+
+        let challenge = proof.recompute_challenge(&tau_list, &nonce).unwrap();
+
+        proof.ver = SerializationVersion::LEGACY;
+        assert_eq!(challenge, proof.recompute_challenge(&tau_list, &nonce).unwrap());
+
+        let legacy_challenge = proof._recompute_challenge(HashBuilder::new().unwrap(), &tau_list, &nonce).unwrap();
+
+        let mut hash_values = tau_list.clone();
+        hash_values.extend(proof.aggregated_proof.c_list.iter().cloned());
+        hash_values.push(nonce.to_bytes().unwrap());
+        assert_eq!(hash::get_hash_as_int(&hash_values).unwrap(), legacy_challenge);
+        assert_ne!(challenge, legacy_challenge);
+    }
+
+    #[test]
+    fn credential_public_key_serialization_version_works() {
+        let pub_key = issuer::mocks::credential_public_key();
+
+        let pub_key_json = serde_json::to_value(&pub_key).unwrap();
+        assert_eq!(pub_key, serde_json::from_value::<CredentialPublicKey>(pub_key_json.clone()).unwrap());
+
+        let mut legacy_json = pub_key_json.clone();
+        legacy_json.as_object_mut().unwrap().remove("ver");
+        let legacy = serde_json::from_value::<CredentialPublicKey>(legacy_json).unwrap();
+        assert_eq!(SerializationVersion::LEGACY, legacy.ver);
+        assert_eq!(pub_key.p_key, legacy.p_key);
+
+        let mut unknown_json = pub_key_json.clone();
+        unknown_json["ver"] = json!(100);
+        assert!(serde_json::from_value::<CredentialPublicKey>(unknown_json).is_err());
+    }
+
+    #[test]
+    fn credential_public_key_tag_works() {
+        let mut pub_key = issuer::mocks::credential_public_key();
+        assert_eq!(None, pub_key.tag());
+        assert!(serde_json::to_value(&pub_key).unwrap().get("tag").is_none());
+
+        pub_key.set_tag(Some("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0"));
+
+        let pub_key_json = serde_json::to_string(&pub_key).unwrap();
+        let deserialized = serde_json::from_str::<CredentialPublicKey>(&pub_key_json).unwrap();
+        assert_eq!(Some("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0"), deserialized.tag());
+        assert_eq!(pub_key, deserialized);
+        assert_eq!(Some("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0"), pub_key.clone().unwrap().tag());
+
+        pub_key.set_tag(None);
+        assert_eq!(issuer::mocks::credential_public_key(), pub_key);
+    }
+
+    #[test]
+    fn credential_public_key_tag_doesnt_affect_canonical_json_and_equality() {
+        let pub_key = issuer::mocks::credential_public_key();
+        let mut tagged_pub_key = issuer::mocks::credential_public_key();
+        tagged_pub_key.set_tag(Some("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0"));
+
+        assert_eq!(pub_key, tagged_pub_key);
+        assert_eq!(pub_key.to_canonical_json().unwrap(), tagged_pub_key.to_canonical_json().unwrap());
+
+        let key_correctness_proof = issuer::mocks::credential_key_correctness_proof();
+        assert_eq!(helpers::get_credential_offer_digest(&pub_key, &key_correctness_proof).unwrap(),
+                   helpers::get_credential_offer_digest(&tagged_pub_key, &key_correctness_proof).unwrap());
+    }
+
+    #[test]
+    fn credential_signature_serialization_version_works() {
+        let signature = prover::mocks::credential();
+
+        let mut legacy_json = serde_json::to_value(&signature).unwrap();
+        legacy_json.as_object_mut().unwrap().remove("ver");
+        let legacy = serde_json::from_value::<CredentialSignature>(legacy_json).unwrap();
+        assert_eq!(SerializationVersion::LEGACY, legacy.ver);
+        assert_eq!(signature.p_credential, legacy.p_credential);
+    }
+
+    #[test]
+    fn credential_private_key_serialization_has_fixed_length() {
+        let priv_key = issuer::mocks::credential_private_key();
+        let small_priv_key = CredentialPrivateKey {
+            p_key: CredentialPrimaryPrivateKey {
+                p: BigNumber::from_u32(11).unwrap(),
+                q: BigNumber::from_u32(23).unwrap()
+            }
+        };
+
+        let priv_key_json = serde_json::to_string(&priv_key).unwrap();
+        let small_priv_key_json = serde_json::to_string(&small_priv_key).unwrap();
+        assert_eq!(priv_key_json.len(), small_priv_key_json.len());
+
+        let deserialized = serde_json::from_str::<CredentialPrivateKey>(&small_priv_key_json).unwrap();
+        assert_eq!(small_priv_key.p_key, deserialized.p_key);
+
+        let legacy_json = json!({"p_key": {"p": "11", "q": "23"}});
+        let legacy = serde_json::from_value::<CredentialPrivateKey>(legacy_json).unwrap();
+        assert_eq!(small_priv_key.p_key, legacy.p_key);
+    }
+
+    #[test]
+    fn master_secret_serialization_has_fixed_length() {
+        let master_secret = MasterSecret { ms: BigNumber::from_u32(1).unwrap() };
+        let master_secret_json = serde_json::to_string(&master_secret).unwrap();
+        assert_eq!(r#"{"ms":"0x"#.len() + 2 * constants::LARGE_MASTER_SECRET / 8 + r#""}"#.len(), master_secret_json.len());
+
+        let deserialized = serde_json::from_str::<MasterSecret>(&master_secret_json).unwrap();
+        assert_eq!(master_secret.ms, deserialized.ms);
+    }
+
+    #[test]
+    fn credential_schema_serialization_works_for_public_attrs() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_public_attr("name").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let credential_schema_json = serde_json::to_string(&credential_schema).unwrap();
+        let restored = serde_json::from_str::<CredentialSchema>(&credential_schema_json).unwrap();
+        assert_eq!(credential_schema.attrs, restored.attrs);
+        assert_eq!(btreeset!["name".to_string()], restored.public_attrs);
+
+        let legacy = serde_json::from_str::<CredentialSchema>(r#"{"attrs":["age","name"]}"#).unwrap();
+        assert!(legacy.public_attrs.is_empty());
+    }
+
+    #[test]
+    fn credential_signature_credential_id_works() {
+        let signature = prover::mocks::credential();
+        let pub_key = issuer::mocks::credential_public_key();
+
+        let credential_id = signature.credential_id(&pub_key).unwrap();
+        assert_eq!(64, credential_id.len());
+
+        let unprefixed_hash = BigNumber::hash_array(&vec![signature.p_credential.a.to_bytes().unwrap(),
+                                                          pub_key.p_key.n.to_bytes().unwrap()]).unwrap();
+        let unprefixed_id: String = unprefixed_hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_ne!(unprefixed_id, credential_id);
+
+        let restored_signature = serde_json::from_str::<CredentialSignature>(&serde_json::to_string(&signature).unwrap()).unwrap();
+        let restored_pub_key = serde_json::from_str::<CredentialPublicKey>(&serde_json::to_string(&pub_key).unwrap()).unwrap();
+        assert_eq!(credential_id, restored_signature.credential_id(&restored_pub_key).unwrap());
+
+        let mut other_signature_json = serde_json::to_value(&signature).unwrap();
+        other_signature_json["p_credential"]["a"] = json!("12345");
+        let other_signature = serde_json::from_value::<CredentialSignature>(other_signature_json).unwrap();
+        assert_ne!(credential_id, other_signature.credential_id(&pub_key).unwrap());
+    }
+
+    #[test]
+    fn credential_values_builder_add_hex_works() {
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_hex_known("age", "1c").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        credential_values_builder.add_hex_hidden("master_secret", "00FF").unwrap();
+        credential_values_builder.add_hex_commitment("name", "0100", "02").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let mut expected_values_builder = CredentialValuesBuilder::new().unwrap();
+        expected_values_builder.add_dec_known("age", "28").unwrap();
+        expected_values_builder.add_hex_known("height", "af").unwrap();
+        expected_values_builder.add_dec_hidden("master_secret", "255").unwrap();
+        expected_values_builder.add_dec_commitment("name", "256", "2").unwrap();
+        let expected_values = expected_values_builder.finalize().unwrap();
+
+        assert_eq!(expected_values.len(), credential_values.len());
+        for attr in expected_values.attrs() {
+            assert_eq!(expected_values.get(attr), credential_values.get(attr));
+        }
+    }
+
+    #[test]
+    fn credential_values_builder_add_hex_works_for_invalid_hex() {
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+
+        for hex in ["", "abc", "0x1c", "zz", "-1c"].iter() {
+            match credential_values_builder.add_hex_known("age", hex) {
+                Err(IndyCryptoError::InvalidStructure(_)) => {}
+                res => panic!("InvalidStructure error expected for {:?}, got {:?}", hex, res)
+            }
+        }
+        assert!(credential_values_builder.add_hex_commitment("name", "0100", "2").is_err());
+    }
+
+    #[test]
+    fn credential_values_builder_add_base58_works() {
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_base58_known("age", "V").unwrap();
+        credential_values_builder.add_base58_hidden("master_secret", "5Q").unwrap();
+        credential_values_builder.add_base58_commitment("name", "5R", "3").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let mut expected_values_builder = CredentialValuesBuilder::new().unwrap();
+        expected_values_builder.add_dec_known("age", "28").unwrap();
+        expected_values_builder.add_dec_hidden("master_secret", "255").unwrap();
+        expected_values_builder.add_dec_commitment("name", "256", "2").unwrap();
+        let expected_values = expected_values_builder.finalize().unwrap();
+
+        assert_eq!(expected_values.len(), credential_values.len());
+        for attr in expected_values.attrs() {
+            assert_eq!(expected_values.get(attr), credential_values.get(attr));
+        }
+    }
+
+    #[test]
+    fn credential_values_builder_add_base58_works_for_invalid_base58() {
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+
+        for base58 in ["", "0U", "OU", "lU", "IU"].iter() {
+            match credential_values_builder.add_base58_known("age", base58) {
+                Err(IndyCryptoError::InvalidStructure(_)) => {}
+                res => panic!("InvalidStructure error expected for {:?}, got {:?}", base58, res)
+            }
+        }
+    }
+
+    #[test]
+    fn credential_values_builder_finalize_works_for_empty_values() {
+        let credential_values_builder = CredentialValuesBuilder::new().unwrap();
+
+        match credential_values_builder.finalize() {
+            Err(IndyCryptoError::InvalidStructure(ref err)) => assert_eq!("Credential values are empty", err),
+            _ => panic!("InvalidStructure error expected")
+        }
+    }
+
+    #[test]
+    fn sub_proof_request_partition_works() {
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("age", "28").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let partition = sub_proof_request.partition(&credential_values);
+
+        assert_eq!(&btreeset!["name".to_string()], partition.revealed());
+        assert_eq!(&btreeset!["age".to_string()], partition.predicated());
+        assert_eq!(&btreeset!["height".to_string(), "sex".to_string()], partition.hidden());
+    }
+
+    #[test]
+    fn credential_values_accessors_work() {
+        let credential_values = issuer::mocks::credential_values();
+
+        assert_eq!(5, credential_values.len());
+        assert!(!credential_values.is_empty());
+        assert_eq!(vec!["age", "height", "master_secret", "name", "sex"],
+                   credential_values.attrs().map(|attr| attr.as_str()).collect::<Vec<&str>>());
+
+        assert_eq!(&CredentialValue::Known { value: BigNumber::from_u32(25).unwrap() }, credential_values.get("age").unwrap());
+        assert!(credential_values.get("master_secret").unwrap().is_hidden());
+        assert!(credential_values.get("weight").is_none());
+    }
+
+    #[test]
+    fn attr_names_works_for_nfc_and_nfd_forms() {
+        let nfc = "caf\u{e9}";
+        let nfd = "cafe\u{301}";
+        assert_ne!(nfc, nfd);
+
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_attr(nfc).unwrap();
+        credential_schema_builder.add_attr(nfd).unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+        assert_eq!(btreeset![nfc.to_string()], credential_schema.attrs);
+
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_dec_known(nfd, "1").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+        assert!(credential_values.attrs_values.contains_key(nfc));
+
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_revealed_attr(nfd).unwrap();
+        sub_proof_request_builder.add_predicate(nfd, "GE", 1).unwrap();
+        let sub_proof_request = sub_proof_request_builder.value;
+        assert!(sub_proof_request.revealed_attrs.contains(nfc));
+        assert!(sub_proof_request.predicates.iter().all(|predicate| predicate.attr_name == nfc));
+    }
+
+    #[test]
+    fn credential_schema_builder_add_attr_checked_works_for_duplicated_attr() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_attr_checked("name").unwrap();
+
+        let res = credential_schema_builder.add_attr_checked("name");
+        match res {
+            Err(IndyCryptoError::InvalidStructure(_)) => {}
+            _ => panic!("InvalidStructure error expected")
+        }
+
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.finalize().unwrap();
+    }
+
+    #[test]
+    fn credential_schema_builder_works_for_attrs_limit() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        for i in 0..constants::MAX_ATTRS_COUNT {
+            credential_schema_builder.add_attr(&format!("attr{}", i)).unwrap();
+        }
+        assert_eq!(constants::MAX_ATTRS_COUNT, credential_schema_builder.finalize().unwrap().attrs.len());
+
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        for i in 0..constants::MAX_ATTRS_COUNT + 1 {
+            credential_schema_builder.add_attr(&format!("attr{}", i)).unwrap();
+        }
+        match credential_schema_builder.finalize() {
+            Err(IndyCryptoError::InvalidStructure(_)) => {}
+            _ => panic!("InvalidStructure error expected")
+        }
+
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap()
+            .with_limits(SizeLimits::new(2, constants::MAX_PREDICATES_COUNT).unwrap()).unwrap();
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        credential_schema_builder.add_attr("sex").unwrap();
+        assert!(credential_schema_builder.finalize().is_err());
+    }
+
+    #[test]
+    fn sub_proof_request_builder_works_for_limits() {
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        for i in 0..constants::MAX_ATTRS_COUNT {
+            sub_proof_request_builder.add_revealed_attr(&format!("attr{}", i)).unwrap();
+        }
+        for value in 0..constants::MAX_PREDICATES_COUNT as i32 {
+            sub_proof_request_builder.add_predicate("age", "GE", value).unwrap();
+        }
+        sub_proof_request_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        for i in 0..constants::MAX_ATTRS_COUNT + 1 {
+            sub_proof_request_builder.add_revealed_attr(&format!("attr{}", i)).unwrap();
+        }
+        assert!(sub_proof_request_builder.finalize().is_err());
+
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        for value in 0..constants::MAX_PREDICATES_COUNT as i32 + 1 {
+            sub_proof_request_builder.add_predicate("age", "GE", value).unwrap();
+        }
+        assert!(sub_proof_request_builder.finalize().is_err());
+
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap()
+            .with_limits(SizeLimits::new(constants::MAX_ATTRS_COUNT, 1).unwrap()).unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        sub_proof_request_builder.add_predicate("height", "LE", 200).unwrap();
+        assert!(sub_proof_request_builder.finalize().is_err());
+
+        assert!(SizeLimits::new(0, 1).is_err());
+    }
+
+    #[test]
+    fn credential_schema_builder_works_for_reserved_attr() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.add_attr("master_secret").unwrap();
+
+        let res = credential_schema_builder.finalize();
+        match res {
+            Err(IndyCryptoError::InvalidStructure(_)) => {}
+            _ => panic!("InvalidStructure error expected")
+        }
+    }
+
+    #[test]
+    fn non_credential_schema_builder_works_for_reserved_attr() {
+        let mut non_credential_schema_builder = NonCredentialSchemaBuilder::new().unwrap();
+        non_credential_schema_builder.add_attr("master_secret").unwrap();
+
+        let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+        assert!(non_credential_schema.attrs.contains("master_secret"));
+    }
+
     #[test]
     fn primary_equal_proof_conversion_works() {
         let string1 = r#"{
@@ -959,6 +3322,7 @@ mod test {
                                             &non_credential_schema,
                                             &cred_signature,
                                             &cred_values,
+                                            issuer::mocks::CREDENTIAL_KEY_ID,
                                             &cred_pub_key).unwrap();
 
         let proof_request_nonce = new_nonce().unwrap();
@@ -968,7 +3332,176 @@ mod test {
         proof_verifier.add_sub_proof_request(&sub_proof_request,
                                              &credential_schema,
                                              &non_credential_schema,
+                                             issuer::mocks::CREDENTIAL_KEY_ID,
                                              &cred_pub_key).unwrap();
         assert!(proof_verifier.verify(&proof, &proof_request_nonce).unwrap());
     }
+
+    #[test]
+    fn credential_primary_public_key_deserialize_works_for_valid_key() {
+        let pub_key = issuer::mocks::credential_primary_public_key();
+        let json = serde_json::to_string(&pub_key).unwrap();
+
+        let deserialized: CredentialPrimaryPublicKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(pub_key, deserialized);
+    }
+
+    #[test]
+    fn credential_primary_public_key_deserialize_works_for_too_small_modulus() {
+        let pub_key = issuer::mocks::credential_primary_public_key();
+        let mut json = serde_json::to_value(&pub_key).unwrap();
+        json["n"] = json!("35");
+
+        let res: Result<CredentialPrimaryPublicKey, _> = serde_json::from_value(json);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn credential_primary_public_key_deserialize_works_for_r_out_of_range() {
+        let pub_key = issuer::mocks::credential_primary_public_key();
+        let mut json = serde_json::to_value(&pub_key).unwrap();
+        let n = json["n"].clone();
+        json["r"]["name"] = n;
+
+        let res: Result<CredentialPrimaryPublicKey, _> = serde_json::from_value(json);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn credential_primary_public_key_validate_works() {
+        issuer::mocks::credential_primary_public_key().validate().unwrap();
+    }
+
+    #[test]
+    fn credential_primary_public_key_validate_works_for_prime_modulus() {
+        // Mersenne prime 2^2203 - 1
+        let n = BigNumber::from_hex(&format!("7{}", "F".repeat(550))).unwrap();
+        let pub_key = CredentialPrimaryPublicKey {
+            n,
+            s: BigNumber::from_u32(4).unwrap(),
+            r: HashMap::new(),
+            z: BigNumber::from_u32(4).unwrap()
+        };
+
+        pub_key.check_structure().unwrap();
+        assert!(match pub_key.validate() { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+
+        let json = serde_json::to_string(&pub_key).unwrap();
+        let deserialized: CredentialPrimaryPublicKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(pub_key, deserialized);
+    }
+
+    #[test]
+    fn credential_primary_public_key_from_compact_bytes_works_for_too_small_modulus() {
+        let mut pub_key = issuer::mocks::credential_primary_public_key();
+        pub_key.n = BigNumber::from_u32(35).unwrap();
+
+        let bytes = pub_key.to_compact_bytes().unwrap();
+
+        let res = CredentialPrimaryPublicKey::from_compact_bytes(&bytes);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn proof_to_transcript_digest_works() {
+        let proof = prover::mocks::proof();
+        let nonce = prover::mocks::proof_request_nonce();
+
+        let digest = proof.to_transcript_digest(&nonce).unwrap();
+
+        let proof_json = serde_json::to_value(&proof).unwrap();
+        let restored_proof: Proof = serde_json::from_value(proof_json.clone()).unwrap();
+        assert_eq!(digest, restored_proof.to_transcript_digest(&nonce).unwrap());
+
+        assert_ne!(digest, proof.to_transcript_digest(&new_nonce().unwrap()).unwrap());
+
+        let mut tampered_proof_json = proof_json.clone();
+        tampered_proof_json["aggregated_proof"]["c_hash"] = json!("1");
+        let tampered_proof: Proof = serde_json::from_value(tampered_proof_json).unwrap();
+        assert_ne!(digest, tampered_proof.to_transcript_digest(&nonce).unwrap());
+
+        let mut tampered_proof_json = proof_json;
+        tampered_proof_json["proofs"][0]["primary_proof"]["eq_proof"]["e"] = json!("1");
+        let tampered_proof: Proof = serde_json::from_value(tampered_proof_json).unwrap();
+        assert_ne!(digest, tampered_proof.to_transcript_digest(&nonce).unwrap());
+    }
+
+    #[test]
+    fn proof_to_canonical_json_is_byte_stable() {
+        let canonical_json = prover::mocks::proof().to_canonical_json().unwrap();
+
+        for _ in 0..10 {
+            assert_eq!(canonical_json, prover::mocks::proof().to_canonical_json().unwrap());
+        }
+
+        let restored: Proof = serde_json::from_str(&canonical_json).unwrap();
+        assert_eq!(prover::mocks::proof(), restored);
+        assert_eq!(canonical_json, restored.to_canonical_json().unwrap());
+    }
+
+    fn _encode_attribute(value: &str) -> BigNumber {
+        let mut hasher = BigNumberHasher::new().unwrap();
+        hasher.update(value.as_bytes()).unwrap();
+        BigNumber::from_bytes(&hasher.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn proof_decode_revealed_attrs_works() {
+        let encoded_name = _encode_attribute("Alex");
+        let encoding_table: HashMap<String, String> = hashmap![encoded_name.to_dec().unwrap() => "Alex".to_string()];
+
+        let mut proof = prover::mocks::proof();
+        proof.proofs[0].primary_proof.eq_proof.revealed_attrs = btreemap![
+            "name".to_string() => encoded_name,
+            "age".to_string() => BigNumber::from_dec("28").unwrap()
+        ];
+
+        let revealed_attrs = proof.decode_revealed_attrs(|value| {
+            value.to_dec().ok().and_then(|value| encoding_table.get(&value).cloned())
+        }).unwrap();
+
+        assert_eq!(vec![btreemap!["name".to_string() => "Alex".to_string(), "age".to_string() => "28".to_string()]],
+                   revealed_attrs);
+    }
+
+    #[test]
+    fn proof_constrained_attrs_works() {
+        let proof = prover::mocks::proof();
+        let sub_proof_request = prover::mocks::sub_proof_request();
+
+        let (revealed_attrs, predicates) = proof.constrained_attrs().unwrap();
+
+        assert_eq!(sub_proof_request.revealed_attrs(), &revealed_attrs);
+        assert_eq!(sub_proof_request.predicates(), &predicates);
+    }
+
+    #[test]
+    fn proof_predicates_works() {
+        let proof = prover::mocks::proof();
+
+        let expected_predicate = Predicate { attr_name: "age".to_string(), p_type: PredicateType::GE, value: 18 };
+        assert_eq!(vec![vec![&expected_predicate]], proof.predicates());
+        assert_eq!(&expected_predicate, proof.proofs[0].primary_proof.ne_proofs[0].predicate());
+    }
+
+    #[test]
+    fn credential_public_key_to_canonical_json_is_byte_stable() {
+        let canonical_json = issuer::mocks::credential_public_key().to_canonical_json().unwrap();
+
+        for _ in 0..10 {
+            assert_eq!(canonical_json, issuer::mocks::credential_public_key().to_canonical_json().unwrap());
+        }
+
+        let restored: CredentialPublicKey = serde_json::from_str(&canonical_json).unwrap();
+        assert_eq!(issuer::mocks::credential_public_key(), restored);
+        assert_eq!(canonical_json, restored.to_canonical_json().unwrap());
+
+        let r_start = canonical_json.find("\"r\":{").unwrap() + "\"r\":{".len();
+        let r_keys: Vec<&str> = canonical_json[r_start..]
+            .split('}').next().unwrap()
+            .split(',').map(|entry| entry.split('"').nth(1).unwrap()).collect();
+        let mut sorted_r_keys = r_keys.clone();
+        sorted_r_keys.sort();
+        assert_eq!(sorted_r_keys, r_keys);
+    }
 }
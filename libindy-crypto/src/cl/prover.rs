@@ -1,15 +1,28 @@
-use bn::BigNumber;
+use bn::{BigNumber, BigNumberContext, BnCtxPool};
 use cl::*;
 use cl::constants::*;
 use errors::IndyCryptoError;
 use super::helpers::*;
-use cl::commitment::get_pedersen_commitment;
-use cl::hash::get_hash_as_int;
+use cl::commitment::{get_pedersen_commitment, get_pedersen_commitment_consttime};
+use cl::hash::{get_hash_as_int, HashBuilder};
 
 use std::collections::{HashSet, BTreeMap, BTreeSet};
 
 use std::iter::FromIterator;
 
+#[cfg(any(feature = "blinding_factors_reuse_check", feature = "proof_builder_snapshot"))]
+use std::sync::Mutex;
+
+// Max number of blinding factors fingerprints kept; the oldest ones are forgotten first
+#[cfg(feature = "blinding_factors_reuse_check")]
+const MAX_USED_BLINDING_FACTORS: usize = 4096;
+
+#[cfg(feature = "blinding_factors_reuse_check")]
+lazy_static! {
+    // Fingerprints of the last MAX_USED_BLINDING_FACTORS blinding factors successfully used in this process
+    static ref USED_BLINDING_FACTORS: Mutex<BoundedSet> = Mutex::new(BoundedSet::new(MAX_USED_BLINDING_FACTORS));
+}
+
 /// Credentials owner that can proof and partially disclose the credentials to verifier.
 pub struct Prover {}
 
@@ -31,7 +44,7 @@ impl Prover {
     /// # Arguments
     /// * `credential_pub_key` - Credential public keys.
     /// * `credential_key_correctness_proof` - Credential key correctness proof.
-    /// * `credential_values` - Credential values.
+    /// * `credential_values` - Credential values. Must contain value of every attribute of the public key.
     /// * `credential_nonce` - Nonce used for creation of blinded_credential_secrets_correctness_proof.
     ///
     /// # Example
@@ -55,6 +68,7 @@ impl Prover {
     ///
     /// let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
     /// credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+    /// credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
     /// let cred_values = credential_values_builder.finalize().unwrap();
     ///
     /// let (_blinded_credential_secrets, _credential_secrets_blinding_factors, _blinded_credential_secrets_correctness_proof) =
@@ -78,7 +92,10 @@ impl Prover {
                credential_values,
                credential_nonce
         );
+        check_nonce(credential_nonce)?;
+
         Prover::_check_credential_key_correctness_proof(&credential_pub_key.p_key, credential_key_correctness_proof)?;
+        Prover::_check_credential_values_complete(&credential_pub_key.p_key, credential_values)?;
 
         let blinded_primary_credential_secrets =
             Prover::_generate_blinded_primary_credential_secrets_factors(&credential_pub_key.p_key, &credential_values)?;
@@ -114,6 +131,30 @@ impl Prover {
         ))
     }
 
+    /// Creates blinded credential secrets as `blind_credential_secrets` does, but first checks
+    /// that every attribute of non credential schema (for example `master_secret`) has
+    /// `Hidden` or `Commitment` value, so it is never sent to issuer in clear or left out.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public keys.
+    /// * `credential_key_correctness_proof` - Credential key correctness proof.
+    /// * `non_credential_schema` - Non credential schema the credential definition was created for.
+    /// * `credential_values` - Credential values.
+    /// * `credential_nonce` - Nonce used for creation of blinded_credential_secrets_correctness_proof.
+    pub fn blind_credential_secrets_for_schema(credential_pub_key: &CredentialPublicKey,
+                                               credential_key_correctness_proof: &CredentialKeyCorrectnessProof,
+                                               non_credential_schema: &NonCredentialSchema,
+                                               credential_values: &CredentialValues,
+                                               credential_nonce: &Nonce) -> Result<(BlindedCredentialSecrets,
+                                                                                    CredentialSecretsBlindingFactors,
+                                                                                    BlindedCredentialSecretsCorrectnessProof), IndyCryptoError> {
+        trace!("Prover::blind_credential_secrets_for_schema: >>> non_credential_schema: {:?}", non_credential_schema);
+
+        Prover::_check_non_credential_values(non_credential_schema, credential_values)?;
+
+        Prover::blind_credential_secrets(credential_pub_key, credential_key_correctness_proof, credential_values, credential_nonce)
+    }
+
     /// Updates the credential signature by a master secret blinding data.
     ///
     /// # Arguments
@@ -190,19 +231,141 @@ impl Prover {
                nonce
         );
 
+        check_nonce(nonce)?;
+
+        #[cfg(feature = "blinding_factors_reuse_check")]
+        Prover::_check_blinding_factors_reuse(credential_secrets_blinding_factors)?;
+
+        Prover::validate_signature_e(credential_signature)?;
+
         Prover::_process_primary_credential(&mut credential_signature.p_credential, &credential_secrets_blinding_factors.v_prime)?;
 
+        let mut ctx = BigNumber::new_context()?;
+
+        let q = Prover::_calc_signature_q(&credential_signature.p_credential, credential_values, &credential_pub_key.p_key, &mut ctx)?;
+
+        Prover::_check_blinding_factors_correspondence(&credential_signature.p_credential,
+                                                       &credential_secrets_blinding_factors.v_prime,
+                                                       &q,
+                                                       &credential_pub_key.p_key,
+                                                       &mut ctx)?;
+
         Prover::_check_signature_correctness_proof(&credential_signature.p_credential,
-                                                   credential_values,
+                                                   &q,
                                                    signature_correctness_proof,
                                                    &credential_pub_key.p_key,
-                                                   nonce)?;
+                                                   nonce,
+                                                   &mut ctx)?;
+
+        #[cfg(feature = "blinding_factors_reuse_check")]
+        Prover::_record_blinding_factors_usage(credential_secrets_blinding_factors)?;
 
         trace!("Prover::process_credential_signature: <<<");
 
         Ok(())
     }
 
+    /// Checks that credential signature `e` is a prime from `[2^596, 2^596 + 2^119]` range.
+    /// Signature with `e` out of this range or composite `e` doesn't provide security guarantees of CL signatures.
+    ///
+    /// # Arguments
+    /// * `credential_signature` - Credential signature received from issuer.
+    pub fn validate_signature_e(credential_signature: &CredentialSignature) -> Result<(), IndyCryptoError> {
+        trace!("Prover::validate_signature_e: >>> credential_signature: {:?}", credential_signature);
+
+        let e = &credential_signature.p_credential.e;
+
+        if *e < *LARGE_E_START_VALUE || *e > *LARGE_E_END_RANGE_VALUE {
+            return Err(IndyCryptoError::AnoncredsCredentialSignatureInvalid(format!("Credential signature e is out of range")));
+        }
+
+        if !e.is_prime(None)? {
+            return Err(IndyCryptoError::AnoncredsCredentialSignatureInvalid(format!("Credential signature e is not prime")));
+        }
+
+        trace!("Prover::validate_signature_e: <<<");
+
+        Ok(())
+    }
+
+    /// Verifies that processed credential signature is a valid issuer signature of credential values
+    /// under the credential public key: `A^e * S^v * product(R_i^m_i) == Z (mod n)` and `e` is prime.
+    /// Unlike `process_credential_signature` it doesn't require signature correctness proof or issuance nonce,
+    /// so it can be used to check stored credential independently of any presentation.
+    ///
+    /// # Arguments
+    /// * `credential_signature` - Credential signature processed by `process_credential_signature`.
+    /// * `credential_values` - Credential values the signature was issued for.
+    /// * `credential_pub_key` - Credential public key the signature was issued with.
+    pub fn verify_credential(credential_signature: &CredentialSignature,
+                             credential_values: &CredentialValues,
+                             credential_pub_key: &CredentialPublicKey) -> Result<bool, IndyCryptoError> {
+        trace!("Prover::verify_credential: >>> credential_signature: {:?}, credential_values: {:?}, credential_pub_key: {:?}",
+               credential_signature, credential_values, credential_pub_key);
+
+        let p_cred_sig = &credential_signature.p_credential;
+        let p_pub_key = &credential_pub_key.p_key;
+
+        let mut ctx = BigNumber::new_context()?;
+
+        let q = Prover::_calc_signature_q(p_cred_sig, credential_values, p_pub_key, &mut ctx)?;
+        let a_e = p_cred_sig.a.mod_exp(&p_cred_sig.e, &p_pub_key.n, Some(&mut ctx))?;
+
+        let valid = q == a_e && p_cred_sig.e.is_prime(Some(&mut ctx))?;
+
+        trace!("Prover::verify_credential: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Re-randomizes processed credential signature: `A' = A * S^(-r)`, `v' = v + e * r` for random `r`.
+    /// Signature stays valid under the same key, because `A'^e * S^v' = A^e * S^v`.
+    ///
+    /// Note that signature `a` value changes, so `CredentialSignature::credential_id` changes too.
+    ///
+    /// # Arguments
+    /// * `credential_signature` - Credential signature processed by `process_credential_signature`.
+    /// * `credential_pub_key` - Credential public key the signature was issued with.
+    pub fn refresh_signature_randomization(credential_signature: &mut CredentialSignature,
+                                           credential_pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> {
+        trace!("Prover::refresh_signature_randomization: >>> credential_signature: {:?}, credential_pub_key: {:?}",
+               credential_signature, credential_pub_key);
+
+        let p_pub_key = &credential_pub_key.p_key;
+        let p_cred = &mut credential_signature.p_credential;
+
+        let mut ctx = BnCtxPool::take()?;
+
+        let r = bn_rand(LARGE_VPRIME)?;
+
+        p_cred.a = p_pub_key.s
+            .inverse(&p_pub_key.n, Some(&mut ctx))?
+            .mod_exp_consttime(&r, &p_pub_key.n, Some(&mut ctx))?
+            .mod_mul(&p_cred.a, &p_pub_key.n, Some(&mut ctx))?;
+
+        p_cred.v = p_cred.e
+            .mul(&r, Some(&mut ctx))?
+            .add(&p_cred.v)?;
+
+        BnCtxPool::release(ctx);
+
+        trace!("Prover::refresh_signature_randomization: <<<");
+
+        Ok(())
+    }
+
+    /// Creates random salt for revealed commitment to hidden attribute (see `ProofBuilder::add_revealed_commitment`).
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::prover::Prover;
+    ///
+    /// let _salt = Prover::new_commitment_salt().unwrap();
+    /// ```
+    pub fn new_commitment_salt() -> Result<BigNumber, IndyCryptoError> {
+        bn_rand(LARGE_COMMITMENT_SALT)
+    }
+
     /// Creates and returns proof builder.
     ///
     /// The purpose of proof builder is building of proof entity according to the given request .
@@ -214,12 +377,154 @@ impl Prover {
     pub fn new_proof_builder() -> Result<ProofBuilder, IndyCryptoError> {
         Ok(ProofBuilder {
             common_attributes: HashMap::new(),
+            attribute_equalities: Vec::new(),
+            set_memberships: Vec::new(),
+            revealed_commitments: Vec::new(),
+            predicate_security: PredicateSecurityLevel::DEFAULT,
             init_proofs: Vec::new(),
             c_list: Vec::new(),
             tau_list: Vec::new()
         })
     }
 
+    /// Checks whether credential can satisfy sub proof request without building of proof.
+    ///
+    /// Credential satisfies the request if it contains all requested attributes
+    /// and its values satisfy all requested predicates.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes and predicates.
+    /// * `credential_schema` - Credential schema.
+    /// * `credential_values` - Credential values.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    /// use indy_crypto::cl::prover::Prover;
+    /// use indy_crypto::cl::verifier::Verifier;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("age").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+    /// credential_values_builder.add_dec_known("age", "25").unwrap();
+    /// let credential_values = credential_values_builder.finalize().unwrap();
+    ///
+    /// let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+    /// sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+    /// let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+    ///
+    /// assert!(Prover::can_satisfy(&sub_proof_request, &credential_schema, &credential_values).unwrap());
+    /// ```
+    pub fn can_satisfy(sub_proof_request: &SubProofRequest,
+                       credential_schema: &CredentialSchema,
+                       credential_values: &CredentialValues) -> Result<bool, IndyCryptoError> {
+        trace!("Prover::can_satisfy: >>> sub_proof_request: {:?}, credential_schema: {:?}, credential_values: {:?}",
+               sub_proof_request, credential_schema, secret!(credential_values));
+
+        let has_revealed_attrs = sub_proof_request.revealed_attrs
+            .iter()
+            .all(|attr| credential_schema.attrs.contains(attr) && credential_values.attrs_values.contains_key(attr));
+
+        let mut res = has_revealed_attrs;
+
+        if res {
+            for predicate in sub_proof_request.predicates.iter() {
+                if !credential_schema.attrs.contains(&predicate.attr_name) {
+                    res = false;
+                    break;
+                }
+
+                let satisfied = match credential_values.attrs_values.get(&predicate.attr_name) {
+                    Some(value) => predicate.is_satisfied_by(value.value())?,
+                    None => false
+                };
+
+                if !satisfied {
+                    res = false;
+                    break;
+                }
+            }
+        }
+
+        trace!("Prover::can_satisfy: <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    /// Returns predicates of sub proof request that credential values don't satisfy.
+    ///
+    /// Predicate is unsatisfied if credential doesn't contain its attribute or attribute value doesn't satisfy it.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes and predicates.
+    /// * `credential_values` - Credential values.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    /// use indy_crypto::cl::prover::Prover;
+    /// use indy_crypto::cl::verifier::Verifier;
+    ///
+    /// let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+    /// credential_values_builder.add_dec_known("age", "15").unwrap();
+    /// let credential_values = credential_values_builder.finalize().unwrap();
+    ///
+    /// let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+    /// sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+    /// let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+    ///
+    /// let unsatisfied_predicates = Prover::unsatisfied_predicates(&sub_proof_request, &credential_values).unwrap();
+    /// assert_eq!(1, unsatisfied_predicates.len());
+    /// ```
+    pub fn unsatisfied_predicates(sub_proof_request: &SubProofRequest,
+                                  credential_values: &CredentialValues) -> Result<Vec<Predicate>, IndyCryptoError> {
+        trace!("Prover::unsatisfied_predicates: >>> sub_proof_request: {:?}, credential_values: {:?}",
+               sub_proof_request, secret!(credential_values));
+
+        let mut unsatisfied_predicates: Vec<Predicate> = Vec::new();
+
+        for predicate in sub_proof_request.predicates.iter() {
+            let satisfied = match credential_values.attrs_values.get(&predicate.attr_name) {
+                Some(value) => predicate.is_satisfied_by(value.value())?,
+                None => false
+            };
+
+            if !satisfied {
+                unsatisfied_predicates.push(predicate.clone());
+            }
+        }
+
+        trace!("Prover::unsatisfied_predicates: <<< unsatisfied_predicates: {:?}", unsatisfied_predicates);
+
+        Ok(unsatisfied_predicates)
+    }
+
+    /// Verifies received credential offer against digest published by issuer (see `Issuer::credential_offer_digest`)
+    /// and checks credential key correctness proof.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key.
+    /// * `credential_key_correctness_proof` - Credential key correctness proof.
+    /// * `expected_digest` - Known digest of credential offer.
+    pub fn verify_credential_offer(credential_pub_key: &CredentialPublicKey,
+                                   credential_key_correctness_proof: &CredentialKeyCorrectnessProof,
+                                   expected_digest: &[u8; 32]) -> Result<(), IndyCryptoError> {
+        trace!("Prover::verify_credential_offer: >>> credential_pub_key: {:?}, credential_key_correctness_proof: {:?}, expected_digest: {:?}",
+               credential_pub_key, credential_key_correctness_proof, expected_digest);
+
+        if get_credential_offer_digest(credential_pub_key, credential_key_correctness_proof)? != *expected_digest {
+            return Err(IndyCryptoError::InvalidStructure("Credential offer doesn't correspond to expected digest".to_string()));
+        }
+
+        Prover::_check_credential_key_correctness_proof(&credential_pub_key.p_key, credential_key_correctness_proof)?;
+
+        trace!("Prover::verify_credential_offer: <<<");
+
+        Ok(())
+    }
+
     #[cfg(test)]
     pub fn check_credential_key_correctness_proof(pr_pub_key: &CredentialPrimaryPublicKey,
                                                   key_correctness_proof: &CredentialKeyCorrectnessProof) -> Result<(), IndyCryptoError> {
@@ -304,6 +609,32 @@ impl Prover {
         Ok(())
     }
 
+    /// 检查非凭证模式的每个属性（如master_secret）都以Hidden或Commitment值给出
+    fn _check_non_credential_values(non_credential_schema: &NonCredentialSchema,
+                                    credential_values: &CredentialValues) -> Result<(), IndyCryptoError> {
+        for attr in non_credential_schema.attrs.iter() {
+            match credential_values.attrs_values.get(attr) {
+                Some(value) if value.is_hidden() || value.is_commitment() => {}
+                Some(_) => return Err(IndyCryptoError::InvalidStructure(
+                    format!("Non credential schema attribute '{}' must be hidden or committed, but known value is given", attr))),
+                None => return Err(IndyCryptoError::InvalidStructure(
+                    format!("Hidden attribute '{}' of non credential schema is missing in credential values", attr)))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 检查公钥中的每个属性都在CredentialValues中给出值，避免遗漏隐藏属性（如master_secret）
+    fn _check_credential_values_complete(p_pub_key: &CredentialPrimaryPublicKey,
+                                         credential_values: &CredentialValues) -> Result<(), IndyCryptoError> {
+        if let Some(attr) = p_pub_key.r.keys().find(|attr| !credential_values.attrs_values.contains_key(*attr)) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Value by key '{}' of public key not found in credential values", attr)));
+        }
+
+        Ok(())
+    }
+
     /// 生成盲化Primary凭证的Factor
     /// 输入：
     ///     CredentialPrimaryPublicKey
@@ -330,7 +661,7 @@ impl Prover {
             .collect::<BTreeSet<String>>();
         // 公式 2.1
         let u = hidden_attributes.iter().fold(
-            p_pub_key.s.mod_exp(
+            p_pub_key.s.mod_exp_consttime(
                 &v_prime,
                 &p_pub_key.n,
                 Some(&mut ctx),
@@ -343,7 +674,7 @@ impl Prover {
                 )?;
                 let cred_value = &credential_values.attrs_values[attr];
                 acc?.mod_mul(
-                    &pk_r.mod_exp(
+                    &pk_r.mod_exp_consttime(
                         cred_value.value(),
                         &p_pub_key.n,
                         Some(&mut ctx),
@@ -361,7 +692,7 @@ impl Prover {
             if let &CredentialValue::Commitment { ref value, ref blinding_factor } = cv {
                 committed_attributes.insert(
                     attr.clone(),
-                    get_pedersen_commitment(
+                    get_pedersen_commitment_consttime(
                         &p_pub_key.s,
                         blinding_factor,
                         &p_pub_key.z,
@@ -420,7 +751,7 @@ impl Prover {
 
         let mut values: Vec<u8> = Vec::new();
         // 公式2.4前部分
-        let mut u_tilde = p_pub_key.s.mod_exp(
+        let mut u_tilde = p_pub_key.s.mod_exp_consttime(
             &v_dash_tilde,
             &p_pub_key.n,
             Some(&mut ctx),
@@ -443,7 +774,7 @@ impl Prover {
                 // 公式2.4 后部分
                 CredentialValue::Hidden { .. } => {
                     u_tilde = u_tilde.mod_mul(
-                        &pk_r.mod_exp(&m_tilde, &p_pub_key.n, Some(&mut ctx))?,
+                        &pk_r.mod_exp_consttime(&m_tilde, &p_pub_key.n, Some(&mut ctx))?,
                         &p_pub_key.n,
                         Some(&mut ctx),
                     )?;
@@ -452,7 +783,7 @@ impl Prover {
                 // 公式2.3
                 CredentialValue::Commitment { .. } => {
                     let r_tilde = bn_rand(LARGE_MTILDE)?;
-                    let commitment_tilde = get_pedersen_commitment(
+                    let commitment_tilde = get_pedersen_commitment_consttime(
                         &p_pub_key.z,
                         &m_tilde,
                         &p_pub_key.s,
@@ -533,6 +864,50 @@ impl Prover {
         Ok(blinded_credential_secrets_correctness_proof)
     }
 
+    /// Debug safety net: fails if the same blinding factors were already used in this process.
+    /// It doesn't give any cryptographic guarantee.
+    #[cfg(feature = "blinding_factors_reuse_check")]
+    fn _check_blinding_factors_reuse(credential_secrets_blinding_factors: &CredentialSecretsBlindingFactors) -> Result<(), IndyCryptoError> {
+        trace!("Prover::_check_blinding_factors_reuse: >>> credential_secrets_blinding_factors: {:?}", credential_secrets_blinding_factors);
+
+        let fingerprint = Prover::_blinding_factors_fingerprint(credential_secrets_blinding_factors)?;
+
+        let used_blinding_factors = USED_BLINDING_FACTORS.lock()
+            .map_err(|_| IndyCryptoError::InvalidState("Blinding factors registry is poisoned".to_string()))?;
+
+        if used_blinding_factors.contains(&fingerprint) {
+            return Err(IndyCryptoError::InvalidState("Credential secrets blinding factors are reused".to_string()));
+        }
+
+        trace!("Prover::_check_blinding_factors_reuse: <<<");
+
+        Ok(())
+    }
+
+    /// Remembers blinding factors of successfully processed credential signature.
+    #[cfg(feature = "blinding_factors_reuse_check")]
+    fn _record_blinding_factors_usage(credential_secrets_blinding_factors: &CredentialSecretsBlindingFactors) -> Result<(), IndyCryptoError> {
+        trace!("Prover::_record_blinding_factors_usage: >>> credential_secrets_blinding_factors: {:?}", credential_secrets_blinding_factors);
+
+        let fingerprint = Prover::_blinding_factors_fingerprint(credential_secrets_blinding_factors)?;
+
+        let mut used_blinding_factors = USED_BLINDING_FACTORS.lock()
+            .map_err(|_| IndyCryptoError::InvalidState("Blinding factors registry is poisoned".to_string()))?;
+
+        if !used_blinding_factors.insert(fingerprint) {
+            return Err(IndyCryptoError::InvalidState("Credential secrets blinding factors are reused".to_string()));
+        }
+
+        trace!("Prover::_record_blinding_factors_usage: <<<");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "blinding_factors_reuse_check")]
+    fn _blinding_factors_fingerprint(credential_secrets_blinding_factors: &CredentialSecretsBlindingFactors) -> Result<Vec<u8>, IndyCryptoError> {
+        BigNumber::hash(&credential_secrets_blinding_factors.v_prime.to_bytes()?)
+    }
+
     /// 对凭证进行一个偏移： v = v'+v''
     fn _process_primary_credential(p_cred: &mut PrimaryCredentialSignature,
                                    v_prime: &BigNumber) -> Result<(), IndyCryptoError> {
@@ -545,11 +920,42 @@ impl Prover {
         Ok(())
     }
 
+    /// 检查处理后的签名与盲化因子对应（论文步骤2.4.2）
+    /// 处理后v = v' + v''，其中v''是签发者选择的LARGE_VPRIME_PRIME位数（最高位为1），
+    /// 所以v - v'必须在[2^(LARGE_VPRIME_PRIME-1), 2^LARGE_VPRIME_PRIME)范围内，并且只有使用盲化时的v'才满足A^e = q = Z / (S^v * R^m)
+    /// 盲化因子混用不会生成无效凭证，而是直接报错：v''超出范围报InvalidStructure，q != q'报AnoncredsCredentialSignatureInvalid
+    fn _check_blinding_factors_correspondence(p_cred_sig: &PrimaryCredentialSignature,
+                                              v_prime: &BigNumber,
+                                              q: &BigNumber,
+                                              p_pub_key: &CredentialPrimaryPublicKey,
+                                              ctx: &mut BigNumberContext) -> Result<(), IndyCryptoError> {
+        trace!("Prover::_check_blinding_factors_correspondence: >>> p_cred_sig: {:?}, v_prime: {:?}, q: {:?}, p_pub_key: {:?}",
+               p_cred_sig, secret!(v_prime), q, p_pub_key);
+
+        let v_prime_prime = p_cred_sig.v.sub(v_prime)?;
+
+        if v_prime_prime < *LARGE_VPRIME_PRIME_VALUE || v_prime_prime.num_bits()? as usize > LARGE_VPRIME_PRIME {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Credential secrets blinding factors don't correspond to credential signature: v'' is out of range")));
+        }
+
+        let expected_q = p_cred_sig.a.mod_exp(&p_cred_sig.e, &p_pub_key.n, Some(ctx))?;
+
+        if *q != expected_q {
+            return Err(IndyCryptoError::AnoncredsCredentialSignatureInvalid(
+                format!("Credential secrets blinding factors don't correspond to credential signature: q != q'")));
+        }
+
+        trace!("Prover::_check_blinding_factors_correspondence: <<<");
+
+        Ok(())
+    }
+
     /// 验证签名的正确性
     /// 
     /// 输入：
     ///     PrimaryCredentialSignature
-    ///     CredentialValues
+    ///     q（由处理后的签名和CredentialValues计算）
     ///     SignatureCorrectnessProof
     ///     CredentialPrimaryPublicKey
     ///     n1
@@ -557,31 +963,58 @@ impl Prover {
     /// 输出：
     ///     若错误则报错
     /// 
-    /// 论文步骤2.4.1-2.4.3
+    /// 论文步骤2.4.3（步骤2.4.1在validate_signature_e中，步骤2.4.2在_check_blinding_factors_correspondence中）
     fn _check_signature_correctness_proof(p_cred_sig: &PrimaryCredentialSignature,
-                                          cred_values: &CredentialValues,
+                                          q: &BigNumber,
                                           signature_correctness_proof: &SignatureCorrectnessProof,
                                           p_pub_key: &CredentialPrimaryPublicKey,
-                                          nonce: &Nonce) -> Result<(), IndyCryptoError> {
+                                          nonce: &Nonce,
+                                          ctx: &mut BigNumberContext) -> Result<(), IndyCryptoError> {
         trace!("Prover::_check_signature_correctness_proof: >>> p_cred_sig: {:?}, \
-                                                                cred_values: {:?}, \
+                                                                q: {:?}, \
                                                                 signature_correctness_proof: {:?}, \
                                                                 p_pub_key: {:?}, \
                                                                 nonce: {:?}",
                p_cred_sig,
-               cred_values,
+               q,
                signature_correctness_proof,
                p_pub_key,
                nonce
         );
 
+        // 公式2.22
+        let degree = signature_correctness_proof.c.add(
+            &signature_correctness_proof.se.mul(&p_cred_sig.e, Some(ctx))?
+        )?;
 
-        let mut ctx = BigNumber::new_context()?;
+        let a_cap = p_cred_sig.a.mod_exp(&degree, &p_pub_key.n, Some(ctx))?;
+
+        let mut values: Vec<u8> = Vec::new();
+        values.extend_from_slice(&q.to_bytes()?);
+        values.extend_from_slice(&p_cred_sig.a.to_bytes()?);
+        values.extend_from_slice(&a_cap.to_bytes()?);
+        values.extend_from_slice(&nonce.to_bytes()?);
 
-        if !p_cred_sig.e.is_prime(Some(&mut ctx))? {
-            return Err(IndyCryptoError::InvalidStructure(format!("Invalid Signature correctness proof")));
+        // 步骤2.4.3
+        let c = get_hash_as_int(&vec![values])?;
+
+        let valid = signature_correctness_proof.c.eq(&c);
+
+        if !valid {
+            return Err(IndyCryptoError::AnoncredsCredentialSignatureInvalid(format!("Invalid Signature correctness proof c != c'")));
         }
 
+        trace!("Prover::_check_signature_correctness_proof: <<<");
+
+        Ok(())
+    }
+
+    /// Calculates `Q = Z / (S^v * product(R_i^m_i)) (mod n)` for credential signature,
+    /// so signature is valid if `Q == A^e (mod n)`.
+    fn _calc_signature_q(p_cred_sig: &PrimaryCredentialSignature,
+                         cred_values: &CredentialValues,
+                         p_pub_key: &CredentialPrimaryPublicKey,
+                         ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError> {
         // 验证数组完整性
         if let Some((ref attr, _)) = cred_values.attrs_values
             .iter()
@@ -599,69 +1032,178 @@ impl Prover {
                 (value.is_known() || value.is_hidden()) && p_pub_key.r.contains_key(attr.clone())
             })
             .fold(
-                p_pub_key.s.mod_exp(&p_cred_sig.v, &p_pub_key.n, Some(&mut ctx)),
+                p_pub_key.s.mod_exp_consttime(&p_cred_sig.v, &p_pub_key.n, Some(ctx)),
                 |acc, (attr, value)| {
                     acc?.mod_mul(
-                        &p_pub_key.r[&attr.clone()].mod_exp(
+                        &p_pub_key.r[&attr.clone()].mod_exp_consttime(
                             value.value(),
                             &p_pub_key.n,
-                            Some(&mut ctx),
+                            Some(ctx),
                         )?,
                         &p_pub_key.n,
-                        Some(&mut ctx),
+                        Some(ctx),
                     )
                 },
             )?;
+
         // 公式2.21
-        let q = p_pub_key.z.mod_div(&rx, &p_pub_key.n, Some(&mut ctx))?;
-        
-        // 步骤2.4.2
-        let expected_q = p_cred_sig.a.mod_exp(&p_cred_sig.e, &p_pub_key.n, Some(&mut ctx))?;
+        p_pub_key.z.mod_div(&rx, &p_pub_key.n, Some(ctx))
+    }
+}
 
-        if !q.eq(&expected_q) {
-            return Err(IndyCryptoError::InvalidStructure(format!("Invalid Signature correctness proof q != q'")));
+#[derive(Debug)]
+pub struct ProofBuilder {
+    common_attributes: HashMap<String, BigNumber>,
+    attribute_equalities: Vec<(usize /* cred_index_a */, usize /* cred_index_b */, String /* attr_name */)>,
+    set_memberships: Vec<(String /* attr_name */, Vec<BigNumber> /* allowed_values */)>,
+    revealed_commitments: Vec<(String /* attr_name */, BigNumber /* salt */)>,
+    predicate_security: PredicateSecurityLevel,
+    init_proofs: Vec<InitProof>,
+    c_list: Vec<Vec<u8>>,
+    tau_list: Vec<Vec<u8>>,
+}
+
+// Version of ProofBuilder snapshot layout
+#[cfg(feature = "proof_builder_snapshot")]
+const PROOF_BUILDER_SNAPSHOT_VERSION: u32 = 1;
+
+// Max number of ids of restored snapshots kept; the oldest ones are forgotten first
+#[cfg(feature = "proof_builder_snapshot")]
+const MAX_RESTORED_SNAPSHOTS: usize = 4096;
+
+#[cfg(feature = "proof_builder_snapshot")]
+lazy_static! {
+    // One-time ids of the last MAX_RESTORED_SNAPSHOTS snapshots restored in this process
+    static ref RESTORED_SNAPSHOTS: Mutex<BoundedSet> = Mutex::new(BoundedSet::new(MAX_RESTORED_SNAPSHOTS));
+}
+
+/// Persisted state of proof builder: init proofs and constraints needed to finalize them.
+/// c_list and tau_list aren't persisted, they are recomputed from init proofs on restore.
+#[cfg(feature = "proof_builder_snapshot")]
+#[derive(Serialize, Deserialize)]
+struct ProofBuilderSnapshot {
+    ver: u32,
+    id: String,
+    attribute_equalities: Vec<(usize, usize, String)>,
+    predicate_security: PredicateSecurityLevel,
+    init_proofs: Vec<InitProof>,
+}
+
+impl ProofBuilder {
+    /// Creates m_tildes for attributes that will be the same across all subproofs
+    ///
+    /// Any hidden attribute can be common, so credentials can be linked by `master_secret` or by
+    /// other link secret (for example separate one per context to prevent cross-context linkage).
+    /// Only sub proofs of credentials that contain the attribute are linked by it.
+    /// Verifier must add the same attribute by `ProofVerifier::add_common_attribute` to enforce the linkage.
+    ///
+    /// Common attributes are optional. For credentials issued with empty non credential schema
+    /// (without `master_secret`) this call isn't required: common attribute that isn't present
+    /// in the credential schemas of sub proofs is ignored.
+    pub fn add_common_attribute(&mut self, attr_name: &str) -> Result<(), IndyCryptoError> {
+        self.common_attributes.insert(normalize_attr_name(attr_name), bn_rand(LARGE_MVECT)?);
+        Ok(())
+    }
+
+    /// Selects security level of inequality proofs (larger `u_tilde`, `r_tilde` and `alpha_tilde` for higher levels).
+    /// Verifier rejects proofs built below its `ProofVerifier::set_min_predicate_security` level.
+    ///
+    /// Level is written to the proof and bound to its challenge.
+    ///
+    /// Must be called before adding of sub proof requests.
+    ///
+    /// # Arguments
+    /// * `level` - Predicate security level (1 is the default one).
+    pub fn set_predicate_security(&mut self, level: u32) -> Result<(), IndyCryptoError> {
+        if !self.init_proofs.is_empty() {
+            return Err(IndyCryptoError::InvalidState("Predicate security must be selected before sub proof requests are added".to_string()));
         }
 
-        // 公式2.22
-        let degree = signature_correctness_proof.c.add(
-            &signature_correctness_proof.se.mul(&p_cred_sig.e, Some(&mut ctx))?
-        )?;
+        self.predicate_security = PredicateSecurityLevel::new(level)?;
+        Ok(())
+    }
 
-        let a_cap = p_cred_sig.a.mod_exp(&degree, &p_pub_key.n, Some(&mut ctx))?;
+    /// Constrains hidden attribute to have the same value in two sub proofs
+    /// without revealing it (the same m_tilde is used for the attribute in both sub proofs).
+    /// Verifier must add the same constraint by `ProofVerifier::add_attribute_equality`.
+    ///
+    /// Must be called before adding of the later of two sub proof requests.
+    ///
+    /// # Arguments
+    /// * `cred_index_a` - Index of the first sub proof (in order of `add_sub_proof_request` calls).
+    /// * `cred_index_b` - Index of the second sub proof.
+    /// * `attr_name` - Name of hidden attribute present in both credentials.
+    pub fn add_attribute_equality(&mut self, cred_index_a: usize, cred_index_b: usize, attr_name: &str) -> Result<(), IndyCryptoError> {
+        trace!("ProofBuilder::add_attribute_equality: >>> cred_index_a: {:?}, cred_index_b: {:?}, attr_name: {:?}",
+               cred_index_a, cred_index_b, attr_name);
+
+        if cred_index_a == cred_index_b {
+            return Err(IndyCryptoError::InvalidStructure("Attribute equality requires two different sub proofs".to_string()));
+        }
 
-        let mut values: Vec<u8> = Vec::new();
-        values.extend_from_slice(&q.to_bytes()?);
-        values.extend_from_slice(&p_cred_sig.a.to_bytes()?);
-        values.extend_from_slice(&a_cap.to_bytes()?);
-        values.extend_from_slice(&nonce.to_bytes()?);
+        if ::std::cmp::max(cred_index_a, cred_index_b) < self.init_proofs.len() {
+            return Err(IndyCryptoError::InvalidStructure("Attribute equality must be added before sub proof requests it links".to_string()));
+        }
 
-        // 步骤2.4.3
-        let c = get_hash_as_int(&vec![values])?;
+        self.attribute_equalities.push((cred_index_a, cred_index_b, normalize_attr_name(attr_name)));
 
-        let valid = signature_correctness_proof.c.eq(&c);
+        trace!("ProofBuilder::add_attribute_equality: <<<");
 
-        if !valid {
-            return Err(IndyCryptoError::InvalidStructure(format!("Invalid Signature correctness proof c != c'")));
+        Ok(())
+    }
+
+    /// Proves that hidden attribute is equal to one of public allowed values without revealing which one.
+    /// Verifier must add the same constraint (with the same order of values) by `ProofVerifier::add_set_membership`.
+    ///
+    /// Applies to the sub proof request added next, so must be called right before `add_sub_proof_request`.
+    ///
+    /// # Arguments
+    /// * `attr_name` - Name of hidden attribute of the next sub proof.
+    /// * `allowed_values` - Encoded values the attribute may be equal to.
+    pub fn add_set_membership(&mut self, attr_name: &str, allowed_values: &[BigNumber]) -> Result<(), IndyCryptoError> {
+        trace!("ProofBuilder::add_set_membership: >>> attr_name: {:?}, allowed_values: {:?}", attr_name, allowed_values);
+
+        if allowed_values.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure("Set of allowed values is empty".to_string()));
         }
 
-        trace!("Prover::_check_signature_correctness_proof: <<<");
+        let allowed_values = allowed_values.iter()
+            .map(|value| value.clone())
+            .collect::<Result<Vec<BigNumber>, IndyCryptoError>>()?;
+
+        self.set_memberships.push((normalize_attr_name(attr_name), allowed_values));
+
+        trace!("ProofBuilder::add_set_membership: <<<");
 
         Ok(())
     }
-}
 
-#[derive(Debug)]
-pub struct ProofBuilder {
-    common_attributes: HashMap<String, BigNumber>,
-    init_proofs: Vec<InitProof>,
-    c_list: Vec<Vec<u8>>,
-    tau_list: Vec<Vec<u8>>,
-}
+    /// Reveals salted commitment `Z^m * S^salt` to hidden attribute and proves that it is consistent with the signed attribute.
+    /// Attribute value stays hidden, but verifier that knows expected commitment checks it with `Proof::revealed_commitment`.
+    ///
+    /// Applies to the sub proof request added next, so must be called right before `add_sub_proof_request`.
+    ///
+    /// # Arguments
+    /// * `attr_name` - Name of hidden attribute of the next sub proof.
+    /// * `salt` - Salt of the commitment (see `Prover::new_commitment_salt`).
+    pub fn add_revealed_commitment(&mut self, attr_name: &str, salt: &BigNumber) -> Result<(), IndyCryptoError> {
+        trace!("ProofBuilder::add_revealed_commitment: >>> attr_name: {:?}, salt: {:?}", attr_name, secret!(salt));
+
+        if salt.is_negative() || salt.num_bits()? as usize > LARGE_COMMITMENT_SALT {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Salt of revealed commitment must be non negative number of at most {} bits", LARGE_COMMITMENT_SALT)));
+        }
+
+        let attr_name = normalize_attr_name(attr_name);
+
+        if self.revealed_commitments.iter().any(|&(ref attr, _)| *attr == attr_name) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Revealed commitment of attribute '{}' is already added", attr_name)));
+        }
+
+        self.revealed_commitments.push((attr_name, salt.clone()?));
+
+        trace!("ProofBuilder::add_revealed_commitment: <<<");
 
-impl ProofBuilder {
-    /// Creates m_tildes for attributes that will be the same across all subproofs
-    pub fn add_common_attribute(&mut self, attr_name: &str) -> Result<(), IndyCryptoError> {
-        self.common_attributes.insert(attr_name.to_owned(), bn_rand(LARGE_MVECT)?);
         Ok(())
     }
     /// Adds sub proof request to proof builder which will be used fo building of proof.
@@ -734,6 +1276,7 @@ impl ProofBuilder {
     ///                                     &non_credential_schema,
     ///                                     &credential_signature,
     ///                                     &credential_values,
+    ///                                     "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW:3:CL:1",
     ///                                     &credential_pub_key).unwrap();
     /// ```
     pub fn add_sub_proof_request(&mut self,
@@ -742,18 +1285,21 @@ impl ProofBuilder {
                                  non_credential_schema: &NonCredentialSchema,
                                  credential_signature: &CredentialSignature,
                                  credential_values: &CredentialValues,
+                                 key_id: &str,
                                  credential_pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> {
         trace!("ProofBuilder::add_sub_proof_request: >>> sub_proof_request: {:?}, \
                                                          credential_schema: {:?}, \
                                                          non_credential_schema: {:?}, \
                                                          credential_signature: {:?}, \
                                                          credential_values: {:?}, \
+                                                         key_id: {:?}, \
                                                          credential_pub_key: {:?}",
                sub_proof_request,
                credential_schema,
                non_credential_schema,
                credential_signature,
                credential_values,
+               key_id,
                credential_pub_key);
         ProofBuilder::_check_add_sub_proof_request_params_consistency(
             credential_values,
@@ -761,19 +1307,49 @@ impl ProofBuilder {
             credential_schema,
             non_credential_schema,
         )?;
-
-        let primary_init_proof = ProofBuilder::_init_primary_proof(&self.common_attributes,
-                                                                   &credential_pub_key.p_key,
-                                                                   &credential_signature.p_credential,
-                                                                   credential_values,
-                                                                   credential_schema,
-                                                                   non_credential_schema,
-                                                                   sub_proof_request)?;
+        credential_pub_key.p_key.check_schemas(credential_schema, non_credential_schema)?;
+
+        let common_attributes = self._get_linked_attributes(credential_schema, sub_proof_request)?;
+
+        let mut primary_init_proof = ProofBuilder::_init_primary_proof(&common_attributes,
+                                                                       &credential_pub_key.p_key,
+                                                                       &credential_signature.p_credential,
+                                                                       credential_values,
+                                                                       credential_schema,
+                                                                       non_credential_schema,
+                                                                       sub_proof_request,
+                                                                       self.predicate_security)?;
+
+        // Set membership proofs go to c_list sorted by attribute name, so order of add_set_membership calls doesn't matter
+        self.set_memberships.sort_by(|&(ref a, _), &(ref b, _)| a.cmp(b));
+
+        for &(ref attr_name, ref allowed_values) in self.set_memberships.iter() {
+            let set_membership_proof = ProofBuilder::_init_set_membership_proof(&credential_pub_key.p_key,
+                                                                                &primary_init_proof.eq_proof.m_tilde,
+                                                                                credential_values,
+                                                                                sub_proof_request,
+                                                                                attr_name,
+                                                                                allowed_values)?;
+            primary_init_proof.set_membership_proofs.push(set_membership_proof);
+        }
+        self.set_memberships.clear();
+
+        for &(ref attr_name, ref salt) in self.revealed_commitments.iter() {
+            let revealed_commitment = ProofBuilder::_init_revealed_commitment(&credential_pub_key.p_key,
+                                                                              &primary_init_proof.eq_proof.m_tilde,
+                                                                              credential_values,
+                                                                              sub_proof_request,
+                                                                              attr_name,
+                                                                              salt)?;
+            primary_init_proof.eq_proof.revealed_commitments.insert(attr_name.clone(), revealed_commitment);
+        }
+        self.revealed_commitments.clear();
 
         self.c_list.extend_from_slice(&primary_init_proof.as_c_list()?);
         self.tau_list.extend_from_slice(&primary_init_proof.as_tau_list()?);
 
         let init_proof = InitProof {
+            key_id: key_id.to_string(),
             primary_init_proof,
             credential_values: credential_values.clone()?,
             sub_proof_request: sub_proof_request.clone(),
@@ -853,21 +1429,44 @@ impl ProofBuilder {
     ///                                     &non_credential_schema,
     ///                                     &credential_signature,
     ///                                     &credential_values,
+    ///                                     "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW:3:CL:1",
     ///                                     &credential_pub_key).unwrap();
     ///
     /// let proof_request_nonce = new_nonce().unwrap();
     /// let _proof = proof_builder.finalize(&proof_request_nonce).unwrap();
     /// ```
     pub fn finalize(&self, nonce: &Nonce) -> Result<Proof, IndyCryptoError> {
+        self._finalize(nonce, HashBuilder::new_challenge()?)
+    }
+
+    fn _finalize(&self, nonce: &Nonce, mut hash_builder: HashBuilder) -> Result<Proof, IndyCryptoError> {
         trace!("ProofBuilder::finalize: >>> nonce: {:?}", nonce);
 
-        let mut values: Vec<Vec<u8>> = Vec::new();
-        values.extend_from_slice(&self.tau_list);
-        values.extend_from_slice(&self.c_list);
-        values.push(nonce.to_bytes()?);
+        check_nonce(nonce)?;
+
+        if let Some(&(cred_index_a, cred_index_b, ref attr)) = self.attribute_equalities.iter()
+            .find(|&&(cred_index_a, cred_index_b, _)| ::std::cmp::max(cred_index_a, cred_index_b) >= self.init_proofs.len()) {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Sub proof not found for equality of attribute '{}' in sub proofs {} and {}", attr, cred_index_a, cred_index_b)));
+        }
+
+        if let Some(&(ref attr, _)) = self.set_memberships.first() {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Sub proof not found for set membership of attribute '{}'", attr)));
+        }
+
+        if let Some(&(ref attr, _)) = self.revealed_commitments.first() {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Sub proof not found for revealed commitment of attribute '{}'", attr)));
+        }
+
+        hash_builder.update_all(&self.tau_list)?;
+        hash_builder.update_all(&self.c_list)?;
+        hash_builder.update(&nonce.to_bytes()?)?;
+        self.predicate_security.update_challenge(&mut hash_builder)?;
 
         // In the anoncreds whitepaper, `challenge` is denoted by `c_h`
-        let challenge = get_hash_as_int(&values)?;
+        let challenge = hash_builder.finalize_as_int()?;
 
         let mut proofs: Vec<SubProof> = Vec::new();
 
@@ -881,19 +1480,230 @@ impl ProofBuilder {
                 &init_proof.sub_proof_request,
             )?;
 
-            let proof = SubProof { primary_proof };
+            let proof = SubProof { key_id: init_proof.key_id.clone(), primary_proof };
             proofs.push(proof);
         }
 
         let aggregated_proof = AggregatedProof { c_hash: challenge, c_list: self.c_list.clone() };
 
-        let proof = Proof { proofs, aggregated_proof };
+        let proof = Proof {
+            ver: SerializationVersion::current(),
+            proofs,
+            aggregated_proof,
+            common_m: BTreeMap::new(),
+            predicate_security: self.predicate_security
+        };
 
         trace!("ProofBuilder::finalize: <<< proof: {:?}", proof);
 
         Ok(proof)
     }
 
+    /// Resets accumulated sub proof requests, common attributes and proof constraints
+    /// so the builder can be reused for the next proof. Selected predicate security level is kept.
+    ///
+    /// Secret intermediate values (m_tildes of common attributes, salts of revealed commitments,
+    /// randomness of init proofs and credential values) are overwritten with zeros before they are dropped.
+    pub fn clear(&mut self) {
+        trace!("ProofBuilder::clear: >>>");
+
+        clear_bignum_map(&mut self.common_attributes);
+        for &mut (_, ref mut salt) in self.revealed_commitments.iter_mut() {
+            salt.clear();
+        }
+        for init_proof in self.init_proofs.iter_mut() {
+            init_proof.clear();
+        }
+
+        self.common_attributes.clear();
+        self.attribute_equalities.clear();
+        self.set_memberships.clear();
+        self.revealed_commitments.clear();
+        self.init_proofs.clear();
+        self.c_list.clear();
+        self.tau_list.clear();
+
+        trace!("ProofBuilder::clear: <<<");
+    }
+
+    /// Serializes state of the builder (added sub proof requests with their init proofs) to json,
+    /// so proof building can be resumed by `from_snapshot_json` and finished by `ResumedProofBuilder::finalize` later.
+    /// Builder is consumed and its secret values are cleared, so only one snapshot of the same init proofs exists.
+    ///
+    /// Security: snapshot contains credential values and all random values of init proofs
+    /// (m_tildes, e_tilde, v_tilde, r values of inequality proofs etc.). Anyone who gets the snapshot
+    /// and the resulting proof can compute hidden attributes (including master secret) as `m = (m_hat - m_tilde) / c`,
+    /// the same holds for two proofs finalized from one snapshot with different nonces.
+    /// Snapshot must be stored as securely as master secret, must be restored once and must be deleted after restore.
+    /// Repeated restore is rejected only within the same process (by one-time id of the snapshot).
+    #[cfg(feature = "proof_builder_snapshot")]
+    pub fn to_snapshot_json(mut self) -> Result<String, IndyCryptoError> {
+        trace!("ProofBuilder::to_snapshot_json: >>>");
+
+        if let Some(&(ref attr, _)) = self.set_memberships.first() {
+            return Err(IndyCryptoError::InvalidState(
+                format!("Set membership of attribute '{}' isn't applied to a sub proof, snapshot can't be taken", attr)));
+        }
+
+        if let Some(&(ref attr, _)) = self.revealed_commitments.first() {
+            return Err(IndyCryptoError::InvalidState(
+                format!("Revealed commitment of attribute '{}' isn't applied to a sub proof, snapshot can't be taken", attr)));
+        }
+
+        let snapshot = ProofBuilderSnapshot {
+            ver: PROOF_BUILDER_SNAPSHOT_VERSION,
+            id: bn_rand(LARGE_MASTER_SECRET)?.to_hex()?,
+            attribute_equalities: self.attribute_equalities.clone(),
+            predicate_security: self.predicate_security,
+            init_proofs: ::std::mem::replace(&mut self.init_proofs, Vec::new()),
+        };
+
+        let res = ::serde_json::to_string(&snapshot);
+
+        self.init_proofs = snapshot.init_proofs;
+        self.clear();
+
+        let snapshot_json = res?;
+
+        trace!("ProofBuilder::to_snapshot_json: <<< snapshot_json: {:?}", secret!(&snapshot_json));
+
+        Ok(snapshot_json)
+    }
+
+    /// Restores builder from json created by `to_snapshot_json`.
+    /// Restored builder can only be finalized, once.
+    ///
+    /// Snapshot of other version, inconsistent snapshot or snapshot already restored in this process is rejected.
+    ///
+    /// # Arguments
+    /// * `snapshot_json` - Snapshot of proof builder state.
+    #[cfg(feature = "proof_builder_snapshot")]
+    pub fn from_snapshot_json(snapshot_json: &str) -> Result<ResumedProofBuilder, IndyCryptoError> {
+        trace!("ProofBuilder::from_snapshot_json: >>> snapshot_json: {:?}", secret!(snapshot_json));
+
+        let snapshot: ProofBuilderSnapshot = ::serde_json::from_str(snapshot_json)?;
+
+        let mut proof_builder = ProofBuilder {
+            common_attributes: HashMap::new(),
+            attribute_equalities: snapshot.attribute_equalities,
+            set_memberships: Vec::new(),
+            revealed_commitments: Vec::new(),
+            predicate_security: snapshot.predicate_security,
+            init_proofs: snapshot.init_proofs,
+            c_list: Vec::new(),
+            tau_list: Vec::new(),
+        };
+
+        let res = ProofBuilder::_restore_snapshot(&mut proof_builder, snapshot.ver, &snapshot.id);
+
+        if res.is_err() {
+            proof_builder.clear();
+        }
+        res?;
+
+        trace!("ProofBuilder::from_snapshot_json: <<<");
+
+        Ok(ResumedProofBuilder { proof_builder })
+    }
+
+    /// 检查快照版本与一致性（与add_sub_proof_request相同的检查），由init proof重新计算c_list和tau_list，
+    /// 最后登记快照的一次性id
+    #[cfg(feature = "proof_builder_snapshot")]
+    fn _restore_snapshot(proof_builder: &mut ProofBuilder, ver: u32, id: &str) -> Result<(), IndyCryptoError> {
+        if ver != PROOF_BUILDER_SNAPSHOT_VERSION {
+            return Err(IndyCryptoError::InvalidStructure(format!("Unsupported proof builder snapshot version: {}", ver)));
+        }
+
+        for &(cred_index_a, cred_index_b, ref attr) in proof_builder.attribute_equalities.iter() {
+            if cred_index_a == cred_index_b || ::std::cmp::max(cred_index_a, cred_index_b) >= proof_builder.init_proofs.len() {
+                return Err(IndyCryptoError::InvalidStructure(
+                    format!("Invalid equality of attribute '{}' in sub proofs {} and {}", attr, cred_index_a, cred_index_b)));
+            }
+        }
+
+        for init_proof in proof_builder.init_proofs.iter() {
+            ProofBuilder::_check_add_sub_proof_request_params_consistency(&init_proof.credential_values,
+                                                                          &init_proof.sub_proof_request,
+                                                                          &init_proof.credential_schema,
+                                                                          &init_proof.non_credential_schema)?;
+
+            proof_builder.c_list.extend_from_slice(&init_proof.primary_init_proof.as_c_list()?);
+            proof_builder.tau_list.extend_from_slice(&init_proof.primary_init_proof.as_tau_list()?);
+        }
+
+        let mut restored_snapshots = RESTORED_SNAPSHOTS.lock()
+            .map_err(|_| IndyCryptoError::InvalidState("Restored snapshots registry is poisoned".to_string()))?;
+
+        if !restored_snapshots.insert(id.as_bytes().to_vec()) {
+            return Err(IndyCryptoError::InvalidState("Proof builder snapshot is already restored".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Debug only, NOT for production: returns predicates of added sub proof requests
+    /// with deltas reconstructed from their inequality init proofs, in order of sub proofs.
+    ///
+    /// Helps to diagnose encoding mismatches when proof with predicates is rejected by verifier.
+    /// Deltas leak information about hidden attribute values, see `PrimaryPredicateInequalityInitProof::debug_delta`.
+    #[cfg(feature = "debug_delta")]
+    pub fn debug_deltas(&self) -> Result<Vec<(Predicate, BigNumber)>, IndyCryptoError> {
+        let mut deltas = Vec::new();
+
+        for init_proof in self.init_proofs.iter() {
+            for ne_proof in init_proof.primary_init_proof.ne_proofs.iter() {
+                deltas.push((ne_proof.predicate().clone(), ne_proof.debug_delta()?));
+            }
+        }
+
+        Ok(deltas)
+    }
+
+    /// Debug only, NOT for production: same as `debug_deltas` for linear predicates of added sub proof requests.
+    #[cfg(feature = "debug_delta")]
+    pub fn debug_linear_deltas(&self) -> Result<Vec<(LinearPredicate, BigNumber)>, IndyCryptoError> {
+        let mut deltas = Vec::new();
+
+        for init_proof in self.init_proofs.iter() {
+            for linear_ne_proof in init_proof.primary_init_proof.linear_ne_proofs.iter() {
+                deltas.push((linear_ne_proof.predicate.clone(), linear_ne_proof.ne_proof.debug_delta()?));
+            }
+        }
+
+        Ok(deltas)
+    }
+
+    /// 获取新的子证明所使用的公共属性m_tilde
+    /// 包括add_common_attribute添加的属性以及与之前子证明相等的属性
+    fn _get_linked_attributes(&self,
+                              cred_schema: &CredentialSchema,
+                              sub_proof_request: &SubProofRequest) -> Result<HashMap<String, BigNumber>, IndyCryptoError> {
+        let cur_index = self.init_proofs.len();
+        let mut common_attributes = clone_bignum_map(&self.common_attributes)?;
+
+        for &(cred_index_a, cred_index_b, ref attr) in self.attribute_equalities.iter() {
+            let other_index = match (cred_index_a == cur_index, cred_index_b == cur_index) {
+                (true, _) => cred_index_b,
+                (_, true) => cred_index_a,
+                _ => continue
+            };
+
+            if !cred_schema.attrs.contains(attr) || sub_proof_request.known_attrs().contains(attr) {
+                return Err(IndyCryptoError::InvalidStructure(
+                    format!("Attribute '{}' of equality constraint isn't hidden attribute of sub proof {}", attr, cur_index)));
+            }
+
+            if other_index < cur_index {
+                let m_tilde = self.init_proofs[other_index].primary_init_proof.eq_proof.m_tilde.get(attr)
+                    .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in init_proof.mtilde", attr)))?;
+
+                common_attributes.insert(attr.clone(), m_tilde.clone()?);
+            }
+        }
+
+        Ok(common_attributes)
+    }
+
     /// 检查add_sub_proof_request函数的参数完整性
     /// 主要是检查凭证是否对应，凭证属性是否存在
     fn _check_add_sub_proof_request_params_consistency(
@@ -941,6 +1751,11 @@ impl ProofBuilder {
             return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't contain attribute requested in predicate")));
         }
 
+        if sub_proof_request.linear_predicates.iter()
+            .any(|linear_predicate| linear_predicate.coeffs.keys().any(|attr_name| !cred_attrs.contains(attr_name))) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't contain attribute requested in linear predicate")));
+        }
+
         trace!("ProofBuilder::_check_add_sub_proof_request_params_consistency: <<<");
 
         Ok(())
@@ -952,7 +1767,8 @@ impl ProofBuilder {
                            cred_values: &CredentialValues,
                            cred_schema: &CredentialSchema,
                            non_cred_schema_elems: &NonCredentialSchema,
-                           sub_proof_request: &SubProofRequest) -> Result<PrimaryInitProof, IndyCryptoError> {
+                           sub_proof_request: &SubProofRequest,
+                           predicate_security: PredicateSecurityLevel) -> Result<PrimaryInitProof, IndyCryptoError> {
         trace!("ProofBuilder::_init_primary_proof: >>> common_attributes: {:?}, \
                                                        issuer_pub_key: {:?}, \
                                                        c1: {:?}, \
@@ -971,22 +1787,64 @@ impl ProofBuilder {
                                                     sub_proof_request
         )?;
 
+        // EQ predicates are proved by the equality proof itself, so only check that prover is able to satisfy them
+        for predicate in sub_proof_request.predicates.iter().filter(|predicate| predicate.p_type == PredicateType::EQ) {
+            let attr_value = cred_values.attrs_values.get(&predicate.attr_name)
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in cred_values", predicate.attr_name)))?;
+
+            if !predicate.is_satisfied_by(attr_value.value())? {
+                return Err(IndyCryptoError::AnoncredsPredicateNotSatisfied("Predicate is not satisfied".to_string()));
+            }
+        }
+
+        // 差值只在本次调用中缓存，返回前清除，不在ProofBuilder中保留秘密属性值
+        let mut deltas = BTreeMap::new();
+        let res = ProofBuilder::_init_ne_proofs(issuer_pub_key, &eq_proof.m_tilde, cred_values, sub_proof_request, predicate_security, &mut deltas);
+        ProofBuilder::_clear_deltas(deltas);
+        let (ne_proofs, linear_ne_proofs) = res?;
+
+        let primary_init_proof = PrimaryInitProof { eq_proof, ne_proofs, linear_ne_proofs, set_membership_proofs: Vec::new() };
+
+        trace!("ProofBuilder::_init_primary_proof: <<< primary_init_proof: {:?}", primary_init_proof);
+
+        Ok(primary_init_proof)
+    }
+
+    /// 初始化子证明请求中所有谓词（包括线性组合谓词）的不等式证明
+    fn _init_ne_proofs(issuer_pub_key: &CredentialPrimaryPublicKey,
+                       m_tilde: &HashMap<String, BigNumber>,
+                       cred_values: &CredentialValues,
+                       sub_proof_request: &SubProofRequest,
+                       predicate_security: PredicateSecurityLevel,
+                       deltas: &mut BTreeMap<(Predicate, BigNumber), BigNumber>)
+                       -> Result<(Vec<PrimaryPredicateInequalityInitProof>, Vec<PrimaryLinearPredicateInitProof>), IndyCryptoError> {
         let mut ne_proofs: Vec<PrimaryPredicateInequalityInitProof> = Vec::new();
-        for predicate in sub_proof_request.predicates.iter() {
+        for predicate in sub_proof_request.ne_predicates().iter() {
             let ne_proof = ProofBuilder::_init_ne_proof(
-                &issuer_pub_key,
-                &eq_proof.m_tilde,
+                issuer_pub_key,
+                m_tilde,
                 cred_values,
                 predicate,
+                predicate_security,
+                deltas,
             )?;
             ne_proofs.push(ne_proof);
         }
 
-        let primary_init_proof = PrimaryInitProof { eq_proof, ne_proofs };
-
-        trace!("ProofBuilder::_init_primary_proof: <<< primary_init_proof: {:?}", primary_init_proof);
+        let mut linear_ne_proofs: Vec<PrimaryLinearPredicateInitProof> = Vec::new();
+        for linear_predicate in sub_proof_request.linear_predicates.iter() {
+            let linear_ne_proof = ProofBuilder::_init_linear_ne_proof(
+                issuer_pub_key,
+                m_tilde,
+                cred_values,
+                linear_predicate,
+                predicate_security,
+                deltas,
+            )?;
+            linear_ne_proofs.push(linear_ne_proof);
+        }
 
-        Ok(primary_init_proof)
+        Ok((ne_proofs, linear_ne_proofs))
     }
 
     /// 初始化equal部分的证明
@@ -1017,7 +1875,7 @@ impl ProofBuilder {
                                                   sub_proof_request: {:?}",
                cred_pub_key, c1, cred_schema, non_cred_schema_elems, sub_proof_request);
 
-        let mut ctx = BigNumber::new_context()?;
+        let mut ctx = BnCtxPool::take()?;
 
         let r = bn_rand(LARGE_VPRIME)?;
         let e_tilde = bn_rand(LARGE_ETILDE)?;
@@ -1026,7 +1884,7 @@ impl ProofBuilder {
         let unrevealed_attrs = non_cred_schema_elems.attrs.union(&cred_schema.attrs)
             .cloned()
             .collect::<BTreeSet<String>>()
-            .difference(&sub_proof_request.revealed_attrs)
+            .difference(&sub_proof_request.known_attrs())
             .cloned()
             .collect::<HashSet<String>>();
 
@@ -1035,7 +1893,7 @@ impl ProofBuilder {
 
         // 公式4.18
         let a_prime = cred_pub_key.s
-            .mod_exp(&r, &cred_pub_key.n, Some(&mut ctx))?
+            .mod_exp_consttime(&r, &cred_pub_key.n, Some(&mut ctx))?
             .mod_mul(&c1.a, &cred_pub_key.n, Some(&mut ctx))?;
 
         let e_prime = c1.e.sub(&LARGE_E_START_VALUE)?;
@@ -1043,7 +1901,7 @@ impl ProofBuilder {
         let v_prime = c1.v.sub(&c1.e.mul(&r, Some(&mut ctx))?)?;
 
         // 公式4.19
-        let t = calc_teq(&cred_pub_key, &a_prime, &e_tilde, &v_tilde, &m_tilde, &unrevealed_attrs)?;
+        let t = calc_teq_consttime(&cred_pub_key, &a_prime, &e_tilde, &v_tilde, &m_tilde, &unrevealed_attrs)?;
 
         let primary_equal_init_proof = PrimaryEqualInitProof {
             a_prime,
@@ -1052,9 +1910,12 @@ impl ProofBuilder {
             e_prime,
             v_tilde,
             v_prime,
-            m_tilde
+            m_tilde,
+            revealed_commitments: BTreeMap::new()
         };
 
+        BnCtxPool::release(ctx);
+
         trace!("ProofBuilder::_init_eq_proof: <<< primary_equal_init_proof: {:?}", primary_equal_init_proof);
 
         Ok(primary_equal_init_proof)
@@ -1072,31 +1933,122 @@ impl ProofBuilder {
     ///     PrimaryPredicateInequalityInitProof
     /// 
     /// 对应论文公式4.20-4.27
+    ///
+    /// Inequality proof exists only for non negative delta (four squares decomposition),
+    /// so predicate that doesn't hold (for example LE with attribute value greater than predicate value)
+    /// fails with AnoncredsPredicateNotSatisfied error instead of building invalid proof.
     fn _init_ne_proof(p_pub_key: &CredentialPrimaryPublicKey,
                       m_tilde: &HashMap<String, BigNumber>,
                       cred_values: &CredentialValues,
-                      predicate: &Predicate) -> Result<PrimaryPredicateInequalityInitProof, IndyCryptoError> {
-        trace!("ProofBuilder::_init_ne_proof: >>> p_pub_key: {:?}, m_tilde: {:?}, cred_values: {:?}, predicate: {:?}",
-               p_pub_key, m_tilde, cred_values, predicate);
-
-        let mut ctx = BigNumber::new_context()?;
+                      predicate: &Predicate,
+                      predicate_security: PredicateSecurityLevel,
+                      deltas: &mut BTreeMap<(Predicate, BigNumber), BigNumber>) -> Result<PrimaryPredicateInequalityInitProof, IndyCryptoError> {
+        trace!("ProofBuilder::_init_ne_proof: >>> p_pub_key: {:?}, m_tilde: {:?}, cred_values: {:?}, predicate: {:?}, predicate_security: {:?}",
+               p_pub_key, m_tilde, cred_values, predicate, predicate_security);
 
         let attr_value = cred_values.attrs_values.get(&predicate.attr_name)
             .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in cred_values", predicate.attr_name)))?
-            .value()
-            .to_dec()?
-            .parse::<i32>()
-            .map_err(|_| IndyCryptoError::InvalidStructure(format!("Value by key '{}' has invalid format", predicate.attr_name)))?;
+            .value();
+
+        let mj = m_tilde.get(&predicate.attr_name)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in eq_proof.mtilde", predicate.attr_name)))?;
+
+        let primary_predicate_ne_init_proof =
+            ProofBuilder::_init_ne_proof_for_value(p_pub_key, attr_value, mj, predicate, predicate_security, deltas)?;
+
+        trace!("ProofBuilder::_init_ne_proof: <<< primary_predicate_ne_init_proof: {:?}", primary_predicate_ne_init_proof);
+
+        Ok(primary_predicate_ne_init_proof)
+    }
+
+    /// 初始化线性组合谓词的证明
+    ///
+    /// 线性组合的值和m_tilde分别是属性值和属性m_tilde的加权和，不等式证明与单个属性的谓词相同
+    fn _init_linear_ne_proof(p_pub_key: &CredentialPrimaryPublicKey,
+                             m_tilde: &HashMap<String, BigNumber>,
+                             cred_values: &CredentialValues,
+                             linear_predicate: &LinearPredicate,
+                             predicate_security: PredicateSecurityLevel,
+                             deltas: &mut BTreeMap<(Predicate, BigNumber), BigNumber>) -> Result<PrimaryLinearPredicateInitProof, IndyCryptoError> {
+        trace!("ProofBuilder::_init_linear_ne_proof: >>> p_pub_key: {:?}, m_tilde: {:?}, cred_values: {:?}, linear_predicate: {:?}, predicate_security: {:?}",
+               p_pub_key, m_tilde, cred_values, linear_predicate, predicate_security);
+
+        let value = linear_predicate.combine(|attr_name|
+            cred_values.attrs_values.get(attr_name).map(|attr_value| attr_value.value()))?;
+
+        let mj = linear_predicate.combine(|attr_name| m_tilde.get(attr_name))?;
+
+        let ne_proof = ProofBuilder::_init_ne_proof_for_value(p_pub_key, &value, &mj, &linear_predicate.as_predicate(), predicate_security, deltas)?;
+
+        let primary_linear_predicate_init_proof = PrimaryLinearPredicateInitProof {
+            predicate: linear_predicate.clone(),
+            ne_proof
+        };
+
+        trace!("ProofBuilder::_init_linear_ne_proof: <<< primary_linear_predicate_init_proof: {:?}", primary_linear_predicate_init_proof);
+
+        Ok(primary_linear_predicate_init_proof)
+    }
+
+    /// Returns `Predicate::get_delta` result memoized within one `_init_primary_proof` call,
+    /// so delta isn't recomputed for the same predicate and attribute value.
+    fn _get_delta(deltas: &mut BTreeMap<(Predicate, BigNumber), BigNumber>,
+                  predicate: &Predicate,
+                  attr_value: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        let mut key = (predicate.clone(), attr_value.clone()?);
+
+        if let Some(delta) = deltas.get(&key) {
+            key.1.clear();
+            return delta.clone();
+        }
+
+        match predicate.get_delta(attr_value) {
+            Ok(delta) => {
+                deltas.insert(key, delta.clone()?);
+                Ok(delta)
+            }
+            Err(err) => {
+                key.1.clear();
+                Err(err)
+            }
+        }
+    }
+
+    /// 清除缓存的差值及对应的属性值
+    fn _clear_deltas(deltas: BTreeMap<(Predicate, BigNumber), BigNumber>) {
+        for ((_, mut attr_value), mut delta) in deltas {
+            attr_value.clear();
+            delta.clear();
+        }
+    }
+
+    /// Builds inequality proof for value with given `m_tilde` (`mj`) of the value.
+    fn _init_ne_proof_for_value(p_pub_key: &CredentialPrimaryPublicKey,
+                                attr_value: &BigNumber,
+                                mj: &BigNumber,
+                                predicate: &Predicate,
+                                predicate_security: PredicateSecurityLevel,
+                                deltas: &mut BTreeMap<(Predicate, BigNumber), BigNumber>) -> Result<PrimaryPredicateInequalityInitProof, IndyCryptoError> {
+        let mut ctx = BnCtxPool::take()?;
+
+        if !predicate.is_satisfied_by(attr_value)? {
+            return Err(IndyCryptoError::AnoncredsPredicateNotSatisfied("Predicate is not satisfied".to_string()));
+        }
 
         // 公式4.20
-        let delta = predicate.get_delta(attr_value);
+        let delta = ProofBuilder::_get_delta(deltas, predicate, attr_value)?;
 
-        if delta < 0 {
-            return Err(IndyCryptoError::InvalidStructure("Predicate is not satisfied".to_string()));
+        if delta.is_negative() {
+            return Err(IndyCryptoError::AnoncredsPredicateNotSatisfied("Predicate is not satisfied".to_string()));
         }
 
         // 公式4.22
-        let u = four_squares(delta)?;
+        let u_list = four_square_decomposition(&delta)?;
+
+        let mut u = HashMap::new();
+        for (i, u_i) in u_list.iter().enumerate() {
+            u.insert(i.to_string(), u_i.clone()?);
+        }
 
         let mut r = HashMap::new();
         let mut t = HashMap::new();
@@ -1108,8 +2060,8 @@ impl ProofBuilder {
 
             let cur_r = bn_rand(LARGE_VPRIME)?;
             // 公式4.23
-            let cut_t = get_pedersen_commitment(&p_pub_key.z, &cur_u, &p_pub_key.s,
-                                                &cur_r, &p_pub_key.n, &mut ctx)?;
+            let cut_t = get_pedersen_commitment_consttime(&p_pub_key.z, &cur_u, &p_pub_key.s,
+                                                          &cur_r, &p_pub_key.n, &mut ctx)?;
 
             r.insert(i.to_string(), cur_r);
             t.insert(i.to_string(), cut_t.clone()?);
@@ -1119,8 +2071,8 @@ impl ProofBuilder {
         let r_delta = bn_rand(LARGE_VPRIME)?;
 
         // 公式4.24
-        let t_delta = get_pedersen_commitment(&p_pub_key.z, &BigNumber::from_dec(&delta.to_string())?,
-                                              &p_pub_key.s, &r_delta, &p_pub_key.n, &mut ctx)?;
+        let t_delta = get_pedersen_commitment_consttime(&p_pub_key.z, &delta,
+                                                        &p_pub_key.s, &r_delta, &p_pub_key.n, &mut ctx)?;
 
         r.insert("DELTA".to_string(), r_delta);
         t.insert("DELTA".to_string(), t_delta.clone()?);
@@ -1130,18 +2082,15 @@ impl ProofBuilder {
         let mut r_tilde = HashMap::new();
 
         for i in 0..ITERATION {
-            u_tilde.insert(i.to_string(), bn_rand(LARGE_UTILDE)?);
-            r_tilde.insert(i.to_string(), bn_rand(LARGE_RTILDE)?);
+            u_tilde.insert(i.to_string(), bn_rand(predicate_security.u_tilde_size())?);
+            r_tilde.insert(i.to_string(), bn_rand(predicate_security.r_tilde_size())?);
         }
 
-        r_tilde.insert("DELTA".to_string(), bn_rand(LARGE_RTILDE)?);
-        let alpha_tilde = bn_rand(LARGE_ALPHATILDE)?;
-
-        let mj = m_tilde.get(&predicate.attr_name)
-            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in eq_proof.mtilde", predicate.attr_name)))?;
+        r_tilde.insert("DELTA".to_string(), bn_rand(predicate_security.r_tilde_size())?);
+        let alpha_tilde = bn_rand(predicate_security.alpha_tilde_size())?;
 
         // 公式4.25-4.27
-        let tau_list = calc_tne(&p_pub_key, &u_tilde, &r_tilde, &mj, &alpha_tilde, &t, predicate.is_less())?;
+        let tau_list = calc_tne_consttime(&p_pub_key, &u_tilde, &r_tilde, &mj, &alpha_tilde, &t, predicate.is_less())?;
 
         let primary_predicate_ne_init_proof = PrimaryPredicateInequalityInitProof {
             c_list,
@@ -1155,11 +2104,126 @@ impl ProofBuilder {
             t
         };
 
-        trace!("ProofBuilder::_init_ne_proof: <<< primary_predicate_ne_init_proof: {:?}", primary_predicate_ne_init_proof);
+        BnCtxPool::release(ctx);
 
         Ok(primary_predicate_ne_init_proof)
     }
 
+    /// 初始化集合成员证明
+    ///
+    /// 承诺T = Z^m * S^r，通过m_tilde与equality证明中的m关联
+    /// 对于真实分支（属性值所在位置）随机选择rho，Tau = S^rho
+    /// 对于其它分支随机选择c_i和s_i，由calc_tset模拟Tau
+    fn _init_set_membership_proof(p_pub_key: &CredentialPrimaryPublicKey,
+                                  m_tilde: &HashMap<String, BigNumber>,
+                                  cred_values: &CredentialValues,
+                                  sub_proof_request: &SubProofRequest,
+                                  attr_name: &str,
+                                  allowed_values: &[BigNumber]) -> Result<PrimarySetMembershipInitProof, IndyCryptoError> {
+        trace!("ProofBuilder::_init_set_membership_proof: >>> p_pub_key: {:?}, m_tilde: {:?}, cred_values: {:?}, \
+                sub_proof_request: {:?}, attr_name: {:?}, allowed_values: {:?}",
+               p_pub_key, m_tilde, cred_values, sub_proof_request, attr_name, allowed_values);
+
+        let mj = match (sub_proof_request.known_attrs().contains(attr_name), m_tilde.get(attr_name)) {
+            (false, Some(mj)) if cred_values.attrs_values.contains_key(attr_name) => mj,
+            _ => return Err(IndyCryptoError::InvalidStructure(
+                format!("Attribute '{}' of set membership isn't hidden attribute of the sub proof", attr_name)))
+        };
+
+        let attr_value = cred_values.attrs_values[attr_name].value();
+
+        let member_index = allowed_values.iter()
+            .position(|value| value == attr_value)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Value of attribute '{}' is not in the set of allowed values", attr_name)))?;
+
+        let mut ctx = BnCtxPool::take()?;
+
+        let r = bn_rand(LARGE_VPRIME)?;
+        let t = get_pedersen_commitment_consttime(&p_pub_key.z, &attr_value, &p_pub_key.s, &r, &p_pub_key.n, &mut ctx)?;
+
+        let r_tilde = bn_rand(LARGE_SET_MEMBERSHIP_RTILDE)?;
+        let rho = bn_rand(LARGE_SET_MEMBERSHIP_RTILDE)?;
+
+        let mut tau_list = vec![get_pedersen_commitment_consttime(&p_pub_key.z, &mj, &p_pub_key.s, &r_tilde, &p_pub_key.n, &mut ctx)?];
+        let mut c = Vec::new();
+        let mut s = Vec::new();
+
+        for (i, value) in allowed_values.iter().enumerate() {
+            if i == member_index {
+                tau_list.push(p_pub_key.s.mod_exp_consttime(&rho, &p_pub_key.n, Some(&mut ctx))?);
+                c.push(BigNumber::new()?);
+                s.push(BigNumber::new()?);
+            } else {
+                let c_i = bn_rand(LARGE_CHALLENGE)?;
+                let s_i = bn_rand(LARGE_SET_MEMBERSHIP_RTILDE)?;
+                tau_list.push(calc_tset(&p_pub_key, &t, &value, &c_i, &s_i)?);
+                c.push(c_i);
+                s.push(s_i);
+            }
+        }
+
+        let primary_set_membership_init_proof = PrimarySetMembershipInitProof {
+            attr_name: attr_name.to_string(),
+            member_index,
+            t,
+            r,
+            r_tilde,
+            rho,
+            c,
+            s,
+            tau_list
+        };
+
+        BnCtxPool::release(ctx);
+
+        trace!("ProofBuilder::_init_set_membership_proof: <<< primary_set_membership_init_proof: {:?}", secret!(&primary_set_membership_init_proof));
+
+        Ok(primary_set_membership_init_proof)
+    }
+
+    /// 初始化披露承诺
+    ///
+    /// 承诺C = Z^m * S^salt，由盐确定，验证者可以与已知承诺比较
+    /// Tau = Z^m_tilde * S^salt_tilde，通过m_tilde与equality证明中的m关联
+    fn _init_revealed_commitment(p_pub_key: &CredentialPrimaryPublicKey,
+                                 m_tilde: &HashMap<String, BigNumber>,
+                                 cred_values: &CredentialValues,
+                                 sub_proof_request: &SubProofRequest,
+                                 attr_name: &str,
+                                 salt: &BigNumber) -> Result<PrimaryRevealedCommitmentInitProof, IndyCryptoError> {
+        trace!("ProofBuilder::_init_revealed_commitment: >>> p_pub_key: {:?}, m_tilde: {:?}, cred_values: {:?}, \
+                sub_proof_request: {:?}, attr_name: {:?}, salt: {:?}",
+               p_pub_key, m_tilde, cred_values, sub_proof_request, attr_name, secret!(salt));
+
+        let mj = match (sub_proof_request.known_attrs().contains(attr_name), m_tilde.get(attr_name)) {
+            (false, Some(mj)) if cred_values.attrs_values.contains_key(attr_name) => mj,
+            _ => return Err(IndyCryptoError::InvalidStructure(
+                format!("Attribute '{}' of revealed commitment isn't hidden attribute of the sub proof", attr_name)))
+        };
+
+        let attr_value = cred_values.attrs_values[attr_name].value();
+
+        let mut ctx = BnCtxPool::take()?;
+
+        let commitment = get_pedersen_commitment_consttime(&p_pub_key.z, &attr_value, &p_pub_key.s, &salt, &p_pub_key.n, &mut ctx)?;
+
+        let salt_tilde = bn_rand(LARGE_COMMITMENT_SALT_TILDE)?;
+        let tau = get_pedersen_commitment_consttime(&p_pub_key.z, &mj, &p_pub_key.s, &salt_tilde, &p_pub_key.n, &mut ctx)?;
+
+        let primary_revealed_commitment_init_proof = PrimaryRevealedCommitmentInitProof {
+            commitment,
+            salt: salt.clone()?,
+            salt_tilde,
+            tau
+        };
+
+        BnCtxPool::release(ctx);
+
+        trace!("ProofBuilder::_init_revealed_commitment: <<< primary_revealed_commitment_init_proof: {:?}", secret!(&primary_revealed_commitment_init_proof));
+
+        Ok(primary_revealed_commitment_init_proof)
+    }
+
     /// 实例化equality Proof
     /// 
     /// 输入：
@@ -1190,7 +2254,7 @@ impl ProofBuilder {
             sub_proof_request
         );
 
-        let mut ctx = BigNumber::new_context()?;
+        let mut ctx = BnCtxPool::take()?;
 
         // 公式4.29
         let e = challenge
@@ -1208,7 +2272,7 @@ impl ProofBuilder {
             .union(&cred_schema.attrs)
             .cloned()
             .collect::<BTreeSet<String>>()
-            .difference(&sub_proof_request.revealed_attrs)
+            .difference(&sub_proof_request.known_attrs())
             .cloned()
             .collect::<BTreeSet<String>>();
         // 公式4.31
@@ -1239,14 +2303,30 @@ impl ProofBuilder {
             );
         }
 
+        let mut revealed_commitments = BTreeMap::new();
+
+        for (attr, revealed_commitment) in init_proof.revealed_commitments.iter() {
+            let salt = challenge
+                .mul(&revealed_commitment.salt, Some(&mut ctx))?
+                .add(&revealed_commitment.salt_tilde)?;
+
+            revealed_commitments.insert(attr.clone(), PrimaryRevealedCommitmentProof {
+                commitment: revealed_commitment.commitment.clone()?,
+                salt
+            });
+        }
+
         let primary_equal_proof = PrimaryEqualProof {
             revealed_attrs: revealed_attrs_with_values,
             a_prime: init_proof.a_prime.clone()?,
             e,
             v,
-            m
+            m,
+            revealed_commitments
         };
 
+        BnCtxPool::release(ctx);
+
         trace!("ProofBuilder::_finalize_eq_proof: <<< primary_equal_proof: {:?}", primary_equal_proof);
 
         Ok(primary_equal_proof)
@@ -1257,7 +2337,38 @@ impl ProofBuilder {
                           eq_proof: &PrimaryEqualProof) -> Result<PrimaryPredicateInequalityProof, IndyCryptoError> {
         trace!("ProofBuilder::_finalize_ne_proof: >>> c_h: {:?}, init_proof: {:?}, eq_proof: {:?}", c_h, init_proof, eq_proof);
 
-        let mut ctx = BigNumber::new_context()?;
+        let mj = eq_proof.m.get(&init_proof.predicate.attr_name)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in eq_proof.m", init_proof.predicate.attr_name)))?;
+
+        let primary_predicate_ne_proof = ProofBuilder::_finalize_ne_proof_for_mj(c_h, init_proof, mj)?;
+
+        trace!("ProofBuilder::_finalize_ne_proof: <<< primary_predicate_ne_proof: {:?}", primary_predicate_ne_proof);
+
+        Ok(primary_predicate_ne_proof)
+    }
+
+    fn _finalize_linear_ne_proof(c_h: &BigNumber,
+                                 init_proof: &PrimaryLinearPredicateInitProof,
+                                 eq_proof: &PrimaryEqualProof) -> Result<PrimaryLinearPredicateProof, IndyCryptoError> {
+        trace!("ProofBuilder::_finalize_linear_ne_proof: >>> c_h: {:?}, init_proof: {:?}, eq_proof: {:?}", c_h, init_proof, eq_proof);
+
+        let mj = init_proof.predicate.combine(|attr_name| eq_proof.m.get(attr_name))?;
+
+        let primary_linear_predicate_proof = PrimaryLinearPredicateProof {
+            predicate: init_proof.predicate.clone(),
+            ne_proof: ProofBuilder::_finalize_ne_proof_for_mj(c_h, &init_proof.ne_proof, &mj)?
+        };
+
+        trace!("ProofBuilder::_finalize_linear_ne_proof: <<< primary_linear_predicate_proof: {:?}", primary_linear_predicate_proof);
+
+        Ok(primary_linear_predicate_proof)
+    }
+
+    /// Finalizes inequality proof with `m` (`mj`) of the proved value.
+    fn _finalize_ne_proof_for_mj(c_h: &BigNumber,
+                                 init_proof: &PrimaryPredicateInequalityInitProof,
+                                 mj: &BigNumber) -> Result<PrimaryPredicateInequalityProof, IndyCryptoError> {
+        let mut ctx = BnCtxPool::take()?;
         let mut u = HashMap::new();
         let mut r = HashMap::new();
         let mut urproduct = BigNumber::new()?;
@@ -1301,17 +2412,67 @@ impl ProofBuilder {
         let primary_predicate_ne_proof = PrimaryPredicateInequalityProof {
             u,
             r,
-            mj: eq_proof.m[&init_proof.predicate.attr_name].clone()?,
+            mj: mj.clone()?,
             alpha,
             t: clone_bignum_map(&init_proof.t)?,
             predicate: init_proof.predicate.clone()
         };
 
-        trace!("ProofBuilder::_finalize_ne_proof: <<< primary_predicate_ne_proof: {:?}", primary_predicate_ne_proof);
+        BnCtxPool::release(ctx);
 
         Ok(primary_predicate_ne_proof)
     }
 
+    /// 实例化集合成员证明
+    /// 真实分支的挑战c_j = c_H - sum(c_i) mod 2^LARGE_CHALLENGE，响应s_j = rho + c_j * r
+    fn _finalize_set_membership_proof(c_h: &BigNumber,
+                                      init_proof: &PrimarySetMembershipInitProof) -> Result<PrimarySetMembershipProof, IndyCryptoError> {
+        trace!("ProofBuilder::_finalize_set_membership_proof: >>> c_h: {:?}, init_proof: {:?}", c_h, secret!(init_proof));
+
+        let mut ctx = BnCtxPool::take()?;
+
+        let r = c_h
+            .mul(&init_proof.r, Some(&mut ctx))?
+            .add(&init_proof.r_tilde)?;
+
+        let mut c_real = c_h.clone()?;
+        for c_i in init_proof.c.iter() {
+            c_real = c_real.sub(c_i)?;
+        }
+        let c_real = c_real.modulus(&LARGE_CHALLENGE_VALUE, Some(&mut ctx))?;
+
+        let s_real = c_real
+            .mul(&init_proof.r, Some(&mut ctx))?
+            .add(&init_proof.rho)?;
+
+        let mut c = Vec::new();
+        let mut s = Vec::new();
+
+        for i in 0..init_proof.c.len() {
+            if i == init_proof.member_index {
+                c.push(c_real.clone()?);
+                s.push(s_real.clone()?);
+            } else {
+                c.push(init_proof.c[i].clone()?);
+                s.push(init_proof.s[i].clone()?);
+            }
+        }
+
+        let primary_set_membership_proof = PrimarySetMembershipProof {
+            attr_name: init_proof.attr_name.clone(),
+            t: init_proof.t.clone()?,
+            r,
+            c,
+            s
+        };
+
+        BnCtxPool::release(ctx);
+
+        trace!("ProofBuilder::_finalize_set_membership_proof: <<< primary_set_membership_proof: {:?}", primary_set_membership_proof);
+
+        Ok(primary_set_membership_proof)
+    }
+
     /// 实例化Primary凭证Proof
     /// 
     /// 输入：
@@ -1357,7 +2518,21 @@ impl ProofBuilder {
             ne_proofs.push(ne_proof);
         }
 
-        let primary_proof = PrimaryProof { eq_proof, ne_proofs };
+        let mut linear_ne_proofs: Vec<PrimaryLinearPredicateProof> = Vec::new();
+
+        for init_linear_ne_proof in init_proof.linear_ne_proofs.iter() {
+            let linear_ne_proof = ProofBuilder::_finalize_linear_ne_proof(challenge, init_linear_ne_proof, &eq_proof)?;
+            linear_ne_proofs.push(linear_ne_proof);
+        }
+
+        let mut set_membership_proofs: Vec<PrimarySetMembershipProof> = Vec::new();
+
+        for init_set_membership_proof in init_proof.set_membership_proofs.iter() {
+            let set_membership_proof = ProofBuilder::_finalize_set_membership_proof(challenge, init_set_membership_proof)?;
+            set_membership_proofs.push(set_membership_proof);
+        }
+
+        let primary_proof = PrimaryProof { eq_proof, ne_proofs, linear_ne_proofs, set_membership_proofs };
 
         trace!("ProofBuilder::_finalize_primary_proof: <<< primary_proof: {:?}", primary_proof);
 
@@ -1365,10 +2540,33 @@ impl ProofBuilder {
     }
 }
 
+/// Proof builder restored from snapshot by `ProofBuilder::from_snapshot_json`.
+/// It can only be finalized, and only once: `finalize` consumes it and clears its secret values.
+#[cfg(feature = "proof_builder_snapshot")]
+#[derive(Debug)]
+pub struct ResumedProofBuilder {
+    proof_builder: ProofBuilder
+}
+
+#[cfg(feature = "proof_builder_snapshot")]
+impl ResumedProofBuilder {
+    /// Finalizes proof, same as `ProofBuilder::finalize`.
+    ///
+    /// # Arguments
+    /// * `nonce` - Nonce.
+    pub fn finalize(mut self, nonce: &Nonce) -> Result<Proof, IndyCryptoError> {
+        let res = self.proof_builder.finalize(nonce);
+        self.proof_builder.clear();
+        res
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use cl::issuer;
+    use cl::verifier;
+    use cl::hash::CHALLENGE_HASH_ALGORITHM_ID;
     use serde_json;
 
     #[test]
@@ -1413,6 +2611,77 @@ mod tests {
         Prover::check_credential_key_correctness_proof(&pubk, &kcp).unwrap_err();
     }
 
+    #[test]
+    fn verify_credential_offer_works() {
+        let (cred_pub_key, _, mut cred_key_correctness_proof) =
+            issuer::Issuer::new_credential_def(&issuer::mocks::credential_schema(), &issuer::mocks::non_credential_schema()).unwrap();
+
+        let digest = issuer::Issuer::credential_offer_digest(&cred_pub_key, &cred_key_correctness_proof).unwrap();
+        Prover::verify_credential_offer(&cred_pub_key, &cred_key_correctness_proof, &digest).unwrap();
+
+        cred_key_correctness_proof.xz_cap = cred_key_correctness_proof.xz_cap.add(&BigNumber::from_u32(1).unwrap()).unwrap();
+
+        let modified_digest = issuer::Issuer::credential_offer_digest(&cred_pub_key, &cred_key_correctness_proof).unwrap();
+        assert_ne!(digest, modified_digest);
+
+        assert!(Prover::verify_credential_offer(&cred_pub_key, &cred_key_correctness_proof, &digest).is_err());
+        assert!(Prover::verify_credential_offer(&cred_pub_key, &cred_key_correctness_proof, &modified_digest).is_err());
+    }
+
+    #[test]
+    fn can_satisfy_works() {
+        let res = Prover::can_satisfy(&mocks::sub_proof_request(),
+                                      &issuer::mocks::credential_schema(),
+                                      &issuer::mocks::credential_values()).unwrap();
+        assert!(res);
+    }
+
+    #[test]
+    fn can_satisfy_works_for_not_satisfied_predicate() {
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 30).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let res = Prover::can_satisfy(&sub_proof_request,
+                                      &issuer::mocks::credential_schema(),
+                                      &issuer::mocks::credential_values()).unwrap();
+        assert!(!res);
+    }
+
+    #[test]
+    fn unsatisfied_predicates_works() {
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 30).unwrap();
+        sub_proof_request_builder.add_predicate("income", "GE", 5000).unwrap();
+        sub_proof_request_builder.add_predicate("height", "GE", 150).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_dec_known("age", "25").unwrap();
+        credential_values_builder.add_dec_known("income", "1000").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let res = Prover::unsatisfied_predicates(&sub_proof_request, &credential_values).unwrap();
+        assert_eq!(vec![
+            Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GE, value: 30 },
+            Predicate { attr_name: "income".to_owned(), p_type: PredicateType::GE, value: 5000 }
+        ], res);
+    }
+
+    #[test]
+    fn can_satisfy_works_for_missed_attribute() {
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_revealed_attr("status").unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let res = Prover::can_satisfy(&sub_proof_request,
+                                      &issuer::mocks::credential_schema(),
+                                      &issuer::mocks::credential_values()).unwrap();
+        assert!(!res);
+    }
+
     #[test]
     fn generate_master_secret_works() {
         MockHelper::inject();
@@ -1468,6 +2737,123 @@ mod tests {
         assert_eq!(blinded_credential_secrets_correctness_proof, expected_blinded_credential_secrets_correctness_proof);
     }
 
+    #[test]
+    fn blind_credential_secrets_for_schema_works_for_missing_master_secret() {
+        let pk = issuer::mocks::credential_public_key();
+        let key_correctness_proof = issuer::mocks::credential_key_correctness_proof();
+        let non_credential_schema = issuer::mocks::non_credential_schema();
+        let nonce = issuer::mocks::credential_nonce();
+
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_dec_known("age", "25").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        match Prover::blind_credential_secrets_for_schema(&pk, &key_correctness_proof, &non_credential_schema, &credential_values, &nonce) {
+            Err(IndyCryptoError::InvalidStructure(ref err)) =>
+                assert_eq!("Hidden attribute 'master_secret' of non credential schema is missing in credential values", err),
+            res => panic!("InvalidStructure error expected, got {:?}", res)
+        }
+    }
+
+    #[test]
+    fn blind_credential_secrets_for_schema_works_for_known_master_secret() {
+        let pk = issuer::mocks::credential_public_key();
+        let key_correctness_proof = issuer::mocks::credential_key_correctness_proof();
+        let non_credential_schema = issuer::mocks::non_credential_schema();
+        let nonce = issuer::mocks::credential_nonce();
+
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_value_known("master_secret", &mocks::master_secret().value().unwrap()).unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let res = Prover::blind_credential_secrets_for_schema(&pk, &key_correctness_proof, &non_credential_schema, &credential_values, &nonce);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+
+        let credential_values = issuer::mocks::credential_values();
+        Prover::blind_credential_secrets_for_schema(&pk, &key_correctness_proof, &non_credential_schema, &credential_values, &nonce).unwrap();
+    }
+
+    #[test]
+    fn blind_credential_secrets_works_for_missing_value_of_key_attribute() {
+        let pk = issuer::mocks::credential_public_key();
+        let key_correctness_proof = issuer::mocks::credential_key_correctness_proof();
+        let nonce = issuer::mocks::credential_nonce();
+
+        let mut credential_values = issuer::mocks::credential_values();
+        credential_values.attrs_values.remove("master_secret");
+
+        let res = Prover::blind_credential_secrets(&pk, &key_correctness_proof, &credential_values, &nonce);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn blinded_credential_secrets_attributes_works() {
+        let pk = issuer::mocks::credential_public_key();
+        let key_correctness_proof = issuer::mocks::credential_key_correctness_proof();
+        let nonce = issuer::mocks::credential_nonce();
+
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &mocks::master_secret().value().unwrap()).unwrap();
+        credential_values_builder.add_value_commitment("name", &BigNumber::from_u32(1).unwrap(), &BigNumber::from_u32(2).unwrap()).unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("age", "25").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let (blinded_credential_secrets, _, _) =
+            Prover::blind_credential_secrets(&pk, &key_correctness_proof, &credential_values, &nonce).unwrap();
+
+        assert_eq!(vec!["master_secret"], blinded_credential_secrets.hidden_attributes().collect::<Vec<&String>>());
+
+        let committed_attributes = blinded_credential_secrets.committed_attributes().collect::<Vec<(&String, &BigNumber)>>();
+        assert_eq!(1, committed_attributes.len());
+        assert_eq!("name", committed_attributes[0].0);
+    }
+
+    #[test]
+    fn refresh_signature_randomization_works() {
+        let pk = issuer::mocks::credential_public_key();
+        let mut credential_signature = mocks::credential();
+        let p_cred = mocks::primary_credential();
+
+        Prover::refresh_signature_randomization(&mut credential_signature, &pk).unwrap();
+
+        let refreshed = &credential_signature.p_credential;
+        assert_ne!(p_cred.a, refreshed.a);
+        assert_ne!(p_cred.v, refreshed.v);
+        assert_eq!(p_cred.e, refreshed.e);
+
+        let n = &pk.p_key.n;
+        let sign = |p_cred: &PrimaryCredentialSignature|
+            p_cred.a.mod_exp(&p_cred.e, n, None).unwrap()
+                .mod_mul(&pk.p_key.s.mod_exp(&p_cred.v, n, None).unwrap(), n, None).unwrap();
+
+        assert_eq!(sign(&p_cred), sign(refreshed));
+    }
+
+    #[test]
+    fn validate_signature_e_works() {
+        Prover::validate_signature_e(&mocks::credential()).unwrap();
+    }
+
+    #[test]
+    fn validate_signature_e_works_for_composite_e() {
+        let mut credential_signature = mocks::credential();
+        credential_signature.p_credential.e = credential_signature.p_credential.e.add(&BigNumber::from_u32(1).unwrap()).unwrap();
+
+        let res = Prover::validate_signature_e(&credential_signature);
+        assert!(match res { Err(IndyCryptoError::AnoncredsCredentialSignatureInvalid(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn validate_signature_e_works_for_e_out_of_range() {
+        let mut credential_signature = mocks::credential();
+        credential_signature.p_credential.e = BigNumber::from_u32(65537).unwrap();
+
+        let res = Prover::validate_signature_e(&credential_signature);
+        assert!(match res { Err(IndyCryptoError::AnoncredsCredentialSignatureInvalid(_)) => true, _ => false });
+    }
+
     //TODO: conflicts
     #[test]
     fn process_primary_credential_works() {
@@ -1503,6 +2889,53 @@ mod tests {
         assert_eq!(mocks::primary_credential(), credential_signature.p_credential);
     }
 
+    #[cfg(feature = "blinding_factors_reuse_check")]
+    #[test]
+    fn check_blinding_factors_reuse_works() {
+        let credential_secrets_blinding_factors = CredentialSecretsBlindingFactors { v_prime: bn_rand(LARGE_VPRIME).unwrap() };
+
+        Prover::_check_blinding_factors_reuse(&credential_secrets_blinding_factors).unwrap();
+        Prover::_check_blinding_factors_reuse(&credential_secrets_blinding_factors).unwrap();
+
+        Prover::_record_blinding_factors_usage(&credential_secrets_blinding_factors).unwrap();
+
+        let res = Prover::_check_blinding_factors_reuse(&credential_secrets_blinding_factors);
+        match res {
+            Err(IndyCryptoError::InvalidState(_)) => {}
+            _ => panic!("InvalidState error expected")
+        }
+    }
+
+    #[test]
+    fn finalize_works_for_trivial_nonce() {
+        let proof_builder = Prover::new_proof_builder().unwrap();
+
+        for nonce in [BigNumber::new().unwrap(), BigNumber::from_u32(12345).unwrap()].iter() {
+            let res = proof_builder.finalize(nonce);
+            assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+        }
+    }
+
+    #[test]
+    fn add_attribute_equality_works_for_invalid_indexes() {
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        assert!(proof_builder.add_attribute_equality(1, 1, "ssn").is_err());
+
+        proof_builder.add_attribute_equality(0, 1, "ssn").unwrap();
+        assert!(proof_builder.finalize(&mocks::proof_request_nonce()).is_err());
+    }
+
+    #[cfg(feature = "debug_delta")]
+    #[test]
+    fn debug_delta_works() {
+        let ne_init_proof = mocks::primary_ne_init_proof();
+        let predicate = mocks::predicate();
+
+        // age 25 for GE 18 predicate
+        let expected_delta = predicate.get_delta(&BigNumber::from_u32(25).unwrap()).unwrap();
+        assert_eq!(expected_delta, ne_init_proof.debug_delta().unwrap());
+    }
+
     #[test]
     fn init_eq_proof_works() {
         MockHelper::inject();
@@ -1536,11 +2969,128 @@ mod tests {
         let init_ne_proof = ProofBuilder::_init_ne_proof(&pk,
                                                          &init_eq_proof.m_tilde,
                                                          &credential_values,
-                                                         &predicate).unwrap();
+                                                         &predicate,
+                                                         PredicateSecurityLevel::DEFAULT,
+                                                         &mut BTreeMap::new()).unwrap();
 
         assert_eq!(mocks::primary_ne_init_proof(), init_ne_proof);
     }
 
+    #[test]
+    fn init_ne_proof_works_for_memoized_delta() {
+        MockHelper::inject();
+
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let predicate = mocks::predicate();
+        let credential_values = issuer::mocks::credential_values();
+        let mut deltas = BTreeMap::new();
+
+        for _ in 0..2 {
+            let init_ne_proof = ProofBuilder::_init_ne_proof(&pk,
+                                                             &init_eq_proof.m_tilde,
+                                                             &credential_values,
+                                                             &predicate,
+                                                             PredicateSecurityLevel::DEFAULT,
+                                                             &mut deltas).unwrap();
+
+            assert_eq!(mocks::primary_ne_init_proof(), init_ne_proof);
+            assert_eq!(1, deltas.len());
+        }
+    }
+
+    #[test]
+    fn get_delta_works_for_memoized_delta() {
+        let predicate = mocks::predicate();
+        let attr_value = BigNumber::from_u32(25).unwrap();
+        let mut deltas = BTreeMap::new();
+
+        let delta = ProofBuilder::_get_delta(&mut deltas, &predicate, &attr_value).unwrap();
+        assert_eq!(predicate.get_delta(&attr_value).unwrap(), delta);
+        assert_eq!(1, deltas.len());
+
+        assert_eq!(delta, ProofBuilder::_get_delta(&mut deltas, &predicate, &attr_value).unwrap());
+        assert_eq!(1, deltas.len());
+
+        let other_value = BigNumber::from_u32(30).unwrap();
+        assert_eq!(predicate.get_delta(&other_value).unwrap(), ProofBuilder::_get_delta(&mut deltas, &predicate, &other_value).unwrap());
+        assert_eq!(2, deltas.len());
+    }
+
+    #[test]
+    fn init_ne_proof_works_for_value_out_of_i32() {
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let mj = init_eq_proof.m_tilde.get("age").unwrap();
+        let predicate = Predicate { attr_name: "age".to_string(), p_type: PredicateType::GT, value: ::std::i32::MAX };
+
+        let attr_value = BigNumber::from_dec("2147483657").unwrap();
+        assert!(ProofBuilder::_init_ne_proof_for_value(&pk, &attr_value, mj, &predicate, PredicateSecurityLevel::DEFAULT, &mut BTreeMap::new()).is_ok());
+
+        let attr_value = BigNumber::from_dec("2147483647").unwrap();
+        let res = ProofBuilder::_init_ne_proof_for_value(&pk, &attr_value, mj, &predicate, PredicateSecurityLevel::DEFAULT, &mut BTreeMap::new());
+        assert!(match res { Err(IndyCryptoError::AnoncredsPredicateNotSatisfied(_)) => true, _ => false });
+    }
+
+
+    #[test]
+    fn init_ne_proof_works_for_delta_exceeding_max_supported() {
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GE, value: -1 };
+
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_dec_known("age", &::std::i32::MAX.to_string()).unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let res = ProofBuilder::_init_ne_proof(&pk,
+                                               &init_eq_proof.m_tilde,
+                                               &credential_values,
+                                               &predicate,
+                                               PredicateSecurityLevel::DEFAULT,
+                                               &mut BTreeMap::new());
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn init_ne_proof_works_for_le_predicate() {
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let credential_values = issuer::mocks::credential_values();
+
+        for value in [25, 30].iter() {
+            let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::LE, value: *value };
+
+            ProofBuilder::_init_ne_proof(&pk,
+                                         &init_eq_proof.m_tilde,
+                                         &credential_values,
+                                         &predicate,
+                                         PredicateSecurityLevel::DEFAULT,
+                                         &mut BTreeMap::new()).unwrap();
+        }
+    }
+
+    #[test]
+    fn init_ne_proof_works_for_not_satisfied_le_predicate() {
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let credential_values = issuer::mocks::credential_values();
+
+        for (p_type, value) in vec![(PredicateType::LE, 24), (PredicateType::LT, 25)] {
+            let predicate = Predicate { attr_name: "age".to_owned(), p_type, value };
+
+            let res = ProofBuilder::_init_ne_proof(&pk,
+                                                   &init_eq_proof.m_tilde,
+                                                   &credential_values,
+                                                   &predicate,
+                                                   PredicateSecurityLevel::DEFAULT,
+                                                   &mut BTreeMap::new());
+            match res {
+                Err(IndyCryptoError::AnoncredsPredicateNotSatisfied(ref err)) => assert_eq!("Predicate is not satisfied", err),
+                _ => panic!("AnoncredsPredicateNotSatisfied error expected")
+            }
+        }
+    }
 
     #[test]
     fn init_primary_proof_works() {
@@ -1560,7 +3110,8 @@ mod tests {
                                                            &credential_values,
                                                            &credential_schema,
                                                            &non_credential_schema,
-                                                           &sub_proof_request).unwrap();
+                                                           &sub_proof_request,
+                                                           PredicateSecurityLevel::DEFAULT).unwrap();
         assert_eq!(mocks::primary_init_proof(), init_proof);
     }
 
@@ -1620,6 +3171,98 @@ mod tests {
         assert_eq!(mocks::primary_proof(), proof);
     }
 
+    #[test]
+    fn add_sub_proof_request_works_for_public_key_not_correspond_to_schema() {
+        let mut cred_pub_key = issuer::mocks::credential_public_key();
+        cred_pub_key.p_key.r.remove("sex");
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        let res = proof_builder.add_sub_proof_request(&mocks::sub_proof_request(),
+                                                      &issuer::mocks::credential_schema(),
+                                                      &issuer::mocks::non_credential_schema(),
+                                                      &mocks::credential(),
+                                                      &issuer::mocks::credential_values(),
+                                                      issuer::mocks::CREDENTIAL_KEY_ID,
+                                                      &cred_pub_key);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn finalize_works_for_other_hash_algorithm_id() {
+        let credential_schema = issuer::mocks::credential_schema();
+        let non_credential_schema = issuer::mocks::non_credential_schema();
+        let cred_pub_key = issuer::mocks::credential_public_key();
+        let sub_proof_request = mocks::sub_proof_request();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &mocks::credential(),
+                                            &issuer::mocks::credential_values(),
+                                            issuer::mocks::CREDENTIAL_KEY_ID,
+                                            &cred_pub_key).unwrap();
+
+        let nonce = mocks::proof_request_nonce();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+        let other_proof = proof_builder._finalize(&nonce, HashBuilder::with_algorithm_id(CHALLENGE_HASH_ALGORITHM_ID + 1).unwrap()).unwrap();
+
+        let mut proof_verifier = verifier::Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_common_attribute("master_secret").unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             issuer::mocks::CREDENTIAL_KEY_ID,
+                                             &cred_pub_key).unwrap();
+
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+        assert!(!proof_verifier.verify(&other_proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn finalize_works_for_legacy_challenge() {
+        let credential_schema = issuer::mocks::credential_schema();
+        let non_credential_schema = issuer::mocks::non_credential_schema();
+        let cred_pub_key = issuer::mocks::credential_public_key();
+        let sub_proof_request = mocks::sub_proof_request();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &mocks::credential(),
+                                            &issuer::mocks::credential_values(),
+                                            issuer::mocks::CREDENTIAL_KEY_ID,
+                                            &cred_pub_key).unwrap();
+
+        let nonce = mocks::proof_request_nonce();
+
+        let mut legacy_proof = proof_builder._finalize(&nonce, HashBuilder::new().unwrap()).unwrap();
+        legacy_proof.ver = SerializationVersion::LEGACY;
+
+        let mut relabelled_proof = proof_builder.finalize(&nonce).unwrap();
+        relabelled_proof.ver = SerializationVersion::LEGACY;
+
+        let mut proof_verifier = verifier::Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_common_attribute("master_secret").unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             issuer::mocks::CREDENTIAL_KEY_ID,
+                                             &cred_pub_key).unwrap();
+
+        assert!(!proof_verifier.verify(&legacy_proof, &nonce).unwrap());
+        assert!(!proof_verifier.verify(&relabelled_proof, &nonce).unwrap());
+
+        proof_verifier.allow_legacy_challenge(true).unwrap();
+
+        assert!(proof_verifier.verify(&legacy_proof, &nonce).unwrap());
+        assert!(!proof_verifier.verify(&relabelled_proof, &nonce).unwrap());
+    }
+
     extern crate time;
 
     #[test]
@@ -1640,6 +3283,7 @@ mod tests {
                                             &non_credential_schema,
                                             &cred_signature,
                                             &cred_values,
+                                            issuer::mocks::CREDENTIAL_KEY_ID,
                                             &cred_pub_key).unwrap();
         let proof_request_nonce = new_nonce().unwrap();
         let proof = proof_builder.finalize(&proof_request_nonce).unwrap();
@@ -1708,6 +3352,7 @@ pub mod mocks {
 
     pub fn credential() -> CredentialSignature {
         CredentialSignature {
+            ver: SerializationVersion::current(),
             p_credential: primary_credential()
         }
     }
@@ -1728,13 +3373,17 @@ pub mod mocks {
 
     pub fn proof() -> Proof {
         Proof {
+            ver: SerializationVersion::current(),
             proofs: vec![subproof()],
-            aggregated_proof: aggregated_proof()
+            aggregated_proof: aggregated_proof(),
+            common_m: BTreeMap::new(),
+            predicate_security: PredicateSecurityLevel::DEFAULT
         }
     }
 
     pub fn subproof() -> SubProof {
         SubProof {
+            key_id: issuer::mocks::CREDENTIAL_KEY_ID.to_string(),
             primary_proof: primary_proof()
         }
     }
@@ -1742,7 +3391,9 @@ pub mod mocks {
     pub fn primary_init_proof() -> PrimaryInitProof {
         PrimaryInitProof {
             eq_proof: primary_equal_init_proof(),
-            ne_proofs: vec![primary_ne_init_proof()]
+            ne_proofs: vec![primary_ne_init_proof()],
+            linear_ne_proofs: Vec::new(),
+            set_membership_proofs: Vec::new()
         }
     }
 
@@ -1759,7 +3410,8 @@ pub mod mocks {
                 "height".to_string() => BigNumber::from_dec("6461691768834933403326572830814516653957231030793837560544354737855803497655300429843454445497126567767486684087006218691084619904526729989680526652503377438786587511370042964338").unwrap(),
                 "master_secret".to_string() => BigNumber::from_dec("67940925789970108743024738273926421512152745397724199848594503731042154269417576665420030681245389493783225644817826683796657351721363490290016166310023506339911751676800452438014771736117676826911321621579680668201191205819012441197794443970687648330757835198888257781967404396196813475280544039772512800509").unwrap(),
                 "sex".to_string() => BigNumber::from_dec("6461691768834933403326572830814516653957231030793837560544354737855803497655300429843454445497126567767486684087006218691084619904526729989680526652503377438786587511370042964338").unwrap()
-            ]
+            ],
+            revealed_commitments: BTreeMap::new()
         }
     }
 
@@ -1859,7 +3511,8 @@ pub mod mocks {
                 "sex".to_string() => BigNumber::from_dec("6461691768834933403326575020439114193500962122447442182375470664835531264262887123435773676729731478629261405277091910956944655533226659560277758686479462667297473396368211269136").unwrap(),
                 "height".to_string() => BigNumber::from_dec("6461691768834933403326572830814516653957231030793837560544354737855803497655300429843454445497126574195981378365198960707499125538146253636400775219219390979675126287408712407688").unwrap(),
                 "age".to_string() => BigNumber::from_dec("6461691768834933403326572830814516653957231030793837560544354737855803497655300429843454445497126568685843068983890896122000977852186661939211990733462807944627807336518424313388").unwrap()
-            ]
+            ],
+            revealed_commitments: BTreeMap::new()
         }
     }
 
@@ -1915,7 +3568,9 @@ pub mod mocks {
     pub fn primary_proof() -> PrimaryProof {
         PrimaryProof {
             eq_proof: eq_proof(),
-            ne_proofs: vec![ne_proof()]
+            ne_proofs: vec![ne_proof()],
+            linear_ne_proofs: Vec::new(),
+            set_membership_proofs: Vec::new()
         }
     }
 
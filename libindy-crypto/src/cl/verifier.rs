@@ -1,11 +1,13 @@
-use bn::BigNumber;
+use bn::{BigNumber, BnCtxPool, BIGNUMBER_1};
 use cl::*;
-use cl::constants::{LARGE_E_START_VALUE, ITERATION};
+use cl::constants::{LARGE_E_START_VALUE, LARGE_CHALLENGE, LARGE_CHALLENGE_VALUE, LARGE_VPRIME, ITERATION, LARGE_KEY_ROTATION_TILDE};
 use cl::helpers::*;
-use cl::hash::get_hash_as_int;
+use cl::commitment::get_pedersen_commitment;
+use cl::hash::HashBuilder;
 use errors::IndyCryptoError;
 
-use std::collections::BTreeSet;
+use std::cmp::max;
+use std::collections::{BTreeMap, BTreeSet};
 use std::iter::FromIterator;
 
 /// Party that wants to check that prover has some credentials provided by issuer.
@@ -45,24 +47,242 @@ impl Verifier {
     pub fn new_proof_verifier() -> Result<ProofVerifier, IndyCryptoError> {
         Ok(ProofVerifier {
             credentials: Vec::new(),
+            common_attributes: BTreeSet::new(),
+            attribute_equalities: Vec::new(),
+            set_memberships: Vec::new(),
+            required_revealed_attrs: BTreeSet::new(),
+            min_predicate_security: PredicateSecurityLevel::DEFAULT,
+            limits: SizeLimits::DEFAULT,
+            allow_legacy_challenge: false,
         })
     }
+
+    /// Verifies that new credential public key succeeds the old one.
+    ///
+    /// # Arguments
+    /// * `old_credential_pub_key` - Public key of rotated credential definition.
+    /// * `new_credential_pub_key` - Public key of new credential definition.
+    /// * `key_rotation_proof` - Proof created by Issuer during credential definition rotation.
+    pub fn verify_key_rotation(old_credential_pub_key: &CredentialPublicKey,
+                               new_credential_pub_key: &CredentialPublicKey,
+                               key_rotation_proof: &KeyRotationProof) -> Result<bool, IndyCryptoError> {
+        trace!("Verifier::verify_key_rotation: >>> old_credential_pub_key: {:?}, new_credential_pub_key: {:?}, key_rotation_proof: {:?}",
+               old_credential_pub_key, new_credential_pub_key, key_rotation_proof);
+
+        if !Verifier::_check_key_rotation_response(&key_rotation_proof.old_x_cap)? ||
+            !Verifier::_check_key_rotation_response(&key_rotation_proof.new_x_cap)? {
+            trace!("Verifier::verify_key_rotation: <<< valid: false (response out of range)");
+            return Ok(false);
+        }
+
+        let old_t_hat = Verifier::_calc_key_rotation_t_hat(&key_rotation_proof.old_x_cap, &key_rotation_proof.c, &old_credential_pub_key.p_key)?;
+        let new_t_hat = Verifier::_calc_key_rotation_t_hat(&key_rotation_proof.new_x_cap, &key_rotation_proof.c, &new_credential_pub_key.p_key)?;
+
+        let valid = get_key_rotation_challenge(&old_credential_pub_key.p_key, &new_credential_pub_key.p_key, &old_t_hat, &new_t_hat)? ==
+            key_rotation_proof.c;
+
+        trace!("Verifier::verify_key_rotation: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Computes salted commitment `Z^m * S^salt` to attribute value,
+    /// the same one prover reveals with `ProofBuilder::add_revealed_commitment`.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key.
+    /// * `attr_value` - Encoded attribute value.
+    /// * `salt` - Salt of the commitment.
+    pub fn calc_revealed_commitment(credential_pub_key: &CredentialPublicKey,
+                                    attr_value: &BigNumber,
+                                    salt: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        let p_pub_key = &credential_pub_key.p_key;
+        let mut ctx = BigNumber::new_context()?;
+
+        get_pedersen_commitment(&p_pub_key.z, attr_value, &p_pub_key.s, salt, &p_pub_key.n, &mut ctx)
+    }
+
+    /// Checks that sub proof answers the request: attributes revealed and predicates proved
+    /// by the sub proof are exactly the requested ones. Doesn't check the proof itself,
+    /// so use it together with `ProofVerifier::verify` to reject valid proofs built for another request.
+    /// Public attributes of credential schema are always revealed, so they are expected even if the request omits them.
+    /// `EQ` predicates aren't recorded in the proof, so they are checked by `verify` only.
+    ///
+    /// # Arguments
+    /// * `proof` - Proof received from prover.
+    /// * `sub_proof_index` - Index of sub proof (in order of sub proof requests) to check.
+    /// * `sub_proof_request` - Request sent to prover for this sub proof.
+    /// * `credential_schema` - Credential schema of the credential used for this sub proof.
+    pub fn verify_matches_request(proof: &Proof,
+                                  sub_proof_index: usize,
+                                  sub_proof_request: &SubProofRequest,
+                                  credential_schema: &CredentialSchema) -> Result<(), IndyCryptoError> {
+        trace!("Verifier::verify_matches_request: >>> proof: {:?}, sub_proof_index: {:?}, sub_proof_request: {:?}, credential_schema: {:?}",
+               proof, sub_proof_index, sub_proof_request, credential_schema);
+
+        let primary_proof = &proof.proofs.get(sub_proof_index)
+            .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Sub proof {} not found", sub_proof_index)))?
+            .primary_proof;
+
+        let revealed_attrs = primary_proof.eq_proof.revealed_attrs.keys().cloned().collect::<BTreeSet<String>>();
+        let requested_revealed_attrs = sub_proof_request.revealed_attrs.union(&credential_schema.public_attrs).cloned().collect::<BTreeSet<String>>();
+
+        if revealed_attrs != requested_revealed_attrs {
+            return Err(IndyCryptoError::AnoncredsProofRejected(
+                format!("Proof revealed attributes {:?} don't match requested {:?}", revealed_attrs, requested_revealed_attrs)));
+        }
+
+        let predicates = primary_proof.ne_proofs.iter()
+            .map(|ne_proof| ne_proof.predicate.clone())
+            .collect::<BTreeSet<Predicate>>();
+
+        if predicates != sub_proof_request.ne_predicates() {
+            return Err(IndyCryptoError::AnoncredsProofRejected(
+                format!("Proof predicates {:?} don't match requested {:?}", predicates, sub_proof_request.ne_predicates())));
+        }
+
+        let linear_predicates = primary_proof.linear_ne_proofs.iter()
+            .map(|linear_ne_proof| linear_ne_proof.predicate.clone())
+            .collect::<BTreeSet<LinearPredicate>>();
+
+        if linear_predicates != sub_proof_request.linear_predicates {
+            return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof linear predicates don't match requested ones")));
+        }
+
+        trace!("Verifier::verify_matches_request: <<<");
+
+        Ok(())
+    }
+
+    /// Response `x_cap = x_tilde + c * (n - phi(n))` is non negative and bounded,
+    /// otherwise multiple of `phi(n)` could be hidden in it.
+    fn _check_key_rotation_response(x_cap: &BigNumber) -> Result<bool, IndyCryptoError> {
+        Ok(!x_cap.is_negative() && x_cap.num_bits()? <= LARGE_KEY_ROTATION_TILDE as i32 + 1)
+    }
+
+    /// `S^(x_cap - c * n) = S^(x_tilde - c * phi(n)) = S^x_tilde`, because `S^phi(n) = 1`.
+    fn _calc_key_rotation_t_hat(x_cap: &BigNumber,
+                                c: &BigNumber,
+                                p_pub_key: &CredentialPrimaryPublicKey) -> Result<BigNumber, IndyCryptoError> {
+        let mut ctx = BigNumber::new_context()?;
+
+        let exp = x_cap.sub(&c.mul(&p_pub_key.n, Some(&mut ctx))?)?;
+
+        p_pub_key.s.mod_exp(&exp, &p_pub_key.n, Some(&mut ctx))
+    }
 }
 
 
 #[derive(Debug)]
 pub struct ProofVerifier {
     credentials: Vec<VerifiableCredential>,
+    common_attributes: BTreeSet<String>,
+    attribute_equalities: Vec<(usize /* cred_index_a */, usize /* cred_index_b */, String /* attr_name */)>,
+    set_memberships: Vec<(String /* attr_name */, Vec<BigNumber> /* allowed_values */)>,
+    required_revealed_attrs: BTreeSet<String>,
+    min_predicate_security: PredicateSecurityLevel,
+    limits: SizeLimits,
+    allow_legacy_challenge: bool,
 }
 
 impl ProofVerifier {
+    /// Requires hidden attribute (for example `master_secret` or any other link secret) to have the same value
+    /// in all sub proofs whose credential contains the attribute.
+    /// Prover must add the same attribute by `ProofBuilder::add_common_attribute`.
+    ///
+    /// # Arguments
+    /// * `attr_name` - Name of hidden attribute that links credentials.
+    pub fn add_common_attribute(&mut self, attr_name: &str) -> Result<(), IndyCryptoError> {
+        self.common_attributes.insert(normalize_attr_name(attr_name));
+        Ok(())
+    }
+
+    /// Requires hidden attribute to have the same value in two sub proofs.
+    /// Prover must add the same constraint by `ProofBuilder::add_attribute_equality`.
+    ///
+    /// # Arguments
+    /// * `cred_index_a` - Index of the first sub proof (in order of `add_sub_proof_request` calls).
+    /// * `cred_index_b` - Index of the second sub proof.
+    /// * `attr_name` - Name of hidden attribute present in both credentials.
+    pub fn add_attribute_equality(&mut self, cred_index_a: usize, cred_index_b: usize, attr_name: &str) -> Result<(), IndyCryptoError> {
+        if cred_index_a == cred_index_b {
+            return Err(IndyCryptoError::InvalidStructure("Attribute equality requires two different sub proofs".to_string()));
+        }
+
+        self.attribute_equalities.push((cred_index_a, cred_index_b, normalize_attr_name(attr_name)));
+        Ok(())
+    }
+
+    /// Requires hidden attribute to be equal to one of public allowed values.
+    /// Prover must add the same constraint (with the same order of values) by `ProofBuilder::add_set_membership`.
+    ///
+    /// Applies to the sub proof request added next, so must be called right before `add_sub_proof_request`.
+    ///
+    /// # Arguments
+    /// * `attr_name` - Name of hidden attribute of the next sub proof.
+    /// * `allowed_values` - Encoded values the attribute may be equal to.
+    pub fn add_set_membership(&mut self, attr_name: &str, allowed_values: &[BigNumber]) -> Result<(), IndyCryptoError> {
+        if allowed_values.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure("Set of allowed values is empty".to_string()));
+        }
+
+        let allowed_values = allowed_values.iter()
+            .map(|value| value.clone())
+            .collect::<Result<Vec<BigNumber>, IndyCryptoError>>()?;
+
+        self.set_memberships.push((normalize_attr_name(attr_name), allowed_values));
+        Ok(())
+    }
+
+    /// Sets minimal accepted security level of inequality proofs.
+    /// Proofs with predicates built below the level (see `ProofBuilder::set_predicate_security`) are rejected.
+    ///
+    /// Level only widens blinding of prover's responses, so the minimum only protects zero-knowledge margin
+    /// of prover's hidden attributes (it's a policy of the verifier on behalf of provers) and adds no soundness:
+    /// proof of any level convinces the verifier the same way.
+    ///
+    /// # Arguments
+    /// * `level` - Predicate security level (1 is the default one).
+    pub fn set_min_predicate_security(&mut self, level: u32) -> Result<(), IndyCryptoError> {
+        self.min_predicate_security = PredicateSecurityLevel::new(level)?;
+        Ok(())
+    }
+
+    /// Allows proofs of serialization version 1 whose Fiat-Shamir challenge doesn't start with
+    /// hash algorithm identifier. Such proofs are rejected by default: any proof can be labelled as version 1,
+    /// so enable it only while proofs of old provers must be accepted.
+    ///
+    /// # Arguments
+    /// * `allow` - Whether to verify version 1 proofs by challenge without hash algorithm identifier.
+    pub fn allow_legacy_challenge(&mut self, allow: bool) -> Result<(), IndyCryptoError> {
+        self.allow_legacy_challenge = allow;
+        Ok(())
+    }
+
+    /// Overrides default limits (`SizeLimits::DEFAULT`) of credential schemas and sub proof requests
+    /// checked by `add_sub_proof_request`.
+    pub fn with_limits(mut self, limits: SizeLimits) -> Result<ProofVerifier, IndyCryptoError> {
+        self.limits = limits;
+        Ok(self)
+    }
+
+    /// Requires attributes to be revealed in the proof regardless of sub proof requests.
+    /// Proof is rejected if some of attributes isn't revealed by any sub proof (predicate isn't enough).
+    ///
+    /// # Arguments
+    /// * `attrs` - Names of attributes that must be revealed.
+    pub fn require_revealed(&mut self, attrs: &[&str]) -> Result<(), IndyCryptoError> {
+        self.required_revealed_attrs.extend(attrs.iter().map(|attr| normalize_attr_name(attr)));
+        Ok(())
+    }
+
     /// Add sub proof request to proof verifier.
     /// The order of sub-proofs is important: both Prover and Verifier should use the same order.
     ///
     /// # Arguments
     /// * `proof_verifier` - Proof verifier.
     /// * `credential_schema` - Credential schema.
-    /// * `credential_pub_key` - Credential public key.
+    /// * `credential_pub_key` - Credential public key (`CredentialPublicKey` or `PreparedPublicKey` reused between verifiers).
     /// * `rev_reg_pub` - Revocation registry public key.
     /// * `sub_proof_request` - Requested attributes and predicates instance pointer.
     ///
@@ -92,20 +312,35 @@ impl ProofVerifier {
     /// proof_verifier.add_sub_proof_request(&sub_proof_request,
     ///                                      &credential_schema,
     ///                                      &non_credential_schema,
+    ///                                      "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW:3:CL:1",
     ///                                      &credential_pub_key).unwrap();
     /// ```
-    pub fn add_sub_proof_request(&mut self,
-                                 sub_proof_request: &SubProofRequest,
-                                 credential_schema: &CredentialSchema,
-                                 non_credential_schema: &NonCredentialSchema,
-                                 credential_pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> {
+    pub fn add_sub_proof_request<K: VerifierPublicKey>(&mut self,
+                                                       sub_proof_request: &SubProofRequest,
+                                                       credential_schema: &CredentialSchema,
+                                                       non_credential_schema: &NonCredentialSchema,
+                                                       key_id: &str,
+                                                       credential_pub_key: &K) -> Result<(), IndyCryptoError> {
+        // Sub proof request may be deserialized bypassing the builder, so limits are checked here again
+        self.limits.check_credential_schema(credential_schema)?;
+        self.limits.check_sub_proof_request(sub_proof_request)?;
+
         ProofVerifier::_check_add_sub_proof_request_params_consistency(sub_proof_request, credential_schema)?;
 
+        let pub_key = credential_pub_key.prepare()?;
+        pub_key.p_key.check_schemas(credential_schema, non_credential_schema)?;
+
+        // The same canonical order as ProofBuilder uses for set membership proofs
+        let mut set_memberships = ::std::mem::replace(&mut self.set_memberships, Vec::new());
+        set_memberships.sort_by(|&(ref a, _), &(ref b, _)| a.cmp(b));
+
         self.credentials.push(VerifiableCredential {
-            pub_key: credential_pub_key.clone()?,
+            key_id: key_id.to_string(),
+            pub_key,
             sub_proof_request: sub_proof_request.clone(),
             credential_schema: credential_schema.clone(),
-            non_credential_schema: non_credential_schema.clone()
+            non_credential_schema: non_credential_schema.clone(),
+            set_memberships
         });
         Ok(())
     }
@@ -176,6 +411,7 @@ impl ProofVerifier {
     ///                                     &non_credential_schema,
     ///                                     &credential_signature,
     ///                                     &credential_values,
+    ///                                     "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW:3:CL:1",
     ///                                     &credential_pub_key).unwrap();
     ///
     /// let proof_request_nonce = new_nonce().unwrap();
@@ -186,6 +422,7 @@ impl ProofVerifier {
     /// proof_verifier.add_sub_proof_request(&sub_proof_request,
     ///                                      &credential_schema,
     ///                                      &non_credential_schema,
+    ///                                      "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW:3:CL:1",
     ///                                      &credential_pub_key).unwrap();
     /// assert!(proof_verifier.verify(&proof, &proof_request_nonce).unwrap());
     /// ```
@@ -194,57 +431,191 @@ impl ProofVerifier {
                   nonce: &Nonce) -> Result<bool, IndyCryptoError> {
         trace!("ProofVerifier::verify: >>> proof: {:?}, nonce: {:?}", proof, nonce);
 
-        ProofVerifier::_check_verify_params_consistency(&self.credentials, proof)?;
+        if !ProofVerifier::_verify_required_revealed_attrs(&self.required_revealed_attrs, proof) {
+            trace!("ProofVerifier::verify: <<< valid: false (required revealed attribute)");
+            return Ok(false);
+        }
 
-        let mut tau_list: Vec<Vec<u8>> = Vec::new();
+        if !ProofVerifier::_verify_predicate_security(self.min_predicate_security, proof)? {
+            trace!("ProofVerifier::verify: <<< valid: false (predicate security)");
+            return Ok(false);
+        }
 
-        assert_eq!(proof.proofs.len(), self.credentials.len()); //FIXME return error
-        for idx in 0..proof.proofs.len() {
-            let proof_item = &proof.proofs[idx];
-            let credential = &self.credentials[idx];
+        if !ProofVerifier::_verify_common_attributes(&self.common_attributes, proof) {
+            trace!("ProofVerifier::verify: <<< valid: false (common attribute)");
+            return Ok(false);
+        }
 
+        if !ProofVerifier::_verify_attribute_equalities(&self.attribute_equalities, proof)? {
+            trace!("ProofVerifier::verify: <<< valid: false (attribute equality)");
+            return Ok(false);
+        }
+
+        if proof.ver == SerializationVersion::LEGACY && !self.allow_legacy_challenge {
+            trace!("ProofVerifier::verify: <<< valid: false (legacy challenge)");
+            return Ok(false);
+        }
+
+        let c_hver = self.recompute_challenge(proof, nonce)?;
+
+        info!(target: "anoncreds_service", "Verifier verify proof -> done");
+
+        let valid = c_hver == proof.aggregated_proof.c_hash;
+
+        trace!("ProofVerifier::verify: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+    
+    /// Recomputes Fiat-Shamir challenge of the proof from public inputs (credential public keys,
+    /// sub proof requests, proof values and nonce). Proof is valid if the result equals `proof.challenge()`.
+    ///
+    /// # Arguments
+    /// * `proof` - Proof generated by Prover.
+    /// * `nonce` - Nonce.
+    pub fn recompute_challenge(&self,
+                               proof: &Proof,
+                               nonce: &Nonce) -> Result<BigNumber, IndyCryptoError> {
+        trace!("ProofVerifier::recompute_challenge: >>> proof: {:?}, nonce: {:?}", proof, nonce);
+
+        if proof.ver == SerializationVersion::LEGACY && !self.allow_legacy_challenge {
+            return Err(IndyCryptoError::InvalidStructure("Challenge of version 1 proof isn't allowed".to_string()));
+        }
+
+        if let Some(&(ref attr, _)) = self.set_memberships.first() {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Sub proof request not found for set membership of attribute '{}'", attr)));
+        }
+
+        let credentials = ProofVerifier::_match_credentials(&self.credentials, proof)?;
+
+        ProofVerifier::_check_verify_params_consistency(&credentials, proof)?;
+
+        for (proof_item, credential) in proof.proofs.iter().zip(credentials.iter()) {
+            ProofVerifier::_check_eq_proof_structure(&credential.pub_key.p_key, &proof_item.primary_proof.eq_proof)?;
+
+            for ne_proof in proof_item.primary_proof.all_ne_proofs() {
+                ProofVerifier::_check_ne_proof_structure(&credential.pub_key.p_key, ne_proof, proof.predicate_security)?;
+            }
+        }
+
+        let mut tau_list: Vec<Vec<u8>> = Vec::new();
+
+        for (proof_item, credential) in proof.proofs.iter().zip(credentials) {
             tau_list.append_vec(
-                &ProofVerifier::_verify_primary_proof(&credential.pub_key.p_key,
+                &ProofVerifier::_verify_primary_proof(&credential.pub_key,
                                                       &proof.aggregated_proof.c_hash,
                                                       &proof_item.primary_proof,
+                                                      &proof.common_m,
                                                       &credential.credential_schema,
                                                       &credential.non_credential_schema,
-                                                      &credential.sub_proof_request)?
+                                                      &credential.sub_proof_request,
+                                                      &credential.set_memberships)?
             )?;
         }
 
-        let mut values: Vec<Vec<u8>> = Vec::new();
-        values.extend_from_slice(&tau_list);
-        values.extend_from_slice(&proof.aggregated_proof.c_list);
-        values.push(nonce.to_bytes()?);
+        ProofVerifier::_check_c_list_consistency(proof, tau_list.len())?;
 
-        let c_hver = get_hash_as_int(&values)?;
+        let challenge = if proof.ver == SerializationVersion::LEGACY {
+            proof._recompute_challenge(HashBuilder::new()?, &tau_list, nonce)?
+        } else {
+            proof.recompute_challenge(&tau_list, nonce)?
+        };
 
-        info!(target: "anoncreds_service", "Verifier verify proof -> done");
+        trace!("ProofVerifier::recompute_challenge: <<< challenge: {:?}", challenge);
 
-        let valid = c_hver == proof.aggregated_proof.c_hash;
+        Ok(challenge)
+    }
 
-        trace!("ProofVerifier::verify: <<< valid: {:?}", valid);
+    /// 检查每个必须公开的属性至少在一个子证明中公开
+    fn _verify_required_revealed_attrs(required_revealed_attrs: &BTreeSet<String>,
+                                       proof: &Proof) -> bool {
+        required_revealed_attrs.iter()
+            .all(|attr| proof.proofs.iter().any(|sub_proof| sub_proof.primary_proof.eq_proof.revealed_attrs.contains_key(attr)))
+    }
 
-        Ok(valid)
+    /// 检查证明的不等式证明安全级别不低于要求的最低级别（没有不等式证明时不检查）
+    /// 级别已经包含在挑战中，响应大小在_check_ne_proof_structure中按级别检查
+    fn _verify_predicate_security(min_predicate_security: PredicateSecurityLevel,
+                                  proof: &Proof) -> Result<bool, IndyCryptoError> {
+        let has_ne_proofs = proof.proofs.iter()
+            .any(|sub_proof| sub_proof.primary_proof.all_ne_proofs().next().is_some());
+
+        Ok(!has_ne_proofs || proof.predicate_security >= min_predicate_security)
     }
-    
+
+    /// 检查公共属性（如link secret）在包含它的所有子证明中的m值是否相同
+    fn _verify_common_attributes(common_attributes: &BTreeSet<String>,
+                                 proof: &Proof) -> bool {
+        common_attributes.iter().all(|attr| {
+            let mut m_values = proof.proofs.iter()
+                .filter_map(|sub_proof| sub_proof.primary_proof.eq_proof.m.get(attr).or(proof.common_m.get(attr)));
+
+            match m_values.next() {
+                Some(m) => m_values.all(|other_m| other_m == m),
+                None => true
+            }
+        })
+    }
+
+    /// 检查相等约束的属性在两个子证明中的m值是否相同
+    /// 相同的m_tilde和相同的属性值给出相同的m值
+    fn _verify_attribute_equalities(attribute_equalities: &Vec<(usize, usize, String)>,
+                                    proof: &Proof) -> Result<bool, IndyCryptoError> {
+        for &(cred_index_a, cred_index_b, ref attr) in attribute_equalities.iter() {
+            let m_a = ProofVerifier::_get_hidden_attr_m(proof, cred_index_a, attr)?;
+            let m_b = ProofVerifier::_get_hidden_attr_m(proof, cred_index_b, attr)?;
+
+            if m_a != m_b {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn _get_hidden_attr_m<'a>(proof: &'a Proof, cred_index: usize, attr: &str) -> Result<&'a BigNumber, IndyCryptoError> {
+        let sub_proof = proof.proofs.get(cred_index)
+            .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Sub proof {} not found", cred_index)))?;
+
+        sub_proof.primary_proof.eq_proof.m.get(attr)
+            .or(proof.common_m.get(attr))
+            .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Hidden attribute '{}' not found in sub proof {}", attr, cred_index)))
+    }
+
     /// 检查add_sub_proof_request函数的数据完整性
     fn _check_add_sub_proof_request_params_consistency(sub_proof_request: &SubProofRequest,
                                                        cred_schema: &CredentialSchema) -> Result<(), IndyCryptoError> {
         trace!("ProofVerifier::_check_add_sub_proof_request_params_consistency: >>> sub_proof_request: {:?}, cred_schema: {:?}", sub_proof_request, cred_schema);
 
-        if sub_proof_request.revealed_attrs.difference(&cred_schema.attrs).count() != 0 {
-            return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't contain requested attribute")));
+        if let Some(attr) = sub_proof_request.revealed_attrs.difference(&cred_schema.attrs).next() {
+            return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't contain requested attribute {:?}", attr)));
         }
 
-        let predicates_attrs =
-            sub_proof_request.predicates.iter()
-                .map(|predicate| predicate.attr_name.clone())
-                .collect::<BTreeSet<String>>();
+        if let Some(predicate) = sub_proof_request.predicates.iter().find(|predicate| !cred_schema.attrs.contains(&predicate.attr_name)) {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Credential doesn't contain attribute {:?} requested in predicate", predicate.attr_name)));
+        }
 
-        if predicates_attrs.difference(&cred_schema.attrs).count() != 0 {
-            return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't contain attribute requested in predicate")));
+        if let Some(predicate) = sub_proof_request.predicates.iter().find(|predicate| cred_schema.public_attrs.contains(&predicate.attr_name)) {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Public attribute {:?} can't be used in predicate", predicate.attr_name)));
+        }
+
+        for linear_predicate in sub_proof_request.linear_predicates.iter() {
+            if let Some(attr) = linear_predicate.coeffs.keys().find(|attr| !cred_schema.attrs.contains(*attr)) {
+                return Err(IndyCryptoError::InvalidStructure(
+                    format!("Credential doesn't contain attribute {:?} requested in linear predicate", attr)));
+            }
+
+            if let Some(attr) = linear_predicate.coeffs.keys().find(|attr| cred_schema.public_attrs.contains(*attr)) {
+                return Err(IndyCryptoError::InvalidStructure(
+                    format!("Public attribute {:?} can't be used in linear predicate", attr)));
+            }
+        }
+
+        if let Some(attr) = cred_schema.public_attrs.difference(&sub_proof_request.revealed_attrs).next() {
+            return Err(IndyCryptoError::InvalidStructure(format!("Public attribute {:?} can't be hidden", attr)));
         }
 
         trace!("ProofVerifier::_check_add_sub_proof_request_params_consistency: <<<");
@@ -254,11 +625,33 @@ impl ProofVerifier {
 
     /// 检查verify函数的数据完整性
     /// 主要包括验证数据长度、数据逐项对比得出的
-    fn _check_verify_params_consistency(credentials: &Vec<VerifiableCredential>,
+    /// 按照key_id为每个子证明选择对应的凭证（相同key_id的凭证按添加顺序匹配）
+    fn _match_credentials<'a>(credentials: &'a Vec<VerifiableCredential>,
+                              proof: &Proof) -> Result<Vec<&'a VerifiableCredential>, IndyCryptoError> {
+        if proof.proofs.len() != credentials.len() {
+            return Err(IndyCryptoError::AnoncredsProofRejected(
+                format!("Proof contains {} sub proofs, but {} sub proof requests were added", proof.proofs.len(), credentials.len())));
+        }
+
+        let mut used = vec![false; credentials.len()];
+        let mut matched = Vec::new();
+
+        for sub_proof in proof.proofs.iter() {
+            let idx = (0..credentials.len())
+                .find(|&idx| !used[idx] && credentials[idx].key_id == sub_proof.key_id)
+                .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Sub proof request for key {:?} not found", sub_proof.key_id)))?;
+
+            used[idx] = true;
+            matched.push(&credentials[idx]);
+        }
+
+        Ok(matched)
+    }
+
+    fn _check_verify_params_consistency(credentials: &[&VerifiableCredential],
                                         proof: &Proof) -> Result<(), IndyCryptoError> {
         trace!("ProofVerifier::_check_verify_params_consistency: >>> credentials: {:?}, proof: {:?}", credentials, proof);
 
-        assert_eq!(proof.proofs.len(), credentials.len()); //FIXME return error
         for idx in 0..proof.proofs.len() {
             let proof_for_credential = &proof.proofs[idx];
             let credential = &credentials[idx];
@@ -269,14 +662,43 @@ impl ProofVerifier {
                 return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof revealed attributes not correspond to requested attributes")));
             }
 
+            // Inequality proofs must follow canonical (sorted) order of predicates, as c_list does
             let proof_predicates =
                 proof_for_credential.primary_proof.ne_proofs.iter()
                     .map(|ne_proof| ne_proof.predicate.clone())
-                    .collect::<BTreeSet<Predicate>>();
+                    .collect::<Vec<Predicate>>();
 
-            if proof_predicates != credential.sub_proof_request.predicates {
+            if proof_predicates != credential.sub_proof_request.ne_predicates().into_iter().collect::<Vec<Predicate>>() {
                 return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof predicates not correspond to requested predicates")));
             }
+
+            let proof_linear_predicates =
+                proof_for_credential.primary_proof.linear_ne_proofs.iter()
+                    .map(|linear_ne_proof| linear_ne_proof.predicate.clone())
+                    .collect::<Vec<LinearPredicate>>();
+
+            if proof_linear_predicates != credential.sub_proof_request.linear_predicates.iter().cloned().collect::<Vec<LinearPredicate>>() {
+                return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof linear predicates not correspond to requested linear predicates")));
+            }
+
+            if proof_for_credential.primary_proof.linear_ne_proofs.iter()
+                .any(|linear_ne_proof| linear_ne_proof.ne_proof.predicate != linear_ne_proof.predicate.as_predicate()) {
+                return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof linear predicate not correspond to its inequality proof")));
+            }
+
+            let proof_set_memberships_attrs =
+                proof_for_credential.primary_proof.set_membership_proofs.iter()
+                    .map(|set_membership_proof| &set_membership_proof.attr_name)
+                    .collect::<Vec<&String>>();
+
+            let requested_set_memberships_attrs =
+                credential.set_memberships.iter()
+                    .map(|&(ref attr, _)| attr)
+                    .collect::<Vec<&String>>();
+
+            if proof_set_memberships_attrs != requested_set_memberships_attrs {
+                return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof set memberships not correspond to requested set memberships")));
+            }
         }
 
         trace!("ProofVerifier::_check_verify_params_consistency: <<<");
@@ -284,10 +706,136 @@ impl ProofVerifier {
         Ok(())
     }
     
+    /// 在使用之前检查相等证明中的剩余类元素，a_prime必须在[0, n)范围内
+    /// 未约简的a_prime（例如a_prime + n）在某些验证等式中仍可能被接受，削弱可靠性
+    fn _check_eq_proof_structure(p_pub_key: &CredentialPrimaryPublicKey,
+                                 proof: &PrimaryEqualProof) -> Result<(), IndyCryptoError> {
+        trace!("ProofVerifier::_check_eq_proof_structure: >>> p_pub_key: {:?}, proof: {:?}", p_pub_key, proof);
+
+        if proof.a_prime.is_negative() || proof.a_prime >= p_pub_key.n {
+            return Err(IndyCryptoError::InvalidStructure(format!("Proof a_prime is out of range [0, n)")));
+        }
+
+        trace!("ProofVerifier::_check_eq_proof_structure: <<<");
+
+        Ok(())
+    }
+
+    /// 在幂运算之前检查不等式证明的结构，格式错误的证明被快速拒绝
+    /// u_i = u_tilde_i + c * u_i，u_i来自delta的四平方分解，所以u_i非负且不比u_tilde长1位以上
+    /// r_i = r_tilde_i + c * r_i，不比r_tilde和c * r_i中较长的一个长1位以上
+    /// alpha = alpha_tilde + c * (r_delta - sum(u_i * r_i))，第二项更短，所以不比alpha_tilde长1位以上
+    /// u_tilde、r_tilde、alpha_tilde的大小由证明中的安全级别决定
+    /// T_i和T_delta必须在(1, n)范围内
+    fn _check_ne_proof_structure(p_pub_key: &CredentialPrimaryPublicKey,
+                                 proof: &PrimaryPredicateInequalityProof,
+                                 predicate_security: PredicateSecurityLevel) -> Result<(), IndyCryptoError> {
+        trace!("ProofVerifier::_check_ne_proof_structure: >>> p_pub_key: {:?}, proof: {:?}, predicate_security: {:?}",
+               p_pub_key, proof, predicate_security);
+
+        let predicate_failed = |description: String|
+            IndyCryptoError::AnoncredsPredicateFailed(format!("Predicate on {:?}: {}", proof.predicate.attr_name, description));
+
+        let u_keys = (0..ITERATION).map(|i| i.to_string()).collect::<BTreeSet<String>>();
+        let mut r_keys = u_keys.clone();
+        r_keys.insert("DELTA".to_string());
+
+        if BTreeSet::from_iter(proof.u.keys().cloned()) != u_keys {
+            return Err(predicate_failed("invalid set of u values".to_string()));
+        }
+
+        if BTreeSet::from_iter(proof.r.keys().cloned()) != r_keys {
+            return Err(predicate_failed("invalid set of r values".to_string()));
+        }
+
+        if BTreeSet::from_iter(proof.t.keys().cloned()) != r_keys {
+            return Err(predicate_failed("invalid set of t values".to_string()));
+        }
+
+        let u_max_bits = predicate_security.u_tilde_size() + 1;
+
+        for (key, u) in proof.u.iter() {
+            if u.is_negative() || u.num_bits()? as usize > u_max_bits {
+                return Err(predicate_failed(format!("u[{}] is out of range", key)));
+            }
+        }
+
+        let r_max_bits = max(predicate_security.r_tilde_size(), LARGE_CHALLENGE + LARGE_VPRIME) + 1;
+
+        for (key, r) in proof.r.iter() {
+            if r.is_negative() || r.num_bits()? as usize > r_max_bits {
+                return Err(predicate_failed(format!("r[{}] is out of range", key)));
+            }
+        }
+
+        if proof.alpha.num_bits()? as usize > predicate_security.alpha_tilde_size() + 1 {
+            return Err(predicate_failed("alpha is out of range".to_string()));
+        }
+
+        for (key, t) in proof.t.iter() {
+            if *t <= *BIGNUMBER_1 || *t >= p_pub_key.n {
+                return Err(predicate_failed(format!("t[{}] is out of range", key)));
+            }
+        }
+
+        trace!("ProofVerifier::_check_ne_proof_structure: <<<");
+
+        Ok(())
+    }
+
+    /// 检查aggregated_proof.c_list与重建的tau_list逐项对应：
+    /// 每个子证明对应a_prime和披露承诺C，每个不等式证明（包括线性组合谓词的）对应T_0..T_3和T_delta，每个集合成员证明对应T，顺序与证明生成时一致
+    /// 多出或缺少的c_list元素不会被验证者重建，必须直接拒绝
+    fn _check_c_list_consistency(proof: &Proof,
+                                 tau_list_len: usize) -> Result<(), IndyCryptoError> {
+        trace!("ProofVerifier::_check_c_list_consistency: >>> proof: {:?}, tau_list_len: {:?}", proof, tau_list_len);
+
+        let mut expected_c_list: Vec<Vec<u8>> = Vec::new();
+        let mut expected_tau_list_len = 0;
+
+        for sub_proof in proof.proofs.iter() {
+            expected_c_list.push(sub_proof.primary_proof.eq_proof.a_prime.to_bytes()?);
+            expected_tau_list_len += 1;
+
+            for revealed_commitment in sub_proof.primary_proof.eq_proof.revealed_commitments.values() {
+                expected_c_list.push(revealed_commitment.commitment.to_bytes()?);
+                expected_tau_list_len += 1;
+            }
+
+            for ne_proof in sub_proof.primary_proof.all_ne_proofs() {
+                for key in (0..ITERATION).map(|i| i.to_string()).chain(Some("DELTA".to_string())) {
+                    let t = ne_proof.t.get(&key)
+                        .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Value by key '{}' not found in proof.t", key)))?;
+                    expected_c_list.push(t.to_bytes()?);
+                }
+                expected_tau_list_len += ITERATION + 2;
+            }
+
+            for set_membership_proof in sub_proof.primary_proof.set_membership_proofs.iter() {
+                expected_c_list.push(set_membership_proof.t.to_bytes()?);
+                expected_tau_list_len += 1 + set_membership_proof.c.len();
+            }
+        }
+
+        if tau_list_len != expected_tau_list_len || proof.aggregated_proof.c_list.len() != expected_c_list.len() {
+            return Err(IndyCryptoError::AnoncredsProofRejected(
+                format!("Proof c_list length {} doesn't match reconstructed length {} (tau_list length {}, expected {})",
+                        proof.aggregated_proof.c_list.len(), expected_c_list.len(), tau_list_len, expected_tau_list_len)));
+        }
+
+        if proof.aggregated_proof.c_list != expected_c_list {
+            return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof c_list not correspond to proof values")));
+        }
+
+        trace!("ProofVerifier::_check_c_list_consistency: <<<");
+
+        Ok(())
+    }
+
     /// 验证Primary凭证的正确性
     /// 
     /// 输入：
-    ///     CredentialPrimaryPublicKey
+    ///     PreparedPublicKey
     ///     c_H
     ///     PrimaryProof
     ///     CredentialSchema
@@ -298,24 +846,57 @@ impl ProofVerifier {
     ///     tau_list
     /// 
     /// 对应论文公式 4.41-4.46
-    fn _verify_primary_proof(p_pub_key: &CredentialPrimaryPublicKey,
+    fn _verify_primary_proof(pub_key: &PreparedPublicKey,
                              c_hash: &BigNumber,
                              primary_proof: &PrimaryProof,
+                             common_m: &BTreeMap<String, BigNumber>,
                              cred_schema: &CredentialSchema,
                              non_cred_schema: &NonCredentialSchema,
-                             sub_proof_request: &SubProofRequest) -> Result<Vec<BigNumber>, IndyCryptoError> {
-        trace!("ProofVerifier::_verify_primary_proof: >>> p_pub_key: {:?}, c_hash: {:?}, primary_proof: {:?}, cred_schema: {:?}, sub_proof_request: {:?}",
-               p_pub_key, c_hash, primary_proof, cred_schema, sub_proof_request);
+                             sub_proof_request: &SubProofRequest,
+                             set_memberships: &[(String, Vec<BigNumber>)]) -> Result<Vec<BigNumber>, IndyCryptoError> {
+        trace!("ProofVerifier::_verify_primary_proof: >>> pub_key: {:?}, c_hash: {:?}, primary_proof: {:?}, cred_schema: {:?}, sub_proof_request: {:?}, \
+                set_memberships: {:?}",
+               pub_key, c_hash, primary_proof, cred_schema, sub_proof_request, set_memberships);
 
-        let mut t_hat: Vec<BigNumber> = ProofVerifier::_verify_equality(p_pub_key,
+        let mut t_hat: Vec<BigNumber> = ProofVerifier::_verify_equality(pub_key,
                                                                         &primary_proof.eq_proof,
-                                                                         c_hash,
+                                                                        common_m,
+                                                                        c_hash,
                                                                         cred_schema,
                                                                         non_cred_schema,
                                                                         sub_proof_request)?;
 
         for ne_proof in primary_proof.ne_proofs.iter() {
-            t_hat.append(&mut ProofVerifier::_verify_ne_predicate(p_pub_key, ne_proof, c_hash)?)
+            t_hat.append(&mut ProofVerifier::_verify_ne_predicate(pub_key, ne_proof, c_hash)?)
+        }
+
+        if !primary_proof.linear_ne_proofs.is_empty() {
+            let m = primary_proof.eq_proof.expand_m(common_m)?;
+
+            for linear_ne_proof in primary_proof.linear_ne_proofs.iter() {
+                let mj = linear_ne_proof.predicate.combine(|attr_name| m.get(attr_name))
+                    .map_err(|err| IndyCryptoError::AnoncredsProofRejected(format!("Linear predicate can't be checked: {}", err)))?;
+
+                // m值的加权和把不等式证明与相等证明中的属性绑定
+                if mj != linear_ne_proof.ne_proof.mj {
+                    return Err(IndyCryptoError::AnoncredsProofRejected(
+                        format!("Linear predicate m not correspond to eq_proof.m")));
+                }
+
+                t_hat.append(&mut ProofVerifier::_verify_ne_predicate(pub_key, &linear_ne_proof.ne_proof, c_hash)?)
+            }
+        }
+
+        if !set_memberships.is_empty() {
+            let m = primary_proof.eq_proof.expand_m(common_m)?;
+
+            for (set_membership_proof, &(_, ref allowed_values)) in primary_proof.set_membership_proofs.iter().zip(set_memberships) {
+                let m_hat = m.get(&set_membership_proof.attr_name)
+                    .ok_or(IndyCryptoError::AnoncredsProofRejected(
+                        format!("Value by key '{}' not found in eq_proof.m", set_membership_proof.attr_name)))?;
+
+                t_hat.append(&mut ProofVerifier::_verify_set_membership(&pub_key.p_key, set_membership_proof, m_hat, allowed_values, c_hash)?)
+            }
         }
 
         trace!("ProofVerifier::_verify_primary_proof: <<< t_hat: {:?}", t_hat);
@@ -323,10 +904,64 @@ impl ProofVerifier {
         Ok(t_hat)
     }
 
+    /// 验证集合成员证明，输出Tau集合：Z^{m_hat} * S^{r_hat} * T^{-c_H}以及每个允许值分支的Tau
+    /// 分支挑战之和必须与c_H模2^LARGE_CHALLENGE相等，否则至少一个分支无法被模拟
+    fn _verify_set_membership(p_pub_key: &CredentialPrimaryPublicKey,
+                              proof: &PrimarySetMembershipProof,
+                              m_hat: &BigNumber,
+                              allowed_values: &[BigNumber],
+                              c_hash: &BigNumber) -> Result<Vec<BigNumber>, IndyCryptoError> {
+        trace!("ProofVerifier::_verify_set_membership: >>> p_pub_key: {:?}, proof: {:?}, m_hat: {:?}, allowed_values: {:?}, c_hash: {:?}",
+               p_pub_key, proof, m_hat, allowed_values, c_hash);
+
+        let rejected = |description: &str|
+            IndyCryptoError::AnoncredsProofRejected(format!("Set membership of {:?}: {}", proof.attr_name, description));
+
+        if proof.c.len() != allowed_values.len() || proof.s.len() != allowed_values.len() {
+            return Err(rejected("number of branches doesn't match number of allowed values"));
+        }
+
+        if proof.t <= *BIGNUMBER_1 || proof.t >= p_pub_key.n {
+            return Err(rejected("t is out of range"));
+        }
+
+        let mut ctx = BnCtxPool::take()?;
+
+        let mut c_sum = BigNumber::new()?;
+        for c in proof.c.iter() {
+            if c.is_negative() || *c >= *LARGE_CHALLENGE_VALUE {
+                return Err(rejected("branch challenge is out of range"));
+            }
+            c_sum = c_sum.add(c)?;
+        }
+
+        if c_sum.modulus(&LARGE_CHALLENGE_VALUE, Some(&mut ctx))? != c_hash.modulus(&LARGE_CHALLENGE_VALUE, Some(&mut ctx))? {
+            return Err(rejected("branch challenges don't sum up to the proof challenge"));
+        }
+
+        let mut tau_list = vec![
+            get_pedersen_commitment(&p_pub_key.z, m_hat, &p_pub_key.s, &proof.r, &p_pub_key.n, &mut ctx)?
+                .mod_mul(
+                    &proof.t.mod_exp(&c_hash.set_negative(true)?, &p_pub_key.n, Some(&mut ctx))?,
+                    &p_pub_key.n, Some(&mut ctx)
+                )?
+        ];
+
+        for ((value, c), s) in allowed_values.iter().zip(proof.c.iter()).zip(proof.s.iter()) {
+            tau_list.push(calc_tset(p_pub_key, &proof.t, value, c, s)?);
+        }
+
+        BnCtxPool::release(ctx);
+
+        trace!("ProofVerifier::_verify_set_membership: <<< tau_list: {:?}", tau_list);
+
+        Ok(tau_list)
+    }
+
     /// 验证Prc的正确性
     /// 
     /// 输入：
-    ///     CredentialPrimaryPublicKey
+    ///     PreparedPublicKey
     ///     PrimaryEqualProof
     ///     CredentialSchema
     ///     NonCredentialSchema
@@ -336,14 +971,15 @@ impl ProofVerifier {
     ///     \hat{T}
     /// 
     /// 对应论文公式4.41
-    fn _verify_equality(p_pub_key: &CredentialPrimaryPublicKey,
+    fn _verify_equality(pub_key: &PreparedPublicKey,
                         proof: &PrimaryEqualProof,
+                        common_m: &BTreeMap<String, BigNumber>,
                         c_hash: &BigNumber,
                         cred_schema: &CredentialSchema,
                         non_cred_schema: &NonCredentialSchema,
                         sub_proof_request: &SubProofRequest) -> Result<Vec<BigNumber>, IndyCryptoError> {
-        trace!("ProofVerifier::_verify_equality: >>> p_pub_key: {:?}, proof: {:?}, c_hash: {:?}, cred_schema: {:?}, sub_proof_request: {:?}",
-               p_pub_key, proof, c_hash, cred_schema, sub_proof_request);
+        trace!("ProofVerifier::_verify_equality: >>> pub_key: {:?}, proof: {:?}, c_hash: {:?}, cred_schema: {:?}, sub_proof_request: {:?}",
+               pub_key, proof, c_hash, cred_schema, sub_proof_request);
 
 
         let unrevealed_attrs = cred_schema
@@ -351,17 +987,23 @@ impl ProofVerifier {
             .union(&non_cred_schema.attrs)
             .cloned()
             .collect::<BTreeSet<String>>()
-            .difference(&sub_proof_request.revealed_attrs)
+            .difference(&sub_proof_request.known_attrs())
             .cloned()
             .collect::<HashSet<String>>();
 
-        let t1: BigNumber = calc_teq(&p_pub_key, &proof.a_prime, &proof.e, &proof.v, &proof.m, &unrevealed_attrs)?;
+        let p_pub_key = &pub_key.p_key;
+        let m = proof.expand_m(common_m)?;
 
-        let mut ctx = BigNumber::new_context()?;
+        let t1: BigNumber = calc_teq(pub_key, &proof.a_prime, &proof.e, &proof.v, &m, &unrevealed_attrs)?;
+
+        let mut ctx = BnCtxPool::take()?;
 
         let mut rar = proof.a_prime.mod_exp(&LARGE_E_START_VALUE, &p_pub_key.n, Some(&mut ctx))?;
 
-        for (attr, encoded_value) in &proof.revealed_attrs {
+        // EQ谓词的属性值对验证者已知，与披露属性一样计入rar
+        let eq_predicates_values = sub_proof_request.eq_predicates_values()?;
+
+        for (attr, encoded_value) in proof.revealed_attrs.iter().chain(eq_predicates_values.iter()) {
             let cur_r = p_pub_key.r.get(attr)
                 .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Value by key '{}' not found in pk.r", attr)))?;
 
@@ -370,22 +1012,44 @@ impl ProofVerifier {
                 .mod_mul(&rar, &p_pub_key.n, Some(&mut ctx))?;
         }
 
-        let t2: BigNumber = p_pub_key.z
-            .mod_div(&rar, &p_pub_key.n, Some(&mut ctx))?
-            .inverse(&p_pub_key.n, Some(&mut ctx))?
+        // (Z / rar)^(-c) = (rar * Z^(-1))^c, Z^(-1)预先计算在PreparedPublicKey中
+        let t2: BigNumber = rar
+            .mod_mul(&pub_key.z_inverse, &p_pub_key.n, Some(&mut ctx))?
             .mod_exp(&c_hash, &p_pub_key.n, Some(&mut ctx))?;
 
         let t: BigNumber = t1.mod_mul(&t2, &p_pub_key.n, Some(&mut ctx))?;
 
-        trace!("ProofVerifier::_verify_equality: <<< t: {:?}", t);
+        let mut tau_list = vec![t];
+
+        // 披露承诺：Z^{m_hat} * S^{salt_hat} * C^{-c_H}
+        for (attr, revealed_commitment) in proof.revealed_commitments.iter() {
+            let m_hat = m.get(attr)
+                .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Value by key '{}' not found in eq_proof.m", attr)))?;
 
-        Ok(vec![t])
+            if revealed_commitment.commitment <= *BIGNUMBER_1 || revealed_commitment.commitment >= p_pub_key.n {
+                return Err(IndyCryptoError::AnoncredsProofRejected(format!("Revealed commitment of {:?} is out of range", attr)));
+            }
+
+            tau_list.push(
+                get_pedersen_commitment(&p_pub_key.z, m_hat, &p_pub_key.s, &revealed_commitment.salt, &p_pub_key.n, &mut ctx)?
+                    .mod_mul(
+                        &revealed_commitment.commitment.mod_exp(&c_hash.set_negative(true)?, &p_pub_key.n, Some(&mut ctx))?,
+                        &p_pub_key.n, Some(&mut ctx)
+                    )?
+            );
+        }
+
+        BnCtxPool::release(ctx);
+
+        trace!("ProofVerifier::_verify_equality: <<< tau_list: {:?}", tau_list);
+
+        Ok(tau_list)
     }
 
     /// 验证Prp的正确性
     /// 
     /// 输入：
-    ///     CredentialPrimaryPublicKey
+    ///     PreparedPublicKey
     ///     PrimaryPredicateInequalityProof
     ///     c_H    
     /// 
@@ -393,15 +1057,16 @@ impl ProofVerifier {
     ///     tau_list
     /// 
     /// 对应论文4.42-4.46
-    fn _verify_ne_predicate(p_pub_key: &CredentialPrimaryPublicKey,
+    fn _verify_ne_predicate(pub_key: &PreparedPublicKey,
                             proof: &PrimaryPredicateInequalityProof,
                             c_hash: &BigNumber) -> Result<Vec<BigNumber>, IndyCryptoError> {
-        trace!("ProofVerifier::_verify_ne_predicate: >>> p_pub_key: {:?}, proof: {:?}, c_hash: {:?}", p_pub_key, proof, c_hash);
+        trace!("ProofVerifier::_verify_ne_predicate: >>> pub_key: {:?}, proof: {:?}, c_hash: {:?}", pub_key, proof, c_hash);
 
-        let mut ctx = BigNumber::new_context()?;
+        let p_pub_key = &pub_key.p_key;
+        let mut ctx = BnCtxPool::take()?;
 
         // 公式4.44，4.45，4.46的后半段
-        let mut tau_list = calc_tne(&p_pub_key, &proof.u, &proof.r, &proof.mj,
+        let mut tau_list = calc_tne(pub_key, &proof.u, &proof.r, &proof.mj,
                                     &proof.alpha, &proof.t, proof.predicate.is_less())?;
         
         // 公式4.44前半段*后半段
@@ -439,6 +1104,8 @@ impl ProofVerifier {
             .inverse(&p_pub_key.n, Some(&mut ctx))?
             .mod_mul(&tau_list[ITERATION + 1], &p_pub_key.n, Some(&mut ctx))?;
 
+        BnCtxPool::release(ctx);
+
         trace!("ProofVerifier::_verify_ne_predicate: <<< tau_list: {:?},", tau_list);
 
         Ok(tau_list)
@@ -450,6 +1117,7 @@ mod tests {
     use super::*;
     use cl::prover;
     use cl::issuer;
+    use cl::constants::{MAX_ATTRS_COUNT, MAX_PREDICATES_COUNT};
     use cl::helpers::MockHelper;
     use cl::prover::mocks::*;
 
@@ -464,13 +1132,206 @@ mod tests {
         assert!(sub_proof_request.predicates.contains(&predicate()));
     }
 
+    #[test]
+    fn sub_proof_request_builder_works_for_revealed_attr_used_in_predicate() {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("age").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+
+        let res = sub_proof_request_builder.finalize();
+        assert!(res.is_err())
+    }
+
+    #[test]
+    fn add_sub_proof_request_works_for_predicate_on_unknown_attr() {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("weight", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        let res = proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                       &issuer::mocks::credential_schema(),
+                                                       &issuer::mocks::non_credential_schema(),
+                                                       issuer::mocks::CREDENTIAL_KEY_ID,
+                                                       &issuer::mocks::credential_public_key());
+        match res {
+            Err(IndyCryptoError::InvalidStructure(err)) => assert!(err.contains("weight")),
+            _ => panic!("InvalidStructure error expected")
+        }
+    }
+
+    #[test]
+    fn add_sub_proof_request_works_for_revealed_attr_not_in_schema() {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("weight").unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        let res = proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                       &issuer::mocks::credential_schema(),
+                                                       &issuer::mocks::non_credential_schema(),
+                                                       issuer::mocks::CREDENTIAL_KEY_ID,
+                                                       &issuer::mocks::credential_public_key());
+        match res {
+            Err(IndyCryptoError::InvalidStructure(err)) => assert!(err.contains("weight")),
+            _ => panic!("InvalidStructure error expected")
+        }
+    }
+
+    #[test]
+    fn add_sub_proof_request_works_for_public_attrs_policy() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_public_attr("name").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        credential_schema_builder.add_attr("height").unwrap();
+        credential_schema_builder.add_attr("sex").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &issuer::mocks::non_credential_schema(),
+                                             issuer::mocks::CREDENTIAL_KEY_ID,
+                                             &issuer::mocks::credential_public_key()).unwrap();
+    }
+
+    #[test]
+    fn add_sub_proof_request_works_for_predicate_on_public_attr() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.add_public_attr("age").unwrap();
+        credential_schema_builder.add_attr("height").unwrap();
+        credential_schema_builder.add_attr("sex").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        let res = proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                       &credential_schema,
+                                                       &issuer::mocks::non_credential_schema(),
+                                                       issuer::mocks::CREDENTIAL_KEY_ID,
+                                                       &issuer::mocks::credential_public_key());
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn add_sub_proof_request_works_for_hidden_public_attr() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_public_attr("name").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        credential_schema_builder.add_attr("height").unwrap();
+        credential_schema_builder.add_attr("sex").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        let res = proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                       &credential_schema,
+                                                       &issuer::mocks::non_credential_schema(),
+                                                       issuer::mocks::CREDENTIAL_KEY_ID,
+                                                       &issuer::mocks::credential_public_key());
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn add_sub_proof_request_works_for_public_key_not_correspond_to_schema() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        credential_schema_builder.add_attr("height").unwrap();
+        credential_schema_builder.add_attr("weight").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        let res = proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                       &credential_schema,
+                                                       &issuer::mocks::non_credential_schema(),
+                                                       issuer::mocks::CREDENTIAL_KEY_ID,
+                                                       &issuer::mocks::credential_public_key());
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    fn _age_predicates_sub_proof_request(count: i32) -> SubProofRequest {
+        SubProofRequest {
+            revealed_attrs: BTreeSet::new(),
+            predicates: (0..count).map(|value| Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GE, value }).collect(),
+            linear_predicates: BTreeSet::new(),
+            bitlength_predicates: BTreeMap::new()
+        }
+    }
+
+    #[test]
+    fn add_sub_proof_request_works_for_predicates_limit() {
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&_age_predicates_sub_proof_request(MAX_PREDICATES_COUNT as i32),
+                                             &issuer::mocks::credential_schema(),
+                                             &issuer::mocks::non_credential_schema(),
+                                             issuer::mocks::CREDENTIAL_KEY_ID,
+                                             &issuer::mocks::credential_public_key()).unwrap();
+
+        let res = proof_verifier.add_sub_proof_request(&_age_predicates_sub_proof_request(MAX_PREDICATES_COUNT as i32 + 1),
+                                                       &issuer::mocks::credential_schema(),
+                                                       &issuer::mocks::non_credential_schema(),
+                                                       issuer::mocks::CREDENTIAL_KEY_ID,
+                                                       &issuer::mocks::credential_public_key());
+        match res {
+            Err(IndyCryptoError::InvalidStructure(err)) => assert!(err.contains("predicates")),
+            _ => panic!("InvalidStructure error expected")
+        }
+    }
+
+    #[test]
+    fn add_sub_proof_request_works_for_custom_limits() {
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap()
+            .with_limits(SizeLimits::new(MAX_ATTRS_COUNT, 2).unwrap()).unwrap();
+
+        proof_verifier.add_sub_proof_request(&_age_predicates_sub_proof_request(2),
+                                             &issuer::mocks::credential_schema(),
+                                             &issuer::mocks::non_credential_schema(),
+                                             issuer::mocks::CREDENTIAL_KEY_ID,
+                                             &issuer::mocks::credential_public_key()).unwrap();
+
+        let res = proof_verifier.add_sub_proof_request(&_age_predicates_sub_proof_request(3),
+                                                       &issuer::mocks::credential_schema(),
+                                                       &issuer::mocks::non_credential_schema(),
+                                                       issuer::mocks::CREDENTIAL_KEY_ID,
+                                                       &issuer::mocks::credential_public_key());
+        assert!(res.is_err());
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap()
+            .with_limits(SizeLimits::new(3, MAX_PREDICATES_COUNT).unwrap()).unwrap();
+
+        // mock credential schema contains 4 attributes
+        let res = proof_verifier.add_sub_proof_request(&_age_predicates_sub_proof_request(1),
+                                                       &issuer::mocks::credential_schema(),
+                                                       &issuer::mocks::non_credential_schema(),
+                                                       issuer::mocks::CREDENTIAL_KEY_ID,
+                                                       &issuer::mocks::credential_public_key());
+        assert!(res.is_err());
+    }
+
     //FIXME: test error
     #[test]
     fn verify_equality_works() {
         MockHelper::inject();
 
         let proof = prover::mocks::eq_proof();
-        let pk = issuer::mocks::credential_primary_public_key();
+        let pk = PreparedPublicKey::new(&issuer::mocks::credential_public_key()).unwrap();
         let c_h = prover::mocks::aggregated_proof().c_hash;
         let credential_schema = issuer::mocks::credential_schema();
         let non_credential_schema = issuer::mocks::non_credential_schema();
@@ -481,6 +1342,7 @@ mod tests {
 
         let res: Vec<BigNumber> = ProofVerifier::_verify_equality(&pk,
                                                                   &proof,
+                                                                  &BTreeMap::new(),
                                                                   &c_h,
                                                                   &credential_schema,
                                                                   &non_credential_schema,
@@ -489,13 +1351,54 @@ mod tests {
         assert_eq!("24735941777895529105404791875677543193768790809044401882213176069297746596979908303045602781737273082325834321313102509105261035350172857739519848575665507246590968635569697846017522027350227113786826534000327321925751471543441335011436516936908551111872665325183937529233459517434872865188836825197568138101088329512606597175637083157790106170810113929317513223926839486848824617767537866976952033271311058437391529262575662520038666412921806596059429973742472709048576355721805055483994170222252078224605850854735401965559215984156252015804210704887914024713943308918331978124221492540200419602908463972950379120737", res[0].to_dec().unwrap());
     }
 
+    #[test]
+    fn _check_eq_proof_structure_works() {
+        let proof = prover::mocks::eq_proof();
+        let pk = issuer::mocks::credential_primary_public_key();
+
+        assert!(ProofVerifier::_check_eq_proof_structure(&pk, &proof).is_ok());
+    }
+
+    #[test]
+    fn _check_eq_proof_structure_works_for_non_reduced_a_prime() {
+        let mut proof = prover::mocks::eq_proof();
+        let pk = issuer::mocks::credential_primary_public_key();
+        proof.a_prime = proof.a_prime.add(&pk.n).unwrap();
+
+        let res = ProofVerifier::_check_eq_proof_structure(&pk, &proof);
+        assert!(match res { Err(IndyCryptoError::InvalidStructure(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn _check_ne_proof_structure_works_for_missing_u_value() {
+        let mut proof = prover::mocks::ne_proof();
+        proof.u.remove("3");
+        let pk = issuer::mocks::credential_primary_public_key();
+
+        let res = ProofVerifier::_check_ne_proof_structure(&pk, &proof, PredicateSecurityLevel::DEFAULT);
+        assert!(match res { Err(IndyCryptoError::AnoncredsPredicateFailed(_)) => true, _ => false });
+    }
+
+    #[test]
+    fn _check_ne_proof_structure_works_for_alpha_above_security_level() {
+        let mut proof = prover::mocks::ne_proof();
+        let pk = issuer::mocks::credential_primary_public_key();
+        let level_2 = PredicateSecurityLevel::new(2).unwrap();
+        proof.alpha.set_bit(level_2.alpha_tilde_size() as i32 - 1).unwrap();
+
+        ProofVerifier::_check_ne_proof_structure(&pk, &proof, level_2).unwrap();
+
+        let res = ProofVerifier::_check_ne_proof_structure(&pk, &proof, PredicateSecurityLevel::DEFAULT);
+        assert!(match res { Err(IndyCryptoError::AnoncredsPredicateFailed(_)) => true, _ => false });
+    }
+
     #[test]
     fn _verify_ne_predicate_works() {
         MockHelper::inject();
 
         let proof = prover::mocks::ne_proof();
         let c_h = prover::mocks::aggregated_proof().c_hash;
-        let pk = issuer::mocks::credential_primary_public_key();
+        let pk = PreparedPublicKey::new(&issuer::mocks::credential_public_key()).unwrap();
 
         let res = ProofVerifier::_verify_ne_predicate(&pk, &proof, &c_h);
 
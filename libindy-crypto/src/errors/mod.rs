@@ -50,6 +50,7 @@ pub enum ErrorCode
     CommonInvalidParam12 = 111,
 
     // Invalid library state was detected in runtime. It signals library bug
+    // or concurrent mutation of the same object through FFI (detected in debug builds only)
     CommonInvalidState = 112,
 
     // Object (json, config, key, credential and etc...) passed by library caller has invalid structure
@@ -69,6 +70,18 @@ pub enum ErrorCode
 
     // Proof rejected
     AnoncredsProofRejected = 118,
+
+    // Predicate proof is malformed
+    AnoncredsPredicateFailed = 119,
+
+    // Long running operation was cancelled by caller
+    CommonOperationCancelled = 120,
+
+    // Credential signature doesn't correspond to credential values, public key or signature correctness proof
+    AnoncredsCredentialSignatureInvalid = 121,
+
+    // Credential values don't satisfy requested predicate, so proof can't be built
+    AnoncredsPredicateNotSatisfied = 122,
 }
 
 pub trait ToErrorCode {
@@ -93,6 +106,10 @@ pub trait ToErrorCode {
     AnoncredsInvalidRevocationAccumulatorIndex(String),
     AnoncredsCredentialRevoked(String),
     AnoncredsProofRejected(String),
+    AnoncredsPredicateFailed(String),
+    OperationCancelled(String),
+    AnoncredsCredentialSignatureInvalid(String),
+    AnoncredsPredicateNotSatisfied(String),
 }
 
 impl fmt::Display for IndyCryptoError {
@@ -114,6 +131,10 @@ impl fmt::Display for IndyCryptoError {
             IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(ref description) => write!(f, "Invalid revocation accumulator index: {}", description),
             IndyCryptoError::AnoncredsCredentialRevoked(ref description) => write!(f, "Credential revoked: {}", description),
             IndyCryptoError::AnoncredsProofRejected(ref description) => write!(f, "Proof rejected: {}", description),
+            IndyCryptoError::AnoncredsPredicateFailed(ref description) => write!(f, "Predicate failed: {}", description),
+            IndyCryptoError::OperationCancelled(ref description) => write!(f, "Operation cancelled: {}", description),
+            IndyCryptoError::AnoncredsCredentialSignatureInvalid(ref description) => write!(f, "Credential signature invalid: {}", description),
+            IndyCryptoError::AnoncredsPredicateNotSatisfied(ref description) => write!(f, "Predicate not satisfied: {}", description),
         }
     }
 }
@@ -137,6 +158,10 @@ impl Error for IndyCryptoError {
             IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(ref description) => description,
             IndyCryptoError::AnoncredsCredentialRevoked(ref description) => description,
             IndyCryptoError::AnoncredsProofRejected(ref description) => description,
+            IndyCryptoError::AnoncredsPredicateFailed(ref description) => description,
+            IndyCryptoError::OperationCancelled(ref description) => description,
+            IndyCryptoError::AnoncredsCredentialSignatureInvalid(ref description) => description,
+            IndyCryptoError::AnoncredsPredicateNotSatisfied(ref description) => description,
         }
     }
 
@@ -158,6 +183,10 @@ impl Error for IndyCryptoError {
             IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(_) => None,
             IndyCryptoError::AnoncredsCredentialRevoked(_) => None,
             IndyCryptoError::AnoncredsProofRejected(_) => None,
+            IndyCryptoError::AnoncredsPredicateFailed(_) => None,
+            IndyCryptoError::OperationCancelled(_) => None,
+            IndyCryptoError::AnoncredsCredentialSignatureInvalid(_) => None,
+            IndyCryptoError::AnoncredsPredicateNotSatisfied(_) => None,
         }
     }
 }
@@ -181,6 +210,10 @@ impl ToErrorCode for IndyCryptoError {
             IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(_) => ErrorCode::AnoncredsInvalidRevocationAccumulatorIndex,
             IndyCryptoError::AnoncredsCredentialRevoked(_) => ErrorCode::AnoncredsCredentialRevoked,
             IndyCryptoError::AnoncredsProofRejected(_) => ErrorCode::AnoncredsProofRejected,
+            IndyCryptoError::AnoncredsPredicateFailed(_) => ErrorCode::AnoncredsPredicateFailed,
+            IndyCryptoError::OperationCancelled(_) => ErrorCode::CommonOperationCancelled,
+            IndyCryptoError::AnoncredsCredentialSignatureInvalid(_) => ErrorCode::AnoncredsCredentialSignatureInvalid,
+            IndyCryptoError::AnoncredsPredicateNotSatisfied(_) => ErrorCode::AnoncredsPredicateNotSatisfied,
         }
     }
 }
@@ -195,4 +228,41 @@ impl From<log::SetLoggerError> for IndyCryptoError {
     fn from(err: log::SetLoggerError) -> IndyCryptoError{
         IndyCryptoError::InvalidState(err.description().to_owned())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_error_code_works() {
+        let description = || "description".to_string();
+
+        let cases = vec![
+            (IndyCryptoError::InvalidParam1(description()), ErrorCode::CommonInvalidParam1),
+            (IndyCryptoError::InvalidParam2(description()), ErrorCode::CommonInvalidParam2),
+            (IndyCryptoError::InvalidParam3(description()), ErrorCode::CommonInvalidParam3),
+            (IndyCryptoError::InvalidParam4(description()), ErrorCode::CommonInvalidParam4),
+            (IndyCryptoError::InvalidParam5(description()), ErrorCode::CommonInvalidParam5),
+            (IndyCryptoError::InvalidParam6(description()), ErrorCode::CommonInvalidParam6),
+            (IndyCryptoError::InvalidParam7(description()), ErrorCode::CommonInvalidParam7),
+            (IndyCryptoError::InvalidParam8(description()), ErrorCode::CommonInvalidParam8),
+            (IndyCryptoError::InvalidParam9(description()), ErrorCode::CommonInvalidParam9),
+            (IndyCryptoError::InvalidState(description()), ErrorCode::CommonInvalidState),
+            (IndyCryptoError::InvalidStructure(description()), ErrorCode::CommonInvalidStructure),
+            (IndyCryptoError::IOError(io::Error::new(io::ErrorKind::Other, description())), ErrorCode::CommonIOError),
+            (IndyCryptoError::AnoncredsRevocationAccumulatorIsFull(description()), ErrorCode::AnoncredsRevocationAccumulatorIsFull),
+            (IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(description()), ErrorCode::AnoncredsInvalidRevocationAccumulatorIndex),
+            (IndyCryptoError::AnoncredsCredentialRevoked(description()), ErrorCode::AnoncredsCredentialRevoked),
+            (IndyCryptoError::AnoncredsProofRejected(description()), ErrorCode::AnoncredsProofRejected),
+            (IndyCryptoError::AnoncredsPredicateFailed(description()), ErrorCode::AnoncredsPredicateFailed),
+            (IndyCryptoError::OperationCancelled(description()), ErrorCode::CommonOperationCancelled),
+            (IndyCryptoError::AnoncredsCredentialSignatureInvalid(description()), ErrorCode::AnoncredsCredentialSignatureInvalid),
+            (IndyCryptoError::AnoncredsPredicateNotSatisfied(description()), ErrorCode::AnoncredsPredicateNotSatisfied),
+        ];
+
+        for (err, error_code) in cases {
+            assert_eq!(error_code, err.to_error_code());
+        }
+    }
+}
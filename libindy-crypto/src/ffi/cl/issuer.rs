@@ -7,6 +7,7 @@ use libc::c_char;
 
 use serde_json;
 use std::os::raw::c_void;
+use std::ptr;
 
 
 /// Creates and returns credential definition (public and private keys, correctness proof) entities.
@@ -73,8 +74,83 @@ pub extern fn cl_issuer_new_credential_def(credential_schema: *const c_void,
     res
 }
 
+#[derive(Serialize)]
+struct CredentialDefinitionBundle<'a> {
+    credential_pub_key: &'a CredentialPublicKey,
+    credential_key_correctness_proof: &'a CredentialKeyCorrectnessProof
+}
+
+/// Creates credential definition and returns its public part (public key and key correctness proof)
+/// as a single json bundle `{"credential_pub_key": {..}, "credential_key_correctness_proof": {..}}`.
+///
+/// Credential private key is returned as a separate json to avoid storing it together with public data.
+///
+/// Note: Returned credential definition bundle json must be deallocated by calling cl_free_string.
+/// Credential private key json contains secret values so it must be deallocated
+/// by calling cl_free_secret_str that wipes memory before releasing it.
+///
+/// # Arguments
+/// * `credential_schema` - Reference that contains credential schema instance pointer.
+/// * `non_credential_schema` - Reference that contains non credential schema instance pointer
+/// * `credential_def_bundle_json_p` - Reference that will contain credential public key and key correctness proof json.
+/// * `credential_priv_key_json_p` - Reference that will contain credential private key json.
+#[no_mangle]
+pub extern fn cl_issuer_new_credential_def_bundle(credential_schema: *const c_void,
+                                                  non_credential_schema: *const c_void,
+                                                  credential_def_bundle_json_p: *mut *const c_char,
+                                                  credential_priv_key_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_issuer_new_credential_def_bundle: >>> credential_schema: {:?}, \
+                                                         non_credential_schema: {:?}, \
+                                                         credential_def_bundle_json_p: {:?}, \
+                                                         credential_priv_key_json_p: {:?}",
+           credential_schema,
+           non_credential_schema,
+           credential_def_bundle_json_p,
+           credential_priv_key_json_p);
+
+    check_useful_c_reference!(credential_schema, CredentialSchema, ErrorCode::CommonInvalidParam1);
+    check_useful_c_reference!(non_credential_schema, NonCredentialSchema, ErrorCode::CommonInvalidParam2);
+    check_useful_c_ptr!(credential_def_bundle_json_p, ErrorCode::CommonInvalidParam3);
+    check_useful_c_ptr!(credential_priv_key_json_p, ErrorCode::CommonInvalidParam4);
+
+    trace!("cl_issuer_new_credential_def_bundle: entities: \
+                                                     credential_schema: {:?}, \
+                                                     non_credential_schema: {:?}", credential_schema, non_credential_schema);
+
+    let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+        match Issuer::new_credential_def(credential_schema, non_credential_schema) {
+            Ok(credential_def) => credential_def,
+            Err(err) => return err.to_error_code()
+        };
+
+    let credential_def_bundle = CredentialDefinitionBundle {
+        credential_pub_key: &credential_pub_key,
+        credential_key_correctness_proof: &credential_key_correctness_proof
+    };
+
+    let res = match (serde_json::to_string(&credential_def_bundle), serde_json::to_string(&credential_priv_key)) {
+        (Ok(credential_def_bundle_json), Ok(credential_priv_key_json)) => {
+            trace!("cl_issuer_new_credential_def_bundle: credential_def_bundle_json: {:?}, credential_priv_key_json: {:?}",
+                   credential_def_bundle_json, secret!(&credential_priv_key_json));
+            unsafe {
+                *credential_def_bundle_json_p = CTypesUtils::string_to_cstring(credential_def_bundle_json).into_raw();
+                *credential_priv_key_json_p = CTypesUtils::string_to_cstring(credential_priv_key_json).into_raw();
+                trace!("cl_issuer_new_credential_def_bundle: *credential_def_bundle_json_p: {:?}, *credential_priv_key_json_p: {:?}",
+                       *credential_def_bundle_json_p, *credential_priv_key_json_p);
+            }
+            ErrorCode::Success
+        }
+        _ => ErrorCode::CommonInvalidState
+    };
+
+    trace!("cl_issuer_new_credential_def_bundle: <<< res: {:?}", res);
+    res
+}
+
 /// Returns json representation of credential public key.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `credential_pub_key` - Reference that contains credential public key instance pointer.
 /// * `credential_pub_key_p` - Reference that will contain credential public key json.
@@ -139,6 +215,59 @@ pub extern fn cl_credential_public_key_from_json(credential_pub_key_json: *const
     res
 }
 
+/// Sets issuer label (for example schema id) of credential public key.
+/// The tag is carried by json serialization of the key and isn't used by cryptographic operations.
+///
+/// # Arguments
+/// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+/// * `tag` - Tag as null terminated string (optional, null removes the tag).
+#[no_mangle]
+pub extern fn cl_credential_public_key_set_tag(credential_pub_key: *const c_void,
+                                               tag: *const c_char) -> ErrorCode {
+    trace!("cl_credential_public_key_set_tag: >>> credential_pub_key: {:?}, tag: {:?}", credential_pub_key, tag);
+
+    check_useful_mut_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam1);
+    check_useful_opt_c_str!(tag, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_credential_public_key_set_tag: entity: tag: {:?}", tag);
+
+    credential_pub_key.set_tag(tag.as_ref().map(String::as_str));
+
+    let res = ErrorCode::Success;
+
+    trace!("cl_credential_public_key_set_tag: <<< res: {:?}", res);
+    res
+}
+
+/// Returns issuer label (for example schema id) of credential public key.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+/// * `tag_p` - Reference that will contain tag as null terminated string (null if the key isn't tagged).
+#[no_mangle]
+pub extern fn cl_credential_public_key_get_tag(credential_pub_key: *const c_void,
+                                               tag_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_credential_public_key_get_tag: >>> credential_pub_key: {:?}, tag_p: {:?}", credential_pub_key, tag_p);
+
+    check_useful_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(tag_p, ErrorCode::CommonInvalidParam2);
+
+    unsafe {
+        *tag_p = match credential_pub_key.tag() {
+            Some(tag) => CTypesUtils::string_to_cstring(tag.to_string()).into_raw(),
+            None => ptr::null()
+        };
+        trace!("cl_credential_public_key_get_tag: *tag_p: {:?}", *tag_p);
+    }
+
+    let res = ErrorCode::Success;
+
+    trace!("cl_credential_public_key_get_tag: <<< res: {:?}", res);
+    res
+}
+
 /// Deallocates credential public key instance.
 ///
 /// # Arguments
@@ -158,8 +287,136 @@ pub extern fn cl_credential_public_key_free(credential_pub_key: *const c_void) -
     res
 }
 
+/// Creates and returns credential primary public key from json.
+///
+/// Legacy json with `rms` field is also supported.
+///
+/// Note: Credential primary public key instance deallocation must be performed
+/// by calling cl_credential_primary_public_key_free
+///
+/// # Arguments
+/// * `credential_primary_pub_key_json` - Reference that contains credential primary public key json.
+/// * `credential_primary_pub_key_p` - Reference that will contain credential primary public key instance pointer.
+#[no_mangle]
+pub extern fn cl_credential_primary_public_key_from_json(credential_primary_pub_key_json: *const c_char,
+                                                         credential_primary_pub_key_p: *mut *const c_void) -> ErrorCode {
+    trace!("cl_credential_primary_public_key_from_json: >>> credential_primary_pub_key_json: {:?}, credential_primary_pub_key_p: {:?}",
+           credential_primary_pub_key_json, credential_primary_pub_key_p);
+
+    check_useful_c_str!(credential_primary_pub_key_json, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(credential_primary_pub_key_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_credential_primary_public_key_from_json: entity: credential_primary_pub_key_json: {:?}", credential_primary_pub_key_json);
+
+    let res = match serde_json::from_str::<CredentialPrimaryPublicKey>(&credential_primary_pub_key_json) {
+        Ok(credential_primary_pub_key) => {
+            trace!("cl_credential_primary_public_key_from_json: credential_primary_pub_key: {:?}", credential_primary_pub_key);
+            unsafe {
+                *credential_primary_pub_key_p = Box::into_raw(Box::new(credential_primary_pub_key)) as *const c_void;
+                trace!("cl_credential_primary_public_key_from_json: *credential_primary_pub_key_p: {:?}", *credential_primary_pub_key_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidStructure
+    };
+
+    trace!("cl_credential_primary_public_key_from_json: <<< res: {:?}", res);
+    res
+}
+
+/// Deallocates credential primary public key instance.
+///
+/// # Arguments
+/// * `credential_primary_pub_key` - Reference that contains credential primary public key instance pointer.
+#[no_mangle]
+pub extern fn cl_credential_primary_public_key_free(credential_primary_pub_key: *const c_void) -> ErrorCode {
+    trace!("cl_credential_primary_public_key_free: >>> credential_primary_pub_key: {:?}", credential_primary_pub_key);
+
+    check_useful_c_ptr!(credential_primary_pub_key, ErrorCode::CommonInvalidParam1);
+
+    let credential_primary_pub_key = unsafe { Box::from_raw(credential_primary_pub_key as *mut CredentialPrimaryPublicKey); };
+    trace!("cl_credential_primary_public_key_free: entity: credential_primary_pub_key: {:?}", credential_primary_pub_key);
+
+    let res = ErrorCode::Success;
+
+    trace!("cl_credential_primary_public_key_free: <<< res: {:?}", res);
+    res
+}
+
+/// Returns decimal representation of RSA modulus `n` of credential primary public key.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `credential_primary_pub_key` - Reference that contains credential primary public key instance pointer.
+/// * `n_p` - Reference that will contain decimal string of `n`.
+#[no_mangle]
+pub extern fn cl_credential_primary_public_key_get_n(credential_primary_pub_key: *const c_void,
+                                                     n_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_credential_primary_public_key_get_n: >>> credential_primary_pub_key: {:?}, n_p: {:?}", credential_primary_pub_key, n_p);
+
+    check_useful_c_reference!(credential_primary_pub_key, CredentialPrimaryPublicKey, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(n_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_credential_primary_public_key_get_n: entity: credential_primary_pub_key: {:?}", credential_primary_pub_key);
+
+    let res = match credential_primary_pub_key.n().to_dec() {
+        Ok(n) => {
+            trace!("cl_credential_primary_public_key_get_n: n: {:?}", n);
+            unsafe {
+                let n = CTypesUtils::string_to_cstring(n);
+                *n_p = n.into_raw();
+                trace!("cl_credential_primary_public_key_get_n: *n_p: {:?}", *n_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_primary_public_key_get_n: <<< res: {:?}", res);
+    res
+}
+
+/// Creates and returns credential public key from credential primary public key.
+///
+/// Note: Credential public key instance deallocation must be performed
+/// by calling cl_credential_public_key_free
+///
+/// # Arguments
+/// * `credential_primary_pub_key` - Reference that contains credential primary public key instance pointer.
+/// * `credential_pub_key_p` - Reference that will contain credential public key instance pointer.
+#[no_mangle]
+pub extern fn cl_credential_public_key_build_from_parts(credential_primary_pub_key: *const c_void,
+                                                        credential_pub_key_p: *mut *const c_void) -> ErrorCode {
+    trace!("cl_credential_public_key_build_from_parts: >>> credential_primary_pub_key: {:?}, credential_pub_key_p: {:?}",
+           credential_primary_pub_key, credential_pub_key_p);
+
+    check_useful_c_reference!(credential_primary_pub_key, CredentialPrimaryPublicKey, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(credential_pub_key_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_credential_public_key_build_from_parts: entity: credential_primary_pub_key: {:?}", credential_primary_pub_key);
+
+    let res = match CredentialPublicKey::build_from_parts(credential_primary_pub_key) {
+        Ok(credential_pub_key) => {
+            trace!("cl_credential_public_key_build_from_parts: credential_pub_key: {:?}", credential_pub_key);
+            unsafe {
+                *credential_pub_key_p = Box::into_raw(Box::new(credential_pub_key)) as *const c_void;
+                trace!("cl_credential_public_key_build_from_parts: *credential_pub_key_p: {:?}", *credential_pub_key_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_public_key_build_from_parts: <<< res: {:?}", res);
+    res
+}
+
 /// Returns json representation of credential private key.
 ///
+/// Note: Credential private key json contains secret values so it must be deallocated
+/// by calling cl_free_secret_str that wipes memory before releasing it.
+///
 /// # Arguments
 /// * `credential_priv_key` - Reference that contains credential private key instance pointer.
 /// * `credential_pub_key_p` - Reference that will contain credential private key json.
@@ -245,6 +502,8 @@ pub extern fn cl_credential_private_key_free(credential_priv_key: *const c_void)
 
 /// Returns json representation of credential key correctness proof.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `credential_key_correctness_proof` - Reference that contains credential key correctness proof instance pointer.
 /// * `credential_key_correctness_proof_p` - Reference that will contain credential key correctness proof json.
@@ -406,11 +665,13 @@ pub extern fn cl_issuer_sign_credential(prover_id: *const c_char,
     };
 
     trace!("cl_issuer_sign_credential: <<< res: {:?}", res);
-    ErrorCode::Success
+    res
 }
 
 /// Returns json representation of credential signature.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `credential_signature` - Reference that contains credential signature pointer.
 /// * `credential_signature_json_p` - Reference that will contain credential signature json.
@@ -495,8 +756,49 @@ pub extern fn cl_credential_signature_free(credential_signature: *const c_void)
     res
 }
 
+/// Returns deterministic non-secret identifier of credential.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `credential_signature` - Reference that contains credential signature instance pointer.
+/// * `credential_pub_key` - Reference that contains credential public key instance pointer.
+/// * `credential_id_p` - Reference that will contain credential identifier.
+#[no_mangle]
+pub extern fn cl_credential_signature_get_credential_id(credential_signature: *const c_void,
+                                                        credential_pub_key: *const c_void,
+                                                        credential_id_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_credential_signature_get_credential_id: >>> credential_signature: {:?}, credential_pub_key: {:?}, credential_id_p: {:?}",
+           credential_signature, credential_pub_key, credential_id_p);
+
+    check_useful_c_reference!(credential_signature, CredentialSignature, ErrorCode::CommonInvalidParam1);
+    check_useful_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam2);
+    check_useful_c_ptr!(credential_id_p, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_credential_signature_get_credential_id: entities: credential_signature: {:?}, credential_pub_key: {:?}",
+           secret!(credential_signature), credential_pub_key);
+
+    let res = match credential_signature.credential_id(credential_pub_key) {
+        Ok(credential_id) => {
+            trace!("cl_credential_signature_get_credential_id: credential_id: {:?}", credential_id);
+            unsafe {
+                let credential_id = CTypesUtils::string_to_cstring(credential_id);
+                *credential_id_p = credential_id.into_raw();
+                trace!("cl_credential_signature_get_credential_id: credential_id_p: {:?}", *credential_id_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_signature_get_credential_id: <<< res: {:?}", res);
+    res
+}
+
 /// Returns json representation of signature correctness proof.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `signature_correctness_proof` - Reference that contains signature correctness proof instance pointer.
 /// * `signature_correctness_proof_json_p` - Reference that will contain signature correctness proof json.
@@ -585,10 +887,12 @@ pub extern fn cl_signature_correctness_proof_free(signature_correctness_proof: *
 mod tests {
     use super::*;
 
+    use std::ffi::CString;
     use std::ptr;
     use ffi::cl::mocks::*;
     use ffi::cl::issuer::mocks::*;
     use ffi::cl::prover::mocks::*;
+    use ffi::cl::prover::cl_free_secret_str;
 
     #[test]
     fn cl_issuer_new_credential_def_works() {
@@ -614,6 +918,109 @@ mod tests {
         _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
     }
 
+    #[test]
+    fn cl_issuer_new_credential_def_works_for_serialization_of_all_artifacts() {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+
+        let mut credential_pub_key_json_p: *const c_char = ptr::null();
+        let err_code = cl_credential_public_key_to_json(credential_pub_key, &mut credential_pub_key_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut credential_priv_key_json_p: *const c_char = ptr::null();
+        let err_code = cl_credential_private_key_to_json(credential_priv_key, &mut credential_priv_key_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut credential_key_correctness_proof_json_p: *const c_char = ptr::null();
+        let err_code = cl_credential_key_correctness_proof_to_json(credential_key_correctness_proof,
+                                                                   &mut credential_key_correctness_proof_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_credential_pub_key: *const c_void = ptr::null();
+        let err_code = cl_credential_public_key_from_json(credential_pub_key_json_p, &mut restored_credential_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_credential_priv_key: *const c_void = ptr::null();
+        let err_code = cl_credential_private_key_from_json(credential_priv_key_json_p, &mut restored_credential_priv_key);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_credential_key_correctness_proof: *const c_void = ptr::null();
+        let err_code = cl_credential_key_correctness_proof_from_json(credential_key_correctness_proof_json_p,
+                                                                     &mut restored_credential_key_correctness_proof);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_credential_def(restored_credential_pub_key, restored_credential_priv_key, restored_credential_key_correctness_proof);
+    }
+
+    #[test]
+    fn cl_issuer_new_credential_def_bundle_works() {
+        let credential_schema = _credential_schema();
+        let non_credential_schema = _non_credential_schema();
+
+        let mut credential_def_bundle_json_p: *const c_char = ptr::null();
+        let mut credential_priv_key_json_p: *const c_char = ptr::null();
+        let err_code = cl_issuer_new_credential_def_bundle(credential_schema,
+                                                           non_credential_schema,
+                                                           &mut credential_def_bundle_json_p,
+                                                           &mut credential_priv_key_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let credential_def_bundle_json = unsafe { CString::from_raw(credential_def_bundle_json_p as *mut c_char) };
+        let credential_def_bundle: serde_json::Value = serde_json::from_str(credential_def_bundle_json.to_str().unwrap()).unwrap();
+        assert!(credential_def_bundle.get("credential_priv_key").is_none());
+
+        let credential_pub_key_json = CString::new(credential_def_bundle["credential_pub_key"].to_string()).unwrap();
+        let mut credential_pub_key: *const c_void = ptr::null();
+        let err_code = cl_credential_public_key_from_json(credential_pub_key_json.as_ptr(), &mut credential_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let credential_key_correctness_proof_json = CString::new(credential_def_bundle["credential_key_correctness_proof"].to_string()).unwrap();
+        let mut credential_key_correctness_proof: *const c_void = ptr::null();
+        let err_code = cl_credential_key_correctness_proof_from_json(credential_key_correctness_proof_json.as_ptr(),
+                                                                     &mut credential_key_correctness_proof);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut credential_priv_key: *const c_void = ptr::null();
+        let err_code = cl_credential_private_key_from_json(credential_priv_key_json_p, &mut credential_priv_key);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let err_code = cl_free_secret_str(credential_priv_key_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let prover_id = _prover_did();
+        let credential_values = _credential_values();
+        let credential_nonce = _nonce();
+        let credential_issuance_nonce = _nonce();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                                   credential_key_correctness_proof,
+                                                                                   credential_values,
+                                                                                   credential_nonce);
+
+        let mut credential_signature_p: *const c_void = ptr::null();
+        let mut credential_signature_correctness_proof_p: *const c_void = ptr::null();
+        let err_code = cl_issuer_sign_credential(prover_id.as_ptr(),
+                                                 blinded_credential_secrets,
+                                                 blinded_credential_secrets_correctness_proof,
+                                                 credential_nonce,
+                                                 credential_issuance_nonce,
+                                                 credential_values,
+                                                 credential_pub_key,
+                                                 credential_priv_key,
+                                                 &mut credential_signature_p,
+                                                 &mut credential_signature_correctness_proof_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_credential_schema(credential_schema);
+        _free_non_credential_schema(non_credential_schema);
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_credential_values(credential_values);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_nonce(credential_nonce);
+        _free_nonce(credential_issuance_nonce);
+        _free_credential_signature(credential_signature_p, credential_signature_correctness_proof_p);
+    }
+
     #[test]
     fn cl_credential_public_key_to_json_works() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -640,6 +1047,100 @@ mod tests {
         _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
     }
 
+    #[test]
+    fn cl_credential_public_key_tag_works() {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+
+        let mut tag_p: *const c_char = ptr::null();
+        let err_code = cl_credential_public_key_get_tag(credential_pub_key, &mut tag_p);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(tag_p.is_null());
+
+        let tag = CString::new("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0").unwrap();
+        let err_code = cl_credential_public_key_set_tag(credential_pub_key, tag.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut credential_pub_key_json_p: *const c_char = ptr::null();
+        let err_code = cl_credential_public_key_to_json(credential_pub_key, &mut credential_pub_key_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_credential_pub_key: *const c_void = ptr::null();
+        let err_code = cl_credential_public_key_from_json(credential_pub_key_json_p, &mut restored_credential_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+        unsafe { CString::from_raw(credential_pub_key_json_p as *mut c_char); }
+
+        let err_code = cl_credential_public_key_get_tag(restored_credential_pub_key, &mut tag_p);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert_eq!("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0", unsafe { CString::from_raw(tag_p as *mut c_char) }.into_string().unwrap());
+
+        let err_code = cl_credential_public_key_set_tag(restored_credential_pub_key, ptr::null());
+        assert_eq!(err_code, ErrorCode::Success);
+        let err_code = cl_credential_public_key_get_tag(restored_credential_pub_key, &mut tag_p);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(tag_p.is_null());
+
+        let err_code = cl_credential_public_key_free(restored_credential_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+    }
+
+    #[test]
+    fn cl_credential_public_key_build_from_parts_works() {
+        let mut credential_primary_pub_key_json = serde_json::to_value(&::cl::issuer::mocks::credential_primary_public_key()).unwrap();
+        let rms = credential_primary_pub_key_json["r"].as_object_mut().unwrap().remove("master_secret").unwrap();
+        credential_primary_pub_key_json["rms"] = rms;
+        let credential_primary_pub_key_json = CString::new(credential_primary_pub_key_json.to_string()).unwrap();
+
+        let mut credential_primary_pub_key: *const c_void = ptr::null();
+        let err_code = cl_credential_primary_public_key_from_json(credential_primary_pub_key_json.as_ptr(), &mut credential_primary_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(!credential_primary_pub_key.is_null());
+
+        let mut credential_pub_key: *const c_void = ptr::null();
+        let err_code = cl_credential_public_key_build_from_parts(credential_primary_pub_key, &mut credential_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(!credential_pub_key.is_null());
+
+        let credential_pub_key_ref = unsafe { &*(credential_pub_key as *const CredentialPublicKey) };
+        assert_eq!(::cl::issuer::mocks::credential_primary_public_key(), credential_pub_key_ref.get_primary_key().unwrap());
+
+        let err_code = cl_credential_public_key_free(credential_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let err_code = cl_credential_primary_public_key_free(credential_primary_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+    }
+
+    #[test]
+    fn cl_credential_primary_public_key_get_n_works() {
+        let credential_primary_pub_key_json = serde_json::to_value(&::cl::issuer::mocks::credential_primary_public_key()).unwrap();
+        let credential_primary_pub_key_json_str = CString::new(credential_primary_pub_key_json.to_string()).unwrap();
+
+        let mut credential_primary_pub_key: *const c_void = ptr::null();
+        let err_code = cl_credential_primary_public_key_from_json(credential_primary_pub_key_json_str.as_ptr(), &mut credential_primary_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut n_p: *const c_char = ptr::null();
+        let err_code = cl_credential_primary_public_key_get_n(credential_primary_pub_key, &mut n_p);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(!n_p.is_null());
+
+        let n = unsafe { CString::from_raw(n_p as *mut c_char) }.into_string().unwrap();
+        assert_eq!(credential_primary_pub_key_json["n"].as_str().unwrap(), n);
+
+        let err_code = cl_credential_primary_public_key_free(credential_primary_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
+    }
+
+    #[test]
+    fn cl_credential_primary_public_key_get_n_works_for_null() {
+        let mut n_p: *const c_char = ptr::null();
+        let err_code = cl_credential_primary_public_key_get_n(ptr::null(), &mut n_p);
+        assert_eq!(err_code, ErrorCode::CommonInvalidParam1);
+        assert!(n_p.is_null());
+    }
+
     #[test]
     fn cl_credential_private_key_to_json_works() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -693,6 +1194,38 @@ mod tests {
         _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
     }
 
+    #[test]
+    fn cl_credential_key_correctness_proof_json_round_trip_works() {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+
+        let mut credential_key_correctness_proof_json_p: *const c_char = ptr::null();
+        let err_code = cl_credential_key_correctness_proof_to_json(credential_key_correctness_proof, &mut credential_key_correctness_proof_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_credential_key_correctness_proof_p: *const c_void = ptr::null();
+        let err_code = cl_credential_key_correctness_proof_from_json(credential_key_correctness_proof_json_p,
+                                                                     &mut restored_credential_key_correctness_proof_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let original = unsafe { &*(credential_key_correctness_proof as *const CredentialKeyCorrectnessProof) };
+        let restored = unsafe { &*(restored_credential_key_correctness_proof_p as *const CredentialKeyCorrectnessProof) };
+        assert_eq!(original, restored);
+
+        let mut restored_credential_key_correctness_proof_json_p: *const c_char = ptr::null();
+        let err_code = cl_credential_key_correctness_proof_to_json(restored_credential_key_correctness_proof_p,
+                                                                   &mut restored_credential_key_correctness_proof_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let json = unsafe { CString::from_raw(credential_key_correctness_proof_json_p as *mut c_char) };
+        let restored_json = unsafe { CString::from_raw(restored_credential_key_correctness_proof_json_p as *mut c_char) };
+        assert_eq!(json, restored_json);
+
+        let err_code = cl_credential_key_correctness_proof_free(restored_credential_key_correctness_proof_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+    }
+
     #[test]
     fn cl_credential_def_free_works() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -775,6 +1308,57 @@ mod tests {
         _free_credential_signature(credential_signature, signature_correctness_proof);
     }
 
+    #[test]
+    fn cl_credential_signature_get_credential_id_works() {
+        let credential_values = _credential_values();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_nonce = _nonce();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                              credential_key_correctness_proof,
+                                                                              credential_values,
+                                                                              credential_nonce);
+        let credential_issuance_nonce = _nonce();
+        let (credential_signature, signature_correctness_proof) = _credential_signature(blinded_credential_secrets,
+                                                                                        blinded_credential_secrets_correctness_proof,
+                                                                                        credential_nonce,
+                                                                                        credential_issuance_nonce,
+                                                                                        credential_values,
+                                                                                        credential_pub_key,
+                                                                                        credential_priv_key);
+
+
+        let mut credential_id_p: *const c_char = ptr::null();
+        let err_code = cl_credential_signature_get_credential_id(credential_signature, credential_pub_key, &mut credential_id_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut credential_signature_json_p: *const c_char = ptr::null();
+        let err_code = cl_credential_signature_to_json(credential_signature, &mut credential_signature_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_credential_signature: *const c_void = ptr::null();
+        let err_code = cl_credential_signature_from_json(credential_signature_json_p, &mut restored_credential_signature);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_credential_id_p: *const c_char = ptr::null();
+        let err_code = cl_credential_signature_get_credential_id(restored_credential_signature, credential_pub_key, &mut restored_credential_id_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let credential_id = unsafe { CString::from_raw(credential_id_p as *mut c_char) };
+        let restored_credential_id = unsafe { CString::from_raw(restored_credential_id_p as *mut c_char) };
+        assert_eq!(credential_id, restored_credential_id);
+
+        let err_code = cl_credential_signature_free(restored_credential_signature);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_credential_values(credential_values);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_nonce(credential_nonce);
+        _free_nonce(credential_issuance_nonce);
+        _free_credential_signature(credential_signature, signature_correctness_proof);
+    }
+
     #[test]
     fn cl_credential_signature_from_json_works() {
         let credential_values = _credential_values();
@@ -6,6 +6,7 @@ use errors::ErrorCode;
 use ffi::ctypes::CTypesUtils;
 
 use serde_json;
+use std::collections::HashMap;
 use std::os::raw::c_void;
 use libc::c_char;
 
@@ -69,6 +70,31 @@ pub extern fn cl_credential_schema_builder_add_attr(credential_schema_builder: *
     res
 }
 
+/// Adds new attribute to credential schema.
+/// Unlike cl_credential_schema_builder_add_attr returns error if attribute was already added.
+///
+/// # Arguments
+/// * `credential_schema_builder` - Reference that contains credential schema builder instance pointer.
+/// * `attr` - Attribute to add as null terminated string.
+#[no_mangle]
+pub extern fn cl_credential_schema_builder_add_attr_checked(credential_schema_builder: *const c_void,
+                                                            attr: *const c_char) -> ErrorCode {
+    trace!("cl_credential_schema_builder_add_attr_checked: >>> credential_schema_builder: {:?}, attr: {:?}", credential_schema_builder, attr);
+
+    check_useful_mut_c_reference!(credential_schema_builder, CredentialSchemaBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_credential_schema_builder_add_attr_checked: entities: credential_schema_builder: {:?}, attr: {:?}", credential_schema_builder, attr);
+
+    let res = match credential_schema_builder.add_attr_checked(&attr) {
+        Ok(_) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_schema_builder_add_attr_checked: <<< res: {:?}", res);
+    res
+}
+
 /// Deallocates credential schema builder and returns credential schema entity instead.
 ///
 /// Note: Credentials schema instance deallocation must be performed by
@@ -105,6 +131,45 @@ pub extern fn cl_credential_schema_builder_finalize(credential_schema_builder: *
     res
 }
 
+/// Creates and returns credential schema with attributes from json array of attribute names.
+///
+/// Note that credential schema deallocation must be performed by
+/// calling cl_credential_schema_free.
+///
+/// # Arguments
+/// * `attrs_json` - Reference that contains json array of attribute names, for example `["name","age"]`.
+/// * `credential_schema_p` - Reference that will contain credentials schema instance pointer.
+#[no_mangle]
+pub extern fn cl_credential_schema_from_json_attrs(attrs_json: *const c_char,
+                                                   credential_schema_p: *mut *const c_void) -> ErrorCode {
+    trace!("cl_credential_schema_from_json_attrs: >>> attrs_json: {:?}, credential_schema_p: {:?}", attrs_json, credential_schema_p);
+
+    check_useful_c_str!(attrs_json, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(credential_schema_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_credential_schema_from_json_attrs: entities: attrs_json: {:?}", attrs_json);
+
+    let attrs = match serde_json::from_str::<Vec<String>>(&attrs_json) {
+        Ok(attrs) => attrs,
+        Err(_) => return ErrorCode::CommonInvalidStructure
+    };
+
+    let res = match CredentialSchema::from_attrs(&attrs.iter().map(String::as_str).collect::<Vec<&str>>()) {
+        Ok(credential_schema) => {
+            trace!("cl_credential_schema_from_json_attrs: credential_schema: {:?}", credential_schema);
+            unsafe {
+                *credential_schema_p = Box::into_raw(Box::new(credential_schema)) as *const c_void;
+                trace!("cl_credential_schema_from_json_attrs: *credential_schema_p: {:?}", *credential_schema_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_schema_from_json_attrs: <<< res: {:?}", res);
+    res
+}
+
 /// Deallocates credential schema instance.
 ///
 /// # Arguments
@@ -354,11 +419,187 @@ pub extern fn cl_credential_values_builder_add_dec_commitment(credential_values_
     res
 }
 
+/// Adds new known attribute hex_value to credential values map.
+///
+/// # Arguments
+/// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+/// * `attr` - Credential attr to add as null terminated string.
+/// * `hex_value` - Credential attr hex_value. Big-endian hex BigNum representation (even number of digits) as null terminated string.
+#[no_mangle]
+pub extern fn cl_credential_values_builder_add_hex_known(credential_values_builder: *const c_void,
+                                                         attr: *const c_char,
+                                                         hex_value: *const c_char) -> ErrorCode {
+    trace!("cl_credential_values_builder_add_hex_known: >>> credential_values_builder: {:?}, attr: {:?}, hex_value: {:?}",
+           credential_values_builder, attr, hex_value);
+
+    check_useful_mut_c_reference!(credential_values_builder, CredentialValuesBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(hex_value, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_credential_values_builder_add_hex_known: entities: credential_values_builder: {:?}, attr: {:?}, hex_value: {:?}", credential_values_builder, attr, hex_value);
+
+    let res = match credential_values_builder.add_hex_known(&attr, &hex_value) {
+        Ok(_) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_values_builder_add_hex_known: <<< res: {:?}", res);
+    res
+}
+
+/// Adds new hidden attribute hex_value to credential values map.
+///
+/// # Arguments
+/// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+/// * `attr` - Credential attr to add as null terminated string.
+/// * `hex_value` - Credential attr hex_value. Big-endian hex BigNum representation (even number of digits) as null terminated string.
+#[no_mangle]
+pub extern fn cl_credential_values_builder_add_hex_hidden(credential_values_builder: *const c_void,
+                                                          attr: *const c_char,
+                                                          hex_value: *const c_char) -> ErrorCode {
+    trace!("cl_credential_values_builder_add_hex_hidden: >>> credential_values_builder: {:?}, attr: {:?}, hex_value: {:?}",
+           credential_values_builder, attr, hex_value);
+
+    check_useful_mut_c_reference!(credential_values_builder, CredentialValuesBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(hex_value, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_credential_values_builder_add_hex_hidden: entities: credential_values_builder: {:?}, attr: {:?}, hex_value: {:?}", credential_values_builder, attr, hex_value);
+
+    let res = match credential_values_builder.add_hex_hidden(&attr, &hex_value) {
+        Ok(_) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_values_builder_add_hex_hidden: <<< res: {:?}", res);
+    res
+}
+
+/// Adds new committed attribute hex_value to credential values map.
+///
+/// # Arguments
+/// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+/// * `attr` - Credential attr to add as null terminated string.
+/// * `hex_value` - Credential attr hex_value. Big-endian hex BigNum representation (even number of digits) as null terminated string.
+/// * `hex_blinding_factor` - Credential blinding factor. Big-endian hex BigNum representation as null terminated string
+#[no_mangle]
+pub extern fn cl_credential_values_builder_add_hex_commitment(credential_values_builder: *const c_void,
+                                                              attr: *const c_char,
+                                                              hex_value: *const c_char,
+                                                              hex_blinding_factor: *const c_char) -> ErrorCode {
+    trace!("cl_credential_values_builder_add_hex_commitment: >>> credential_values_builder: {:?}, attr: {:?}, hex_value: {:?}, hex_blinding_factor: {:?}",
+           credential_values_builder, attr, hex_value, hex_blinding_factor);
+
+    check_useful_mut_c_reference!(credential_values_builder, CredentialValuesBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(hex_value, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(hex_blinding_factor, ErrorCode::CommonInvalidParam4);
+
+    trace!("cl_credential_values_builder_add_hex_commitment: entities: credential_values_builder: {:?}, attr: {:?}, hex_value: {:?}, hex_blinding_factor: {:?}", credential_values_builder, attr, hex_value, hex_blinding_factor);
+
+    let res = match credential_values_builder.add_hex_commitment(&attr, &hex_value, &hex_blinding_factor) {
+        Ok(_) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_values_builder_add_hex_commitment: <<< res: {:?}", res);
+    res
+}
+
+/// Adds new known attribute base58_value to credential values map.
+///
+/// # Arguments
+/// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+/// * `attr` - Credential attr to add as null terminated string.
+/// * `base58_value` - Credential attr base58_value. BigNum representation in base58 (Bitcoin alphabet) as null terminated string.
+#[no_mangle]
+pub extern fn cl_credential_values_builder_add_base58_known(credential_values_builder: *const c_void,
+                                                            attr: *const c_char,
+                                                            base58_value: *const c_char) -> ErrorCode {
+    trace!("cl_credential_values_builder_add_base58_known: >>> credential_values_builder: {:?}, attr: {:?}, base58_value: {:?}",
+           credential_values_builder, attr, base58_value);
+
+    check_useful_mut_c_reference!(credential_values_builder, CredentialValuesBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(base58_value, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_credential_values_builder_add_base58_known: entities: credential_values_builder: {:?}, attr: {:?}, base58_value: {:?}", credential_values_builder, attr, base58_value);
+
+    let res = match credential_values_builder.add_base58_known(&attr, &base58_value) {
+        Ok(_) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_values_builder_add_base58_known: <<< res: {:?}", res);
+    res
+}
+
+/// Adds new hidden attribute base58_value to credential values map.
+///
+/// # Arguments
+/// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+/// * `attr` - Credential attr to add as null terminated string.
+/// * `base58_value` - Credential attr base58_value. BigNum representation in base58 (Bitcoin alphabet) as null terminated string.
+#[no_mangle]
+pub extern fn cl_credential_values_builder_add_base58_hidden(credential_values_builder: *const c_void,
+                                                             attr: *const c_char,
+                                                             base58_value: *const c_char) -> ErrorCode {
+    trace!("cl_credential_values_builder_add_base58_hidden: >>> credential_values_builder: {:?}, attr: {:?}, base58_value: {:?}",
+           credential_values_builder, attr, base58_value);
+
+    check_useful_mut_c_reference!(credential_values_builder, CredentialValuesBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(base58_value, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_credential_values_builder_add_base58_hidden: entities: credential_values_builder: {:?}, attr: {:?}, base58_value: {:?}", credential_values_builder, attr, base58_value);
+
+    let res = match credential_values_builder.add_base58_hidden(&attr, &base58_value) {
+        Ok(_) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_values_builder_add_base58_hidden: <<< res: {:?}", res);
+    res
+}
+
+/// Adds new committed attribute base58_value to credential values map.
+///
+/// # Arguments
+/// * `credential_values_builder` - Reference that contains credential values builder instance pointer.
+/// * `attr` - Credential attr to add as null terminated string.
+/// * `base58_value` - Credential attr base58_value. BigNum representation in base58 (Bitcoin alphabet) as null terminated string.
+/// * `base58_blinding_factor` - Credential blinding factor. BigNum representation in base58 (Bitcoin alphabet) as null terminated string
+#[no_mangle]
+pub extern fn cl_credential_values_builder_add_base58_commitment(credential_values_builder: *const c_void,
+                                                                 attr: *const c_char,
+                                                                 base58_value: *const c_char,
+                                                                 base58_blinding_factor: *const c_char) -> ErrorCode {
+    trace!("cl_credential_values_builder_add_base58_commitment: >>> credential_values_builder: {:?}, attr: {:?}, base58_value: {:?}, base58_blinding_factor: {:?}",
+           credential_values_builder, attr, base58_value, base58_blinding_factor);
+
+    check_useful_mut_c_reference!(credential_values_builder, CredentialValuesBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(base58_value, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(base58_blinding_factor, ErrorCode::CommonInvalidParam4);
+
+    trace!("cl_credential_values_builder_add_base58_commitment: entities: credential_values_builder: {:?}, attr: {:?}, base58_value: {:?}, base58_blinding_factor: {:?}", credential_values_builder, attr, base58_value, base58_blinding_factor);
+
+    let res = match credential_values_builder.add_base58_commitment(&attr, &base58_value, &base58_blinding_factor) {
+        Ok(_) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_credential_values_builder_add_base58_commitment: <<< res: {:?}", res);
+    res
+}
+
 /// Deallocates credential values builder and returns credential values entity instead.
 ///
 /// Note: Credentials values instance deallocation must be performed by
 /// calling cl_credential_values_free.
 ///
+/// Returns CommonInvalidStructure if no values were added (builder is deallocated in this case too).
+///
 /// # Arguments
 /// * `credential_values_builder` - Reference that contains credential attribute builder instance pointer.
 /// * `credential_values_p` - Reference that will contain credentials values instance pointer.
@@ -472,7 +713,7 @@ pub extern fn cl_sub_proof_request_builder_add_revealed_attr(sub_proof_request_b
 /// # Arguments
 /// * `sub_proof_request_builder` - Reference that contains sub proof request builder instance pointer.
 /// * `attr_name` - Related attribute
-/// * `p_type` - Predicate type (Currently `GE` only).
+/// * `p_type` - Predicate type (`GE`, `LE`, `GT`, `LT` or `EQ`).
 /// * `value` - Requested value.
 #[no_mangle]
 pub extern fn cl_sub_proof_request_builder_add_predicate(sub_proof_request_builder: *const c_void,
@@ -554,6 +795,188 @@ pub extern fn cl_sub_proof_request_free(sub_proof_request: *const c_void) -> Err
     res
 }
 
+/// Returns json with names of credential attributes split into `revealed`, `predicated` and `hidden`
+/// according to the sub proof request.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+/// * `credential_values` - Reference that contains credential values instance pointer.
+/// * `partition_json_p` - Reference that will contain partition json.
+#[no_mangle]
+pub extern fn cl_sub_proof_request_partition_json(sub_proof_request: *const c_void,
+                                                  credential_values: *const c_void,
+                                                  partition_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_sub_proof_request_partition_json: >>> sub_proof_request: {:?}, credential_values: {:?}, partition_json_p: {:?}",
+           sub_proof_request, credential_values, partition_json_p);
+
+    check_useful_c_reference!(sub_proof_request, SubProofRequest, ErrorCode::CommonInvalidParam1);
+    check_useful_c_reference!(credential_values, CredentialValues, ErrorCode::CommonInvalidParam2);
+    check_useful_c_ptr!(partition_json_p, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_sub_proof_request_partition_json: entities: sub_proof_request: {:?}, credential_values: {:?}",
+           sub_proof_request, secret!(&credential_values));
+
+    let partition = sub_proof_request.partition(credential_values);
+
+    let res = match serde_json::to_string(&partition) {
+        Ok(partition_json) => {
+            trace!("cl_sub_proof_request_partition_json: partition_json: {:?}", partition_json);
+            unsafe {
+                let partition_json = CTypesUtils::string_to_cstring(partition_json);
+                *partition_json_p = partition_json.into_raw();
+                trace!("cl_sub_proof_request_partition_json: partition_json_p: {:?}", *partition_json_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidState
+    };
+
+    trace!("cl_sub_proof_request_partition_json: <<< res: {:?}", res);
+    res
+}
+
+/// Returns json array of attribute names requested to be revealed by sub proof request.
+/// Names are sorted, so json is the same for equal requests.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+/// * `revealed_attrs_json_p` - Reference that will contain revealed attributes json.
+#[no_mangle]
+pub extern fn cl_sub_proof_request_get_revealed_attrs_json(sub_proof_request: *const c_void,
+                                                           revealed_attrs_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_sub_proof_request_get_revealed_attrs_json: >>> sub_proof_request: {:?}, revealed_attrs_json_p: {:?}",
+           sub_proof_request, revealed_attrs_json_p);
+
+    check_useful_c_reference!(sub_proof_request, SubProofRequest, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(revealed_attrs_json_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_sub_proof_request_get_revealed_attrs_json: entity >>> sub_proof_request: {:?}", sub_proof_request);
+
+    let res = match serde_json::to_string(sub_proof_request.revealed_attrs()) {
+        Ok(revealed_attrs_json) => {
+            trace!("cl_sub_proof_request_get_revealed_attrs_json: revealed_attrs_json: {:?}", revealed_attrs_json);
+            unsafe {
+                let revealed_attrs_json = CTypesUtils::string_to_cstring(revealed_attrs_json);
+                *revealed_attrs_json_p = revealed_attrs_json.into_raw();
+                trace!("cl_sub_proof_request_get_revealed_attrs_json: revealed_attrs_json_p: {:?}", *revealed_attrs_json_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidState
+    };
+
+    trace!("cl_sub_proof_request_get_revealed_attrs_json: <<< res: {:?}", res);
+    res
+}
+
+/// Returns json array of predicates of sub proof request: `[{"attr_name": string, "p_type": "GE"|"LE"|"GT"|"LT"|"EQ", "value": int}]`.
+/// Predicates are sorted, so json is the same for equal requests.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+/// * `predicates_json_p` - Reference that will contain predicates json.
+#[no_mangle]
+pub extern fn cl_sub_proof_request_get_predicates_json(sub_proof_request: *const c_void,
+                                                       predicates_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_sub_proof_request_get_predicates_json: >>> sub_proof_request: {:?}, predicates_json_p: {:?}",
+           sub_proof_request, predicates_json_p);
+
+    check_useful_c_reference!(sub_proof_request, SubProofRequest, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(predicates_json_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_sub_proof_request_get_predicates_json: entity >>> sub_proof_request: {:?}", sub_proof_request);
+
+    let res = match serde_json::to_string(sub_proof_request.predicates()) {
+        Ok(predicates_json) => {
+            trace!("cl_sub_proof_request_get_predicates_json: predicates_json: {:?}", predicates_json);
+            unsafe {
+                let predicates_json = CTypesUtils::string_to_cstring(predicates_json);
+                *predicates_json_p = predicates_json.into_raw();
+                trace!("cl_sub_proof_request_get_predicates_json: predicates_json_p: {:?}", *predicates_json_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidState
+    };
+
+    trace!("cl_sub_proof_request_get_predicates_json: <<< res: {:?}", res);
+    res
+}
+
+/// Returns json representation of sub proof request.
+/// Attributes and predicates are ordered, so json is the same for equal requests.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `sub_proof_request` - Reference that contains sub proof request instance pointer.
+/// * `sub_proof_request_json_p` - Reference that will contain sub proof request json.
+#[no_mangle]
+pub extern fn cl_sub_proof_request_to_json(sub_proof_request: *const c_void,
+                                           sub_proof_request_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_sub_proof_request_to_json: >>> sub_proof_request: {:?}, sub_proof_request_json_p: {:?}", sub_proof_request, sub_proof_request_json_p);
+
+    check_useful_c_reference!(sub_proof_request, SubProofRequest, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(sub_proof_request_json_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_sub_proof_request_to_json: entity >>> sub_proof_request: {:?}", sub_proof_request);
+
+    let res = match serde_json::to_string(sub_proof_request) {
+        Ok(sub_proof_request_json) => {
+            trace!("cl_sub_proof_request_to_json: sub_proof_request_json: {:?}", sub_proof_request_json);
+            unsafe {
+                let sub_proof_request_json = CTypesUtils::string_to_cstring(sub_proof_request_json);
+                *sub_proof_request_json_p = sub_proof_request_json.into_raw();
+                trace!("cl_sub_proof_request_to_json: sub_proof_request_json_p: {:?}", *sub_proof_request_json_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidState
+    };
+
+    trace!("cl_sub_proof_request_to_json: <<< res: {:?}", res);
+    res
+}
+
+/// Creates and returns sub proof request from json.
+///
+/// Note: Sub proof request instance deallocation must be performed by calling cl_sub_proof_request_free.
+///
+/// # Arguments
+/// * `sub_proof_request_json` - Reference that contains sub proof request json.
+/// * `sub_proof_request_p` - Reference that will contain sub proof request instance pointer.
+#[no_mangle]
+pub extern fn cl_sub_proof_request_from_json(sub_proof_request_json: *const c_char,
+                                             sub_proof_request_p: *mut *const c_void) -> ErrorCode {
+    trace!("cl_sub_proof_request_from_json: >>> sub_proof_request_json: {:?}, sub_proof_request_p: {:?}", sub_proof_request_json, sub_proof_request_p);
+
+    check_useful_c_str!(sub_proof_request_json, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(sub_proof_request_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_sub_proof_request_from_json: entity: sub_proof_request_json: {:?}", sub_proof_request_json);
+
+    let res = match serde_json::from_str::<SubProofRequest>(&sub_proof_request_json) {
+        Ok(sub_proof_request) => {
+            trace!("cl_sub_proof_request_from_json: sub_proof_request: {:?}", sub_proof_request);
+            unsafe {
+                *sub_proof_request_p = Box::into_raw(Box::new(sub_proof_request)) as *const c_void;
+                trace!("cl_sub_proof_request_from_json: *sub_proof_request_p: {:?}", *sub_proof_request_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidStructure
+    };
+
+    trace!("cl_sub_proof_request_from_json: <<< res: {:?}", res);
+    res
+}
+
 /// Creates random nonce.
 ///
 /// Note that nonce deallocation must be performed by calling cl_nonce_free.
@@ -584,6 +1007,8 @@ pub extern fn cl_new_nonce(nonce_p: *mut *const c_void) -> ErrorCode {
 
 /// Returns json representation of nonce.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `nonce` - Reference that contains nonce instance pointer.
 /// * `nonce_json_p` - Reference that will contain nonce json.
@@ -618,6 +1043,8 @@ pub extern fn cl_nonce_to_json(nonce: *const c_void,
 ///
 /// Note: Nonce instance deallocation must be performed by calling cl_nonce_free.
 ///
+/// Returns CommonInvalidStructure for zero nonce or nonce shorter than 64 bits.
+///
 /// # Arguments
 /// * `nonce_json` - Reference that contains nonce json.
 /// * `nonce_p` - Reference that will contain nonce instance pointer.
@@ -632,6 +1059,7 @@ pub extern fn cl_nonce_from_json(nonce_json: *const c_char,
     trace!("cl_nonce_from_json: entity: nonce_json: {:?}", nonce_json);
 
     let res = match serde_json::from_str::<Nonce>(&nonce_json) {
+        Ok(ref nonce) if check_nonce(nonce).is_err() => ErrorCode::CommonInvalidStructure,
         Ok(nonce) => {
             trace!("cl_nonce_from_json: nonce: {:?}", nonce);
             unsafe {
@@ -666,19 +1094,176 @@ pub extern fn cl_nonce_free(nonce: *const c_void) -> ErrorCode {
     res
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Returns count of sub proofs contained in proof.
+///
+/// # Arguments
+/// * `proof` - Reference that contains proof instance pointer.
+/// * `count_p` - Reference that will contain count of sub proofs.
+#[no_mangle]
+pub extern fn cl_proof_get_sub_proof_count(proof: *const c_void,
+                                           count_p: *mut usize) -> ErrorCode {
+    trace!("cl_proof_get_sub_proof_count: >>> proof: {:?}, count_p: {:?}", proof, count_p);
 
-    use std::ffi::CString;
-    use std::ptr;
-    use ffi::cl::mocks::*;
+    check_useful_c_reference!(proof, Proof, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(count_p, ErrorCode::CommonInvalidParam2);
 
-    #[test]
-    fn cl_credential_schema_builder_new_works() {
-        let mut credential_schema_builder: *const c_void = ptr::null();
-        let err_code = cl_credential_schema_builder_new(&mut credential_schema_builder);
-        assert_eq!(err_code, ErrorCode::Success);
+    trace!("cl_proof_get_sub_proof_count: entity: proof: {:?}", proof);
+
+    unsafe {
+        *count_p = proof.sub_proofs_count();
+        trace!("cl_proof_get_sub_proof_count: *count_p: {:?}", *count_p);
+    }
+
+    let res = ErrorCode::Success;
+
+    trace!("cl_proof_get_sub_proof_count: <<< res: {:?}", res);
+    res
+}
+
+/// Returns json with predicates proved by inequality proofs of each sub proof (in order of sub proofs)
+/// as array of arrays of `{"attr_name": .., "p_type": .., "value": ..}` objects.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `proof` - Reference that contains proof instance pointer.
+/// * `predicates_json_p` - Reference that will contain predicates json.
+#[no_mangle]
+pub extern fn cl_proof_get_predicates_json(proof: *const c_void,
+                                           predicates_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_proof_get_predicates_json: >>> proof: {:?}, predicates_json_p: {:?}", proof, predicates_json_p);
+
+    check_useful_c_reference!(proof, Proof, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(predicates_json_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_proof_get_predicates_json: entity: proof: {:?}", proof);
+
+    let res = match serde_json::to_string(&proof.predicates()) {
+        Ok(predicates_json) => {
+            trace!("cl_proof_get_predicates_json: predicates_json: {:?}", predicates_json);
+            unsafe {
+                let predicates_json = CTypesUtils::string_to_cstring(predicates_json);
+                *predicates_json_p = predicates_json.into_raw();
+                trace!("cl_proof_get_predicates_json: predicates_json_p: {:?}", *predicates_json_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidState
+    };
+
+    trace!("cl_proof_get_predicates_json: <<< res: {:?}", res);
+    res
+}
+
+/// Returns SHA-256 digest (hex encoded) of canonical serialization of the proof and the nonce.
+/// Compact record of exactly what was verified.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `proof` - Reference that contains proof instance pointer.
+/// * `nonce` - Reference that contains nonce instance pointer.
+/// * `transcript_digest_p` - Reference that will contain hex encoded digest.
+#[no_mangle]
+pub extern fn cl_proof_get_transcript_digest(proof: *const c_void,
+                                             nonce: *const c_void,
+                                             transcript_digest_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_proof_get_transcript_digest: >>> proof: {:?}, nonce: {:?}, transcript_digest_p: {:?}", proof, nonce, transcript_digest_p);
+
+    check_useful_c_reference!(proof, Proof, ErrorCode::CommonInvalidParam1);
+    check_useful_c_reference!(nonce, Nonce, ErrorCode::CommonInvalidParam2);
+    check_useful_c_ptr!(transcript_digest_p, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_proof_get_transcript_digest: entities: proof: {:?}, nonce: {:?}", proof, nonce);
+
+    let res = match proof.to_transcript_digest(nonce) {
+        Ok(transcript_digest) => {
+            let transcript_digest: String = transcript_digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+            trace!("cl_proof_get_transcript_digest: transcript_digest: {:?}", transcript_digest);
+            unsafe {
+                let transcript_digest = CTypesUtils::string_to_cstring(transcript_digest);
+                *transcript_digest_p = transcript_digest.into_raw();
+                trace!("cl_proof_get_transcript_digest: transcript_digest_p: {:?}", *transcript_digest_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_proof_get_transcript_digest: <<< res: {:?}", res);
+    res
+}
+
+/// Returns json with revealed attributes of each sub proof (in order of sub proofs)
+/// as array of objects mapping attribute name to value.
+/// Values found in encoding table are replaced by original ones, others are returned as decimal strings.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `proof` - Reference that contains proof instance pointer.
+/// * `encoding_table_json` - Reference that contains json object mapping encoded decimal value to original one.
+/// * `revealed_attrs_json_p` - Reference that will contain revealed attributes json.
+#[no_mangle]
+pub extern fn cl_proof_decode_revealed_attrs(proof: *const c_void,
+                                             encoding_table_json: *const c_char,
+                                             revealed_attrs_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_proof_decode_revealed_attrs: >>> proof: {:?}, encoding_table_json: {:?}, revealed_attrs_json_p: {:?}",
+           proof, encoding_table_json, revealed_attrs_json_p);
+
+    check_useful_c_reference!(proof, Proof, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(encoding_table_json, ErrorCode::CommonInvalidParam2);
+    check_useful_c_ptr!(revealed_attrs_json_p, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_proof_decode_revealed_attrs: entities: proof: {:?}, encoding_table_json: {:?}", proof, encoding_table_json);
+
+    let encoding_table = match serde_json::from_str::<HashMap<String, String>>(&encoding_table_json) {
+        Ok(encoding_table) => encoding_table,
+        Err(_) => return ErrorCode::CommonInvalidStructure
+    };
+
+    let revealed_attrs = proof.decode_revealed_attrs(|value| {
+        value.to_dec().ok().and_then(|value| encoding_table.get(&value).cloned())
+    });
+
+    let res = match revealed_attrs {
+        Ok(revealed_attrs) => {
+            match serde_json::to_string(&revealed_attrs) {
+                Ok(revealed_attrs_json) => {
+                    trace!("cl_proof_decode_revealed_attrs: revealed_attrs_json: {:?}", revealed_attrs_json);
+                    unsafe {
+                        let revealed_attrs_json = CTypesUtils::string_to_cstring(revealed_attrs_json);
+                        *revealed_attrs_json_p = revealed_attrs_json.into_raw();
+                        trace!("cl_proof_decode_revealed_attrs: revealed_attrs_json_p: {:?}", *revealed_attrs_json_p);
+                    }
+                    ErrorCode::Success
+                }
+                Err(_) => ErrorCode::CommonInvalidState
+            }
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_proof_decode_revealed_attrs: <<< res: {:?}", res);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::CString;
+    use std::ptr;
+    use ffi::cl::mocks::*;
+    use ffi::cl::issuer::mocks::*;
+    use ffi::cl::prover::*;
+    use ffi::cl::prover::mocks::*;
+
+    #[test]
+    fn cl_credential_schema_builder_new_works() {
+        let mut credential_schema_builder: *const c_void = ptr::null();
+        let err_code = cl_credential_schema_builder_new(&mut credential_schema_builder);
+        assert_eq!(err_code, ErrorCode::Success);
         assert!(!credential_schema_builder.is_null());
 
         _free_credential_schema_builder(credential_schema_builder);
@@ -716,6 +1301,40 @@ mod tests {
         _free_credential_schema_builder(credential_schema_builder);
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    fn cl_credential_schema_builder_add_attr_works_for_concurrent_mutation() {
+        let credential_schema_builder = _credential_schema_builder();
+        let attr = CString::new("sex").unwrap();
+
+        {
+            // Simulates another call that is mutating the builder at the same time
+            let _borrow_guard = ::ffi::ctypes::MutBorrowGuard::acquire(credential_schema_builder).unwrap();
+
+            let err_code = cl_credential_schema_builder_add_attr(credential_schema_builder, attr.as_ptr());
+            assert_eq!(err_code, ErrorCode::CommonInvalidState);
+        }
+
+        let err_code = cl_credential_schema_builder_add_attr(credential_schema_builder, attr.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_credential_schema_builder(credential_schema_builder);
+    }
+
+    #[test]
+    fn cl_credential_schema_builder_add_attr_checked_works_for_duplicated_attr() {
+        let credential_schema_builder = _credential_schema_builder();
+
+        let attr = CString::new("name").unwrap();
+        let err_code = cl_credential_schema_builder_add_attr_checked(credential_schema_builder, attr.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let err_code = cl_credential_schema_builder_add_attr_checked(credential_schema_builder, attr.as_ptr());
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+
+        _free_credential_schema_builder(credential_schema_builder);
+    }
+
     #[test]
     fn cl_non_credential_schema_builder_add_attr_works() {
         let non_credential_schema_builder = _non_credential_schema_builder();
@@ -772,6 +1391,30 @@ mod tests {
         _free_non_credential_schema(non_credential_schema);
     }
 
+    #[test]
+    fn cl_credential_schema_from_json_attrs_works() {
+        let attrs_json = CString::new(r#"["name","age"]"#).unwrap();
+
+        let mut credential_schema_p: *const c_void = ptr::null();
+        let err_code = cl_credential_schema_from_json_attrs(attrs_json.as_ptr(), &mut credential_schema_p);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(!credential_schema_p.is_null());
+
+        let credential_schema = unsafe { &*(credential_schema_p as *const CredentialSchema) };
+        assert_eq!(json!({"attrs": ["age", "name"], "public_attrs": []}), serde_json::to_value(credential_schema).unwrap());
+
+        _free_credential_schema(credential_schema_p);
+    }
+
+    #[test]
+    fn cl_credential_schema_from_json_attrs_works_for_invalid_json() {
+        let attrs_json = CString::new(r#"{"name":"age"}"#).unwrap();
+
+        let mut credential_schema_p: *const c_void = ptr::null();
+        let err_code = cl_credential_schema_from_json_attrs(attrs_json.as_ptr(), &mut credential_schema_p);
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+    }
+
     #[test]
     fn cl_credential_schema_free_works() {
         let credential_schema = _credential_schema();
@@ -795,9 +1438,24 @@ mod tests {
         assert_eq!(err_code, ErrorCode::Success);
         assert!(!credential_values_builder.is_null());
 
+        let attr = CString::new("age").unwrap();
+        let dec_value = CString::new("25").unwrap();
+        let err_code = cl_credential_values_builder_add_dec_known(credential_values_builder, attr.as_ptr(), dec_value.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
         _free_credential_values_builder(credential_values_builder);
     }
 
+    #[test]
+    fn cl_credential_values_builder_finalize_works_for_empty_values() {
+        let credential_values_builder = _credential_values_builder();
+
+        let mut credential_values: *const c_void = ptr::null();
+        let err_code = cl_credential_values_builder_finalize(credential_values_builder, &mut credential_values);
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+        assert!(credential_values.is_null());
+    }
+
     #[test]
     fn cl_credential_values_builder_add_dec_known_works() {
         let credential_values_builder = _credential_values_builder();
@@ -851,6 +1509,88 @@ mod tests {
         _free_credential_values_builder(credential_values_builder);
     }
 
+    #[test]
+    fn cl_credential_values_builder_add_hex_works() {
+        let credential_values_builder = _credential_values_builder();
+
+        let attr = CString::new("age").unwrap();
+        let hex_value = CString::new("1c").unwrap();
+        let err_code = cl_credential_values_builder_add_hex_known(credential_values_builder, attr.as_ptr(), hex_value.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let attr = CString::new("master_secret").unwrap();
+        let hex_value = CString::new("00ff").unwrap();
+        let err_code = cl_credential_values_builder_add_hex_hidden(credential_values_builder, attr.as_ptr(), hex_value.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let attr = CString::new("ssn").unwrap();
+        let hex_value = CString::new("0100").unwrap();
+        let hex_blinding_factor = CString::new("02").unwrap();
+        let err_code = cl_credential_values_builder_add_hex_commitment(credential_values_builder, attr.as_ptr(), hex_value.as_ptr(), hex_blinding_factor.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let attr = CString::new("height").unwrap();
+        let hex_value = CString::new("abc").unwrap();
+        let err_code = cl_credential_values_builder_add_hex_known(credential_values_builder, attr.as_ptr(), hex_value.as_ptr());
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+
+        let hex_value = CString::new("xyz1").unwrap();
+        let err_code = cl_credential_values_builder_add_hex_known(credential_values_builder, attr.as_ptr(), hex_value.as_ptr());
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+
+        let mut credential_values: *const c_void = ptr::null();
+        let err_code = cl_credential_values_builder_finalize(credential_values_builder, &mut credential_values);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        {
+            let credential_values = unsafe { &*(credential_values as *const CredentialValues) };
+            assert_eq!("28", credential_values.get("age").unwrap().value().to_dec().unwrap());
+            assert_eq!("255", credential_values.get("master_secret").unwrap().value().to_dec().unwrap());
+            assert_eq!("256", credential_values.get("ssn").unwrap().value().to_dec().unwrap());
+        }
+
+        _free_credential_values(credential_values);
+    }
+
+    #[test]
+    fn cl_credential_values_builder_add_base58_works() {
+        let credential_values_builder = _credential_values_builder();
+
+        let attr = CString::new("age").unwrap();
+        let base58_value = CString::new("V").unwrap();
+        let err_code = cl_credential_values_builder_add_base58_known(credential_values_builder, attr.as_ptr(), base58_value.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let attr = CString::new("master_secret").unwrap();
+        let base58_value = CString::new("5Q").unwrap();
+        let err_code = cl_credential_values_builder_add_base58_hidden(credential_values_builder, attr.as_ptr(), base58_value.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let attr = CString::new("ssn").unwrap();
+        let base58_value = CString::new("5R").unwrap();
+        let base58_blinding_factor = CString::new("3").unwrap();
+        let err_code = cl_credential_values_builder_add_base58_commitment(credential_values_builder, attr.as_ptr(), base58_value.as_ptr(), base58_blinding_factor.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let attr = CString::new("height").unwrap();
+        let base58_value = CString::new("0OIl").unwrap();
+        let err_code = cl_credential_values_builder_add_base58_known(credential_values_builder, attr.as_ptr(), base58_value.as_ptr());
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+
+        let mut credential_values: *const c_void = ptr::null();
+        let err_code = cl_credential_values_builder_finalize(credential_values_builder, &mut credential_values);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        {
+            let credential_values = unsafe { &*(credential_values as *const CredentialValues) };
+            assert_eq!("28", credential_values.get("age").unwrap().value().to_dec().unwrap());
+            assert_eq!("255", credential_values.get("master_secret").unwrap().value().to_dec().unwrap());
+            assert_eq!("256", credential_values.get("ssn").unwrap().value().to_dec().unwrap());
+        }
+
+        _free_credential_values(credential_values);
+    }
+
     #[test]
     fn cl_credential_values_free_works() {
         let credential_values = _credential_values();
@@ -918,6 +1658,110 @@ mod tests {
         _free_sub_proof_request(sub_proof_request);
     }
 
+    #[test]
+    fn cl_sub_proof_request_partition_json_works() {
+        let sub_proof_request = _sub_proof_request();
+        let credential_values = _credential_values();
+
+        let mut partition_json_p: *const c_char = ptr::null();
+        let err_code = cl_sub_proof_request_partition_json(sub_proof_request, credential_values, &mut partition_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let partition_json = unsafe { CString::from_raw(partition_json_p as *mut c_char) };
+        assert_eq!(json!({
+            "revealed": ["name"],
+            "predicated": ["age"],
+            "hidden": ["height", "master_secret", "sex"]
+        }), serde_json::from_str::<serde_json::Value>(partition_json.to_str().unwrap()).unwrap());
+
+        _free_sub_proof_request(sub_proof_request);
+        _free_credential_values(credential_values);
+    }
+
+    #[test]
+    fn cl_sub_proof_request_get_revealed_attrs_json_works() {
+        let sub_proof_request_builder = _sub_proof_request_builder();
+
+        for attr in ["sex", "name"].iter() {
+            let revealed_attr = CString::new(*attr).unwrap();
+            let err_code = cl_sub_proof_request_builder_add_revealed_attr(sub_proof_request_builder, revealed_attr.as_ptr());
+            assert_eq!(err_code, ErrorCode::Success);
+        }
+
+        let mut sub_proof_request: *const c_void = ptr::null();
+        let err_code = cl_sub_proof_request_builder_finalize(sub_proof_request_builder, &mut sub_proof_request);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut revealed_attrs_json_p: *const c_char = ptr::null();
+        let err_code = cl_sub_proof_request_get_revealed_attrs_json(sub_proof_request, &mut revealed_attrs_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let revealed_attrs_json = unsafe { CString::from_raw(revealed_attrs_json_p as *mut c_char) };
+        assert_eq!(json!(["name", "sex"]), serde_json::from_str::<serde_json::Value>(revealed_attrs_json.to_str().unwrap()).unwrap());
+
+        _free_sub_proof_request(sub_proof_request);
+    }
+
+    #[test]
+    fn cl_sub_proof_request_get_predicates_json_works() {
+        let sub_proof_request = _sub_proof_request();
+
+        let mut predicates_json_p: *const c_char = ptr::null();
+        let err_code = cl_sub_proof_request_get_predicates_json(sub_proof_request, &mut predicates_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let predicates_json = unsafe { CString::from_raw(predicates_json_p as *mut c_char) };
+        assert_eq!(json!([{"attr_name": "age", "p_type": "GE", "value": 18}]),
+                   serde_json::from_str::<serde_json::Value>(predicates_json.to_str().unwrap()).unwrap());
+
+        _free_sub_proof_request(sub_proof_request);
+    }
+
+    #[test]
+    fn cl_sub_proof_request_to_json_works() {
+        let sub_proof_request = _sub_proof_request();
+
+        let mut sub_proof_request_json_p: *const c_char = ptr::null();
+        let err_code = cl_sub_proof_request_to_json(sub_proof_request, &mut sub_proof_request_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let sub_proof_request_json = unsafe { CString::from_raw(sub_proof_request_json_p as *mut c_char) };
+        assert_eq!(r#"{"revealed_attrs":["name"],"predicates":[{"attr_name":"age","p_type":"GE","value":18}]}"#,
+                   sub_proof_request_json.to_str().unwrap());
+
+        _free_sub_proof_request(sub_proof_request);
+    }
+
+    #[test]
+    fn cl_sub_proof_request_from_json_works() {
+        let sub_proof_request = _sub_proof_request();
+
+        let mut sub_proof_request_json_p: *const c_char = ptr::null();
+        let err_code = cl_sub_proof_request_to_json(sub_proof_request, &mut sub_proof_request_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_sub_proof_request_p: *const c_void = ptr::null();
+        let err_code = cl_sub_proof_request_from_json(sub_proof_request_json_p, &mut restored_sub_proof_request_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        unsafe {
+            assert_eq!(*(sub_proof_request as *const SubProofRequest), *(restored_sub_proof_request_p as *const SubProofRequest));
+            CString::from_raw(sub_proof_request_json_p as *mut c_char);
+        }
+
+        _free_sub_proof_request(sub_proof_request);
+        _free_sub_proof_request(restored_sub_proof_request_p);
+    }
+
+    #[test]
+    fn cl_sub_proof_request_from_json_works_for_invalid_json() {
+        let sub_proof_request_json = CString::new(r#"{"revealed_attrs":["name"]}"#).unwrap();
+
+        let mut sub_proof_request_p: *const c_void = ptr::null();
+        let err_code = cl_sub_proof_request_from_json(sub_proof_request_json.as_ptr(), &mut sub_proof_request_p);
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+    }
+
     #[test]
     fn cl_sub_proof_request_free_works() {
         let sub_proof_request = _sub_proof_request();
@@ -962,6 +1806,18 @@ mod tests {
         _free_nonce(nonce)
     }
 
+    #[test]
+    fn cl_nonce_from_json_works_for_trivial_nonce() {
+        for nonce_json in ["\"0\"", "\"12345\""].iter() {
+            let nonce_json = CString::new(*nonce_json).unwrap();
+
+            let mut nonce_p: *const c_void = ptr::null();
+            let err_code = cl_nonce_from_json(nonce_json.as_ptr(), &mut nonce_p);
+            assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+            assert!(nonce_p.is_null());
+        }
+    }
+
     #[test]
     fn cl_nonce_free_works() {
         let nonce = _nonce();
@@ -969,6 +1825,130 @@ mod tests {
         let err_code = cl_nonce_free(nonce);
         assert_eq!(err_code, ErrorCode::Success);
     }
+
+    #[test]
+    fn cl_proof_get_sub_proof_count_works() {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_values = _credential_values();
+        let credential_nonce = _nonce();
+        let (blinded_credential_secrets,
+            credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                                        credential_key_correctness_proof,
+                                                                                        credential_values,
+                                                                                        credential_nonce);
+        let credential_issuance_nonce = _nonce();
+        let (credential_signature, signature_correctness_proof) = _credential_signature(blinded_credential_secrets,
+                                                                                        blinded_credential_secrets_correctness_proof,
+                                                                                        credential_nonce,
+                                                                                        credential_issuance_nonce,
+                                                                                        credential_values,
+                                                                                        credential_pub_key,
+                                                                                        credential_priv_key);
+        _process_credential_signature(credential_signature,
+                                      signature_correctness_proof,
+                                      credential_secrets_blinding_factors,
+                                      credential_values,
+                                      credential_pub_key,
+                                      credential_issuance_nonce);
+
+        let credential_schema = _credential_schema();
+        let non_credential_schema = _non_credential_schema();
+        let sub_proof_request = _sub_proof_request();
+        let proof_builder = _proof_builder();
+
+        for _ in 0..2 {
+            let err_code = cl_proof_builder_add_sub_proof_request(proof_builder,
+                                                                  sub_proof_request,
+                                                                  credential_schema,
+                                                                  non_credential_schema,
+                                                                  credential_signature,
+                                                                  credential_values,
+                                                                  _key_id().as_ptr(),
+                                                                  credential_pub_key);
+            assert_eq!(err_code, ErrorCode::Success);
+        }
+
+        let nonce = _nonce();
+        let mut proof: *const c_void = ptr::null();
+        let err_code = cl_proof_builder_finalize(proof_builder, nonce, &mut proof);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut count: usize = 0;
+        let err_code = cl_proof_get_sub_proof_count(proof, &mut count);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert_eq!(2, count);
+
+        _free_proof(proof);
+        _free_nonce(nonce);
+        _free_sub_proof_request(sub_proof_request);
+        _free_credential_schema(credential_schema);
+        _free_non_credential_schema(non_credential_schema);
+        _free_credential_signature(credential_signature, signature_correctness_proof);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_nonce(credential_nonce);
+        _free_nonce(credential_issuance_nonce);
+        _free_credential_values(credential_values);
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+    }
+
+    #[test]
+    fn cl_proof_get_transcript_digest_works() {
+        let proof = Box::into_raw(Box::new(::cl::prover::mocks::proof())) as *const c_void;
+        let nonce = _nonce();
+
+        let mut transcript_digest_p: *const c_char = ptr::null();
+        let err_code = cl_proof_get_transcript_digest(proof, nonce, &mut transcript_digest_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let transcript_digest = unsafe { CString::from_raw(transcript_digest_p as *mut c_char) };
+        assert_eq!(64, transcript_digest.to_str().unwrap().len());
+
+        _free_proof(proof);
+        _free_nonce(nonce);
+    }
+
+    #[test]
+    fn cl_proof_decode_revealed_attrs_works() {
+        let proof = Box::into_raw(Box::new(::cl::prover::mocks::proof())) as *const c_void;
+        let encoding_table_json = CString::new(r#"{"66682250590915135919393234675423675079281389286836524491448775067034910960723":"Alex"}"#).unwrap();
+
+        let mut revealed_attrs_json_p: *const c_char = ptr::null();
+        let err_code = cl_proof_decode_revealed_attrs(proof, encoding_table_json.as_ptr(), &mut revealed_attrs_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let revealed_attrs_json = unsafe { CString::from_raw(revealed_attrs_json_p as *mut c_char) };
+        assert_eq!(r#"[{"name":"Alex"}]"#, revealed_attrs_json.to_str().unwrap());
+
+        _free_proof(proof);
+    }
+
+    #[test]
+    fn cl_proof_get_predicates_json_works() {
+        let proof = Box::into_raw(Box::new(::cl::prover::mocks::proof())) as *const c_void;
+
+        let mut predicates_json_p: *const c_char = ptr::null();
+        let err_code = cl_proof_get_predicates_json(proof, &mut predicates_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let predicates_json = unsafe { CString::from_raw(predicates_json_p as *mut c_char) };
+        assert_eq!(json!([[{"attr_name": "age", "p_type": "GE", "value": 18}]]),
+                   serde_json::from_str::<serde_json::Value>(predicates_json.to_str().unwrap()).unwrap());
+
+        _free_proof(proof);
+    }
+
+    #[test]
+    fn cl_proof_decode_revealed_attrs_works_for_invalid_encoding_table() {
+        let proof = Box::into_raw(Box::new(::cl::prover::mocks::proof())) as *const c_void;
+        let encoding_table_json = CString::new(r#"["Alex"]"#).unwrap();
+
+        let mut revealed_attrs_json_p: *const c_char = ptr::null();
+        let err_code = cl_proof_decode_revealed_attrs(proof, encoding_table_json.as_ptr(), &mut revealed_attrs_json_p);
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+
+        _free_proof(proof);
+    }
 }
 
 pub mod mocks {
@@ -977,6 +1957,10 @@ pub mod mocks {
     use std::ffi::CString;
     use std::ptr;
 
+    pub fn _key_id() -> CString {
+        CString::new("CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW:3:CL:1").unwrap()
+    }
+
     pub fn _credential_schema_builder() -> *const c_void {
         let mut credential_schema_builder: *const c_void = ptr::null();
         let err_code = cl_credential_schema_builder_new(&mut credential_schema_builder);
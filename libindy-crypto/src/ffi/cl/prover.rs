@@ -1,3 +1,4 @@
+use bn::BigNumber;
 use cl::prover::*;
 use cl::*;
 use errors::ToErrorCode;
@@ -5,7 +6,11 @@ use errors::ErrorCode;
 use ffi::ctypes::CTypesUtils;
 
 use serde_json;
+use std::collections::BTreeMap;
 use std::os::raw::c_void;
+use std::ffi::CString;
+use std::ptr;
+use std::slice;
 use libc::c_char;
 
 /// Creates a master secret.
@@ -39,6 +44,9 @@ pub extern fn cl_prover_new_master_secret(master_secret_p: *mut *const c_void) -
 
 /// Returns json representation of master secret.
 ///
+/// Note: Master secret json contains secret value so it must be deallocated
+/// by calling cl_free_secret_str that wipes memory before releasing it.
+///
 /// # Arguments
 /// * `master_secret` - Reference that contains master secret instance pointer.
 /// * `master_secret_json_p` - Reference that will contain master secret json.
@@ -54,7 +62,7 @@ pub extern fn cl_master_secret_to_json(master_secret: *const c_void,
 
     let res = match serde_json::to_string(master_secret) {
         Ok(master_secret_json) => {
-            trace!("cl_master_secret_to_json: master_secret_json: {:?}", master_secret_json);
+            trace!("cl_master_secret_to_json: master_secret_json: {:?}", secret!(&master_secret_json));
             unsafe {
                 let master_secret_json = CTypesUtils::string_to_cstring(master_secret_json);
                 *master_secret_json_p = master_secret_json.into_raw();
@@ -85,11 +93,11 @@ pub extern fn cl_master_secret_from_json(master_secret_json: *const c_char,
     check_useful_c_str!(master_secret_json, ErrorCode::CommonInvalidParam1);
     check_useful_c_ptr!(master_secret_p, ErrorCode::CommonInvalidParam2);
 
-    trace!("cl_master_secret_from_json: entity: master_secret_json: {:?}", master_secret_json);
+    trace!("cl_master_secret_from_json: entity: master_secret_json: {:?}", secret!(&master_secret_json));
 
     let res = match serde_json::from_str::<MasterSecret>(&master_secret_json) {
         Ok(master_secret) => {
-            trace!("cl_master_secret_from_json: master_secret: {:?}", master_secret);
+            trace!("cl_master_secret_from_json: master_secret: {:?}", secret!(&master_secret));
             unsafe {
                 *master_secret_p = Box::into_raw(Box::new(master_secret)) as *const c_void;
                 trace!("cl_master_secret_from_json: *master_secret_p: {:?}", *master_secret_p);
@@ -122,6 +130,27 @@ pub extern fn cl_master_secret_free(master_secret: *const c_void) -> ErrorCode {
     res
 }
 
+/// Wipes and deallocates string that contains secret data (for example master secret json).
+///
+/// # Arguments
+/// * `secret_str` - Reference that contains string pointer returned by library.
+#[no_mangle]
+pub extern fn cl_free_secret_str(secret_str: *const c_char) -> ErrorCode {
+    trace!("cl_free_secret_str: >>> secret_str: {:?}", secret_str);
+
+    check_useful_c_ptr!(secret_str, ErrorCode::CommonInvalidParam1);
+
+    let mut bytes = unsafe { CString::from_raw(secret_str as *mut c_char) }.into_bytes_with_nul();
+    for byte in bytes.iter_mut() {
+        unsafe { ptr::write_volatile(byte, 0); }
+    }
+
+    let res = ErrorCode::Success;
+    trace!("cl_free_secret_str: <<< res: {:?}", res);
+
+    res
+}
+
 /// Creates blinded credential secrets for given issuer key and master secret.
 ///
 /// Note that blinded credential secrets deallocation must be performed by
@@ -214,6 +243,8 @@ pub extern fn cl_prover_blind_credential_secrets(credential_pub_key: *const c_vo
 
 /// Returns json representation of blinded credential secrets.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `blinded_credential_secrets` - Reference that contains Blinded credential secrets pointer.
 /// * `blinded_credential_secrets_json_p` - Reference that will contain blinded credential secrets json.
@@ -280,6 +311,44 @@ pub extern fn cl_blinded_credential_secrets_from_json(blinded_credential_secrets
     res
 }
 
+/// Returns json object that maps committed attributes names to the commitments.
+/// Allows Issuer to check that Prover committed to exactly expected set of attributes.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `blinded_credential_secrets` - Reference that contains blinded credential secrets instance pointer.
+/// * `committed_attrs_json_p` - Reference that will contain committed attributes json.
+#[no_mangle]
+pub extern fn cl_blinded_credential_secrets_get_committed_attrs_json(blinded_credential_secrets: *const c_void,
+                                                                    committed_attrs_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_blinded_credential_secrets_get_committed_attrs_json: >>> blinded_credential_secrets: {:?}, committed_attrs_json_p: {:?}",
+           blinded_credential_secrets, committed_attrs_json_p);
+
+    check_useful_c_reference!(blinded_credential_secrets, BlindedCredentialSecrets, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(committed_attrs_json_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_blinded_credential_secrets_get_committed_attrs_json: entity: blinded_credential_secrets: {:?}", blinded_credential_secrets);
+
+    let committed_attrs = blinded_credential_secrets.committed_attributes().collect::<BTreeMap<&String, &BigNumber>>();
+
+    let res = match serde_json::to_string(&committed_attrs) {
+        Ok(committed_attrs_json) => {
+            trace!("cl_blinded_credential_secrets_get_committed_attrs_json: committed_attrs_json: {:?}", committed_attrs_json);
+            unsafe {
+                let committed_attrs_json = CTypesUtils::string_to_cstring(committed_attrs_json);
+                *committed_attrs_json_p = committed_attrs_json.into_raw();
+                trace!("cl_blinded_credential_secrets_get_committed_attrs_json: committed_attrs_json_p: {:?}", *committed_attrs_json_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidState
+    };
+
+    trace!("cl_blinded_credential_secrets_get_committed_attrs_json: <<< res: {:?}", res);
+    res
+}
+
 /// Deallocates  blinded credential secrets instance.
 ///
 /// # Arguments
@@ -301,6 +370,8 @@ pub extern fn cl_blinded_credential_secrets_free(blinded_credential_secrets: *co
 
 /// Returns json representation of credential secrets blinding factors.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `credential_secrets_blinding_factors` - Reference that contains credential secrets blinding factors pointer.
 /// * `credential_secrets_blinding_factors_json_p` - Reference that will contain credential secrets blinding factors json.
@@ -388,6 +459,8 @@ pub extern fn cl_credential_secrets_blinding_factors_free(credential_secrets_bli
 
 /// Returns json representation of blinded credential secrets correctness proof.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `blinded_credential_secrets_correctness_proof` - Reference that contains blinded credential secrets correctness proof pointer.
 /// * `blinded_credential_secrets_correctness_proof_json_p` - Reference that will contain blinded credential secrets correctness proof json.
@@ -535,7 +608,131 @@ pub extern fn cl_prover_process_credential_signature(credential_signature: *cons
     };
 
     trace!("cl_prover_process_credential_signature: <<< res: {:?}", res);
-    ErrorCode::Success
+    res
+}
+
+/// Verifies that processed credential signature is a valid issuer signature of credential values
+/// under the credential public key. Doesn't require signature correctness proof.
+///
+/// # Arguments
+/// * `credential_signature` - Processed credential signature instance pointer.
+/// * `credential_values` - Credential values instance pointer.
+/// * `credential_pub_key` - Credential public key instance pointer.
+/// * `valid_p` - Reference that will be filled with true - if signature valid or false otherwise.
+#[no_mangle]
+pub extern fn cl_prover_verify_credential(credential_signature: *const c_void,
+                                          credential_values: *const c_void,
+                                          credential_pub_key: *const c_void,
+                                          valid_p: *mut bool) -> ErrorCode {
+    trace!("cl_prover_verify_credential: >>> credential_signature: {:?}, credential_values: {:?}, credential_pub_key: {:?}, valid_p: {:?}",
+           credential_signature, credential_values, credential_pub_key, valid_p);
+
+    check_useful_c_reference!(credential_signature, CredentialSignature, ErrorCode::CommonInvalidParam1);
+    check_useful_c_reference!(credential_values, CredentialValues, ErrorCode::CommonInvalidParam2);
+    check_useful_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam3);
+    check_useful_c_ptr!(valid_p, ErrorCode::CommonInvalidParam4);
+
+    trace!("cl_prover_verify_credential: entities: credential_signature: {:?}, credential_values: {:?}, credential_pub_key: {:?}",
+           credential_signature, secret!(&credential_values), credential_pub_key);
+
+    let res = match Prover::verify_credential(credential_signature, credential_values, credential_pub_key) {
+        Ok(valid) => {
+            trace!("cl_prover_verify_credential: valid: {:?}", valid);
+            unsafe {
+                *valid_p = valid;
+                trace!("cl_prover_verify_credential: *valid_p: {:?}", *valid_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_prover_verify_credential: <<< res: {:?}", res);
+    res
+}
+
+/// Checks whether credential can satisfy sub proof request without building of proof.
+///
+/// # Arguments
+/// * `sub_proof_request` - Reference that contains requested attributes and predicates instance pointer.
+/// * `credential_schema` - Reference that contains credential schema instance pointer.
+/// * `credential_values` - Reference that contains credential values instance pointer.
+/// * `can_satisfy_p` - Reference that will be filled with true - if credential satisfies request or false otherwise.
+#[no_mangle]
+pub extern fn cl_prover_can_satisfy(sub_proof_request: *const c_void,
+                                    credential_schema: *const c_void,
+                                    credential_values: *const c_void,
+                                    can_satisfy_p: *mut bool) -> ErrorCode {
+    trace!("cl_prover_can_satisfy: >>> sub_proof_request: {:?}, credential_schema: {:?}, credential_values: {:?}, can_satisfy_p: {:?}",
+           sub_proof_request, credential_schema, credential_values, can_satisfy_p);
+
+    check_useful_c_reference!(sub_proof_request, SubProofRequest, ErrorCode::CommonInvalidParam1);
+    check_useful_c_reference!(credential_schema, CredentialSchema, ErrorCode::CommonInvalidParam2);
+    check_useful_c_reference!(credential_values, CredentialValues, ErrorCode::CommonInvalidParam3);
+    check_useful_c_ptr!(can_satisfy_p, ErrorCode::CommonInvalidParam4);
+
+    trace!("cl_prover_can_satisfy: entities: sub_proof_request: {:?}, credential_schema: {:?}, credential_values: {:?}",
+           sub_proof_request, credential_schema, secret!(&credential_values));
+
+    let res = match Prover::can_satisfy(sub_proof_request, credential_schema, credential_values) {
+        Ok(can_satisfy) => {
+            trace!("cl_prover_can_satisfy: can_satisfy: {:?}", can_satisfy);
+            unsafe {
+                *can_satisfy_p = can_satisfy;
+                trace!("cl_prover_can_satisfy: *can_satisfy_p: {:?}", *can_satisfy_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_prover_can_satisfy: <<< res: {:?}", res);
+    res
+}
+
+/// Returns json array of sub proof request predicates that credential values don't satisfy.
+///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
+/// # Arguments
+/// * `sub_proof_request` - Reference that contains requested attributes and predicates instance pointer.
+/// * `credential_values` - Reference that contains credential values instance pointer.
+/// * `unsatisfied_predicates_json_p` - Reference that will contain json array of unsatisfied predicates.
+#[no_mangle]
+pub extern fn cl_prover_unsatisfied_predicates(sub_proof_request: *const c_void,
+                                               credential_values: *const c_void,
+                                               unsatisfied_predicates_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("cl_prover_unsatisfied_predicates: >>> sub_proof_request: {:?}, credential_values: {:?}, unsatisfied_predicates_json_p: {:?}",
+           sub_proof_request, credential_values, unsatisfied_predicates_json_p);
+
+    check_useful_c_reference!(sub_proof_request, SubProofRequest, ErrorCode::CommonInvalidParam1);
+    check_useful_c_reference!(credential_values, CredentialValues, ErrorCode::CommonInvalidParam2);
+    check_useful_c_ptr!(unsatisfied_predicates_json_p, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_prover_unsatisfied_predicates: entities: sub_proof_request: {:?}, credential_values: {:?}",
+           sub_proof_request, secret!(&credential_values));
+
+    let res = match Prover::unsatisfied_predicates(sub_proof_request, credential_values) {
+        Ok(unsatisfied_predicates) => {
+            trace!("cl_prover_unsatisfied_predicates: unsatisfied_predicates: {:?}", unsatisfied_predicates);
+            match serde_json::to_string(&unsatisfied_predicates) {
+                Ok(unsatisfied_predicates_json) => {
+                    trace!("cl_prover_unsatisfied_predicates: unsatisfied_predicates_json: {:?}", unsatisfied_predicates_json);
+                    unsafe {
+                        let unsatisfied_predicates_json = CTypesUtils::string_to_cstring(unsatisfied_predicates_json);
+                        *unsatisfied_predicates_json_p = unsatisfied_predicates_json.into_raw();
+                        trace!("cl_prover_unsatisfied_predicates: unsatisfied_predicates_json_p: {:?}", *unsatisfied_predicates_json_p);
+                    }
+                    ErrorCode::Success
+                }
+                Err(_) => ErrorCode::CommonInvalidState
+            }
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_prover_unsatisfied_predicates: <<< res: {:?}", res);
+    res
 }
 
 /// Creates and returns proof builder.
@@ -569,6 +766,53 @@ pub extern fn cl_prover_new_proof_builder(proof_builder_p: *mut *const c_void) -
     res
 }
 
+/// Add a common attribute to the proof builder.
+///
+/// Common attributes (for example master secret) must be equal across all credentials used for proof.
+///
+/// # Arguments
+/// * `proof_builder` - Reference that contain proof builder instance pointer.
+/// * `attr_name` - Common attribute's name.
+#[no_mangle]
+pub extern fn cl_proof_builder_add_common_attribute(proof_builder: *const c_void,
+                                                    attr_name: *const c_char) -> ErrorCode {
+    trace!("cl_proof_builder_add_common_attribute: >>> proof_builder: {:?}, attr_name: {:?}", proof_builder, attr_name);
+
+    check_useful_mut_c_reference!(proof_builder, ProofBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr_name, ErrorCode::CommonInvalidParam2);
+
+    trace!("cl_proof_builder_add_common_attribute: entities: proof_builder: {:?}, attr_name: {:?}", proof_builder, attr_name);
+
+    let res = match proof_builder.add_common_attribute(&attr_name) {
+        Ok(()) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_proof_builder_add_common_attribute: <<< res: {:?}", res);
+    res
+}
+
+/// Resets the proof builder so it can be reused for the next proof.
+///
+/// Accumulated sub proof requests and common attributes are removed, secret intermediate values are zeroized.
+///
+/// # Arguments
+/// * `proof_builder` - Reference that contain proof builder instance pointer.
+#[no_mangle]
+pub extern fn cl_proof_builder_clear(proof_builder: *const c_void) -> ErrorCode {
+    trace!("cl_proof_builder_clear: >>> proof_builder: {:?}", proof_builder);
+
+    check_useful_mut_c_reference!(proof_builder, ProofBuilder, ErrorCode::CommonInvalidParam1);
+
+    trace!("cl_proof_builder_clear: entities: proof_builder: {:?}", proof_builder);
+
+    proof_builder.clear();
+    let res = ErrorCode::Success;
+
+    trace!("cl_proof_builder_clear: <<< res: {:?}", res);
+    res
+}
+
 /// Add a sub proof request to the proof builder
 ///
 /// # Arguments
@@ -578,6 +822,7 @@ pub extern fn cl_prover_new_proof_builder(proof_builder_p: *mut *const c_void) -
 /// * `non_credential_schema` - Reference that contains non credential schema instance pointer.
 /// * `credential_signature` - Reference that contains the credential signature pointer.
 /// * `credential_values` - Reference that contains credential values instance pointer.
+/// * `key_id` - Identifier of credential public key as null terminated string.
 /// * `credential_pub_key` - Reference that contains credential public key instance pointer.
 #[no_mangle]
 pub extern fn cl_proof_builder_add_sub_proof_request(proof_builder: *const c_void,
@@ -586,6 +831,7 @@ pub extern fn cl_proof_builder_add_sub_proof_request(proof_builder: *const c_voi
                                                                  non_credential_schema: *const c_void,
                                                                  credential_signature: *const c_void,
                                                                  credential_values: *const c_void,
+                                                                 key_id: *const c_char,
                                                                  credential_pub_key: *const c_void) -> ErrorCode {
     trace!("cl_proof_builder_add_sub_proof_request: >>> proof_builder: {:?}, \
                                                                     sub_proof_request: {:?}, \
@@ -593,6 +839,7 @@ pub extern fn cl_proof_builder_add_sub_proof_request(proof_builder: *const c_voi
                                                                     non_credential_schema: {:?}, \
                                                                     credential_signature: {:?}, \
                                                                     credential_values: {:?}, \
+                                                                    key_id: {:?}, \
                                                                     credential_pub_key: {:?}",
                     proof_builder,
                     sub_proof_request,
@@ -600,6 +847,7 @@ pub extern fn cl_proof_builder_add_sub_proof_request(proof_builder: *const c_voi
                     non_credential_schema,
                     credential_signature,
                     credential_values,
+                    key_id,
                     credential_pub_key);
 
     check_useful_mut_c_reference!(proof_builder, ProofBuilder, ErrorCode::CommonInvalidParam1);
@@ -608,7 +856,8 @@ pub extern fn cl_proof_builder_add_sub_proof_request(proof_builder: *const c_voi
     check_useful_c_reference!(non_credential_schema, NonCredentialSchema, ErrorCode::CommonInvalidParam4);
     check_useful_c_reference!(credential_signature, CredentialSignature, ErrorCode::CommonInvalidParam5);
     check_useful_c_reference!(credential_values, CredentialValues, ErrorCode::CommonInvalidParam6);
-    check_useful_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam7);
+    check_useful_c_str!(key_id, ErrorCode::CommonInvalidParam7);
+    check_useful_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam8);
 
     trace!("cl_proof_builder_add_sub_proof_request: entities: proof_builder: {:?}, \
                                                                           sub_proof_request: {:?}, \
@@ -616,6 +865,7 @@ pub extern fn cl_proof_builder_add_sub_proof_request(proof_builder: *const c_voi
                                                                           non_credential_schema: {:?}, \
                                                                           credential_signature: {:?}, \
                                                                           credential_values: {:?}, \
+                                                                          key_id: {:?}, \
                                                                           credential_pub_key: {:?}",
            proof_builder,
            sub_proof_request,
@@ -623,6 +873,7 @@ pub extern fn cl_proof_builder_add_sub_proof_request(proof_builder: *const c_voi
            non_credential_schema,
            credential_signature,
            credential_values,
+           key_id,
            credential_pub_key);
 
     let res = match proof_builder.add_sub_proof_request(sub_proof_request,
@@ -630,13 +881,14 @@ pub extern fn cl_proof_builder_add_sub_proof_request(proof_builder: *const c_voi
                                                         non_credential_schema,
                                                         credential_signature,
                                                         credential_values,
+                                                        &key_id,
                                                         credential_pub_key) {
         Ok(()) => ErrorCode::Success,
         Err(err) => err.to_error_code()
     };
 
     trace!("cl_proof_builder_add_sub_proof_request: <<< res: {:?}", res);
-    ErrorCode::Success
+    res
 }
 
 
@@ -683,6 +935,8 @@ pub extern fn cl_proof_builder_finalize(proof_builder: *const c_void,
 
 /// Returns json representation of proof.
 ///
+/// Note: Returned string must be deallocated by calling cl_free_string.
+///
 /// # Arguments
 /// * `proof` - Reference that contains proof instance pointer.
 /// * `proof_json_p` - Reference that will contain proof json.
@@ -746,6 +1000,99 @@ pub extern fn cl_proof_from_json(proof_json: *const c_char,
     res
 }
 
+/// Returns compact binary representation of proof.
+///
+/// Note: Bytes deallocation must be performed by calling cl_proof_bytes_free.
+///
+/// # Arguments
+/// * `proof` - Reference that contains proof instance pointer.
+/// * `bytes_p` - Reference that will contain proof bytes.
+/// * `bytes_len_p` - Reference that will contain proof bytes length.
+#[no_mangle]
+pub extern fn cl_proof_to_bytes(proof: *const c_void,
+                                bytes_p: *mut *const u8,
+                                bytes_len_p: *mut usize) -> ErrorCode {
+    trace!("cl_proof_to_bytes: >>> proof: {:?}, bytes_p: {:?}, bytes_len_p: {:?}", proof, bytes_p, bytes_len_p);
+
+    check_useful_c_reference!(proof, Proof, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(bytes_p, ErrorCode::CommonInvalidParam2);
+    check_useful_c_ptr!(bytes_len_p, ErrorCode::CommonInvalidParam3);
+
+    trace!("cl_proof_to_bytes: entity >>> proof: {:?}", proof);
+
+    let res = match proof.to_bytes() {
+        Ok(bytes) => {
+            trace!("cl_proof_to_bytes: bytes: {:?}", bytes);
+            unsafe {
+                *bytes_len_p = bytes.len();
+                *bytes_p = Box::into_raw(bytes.into_boxed_slice()) as *const u8;
+                trace!("cl_proof_to_bytes: *bytes_p: {:?}, *bytes_len_p: {:?}", *bytes_p, *bytes_len_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_proof_to_bytes: <<< res: {:?}", res);
+    res
+}
+
+/// Deallocates proof bytes returned by cl_proof_to_bytes.
+///
+/// # Arguments
+/// * `bytes` - Proof bytes.
+/// * `bytes_len` - Proof bytes length.
+#[no_mangle]
+pub extern fn cl_proof_bytes_free(bytes: *const u8,
+                                  bytes_len: usize) -> ErrorCode {
+    trace!("cl_proof_bytes_free: >>> bytes: {:?}, bytes_len: {:?}", bytes, bytes_len);
+
+    check_useful_c_ptr!(bytes, ErrorCode::CommonInvalidParam1);
+
+    unsafe { Box::from_raw(slice::from_raw_parts_mut(bytes as *mut u8, bytes_len) as *mut [u8]); }
+
+    let res = ErrorCode::Success;
+
+    trace!("cl_proof_bytes_free: <<< res: {:?}", res);
+    res
+}
+
+/// Creates and returns proof from binary representation.
+///
+/// Note: Proof instance deallocation must be performed by calling cl_proof_free.
+///
+/// # Arguments
+/// * `bytes` - Proof bytes created by cl_proof_to_bytes.
+/// * `bytes_len` - Proof bytes length.
+/// * `proof_p` - Reference that will contain proof instance pointer.
+#[no_mangle]
+pub extern fn cl_proof_from_bytes(bytes: *const u8,
+                                  bytes_len: usize,
+                                  proof_p: *mut *const c_void) -> ErrorCode {
+    trace!("cl_proof_from_bytes: >>> bytes: {:?}, bytes_len: {:?}, proof_p: {:?}", bytes, bytes_len, proof_p);
+
+    check_useful_c_ptr!(bytes, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(proof_p, ErrorCode::CommonInvalidParam3);
+
+    let bytes = unsafe { slice::from_raw_parts(bytes, bytes_len) };
+    trace!("cl_proof_from_bytes: entity: bytes: {:?}", bytes);
+
+    let res = match Proof::from_bytes(bytes) {
+        Ok(proof) => {
+            trace!("cl_proof_from_bytes: proof: {:?}", proof);
+            unsafe {
+                *proof_p = Box::into_raw(Box::new(proof)) as *const c_void;
+                trace!("cl_proof_from_bytes: *proof_p: {:?}", *proof_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("cl_proof_from_bytes: <<< res: {:?}", res);
+    res
+}
+
 /// Deallocates proof instance.
 ///
 /// # Arguments
@@ -774,6 +1121,9 @@ mod tests {
     use ffi::cl::mocks::*;
     use ffi::cl::issuer::mocks::*;
     use ffi::cl::prover::mocks::*;
+    use ffi::cl::{cl_sub_proof_request_builder_add_predicate, cl_sub_proof_request_builder_finalize};
+    use ffi::cl::{cl_credential_values_builder_add_dec_hidden, cl_credential_values_builder_add_dec_known,
+                  cl_credential_values_builder_add_dec_commitment, cl_credential_values_builder_finalize};
 
     #[test]
     fn cl_prover_new_master_secret_works() {
@@ -808,6 +1158,14 @@ mod tests {
         let err_code = cl_master_secret_from_json(master_secret_json_p, &mut master_secret_p);
         assert_eq!(err_code, ErrorCode::Success);
 
+        let expected = unsafe { &*(master_secret as *const MasterSecret) };
+        let actual = unsafe { &*(master_secret_p as *const MasterSecret) };
+        assert_eq!(expected.value().unwrap(), actual.value().unwrap());
+
+        let err_code = cl_free_secret_str(master_secret_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_master_secret(master_secret_p);
         _free_master_secret(master_secret)
     }
 
@@ -819,6 +1177,56 @@ mod tests {
         assert_eq!(err_code, ErrorCode::Success);
     }
 
+    #[test]
+    fn cl_prover_can_satisfy_works() {
+        let sub_proof_request = _sub_proof_request();
+        let credential_schema = _credential_schema();
+        let credential_values = _credential_values();
+
+        let mut can_satisfy = false;
+        let err_code = cl_prover_can_satisfy(sub_proof_request, credential_schema, credential_values, &mut can_satisfy);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(can_satisfy);
+
+        _free_sub_proof_request(sub_proof_request);
+        _free_credential_schema(credential_schema);
+        _free_credential_values(credential_values);
+    }
+
+    #[test]
+    fn cl_prover_unsatisfied_predicates_works() {
+        let sub_proof_request_builder = _sub_proof_request_builder();
+
+        let attr_name = CString::new("age").unwrap();
+        let p_type = CString::new("GE").unwrap();
+        let err_code = cl_sub_proof_request_builder_add_predicate(sub_proof_request_builder, attr_name.as_ptr(), p_type.as_ptr(), 30);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let attr_name = CString::new("income").unwrap();
+        let err_code = cl_sub_proof_request_builder_add_predicate(sub_proof_request_builder, attr_name.as_ptr(), p_type.as_ptr(), 5000);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut sub_proof_request: *const c_void = ptr::null();
+        let err_code = cl_sub_proof_request_builder_finalize(sub_proof_request_builder, &mut sub_proof_request);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let credential_values = _credential_values();
+
+        let mut unsatisfied_predicates_json_p: *const c_char = ptr::null();
+        let err_code = cl_prover_unsatisfied_predicates(sub_proof_request, credential_values, &mut unsatisfied_predicates_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let unsatisfied_predicates_json = unsafe { CString::from_raw(unsatisfied_predicates_json_p as *mut c_char) };
+        let unsatisfied_predicates: serde_json::Value = serde_json::from_str(unsatisfied_predicates_json.to_str().unwrap()).unwrap();
+        assert_eq!(json!([
+            {"attr_name": "age", "p_type": "GE", "value": 30},
+            {"attr_name": "income", "p_type": "GE", "value": 5000}
+        ]), unsatisfied_predicates);
+
+        _free_sub_proof_request(sub_proof_request);
+        _free_credential_values(credential_values);
+    }
+
     #[test]
     fn cl_prover_blind_credential_secrets_works() {
         let credential_values = _credential_values();
@@ -839,6 +1247,7 @@ mod tests {
         assert_eq!(err_code, ErrorCode::Success);
         assert!(!blinded_credential_secrets_p.is_null());
         assert!(!credential_secrets_blinding_factors_p.is_null());
+        assert!(!blinded_credential_secrets_correctness_proof_p.is_null());
 
         _free_blinded_credential_secrets(blinded_credential_secrets_p,
                                     credential_secrets_blinding_factors_p,
@@ -848,6 +1257,58 @@ mod tests {
         _free_nonce(credential_nonce);
     }
 
+    #[test]
+    fn cl_prover_blind_credential_secrets_works_for_serialization_of_all_outputs() {
+        let credential_values = _credential_values();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_nonce = _nonce();
+        let (blinded_credential_secrets,
+            credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                                        credential_key_correctness_proof,
+                                                                                        credential_values,
+                                                                                        credential_nonce);
+
+        let mut blinded_credential_secrets_json_p: *const c_char = ptr::null();
+        let err_code = cl_blinded_credential_secrets_to_json(blinded_credential_secrets, &mut blinded_credential_secrets_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut credential_secrets_blinding_factors_json_p: *const c_char = ptr::null();
+        let err_code = cl_credential_secrets_blinding_factors_to_json(credential_secrets_blinding_factors,
+                                                                      &mut credential_secrets_blinding_factors_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut blinded_credential_secrets_correctness_proof_json_p: *const c_char = ptr::null();
+        let err_code = cl_blinded_credential_secrets_correctness_proof_to_json(blinded_credential_secrets_correctness_proof,
+                                                                               &mut blinded_credential_secrets_correctness_proof_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_blinded_credential_secrets: *const c_void = ptr::null();
+        let err_code = cl_blinded_credential_secrets_from_json(blinded_credential_secrets_json_p,
+                                                               &mut restored_blinded_credential_secrets);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_credential_secrets_blinding_factors: *const c_void = ptr::null();
+        let err_code = cl_credential_secrets_blinding_factors_from_json(credential_secrets_blinding_factors_json_p,
+                                                                        &mut restored_credential_secrets_blinding_factors);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut restored_blinded_credential_secrets_correctness_proof: *const c_void = ptr::null();
+        let err_code = cl_blinded_credential_secrets_correctness_proof_from_json(blinded_credential_secrets_correctness_proof_json_p,
+                                                                                 &mut restored_blinded_credential_secrets_correctness_proof);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_blinded_credential_secrets(blinded_credential_secrets,
+                                         credential_secrets_blinding_factors,
+                                         blinded_credential_secrets_correctness_proof);
+        _free_blinded_credential_secrets(restored_blinded_credential_secrets,
+                                         restored_credential_secrets_blinding_factors,
+                                         restored_blinded_credential_secrets_correctness_proof);
+        _free_credential_values(credential_values);
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_nonce(credential_nonce);
+    }
+
     #[test]
     fn cl_prover_blinded_credential_secrets_free_works() {
         let credential_values = _credential_values();
@@ -914,7 +1375,7 @@ mod tests {
                                                                                         credential_issuance_nonce,
                                                                                         credential_values,
                                                                                         credential_pub_key,
-                                                                                        credential_pub_key);
+                                                                                        credential_priv_key);
         _process_credential_signature(credential_signature,
                                       signature_correctness_proof,
                                       credential_secrets_blinding_factors,
@@ -929,6 +1390,7 @@ mod tests {
                                                                           non_credential_schema,
                                                                           credential_signature,
                                                                           credential_values,
+                                                                          _key_id().as_ptr(),
                                                                           credential_pub_key);
         assert_eq!(err_code, ErrorCode::Success);
 
@@ -946,6 +1408,31 @@ mod tests {
         _free_non_credential_schema(non_credential_schema);
     }
 
+    #[test]
+    fn cl_blinded_credential_secrets_get_committed_attrs_json_works() {
+        let blinded_credential_secrets_json = CString::new(json!({
+            "u": "1",
+            "hidden_attributes": ["master_secret"],
+            "committed_attributes": {"name": "123"}
+        }).to_string()).unwrap();
+
+        let mut blinded_credential_secrets_p: *const c_void = ptr::null();
+        let err_code = cl_blinded_credential_secrets_from_json(blinded_credential_secrets_json.as_ptr(),
+                                                               &mut blinded_credential_secrets_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut committed_attrs_json_p: *const c_char = ptr::null();
+        let err_code = cl_blinded_credential_secrets_get_committed_attrs_json(blinded_credential_secrets_p,
+                                                                              &mut committed_attrs_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let committed_attrs_json = unsafe { CString::from_raw(committed_attrs_json_p as *mut c_char) };
+        assert_eq!(r#"{"name":"123"}"#, committed_attrs_json.to_str().unwrap());
+
+        let err_code = cl_blinded_credential_secrets_free(blinded_credential_secrets_p);
+        assert_eq!(err_code, ErrorCode::Success);
+    }
+
     #[test]
     fn cl_prover_blinded_credential_secrets_from_json_works() {
         let credential_values = _credential_values();
@@ -1073,6 +1560,93 @@ mod tests {
         _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
     }
 
+    #[test]
+    fn cl_prover_blinded_credential_secrets_json_round_trip_works_for_committed_attrs() {
+        let credential_values_builder = _credential_values_builder();
+        let add_value = |add: extern fn(*const c_void, *const c_char, *const c_char) -> ErrorCode, attr: &str, value: &str| {
+            let attr = CString::new(attr).unwrap();
+            let value = CString::new(value).unwrap();
+            assert_eq!(ErrorCode::Success, add(credential_values_builder, attr.as_ptr(), value.as_ptr()));
+        };
+        add_value(cl_credential_values_builder_add_dec_hidden, "master_secret", "21578029250517794450984707538122537192839006240802068037273983354680998203845");
+        add_value(cl_credential_values_builder_add_dec_known, "age", "28");
+        add_value(cl_credential_values_builder_add_dec_known, "height", "175");
+        for &(attr, value, blinding_factor) in [("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103", "42"),
+                                                ("name", "1139481716457488690172217916278103335", "12")].iter() {
+            let attr = CString::new(attr).unwrap();
+            let value = CString::new(value).unwrap();
+            let blinding_factor = CString::new(blinding_factor).unwrap();
+            assert_eq!(ErrorCode::Success, cl_credential_values_builder_add_dec_commitment(credential_values_builder,
+                                                                                           attr.as_ptr(),
+                                                                                           value.as_ptr(),
+                                                                                           blinding_factor.as_ptr()));
+        }
+        let mut credential_values: *const c_void = ptr::null();
+        assert_eq!(ErrorCode::Success, cl_credential_values_builder_finalize(credential_values_builder, &mut credential_values));
+
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_nonce = _nonce();
+        let (blinded_credential_secrets,
+            credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                                        credential_key_correctness_proof,
+                                                                                        credential_values,
+                                                                                        credential_nonce);
+
+        fn _round_trip(entity: *const c_void,
+                       to_json: extern fn(*const c_void, *mut *const c_char) -> ErrorCode,
+                       from_json: extern fn(*const c_char, *mut *const c_void) -> ErrorCode,
+                       free: extern fn(*const c_void) -> ErrorCode) -> serde_json::Value {
+            let mut json_p: *const c_char = ptr::null();
+            assert_eq!(ErrorCode::Success, to_json(entity, &mut json_p));
+
+            let mut restored_p: *const c_void = ptr::null();
+            assert_eq!(ErrorCode::Success, from_json(json_p, &mut restored_p));
+
+            let mut restored_json_p: *const c_char = ptr::null();
+            assert_eq!(ErrorCode::Success, to_json(restored_p, &mut restored_json_p));
+
+            let (json, restored_json) = unsafe {
+                (CString::from_raw(json_p as *mut c_char), CString::from_raw(restored_json_p as *mut c_char))
+            };
+            assert_eq!(json, restored_json);
+            assert_eq!(ErrorCode::Success, free(restored_p));
+
+            serde_json::from_str(json.to_str().unwrap()).unwrap()
+        }
+
+        fn _keys(value: &serde_json::Value) -> Vec<&String> {
+            value.as_object().unwrap().keys().collect()
+        }
+
+        let blinded_credential_secrets_json = _round_trip(blinded_credential_secrets,
+                                                          cl_blinded_credential_secrets_to_json,
+                                                          cl_blinded_credential_secrets_from_json,
+                                                          cl_blinded_credential_secrets_free);
+        assert_eq!(vec!["name", "sex"], _keys(&blinded_credential_secrets_json["committed_attributes"]));
+        assert_eq!(json!(["master_secret"]), blinded_credential_secrets_json["hidden_attributes"]);
+
+        _round_trip(credential_secrets_blinding_factors,
+                    cl_credential_secrets_blinding_factors_to_json,
+                    cl_credential_secrets_blinding_factors_from_json,
+                    cl_credential_secrets_blinding_factors_free);
+
+        let correctness_proof_json = _round_trip(blinded_credential_secrets_correctness_proof,
+                                                 cl_blinded_credential_secrets_correctness_proof_to_json,
+                                                 cl_blinded_credential_secrets_correctness_proof_from_json,
+                                                 cl_blinded_credential_secrets_correctness_proof_free);
+        assert_eq!(vec!["master_secret", "name", "sex"], _keys(&correctness_proof_json["m_caps"]));
+        assert_eq!(vec!["name", "sex"], _keys(&correctness_proof_json["r_caps"]));
+
+        let correctness_proof = unsafe { &*(blinded_credential_secrets_correctness_proof as *const BlindedCredentialSecretsCorrectnessProof) };
+        assert_eq!(serde_json::to_value(correctness_proof).unwrap(), correctness_proof_json);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_credential_values(credential_values);
+        _free_nonce(credential_nonce);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+    }
+
     #[test]
     fn cl_prover_process_credential_signature_signature_works() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -1110,6 +1684,89 @@ mod tests {
         _free_credential_signature(credential_signature, signature_correctness_proof);
     }
 
+    #[test]
+    fn cl_prover_verify_credential_works() {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_nonce = _nonce();
+        let credential_values = _credential_values();
+        let (blinded_credential_secrets,
+            credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                                        credential_key_correctness_proof,
+                                                                                        credential_values,
+                                                                                        credential_nonce);
+
+        let credential_issuance_nonce = _nonce();
+        let (credential_signature, signature_correctness_proof) =
+            _credential_signature(blinded_credential_secrets,
+                                  blinded_credential_secrets_correctness_proof,
+                                  credential_nonce,
+                                  credential_issuance_nonce,
+                                  credential_values,
+                                  credential_pub_key,
+                                  credential_priv_key);
+        _process_credential_signature(credential_signature,
+                                      signature_correctness_proof,
+                                      credential_secrets_blinding_factors,
+                                      credential_values,
+                                      credential_pub_key,
+                                      credential_issuance_nonce);
+
+        let mut valid = false;
+        let err_code = cl_prover_verify_credential(credential_signature,
+                                                   credential_values,
+                                                   credential_pub_key,
+                                                   &mut valid);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(valid);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_credential_values(credential_values);
+        _free_nonce(credential_nonce);
+        _free_nonce(credential_issuance_nonce);
+        _free_credential_signature(credential_signature, signature_correctness_proof);
+    }
+
+    #[test]
+    fn cl_prover_process_credential_signature_works_for_wrong_issuance_nonce() {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_nonce = _nonce();
+        let credential_values = _credential_values();
+        let (blinded_credential_secrets,
+            credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                              credential_key_correctness_proof,
+                                                                              credential_values,
+                                                                              credential_nonce);
+
+        let credential_issuance_nonce = _nonce();
+        let (credential_signature, signature_correctness_proof) =
+            _credential_signature(blinded_credential_secrets,
+                                  blinded_credential_secrets_correctness_proof,
+                                  credential_nonce,
+                                  credential_issuance_nonce,
+                                  credential_values,
+                                  credential_pub_key,
+                                  credential_priv_key);
+        let other_nonce = _nonce();
+        let err_code = cl_prover_process_credential_signature(credential_signature,
+                                                                          credential_values,
+                                                                          signature_correctness_proof,
+                                                                          credential_secrets_blinding_factors,
+                                                                          credential_pub_key,
+                                                                          other_nonce);
+        assert_ne!(err_code, ErrorCode::Success);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_credential_values(credential_values);
+        _free_nonce(credential_nonce);
+        _free_nonce(credential_issuance_nonce);
+        _free_nonce(other_nonce);
+        _free_credential_signature(credential_signature, signature_correctness_proof);
+    }
+
     #[test]
     fn cl_prover_proof_builder_new_works() {
         let mut proof_builder: *const c_void = ptr::null();
@@ -1122,6 +1779,33 @@ mod tests {
         _free_proof_builder(proof_builder, nonce);
     }
 
+    #[test]
+    fn cl_proof_builder_add_common_attribute_works() {
+        let proof_builder = _proof_builder();
+
+        let attr_name = CString::new("master_secret").unwrap();
+        let err_code = cl_proof_builder_add_common_attribute(proof_builder, attr_name.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let nonce = _nonce();
+        _free_proof_builder(proof_builder, nonce);
+    }
+
+    #[test]
+    fn cl_proof_builder_clear_works() {
+        let proof_builder = _proof_builder();
+
+        let attr_name = CString::new("master_secret").unwrap();
+        let err_code = cl_proof_builder_add_common_attribute(proof_builder, attr_name.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let err_code = cl_proof_builder_clear(proof_builder);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let nonce = _nonce();
+        _free_proof_builder(proof_builder, nonce);
+    }
+
     #[test]
     fn cl_prover_proof_builder_finalize_works() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -1159,6 +1843,7 @@ mod tests {
                                                                           non_credential_schema,
                                                                           credential_signature,
                                                                           credential_values,
+                                                                          _key_id().as_ptr(),
                                                                           credential_pub_key);
         assert_eq!(err_code, ErrorCode::Success);
 
@@ -1275,6 +1960,66 @@ mod tests {
         _free_proof(proof);
     }
 
+    #[test]
+    fn cl_proof_bytes_round_trip_works() {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_values = _credential_values();
+        let credential_nonce = _nonce();
+        let (blinded_credential_secrets,
+            credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                              credential_key_correctness_proof,
+                                                                              credential_values,
+                                                                              credential_nonce);
+        let credential_issuance_nonce = _nonce();
+        let (credential_signature, signature_correctness_proof) = _credential_signature(blinded_credential_secrets,
+                                                                                        blinded_credential_secrets_correctness_proof,
+                                                                                        credential_nonce,
+                                                                                        credential_issuance_nonce,
+                                                                                        credential_values,
+                                                                                        credential_pub_key,
+                                                                                        credential_priv_key);
+        _process_credential_signature(credential_signature,
+                                      signature_correctness_proof,
+                                      credential_secrets_blinding_factors,
+                                      credential_values,
+                                      credential_pub_key,
+                                      credential_issuance_nonce);
+
+        let proof_building_nonce = _nonce();
+        let proof = _proof(credential_pub_key,
+                           credential_signature,
+                           proof_building_nonce,
+                           credential_values);
+
+        let mut bytes_p: *const u8 = ptr::null();
+        let mut bytes_len: usize = 0;
+        let err_code = cl_proof_to_bytes(proof, &mut bytes_p, &mut bytes_len);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let mut proof_p: *const c_void = ptr::null();
+        let err_code = cl_proof_from_bytes(bytes_p, bytes_len, &mut proof_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        unsafe { assert_eq!(*(proof as *const Proof), *(proof_p as *const Proof)); }
+
+        let mut truncated_proof_p: *const c_void = ptr::null();
+        let err_code = cl_proof_from_bytes(bytes_p, bytes_len - 1, &mut truncated_proof_p);
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+
+        let err_code = cl_proof_bytes_free(bytes_p, bytes_len);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_nonce(credential_nonce);
+        _free_nonce(credential_issuance_nonce);
+        _free_nonce(proof_building_nonce);
+        _free_credential_signature(credential_signature, signature_correctness_proof);
+        _free_proof(proof);
+        _free_proof(proof_p);
+    }
+
     #[test]
     fn cl_proof_free_works() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -1324,6 +2069,7 @@ pub mod mocks {
 
     use std::ptr;
     use ffi::cl::mocks::*;
+    use ffi::cl::issuer::mocks::*;
 
     pub fn _master_secret() -> *const c_void {
         let mut master_secret_p: *const c_void = ptr::null();
@@ -1392,6 +2138,44 @@ pub mod mocks {
         assert_eq!(err_code, ErrorCode::Success);
     }
 
+    /// Issues credential with given values and processes its signature.
+    /// Returns credential public key, private key, key correctness proof, credential signature and signature correctness proof.
+    pub fn _issued_credential(credential_values: *const c_void) -> (*const c_void, *const c_void, *const c_void, *const c_void, *const c_void) {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_nonce = _nonce();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                                   credential_key_correctness_proof,
+                                                                                   credential_values,
+                                                                                   credential_nonce);
+        let credential_issuance_nonce = _nonce();
+        let (credential_signature, signature_correctness_proof) = _credential_signature(blinded_credential_secrets,
+                                                                                        blinded_credential_secrets_correctness_proof,
+                                                                                        credential_nonce,
+                                                                                        credential_issuance_nonce,
+                                                                                        credential_values,
+                                                                                        credential_pub_key,
+                                                                                        credential_priv_key);
+        _process_credential_signature(credential_signature,
+                                      signature_correctness_proof,
+                                      credential_secrets_blinding_factors,
+                                      credential_values,
+                                      credential_pub_key,
+                                      credential_issuance_nonce);
+
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_nonce(credential_nonce);
+        _free_nonce(credential_issuance_nonce);
+
+        (credential_pub_key, credential_priv_key, credential_key_correctness_proof, credential_signature, signature_correctness_proof)
+    }
+
+    pub fn _free_issued_credential(credential_pub_key: *const c_void, credential_priv_key: *const c_void, credential_key_correctness_proof: *const c_void,
+                                   credential_signature: *const c_void, signature_correctness_proof: *const c_void) {
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_credential_signature(credential_signature, signature_correctness_proof);
+    }
+
     pub fn _proof_builder() -> *const c_void {
         let mut proof_builder: *const c_void = ptr::null();
         let err_code = cl_prover_new_proof_builder(&mut proof_builder);
@@ -1416,13 +2200,19 @@ pub mod mocks {
         let non_credential_schema = _non_credential_schema();
         let sub_proof_request = _sub_proof_request();
 
-        cl_proof_builder_add_sub_proof_request(proof_builder,
+        let attr_name = CString::new("master_secret").unwrap();
+        let err_code = cl_proof_builder_add_common_attribute(proof_builder, attr_name.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let err_code = cl_proof_builder_add_sub_proof_request(proof_builder,
                                                            sub_proof_request,
                                                            credential_schema,
                                                            non_credential_schema,
                                                            credential_signature,
                                                            credential_values,
+                                                           _key_id().as_ptr(),
                                                            credential_pub_key);
+        assert_eq!(err_code, ErrorCode::Success);
 
         let mut proof: *const c_void = ptr::null();
         let err_code = cl_proof_builder_finalize(proof_builder,
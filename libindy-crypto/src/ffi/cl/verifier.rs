@@ -2,13 +2,15 @@ use cl::verifier::*;
 use cl::*;
 use errors::ToErrorCode;
 use errors::ErrorCode;
+use ffi::ctypes::CTypesUtils;
 
 use std::os::raw::c_void;
+use libc::c_char;
 
 /// Creates and returns proof verifier.
 ///
 /// Note that proof verifier deallocation must be performed by
-/// calling cl_proof_verifier_finalize.
+/// calling cl_proof_verifier_verify or cl_proof_verifier_free.
 ///
 /// # Arguments
 /// * `proof_verifier_p` - Reference that will contain proof verifier instance pointer.
@@ -34,39 +36,52 @@ pub extern fn cl_verifier_new_proof_verifier(proof_verifier_p: *mut *const c_voi
     res
 }
 
+/// Add a sub proof request to the proof verifier.
+///
+/// # Arguments
+/// * `proof_verifier` - Reference that contain proof verifier instance pointer.
+/// * `sub_proof_request` - Reference that contain sub proof request instance pointer.
+/// * `credential_schema` - Reference that contains credential schema instance pointer.
+/// * `non_credential_schema` - Reference that contains non credential schema instance pointer.
+/// * `key_id` - Identifier of credential public key as null terminated string.
+/// * `credential_pub_key` - Reference that contains credential public key instance pointer.
 #[no_mangle]
 pub extern fn cl_proof_verifier_add_sub_proof_request(proof_verifier: *const c_void,
                                                                   sub_proof_request: *const c_void,
                                                                   credential_schema: *const c_void,
                                                                   non_credential_schema: *const c_void,
+                                                                  key_id: *const c_char,
                                                                   credential_pub_key: *const c_void) -> ErrorCode {
     trace!("cl_proof_verifier_add_sub_proof_request: >>> proof_verifier: {:?}, \
                                                                      sub_proof_request: {:?} ,\
                                                                      credential_schema: {:?}, \
                                                                      non_credential_schema: {:?}, \
+                                                                     key_id: {:?}, \
                                                                      credential_pub_key: {:?}",
-           proof_verifier, sub_proof_request, credential_schema, non_credential_schema, credential_pub_key);
+           proof_verifier, sub_proof_request, credential_schema, non_credential_schema, key_id, credential_pub_key);
 
     check_useful_mut_c_reference!(proof_verifier, ProofVerifier, ErrorCode::CommonInvalidParam1);
     check_useful_c_reference!(sub_proof_request, SubProofRequest, ErrorCode::CommonInvalidParam2);
     check_useful_c_reference!(credential_schema, CredentialSchema, ErrorCode::CommonInvalidParam3);
     check_useful_c_reference!(non_credential_schema, NonCredentialSchema, ErrorCode::CommonInvalidParam4);
-    check_useful_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam5);
+    check_useful_c_str!(key_id, ErrorCode::CommonInvalidParam5);
+    check_useful_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam6);
 
     trace!("cl_proof_verifier_add_sub_proof_request: entities: proof_verifier: {:?}, sub_proof_request: {:?},\
-                credential_schema: {:?}, non_credential_schema: {:?}, credential_pub_key: {:?}",
-           proof_verifier, sub_proof_request, credential_schema, non_credential_schema, credential_pub_key);
+                credential_schema: {:?}, non_credential_schema: {:?}, key_id: {:?}, credential_pub_key: {:?}",
+           proof_verifier, sub_proof_request, credential_schema, non_credential_schema, key_id, credential_pub_key);
 
     let res = match proof_verifier.add_sub_proof_request(sub_proof_request,
                                                          credential_schema,
                                                          non_credential_schema,
+                                                         &key_id,
                                                          credential_pub_key) {
         Ok(()) => ErrorCode::Success,
         Err(err) => err.to_error_code()
     };
 
     trace!("cl_proof_verifier_add_sub_proof_request: <<< res: {:?}", res);
-    ErrorCode::Success
+    res
 }
 
 
@@ -109,6 +124,25 @@ pub extern fn cl_proof_verifier_verify(proof_verifier: *const c_void,
     res
 }
 
+/// Deallocates proof verifier instance that wasn't consumed by cl_proof_verifier_verify.
+///
+/// # Arguments
+/// * `proof_verifier` - Reference that contains proof verifier instance pointer.
+#[no_mangle]
+pub extern fn cl_proof_verifier_free(proof_verifier: *const c_void) -> ErrorCode {
+    trace!("cl_proof_verifier_free: >>> proof_verifier: {:?}", proof_verifier);
+
+    check_useful_c_ptr!(proof_verifier, ErrorCode::CommonInvalidParam1);
+
+    let proof_verifier = unsafe { Box::from_raw(proof_verifier as *mut ProofVerifier); };
+    trace!("cl_proof_verifier_free: entity: proof_verifier: {:?}", proof_verifier);
+
+    let res = ErrorCode::Success;
+
+    trace!("cl_proof_verifier_free: <<< res: {:?}", res);
+    res
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,6 +152,11 @@ mod tests {
     use super::mocks::*;
     use super::super::issuer::mocks::*;
     use super::super::prover::mocks::*;
+    use super::super::prover::{cl_proof_to_json, cl_proof_from_json};
+    use ffi::ctypes::cl_free_string;
+    use libc::c_char;
+    use serde_json;
+    use std::ffi::{CStr, CString};
 
     #[test]
     fn cl_verifier_new_proof_verifier_works() {
@@ -210,6 +249,7 @@ mod tests {
                                                                            sub_proof_request,
                                                                            credential_schema,
                                                                            non_credential_schema,
+                                                                           _key_id().as_ptr(),
                                                                            credential_pub_key);
         assert_eq!(err_code, ErrorCode::Success);
 
@@ -224,6 +264,14 @@ mod tests {
         _free_credential_signature(credential_signature, signature_correctness_proof);
     }
 
+    #[test]
+    fn cl_proof_verifier_free_works() {
+        let proof_verifier = _proof_verifier();
+
+        let err_code = cl_proof_verifier_free(proof_verifier);
+        assert_eq!(err_code, ErrorCode::Success);
+    }
+
     #[test]
     fn cl_proof_verifier_verify_works_for_primary_proof() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -275,11 +323,124 @@ mod tests {
         _free_sub_proof_request(sub_proof_request);
         _free_credential_signature(credential_signature, signature_correctness_proof);
     }
+
+    #[test]
+    fn cl_proof_verifier_verify_works_for_proof_from_json() {
+        let credential_values = _credential_values();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof,
+            credential_signature, signature_correctness_proof) = _issued_credential(credential_values);
+        let credential_schema = _credential_schema();
+        let non_credential_schema = _non_credential_schema();
+        let sub_proof_request = _sub_proof_request();
+
+        let proof_building_nonce = _nonce();
+        let proof = _proof(credential_pub_key,
+                           credential_signature,
+                           proof_building_nonce,
+                           credential_values);
+
+        let mut proof_json: *const c_char = ptr::null();
+        let err_code = cl_proof_to_json(proof, &mut proof_json);
+        assert_eq!(err_code, ErrorCode::Success);
+        _free_proof(proof);
+
+        let mut proof: *const c_void = ptr::null();
+        let err_code = cl_proof_from_json(proof_json, &mut proof);
+        assert_eq!(err_code, ErrorCode::Success);
+        unsafe { CString::from_raw(proof_json as *mut c_char); }
+
+        let proof_verifier = _proof_verifier();
+        _add_sub_proof_request(proof_verifier, credential_schema, non_credential_schema, credential_pub_key, sub_proof_request);
+
+        let mut valid = false;
+        let err_code = cl_proof_verifier_verify(proof_verifier, proof, proof_building_nonce, &mut valid);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(valid);
+
+        _free_issued_credential(credential_pub_key, credential_priv_key, credential_key_correctness_proof,
+                                credential_signature, signature_correctness_proof);
+        _free_nonce(proof_building_nonce);
+        _free_credential_schema(credential_schema);
+        _free_non_credential_schema(non_credential_schema);
+        _free_sub_proof_request(sub_proof_request);
+        _free_proof(proof);
+    }
+
+    #[test]
+    fn cl_proof_verifier_verify_works_for_tampered_proof() {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_values = _credential_values();
+        let credential_nonce = _nonce();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                                   credential_key_correctness_proof,
+                                                                                   credential_values,
+                                                                                   credential_nonce);
+        let credential_issuance_nonce = _nonce();
+        let (credential_signature, signature_correctness_proof) = _credential_signature(blinded_credential_secrets,
+                                                                                        blinded_credential_secrets_correctness_proof,
+                                                                                        credential_nonce,
+                                                                                        credential_issuance_nonce,
+                                                                                        credential_values,
+                                                                                        credential_pub_key,
+                                                                                        credential_priv_key);
+        let credential_schema = _credential_schema();
+        let non_credential_schema = _non_credential_schema();
+        let sub_proof_request = _sub_proof_request();
+        _process_credential_signature(credential_signature,
+                                      signature_correctness_proof,
+                                      credential_secrets_blinding_factors,
+                                      credential_values,
+                                      credential_pub_key,
+                                      credential_issuance_nonce);
+
+        let proof_building_nonce = _nonce();
+        let proof = _proof(credential_pub_key,
+                           credential_signature,
+                           proof_building_nonce,
+                           credential_values);
+
+        let mut proof_json_p: *const c_char = ptr::null();
+        let err_code = cl_proof_to_json(proof, &mut proof_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+        _free_proof(proof);
+
+        let proof_json = unsafe { CStr::from_ptr(proof_json_p) }.to_str().unwrap();
+        let mut proof_json = serde_json::from_str::<serde_json::Value>(proof_json).unwrap();
+        let err_code = cl_free_string(proof_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+        proof_json["proofs"][0]["primary_proof"]["eq_proof"]["revealed_attrs"]["name"] = json!("1");
+        let proof_json = CString::new(proof_json.to_string()).unwrap();
+
+        let mut proof: *const c_void = ptr::null();
+        let err_code = cl_proof_from_json(proof_json.as_ptr(), &mut proof);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let proof_verifier = _proof_verifier();
+        _add_sub_proof_request(proof_verifier, credential_schema, non_credential_schema, credential_pub_key, sub_proof_request);
+
+        let mut valid = false;
+        let err_code = cl_proof_verifier_verify(proof_verifier, proof, proof_building_nonce, &mut valid);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(!valid);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_nonce(credential_nonce);
+        _free_nonce(credential_issuance_nonce);
+        _free_nonce(proof_building_nonce);
+        _free_credential_schema(credential_schema);
+        _free_non_credential_schema(non_credential_schema);
+        _free_sub_proof_request(sub_proof_request);
+        _free_credential_signature(credential_signature, signature_correctness_proof);
+        _free_proof(proof);
+    }
 }
 
 pub mod mocks {
     use super::*;
     use std::ptr;
+    use ffi::cl::mocks::_key_id;
 
     pub fn _proof_verifier() -> *const c_void {
         let mut proof_verifier_p: *const c_void = ptr::null();
@@ -296,6 +457,7 @@ pub mod mocks {
                                                                            sub_proof_request,
                                                                            credential_schema,
                                                                            non_credential_schema,
+                                                                           _key_id().as_ptr(),
                                                                            credential_pub_key);
         assert_eq!(err_code, ErrorCode::Success);
     }
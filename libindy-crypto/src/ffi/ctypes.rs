@@ -1,8 +1,13 @@
+use errors::ErrorCode;
 use libc::c_char;
 
 use std::ffi::CStr;
 use std::str::Utf8Error;
 use std::ffi::CString;
+#[cfg(debug_assertions)]
+use std::collections::HashSet;
+#[cfg(debug_assertions)]
+use std::sync::Mutex;
 
 pub struct CTypesUtils {}
 
@@ -35,12 +40,66 @@ macro_rules! check_useful_c_reference {
     }
 }
 
+#[cfg(debug_assertions)]
+lazy_static! {
+    // Addresses of objects that are mutably borrowed by FFI calls in progress
+    static ref MUT_BORROWED: Mutex<HashSet<usize>> = Mutex::new(HashSet::new());
+}
+
+/// Marks object behind FFI pointer as mutably borrowed until the guard is dropped.
+///
+/// Objects passed to mutating FFI calls (builders, proof verifier and etc.) are owned by one thread at a time:
+/// the caller must not invoke mutating calls on the same pointer concurrently.
+/// In debug builds concurrent or re-entrant mutable access is detected and rejected
+/// instead of causing undefined behaviour. In release builds the guard is a no-op.
+pub struct MutBorrowGuard {
+    #[cfg(debug_assertions)]
+    addr: usize
+}
+
+impl MutBorrowGuard {
+    #[cfg(debug_assertions)]
+    pub fn acquire<T>(ptr: *const T) -> Option<MutBorrowGuard> {
+        let addr = ptr as usize;
+
+        let mut borrowed = match MUT_BORROWED.lock() {
+            Ok(borrowed) => borrowed,
+            Err(_) => return None
+        };
+
+        if borrowed.insert(addr) {
+            Some(MutBorrowGuard { addr })
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(debug_assertions))]
+    pub fn acquire<T>(_ptr: *const T) -> Option<MutBorrowGuard> {
+        Some(MutBorrowGuard {})
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for MutBorrowGuard {
+    fn drop(&mut self) {
+        if let Ok(mut borrowed) = MUT_BORROWED.lock() {
+            borrowed.remove(&self.addr);
+        }
+    }
+}
+
 macro_rules! check_useful_mut_c_reference {
     ($ptr:ident, $type:ty, $err:expr) => {
         if $ptr.is_null() {
             return $err
         }
 
+        let _borrow_guard = match ::ffi::ctypes::MutBorrowGuard::acquire($ptr) {
+            Some(borrow_guard) => borrow_guard,
+            None => return ::errors::ErrorCode::CommonInvalidState
+        };
+
         let $ptr: &mut $type = unsafe { &mut *($ptr as *mut $type) };;
     }
 }
@@ -82,4 +141,96 @@ macro_rules! check_useful_c_callback {
             None => return $e
         };
     }
-}
\ No newline at end of file
+}
+
+/// Deallocates string returned by library (for example result of any `cl_*_to_json` or `cl_*_get_*_json` call).
+///
+/// Note: Strings returned by library are allocated by Rust allocator so they must be released
+/// by this function instead of `free` of caller's runtime.
+///
+/// # Arguments
+/// * `s` - Reference that contains string pointer returned by library.
+#[no_mangle]
+pub extern fn cl_free_string(s: *const c_char) -> ErrorCode {
+    trace!("cl_free_string: >>> s: {:?}", s);
+
+    check_useful_c_ptr!(s, ErrorCode::CommonInvalidParam1);
+
+    unsafe { CString::from_raw(s as *mut c_char); }
+
+    let res = ErrorCode::Success;
+    trace!("cl_free_string: <<< res: {:?}", res);
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn cl_free_string_works() {
+        let s = CTypesUtils::string_to_cstring("{\"key\":\"value\"}".to_string()).into_raw();
+
+        let err_code = cl_free_string(s);
+        assert_eq!(err_code, ErrorCode::Success);
+    }
+
+    #[test]
+    fn cl_free_string_works_for_null() {
+        let err_code = cl_free_string(ptr::null());
+        assert_eq!(err_code, ErrorCode::CommonInvalidParam1);
+    }
+
+    #[cfg(feature = "leak_check")]
+    mod leak_check {
+        use super::*;
+        use cl::new_nonce;
+        use ffi::cl::cl_nonce_to_json;
+        use std::alloc::{GlobalAlloc, Layout, System};
+        use std::cell::Cell;
+        use std::os::raw::c_void;
+
+        /// Tracks balance of Rust allocations made by current thread, so parallel tests don't interfere.
+        struct CountingAllocator;
+
+        thread_local!(static ALLOCATED: Cell<isize> = Cell::new(0));
+
+        unsafe impl GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() + layout.size() as isize));
+                System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() - layout.size() as isize));
+                System.dealloc(ptr, layout)
+            }
+        }
+
+        #[global_allocator]
+        static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+        fn allocated() -> isize {
+            ALLOCATED.with(|allocated| allocated.get())
+        }
+
+        #[test]
+        fn cl_free_string_works_without_leak() {
+            let nonce = new_nonce().unwrap();
+            let nonce_p = &nonce as *const _ as *const c_void;
+            let mut nonce_json_p: *const c_char = ptr::null();
+
+            let allocated_before = allocated();
+
+            let err_code = cl_nonce_to_json(nonce_p, &mut nonce_json_p);
+            assert_eq!(err_code, ErrorCode::Success);
+            assert!(allocated() > allocated_before);
+
+            let err_code = cl_free_string(nonce_json_p);
+            assert_eq!(err_code, ErrorCode::Success);
+            assert_eq!(allocated_before, allocated());
+        }
+    }
+}
@@ -9,7 +9,7 @@ extern crate log;
 
 use errors::ToErrorCode;
 
-use cl::logger::{EnabledCB, LogCB, FlushCB, IndyCryptoLogger, IndyCryptoDefaultLogger};
+use cl::logger::{IndyCryptoLogger, IndyCryptoDefaultLogger};
 use ffi::ctypes::CTypesUtils;
 
 /// Set custom logger implementation.
@@ -24,11 +24,22 @@ use ffi::ctypes::CTypesUtils;
 ///
 /// #Returns
 /// Error code
+///
+/// Note: Handlers types are the same as `EnabledCB`, `LogCB` and `FlushCB`, but are written out in full,
+/// so generated C header declares them as nullable function pointers.
 #[no_mangle]
 pub extern fn indy_crypto_set_logger(context: *const c_void,
-                                     enabled: Option<EnabledCB>,
-                                     log: Option<LogCB>,
-                                     flush: Option<FlushCB>) -> ErrorCode {
+                                     enabled: Option<extern fn(context: *const c_void,
+                                                               level: u32,
+                                                               target: *const c_char) -> bool>,
+                                     log: Option<extern fn(context: *const c_void,
+                                                           level: u32,
+                                                           target: *const c_char,
+                                                           message: *const c_char,
+                                                           module_path: *const c_char,
+                                                           file: *const c_char,
+                                                           line: u32)>,
+                                     flush: Option<extern fn(context: *const c_void)>) -> ErrorCode {
     trace!("indy_crypto_set_logger >>> context: {:?}, enabled: {:?}, log: {:?}, flush: {:?}", context, log, enabled, flush);
 
     check_useful_c_callback!(log, ErrorCode::CommonInvalidParam3);
@@ -28,17 +28,27 @@ extern crate openssl;
 #[cfg(feature = "bn_openssl")]
 extern crate int_traits;
 
+#[cfg(feature = "bn_rust")]
+extern crate num_bigint;
+
+#[cfg(feature = "bn_rust")]
+extern crate num_traits;
+
+#[cfg(feature = "bn_rust")]
+extern crate num_integer;
+
 #[cfg(feature = "ffi")]
 extern crate libc;
 
 extern crate time;
 
+#[cfg(feature = "cl")]
+extern crate unicode_normalization;
+
 #[cfg(feature = "cl")]
 #[macro_use]
 pub mod cl;
 
-#[cfg(feature = "bn_openssl")]
-#[path = "bn/openssl.rs"]
 pub mod bn;
 
 pub mod errors;
@@ -0,0 +1,203 @@
+extern crate indy_crypto;
+
+use indy_crypto::cl::*;
+use indy_crypto::cl::issuer::Issuer;
+use indy_crypto::cl::prover::Prover;
+use indy_crypto::cl::verifier::Verifier;
+use self::indy_crypto::cl::logger::IndyCryptoDefaultLogger;
+
+use std::time::{Duration, Instant};
+
+/// Timed scenarios of the main anoncreds operations.
+///
+/// In a normal test run each scenario is executed once, so the harness is kept buildable and runnable.
+/// With `bench` feature scenarios are repeated and average times are printed:
+/// `cargo test --release --features bench --test bench -- --nocapture --test-threads=1`
+#[cfg(feature = "bench")]
+const ITERATIONS: u32 = 10;
+
+#[cfg(not(feature = "bench"))]
+const ITERATIONS: u32 = 1;
+
+const PROVER_ID: &'static str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+const KEY_ID: &'static str = "NcYxiDXkpYi6ov5FcYDi1e:3:CL";
+
+fn measure<T, F: FnMut() -> T>(name: &str, mut f: F) -> T {
+    let mut total = Duration::new(0, 0);
+    let mut res = None;
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        res = Some(f());
+        total += start.elapsed();
+    }
+
+    println!("{}: {:?} per iteration ({} iterations)", name, total / ITERATIONS, ITERATIONS);
+
+    res.unwrap()
+}
+
+fn credential_schema() -> CredentialSchema {
+    let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    credential_schema_builder.add_attr("name").unwrap();
+    credential_schema_builder.add_attr("sex").unwrap();
+    credential_schema_builder.add_attr("age").unwrap();
+    credential_schema_builder.add_attr("height").unwrap();
+    credential_schema_builder.finalize().unwrap()
+}
+
+fn non_credential_schema() -> NonCredentialSchema {
+    let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    non_credential_schema_builder.add_attr("master_secret").unwrap();
+    non_credential_schema_builder.finalize().unwrap()
+}
+
+fn credential_values(master_secret: &MasterSecret) -> CredentialValues {
+    let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+    credential_values_builder.add_value_known("master_secret", &master_secret.value().unwrap()).unwrap();
+    credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+    credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+    credential_values_builder.add_dec_known("age", "28").unwrap();
+    credential_values_builder.add_dec_known("height", "175").unwrap();
+    credential_values_builder.finalize().unwrap()
+}
+
+fn sub_proof_request() -> SubProofRequest {
+    let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+    sub_proof_request_builder.add_revealed_attr("name").unwrap();
+    sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+    sub_proof_request_builder.finalize().unwrap()
+}
+
+fn key_id(index: usize) -> String {
+    format!("{}:{}", KEY_ID, index)
+}
+
+#[test]
+fn bench_issuance() {
+    IndyCryptoDefaultLogger::init(None).ok();
+
+    let credential_schema = credential_schema();
+    let non_credential_schema = non_credential_schema();
+    let credential_values = credential_values(&Prover::new_master_secret().unwrap());
+
+    let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+        measure("Issuer::new_credential_def", || Issuer::new_credential_def(&credential_schema, &non_credential_schema).unwrap());
+
+    let credential_nonce = new_nonce().unwrap();
+
+    let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+        measure("Prover::blind_credential_secrets", || {
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap()
+        });
+
+    let credential_issuance_nonce = new_nonce().unwrap();
+
+    let (mut credential_signature, signature_correctness_proof) =
+        measure("Issuer::sign_credential", || {
+            Issuer::sign_credential(PROVER_ID,
+                                    &blinded_credential_secrets,
+                                    &blinded_credential_secrets_correctness_proof,
+                                    &credential_nonce,
+                                    &credential_issuance_nonce,
+                                    &credential_values,
+                                    &credential_pub_key,
+                                    &credential_priv_key).unwrap()
+        });
+
+    Prover::process_credential_signature(&mut credential_signature,
+                                         &credential_values,
+                                         &signature_correctness_proof,
+                                         &credential_secrets_blinding_factors,
+                                         &credential_pub_key,
+                                         &credential_issuance_nonce).unwrap();
+}
+
+#[test]
+fn bench_proof() {
+    IndyCryptoDefaultLogger::init(None).ok();
+
+    let credential_schema = credential_schema();
+    let non_credential_schema = non_credential_schema();
+    let credential_values = credential_values(&Prover::new_master_secret().unwrap());
+    let sub_proof_request = sub_proof_request();
+
+    // Key generation dominates setup, so all credentials are issued with the same key under different key ids
+    let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+        Issuer::new_credential_def(&credential_schema, &non_credential_schema).unwrap();
+
+    let credential_nonce = new_nonce().unwrap();
+    let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+        Prover::blind_credential_secrets(&credential_pub_key,
+                                         &credential_key_correctness_proof,
+                                         &credential_values,
+                                         &credential_nonce).unwrap();
+
+    let credential_issuance_nonce = new_nonce().unwrap();
+    let (mut credential_signature, signature_correctness_proof) =
+        Issuer::sign_credential(PROVER_ID,
+                                &blinded_credential_secrets,
+                                &blinded_credential_secrets_correctness_proof,
+                                &credential_nonce,
+                                &credential_issuance_nonce,
+                                &credential_values,
+                                &credential_pub_key,
+                                &credential_priv_key).unwrap();
+
+    Prover::process_credential_signature(&mut credential_signature,
+                                         &credential_values,
+                                         &signature_correctness_proof,
+                                         &credential_secrets_blinding_factors,
+                                         &credential_pub_key,
+                                         &credential_issuance_nonce).unwrap();
+
+    for &credentials_count in [1, 2, 5].iter() {
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        for i in 0..credentials_count {
+            proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                &credential_schema,
+                                                &non_credential_schema,
+                                                &credential_signature,
+                                                &credential_values,
+                                                &key_id(i),
+                                                &credential_pub_key).unwrap();
+        }
+
+        let proof = measure(&format!("ProofBuilder::finalize ({} credentials)", credentials_count),
+                            || proof_builder.finalize(&nonce).unwrap());
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        for i in 0..credentials_count {
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &key_id(i),
+                                                 &credential_pub_key).unwrap();
+        }
+
+        let valid = measure(&format!("ProofVerifier::verify ({} credentials)", credentials_count),
+                            || proof_verifier.verify(&proof, &nonce).unwrap());
+        assert!(valid);
+
+        let prepared_pub_key = PreparedPublicKey::new(&credential_pub_key).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        for i in 0..credentials_count {
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &key_id(i),
+                                                 &prepared_pub_key).unwrap();
+        }
+
+        let valid = measure(&format!("ProofVerifier::verify with prepared key ({} credentials)", credentials_count),
+                            || proof_verifier.verify(&proof, &nonce).unwrap());
+        assert!(valid);
+    }
+}
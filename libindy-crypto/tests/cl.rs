@@ -2,18 +2,23 @@ extern crate serde_derive;
 extern crate serde_json;
 extern crate indy_crypto;
 
-use indy_crypto::cl::new_nonce;
+use indy_crypto::bn::BigNumber;
+use indy_crypto::cl::{new_nonce, PredicateType, PreparedPublicKey, Proof};
 use indy_crypto::cl::issuer::Issuer;
 use indy_crypto::cl::prover::Prover;
 use indy_crypto::cl::verifier::Verifier;
 use self::indy_crypto::cl::logger::IndyCryptoDefaultLogger;
 
 pub const PROVER_ID: &'static str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+pub const KEY_ID: &'static str = "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1";
+pub const GVT_KEY_ID: &'static str = "NcYxiDXkpYi6ov5FcYDi1e:3:CL:gvt";
+pub const XYZ_KEY_ID: &'static str = "NcYxiDXkpYi6ov5FcYDi1e:3:CL:xyz";
 
 mod test {
     use super::*;
     use indy_crypto::errors::ErrorCode;
     use indy_crypto::errors::ToErrorCode;
+    use indy_crypto::errors::IndyCryptoError;
 
     #[test]
     fn anoncreds_demo() {
@@ -154,6 +159,7 @@ mod test {
                                             &non_credential_schema,
                                             &gvt_credential_signature,
                                             &gvt_credential_values,
+                                            GVT_KEY_ID,
                                             &gvt_credential_pub_key).unwrap();
 
         proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
@@ -161,6 +167,7 @@ mod test {
                                             &non_credential_schema,
                                             &xyz_credential_signature,
                                             &xyz_credential_values,
+                                            XYZ_KEY_ID,
                                             &xyz_credential_pub_key).unwrap();
 
         let proof = proof_builder.finalize(&nonce).unwrap();
@@ -170,11 +177,13 @@ mod test {
         proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
                                              &gvt_credential_schema,
                                              &non_credential_schema,
+                                             GVT_KEY_ID,
                                              &gvt_credential_pub_key).unwrap();
 
         proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
                                              &xyz_credential_schema,
                                              &non_credential_schema,
+                                             XYZ_KEY_ID,
                                              &xyz_credential_pub_key).unwrap();
 
         assert!(proof_verifier.verify(&proof, &nonce).unwrap());
@@ -241,6 +250,7 @@ mod test {
                                             &non_credential_schema,
                                             &credential_signature,
                                             &credential_values,
+                                            KEY_ID,
                                             &credential_pub_key).unwrap();
         let proof = proof_builder.finalize(&nonce).unwrap();
 
@@ -249,6 +259,299 @@ mod test {
         proof_verifier.add_sub_proof_request(&sub_proof_request,
                                              &credential_schema,
                                              &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[cfg(feature = "seeded_credential_def")]
+    #[test]
+    fn anoncreds_works_for_credential_def_from_seed() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def_from_seed(&credential_schema, &non_credential_schema, b"test vector seed").unwrap();
+
+        let (same_credential_pub_key, same_credential_priv_key, same_credential_key_correctness_proof) =
+            Issuer::new_credential_def_from_seed(&credential_schema, &non_credential_schema, b"test vector seed").unwrap();
+
+        assert_eq!(credential_pub_key.to_canonical_json().unwrap(), same_credential_pub_key.to_canonical_json().unwrap());
+        assert_eq!(serde_json::to_string(&credential_priv_key).unwrap(), serde_json::to_string(&same_credential_priv_key).unwrap());
+        assert_eq!(serde_json::to_string(&credential_key_correctness_proof).unwrap(),
+                   serde_json::to_string(&same_credential_key_correctness_proof).unwrap());
+
+        let (other_credential_pub_key, _, _) =
+            Issuer::new_credential_def_from_seed(&credential_schema, &non_credential_schema, b"other seed").unwrap();
+        assert_ne!(credential_pub_key.to_canonical_json().unwrap(), other_credential_pub_key.to_canonical_json().unwrap());
+
+        // seeded credential definition is valid
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        Prover::blind_credential_secrets(&credential_pub_key,
+                                         &credential_key_correctness_proof,
+                                         &credential_values,
+                                         &new_nonce().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn anoncreds_works_for_credential_def_from_primes() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition from pre-generated safe primes
+        let p_safe = BigNumber::from_dec("339691466205334124420684225416114976664801693983977638965244142929126815368572058729088112064799817613978286939645815228365625453787413281038385844651535148924834509418977762481916729806411760907378565568782025068555713875220314629853189526360052592575021838058379206703093126899099727588962411733046327313759").unwrap();
+        let q_safe = BigNumber::from_dec("287788339606071053737522836837374239889206933084097860262956774733934711118652230071540198003318602025835162221262971000871810253527974848382915079525208936900451047193628363363672184699844113448836228771620541217054679236582130436534693275174884092646074900923685210067364412219938890211545945183221768202947").unwrap();
+
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def_from_primes(&credential_schema, &non_credential_schema, &p_safe, &q_safe).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_empty_sub_proof_request() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce).unwrap();
+
+        // 9. Verifier create sub proof request without revealed attributes and predicates (proof of possession)
+        let sub_proof_request = Verifier::new_sub_proof_request_builder().unwrap().finalize().unwrap();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 13. Verifier rejects proof for other nonce
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(!proof_verifier.verify(&proof, &new_nonce().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_empty_non_credential_schema() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema without hidden attributes
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = Issuer::new_non_credential_schema_builder().unwrap().finalize().unwrap();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema).unwrap();
+
+        // 3. Issuer creates credential values without master secret
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("age", "28").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        // 4. Issuer creates nonce used Prover to blind credential secrets
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes (there are no ones)
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof without common attributes
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 13. Prover creates proof with common attribute missed in schemas (it is ignored)
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 14. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
                                              &credential_pub_key).unwrap();
         assert!(proof_verifier.verify(&proof, &nonce).unwrap());
     }
@@ -257,118 +560,1465 @@ mod test {
     fn anoncreds_works_for_multiple_credentials_used_for_proof() {
         IndyCryptoDefaultLogger::init(None).ok();
 
-        // 1. Prover creates master secret
-        let master_secret = Prover::new_master_secret().unwrap();
+        // 1. Prover creates master secret
+        let master_secret = Prover::new_master_secret().unwrap();
+
+        let gvt_credential_values = helpers::gvt_credential_values(&master_secret);
+
+        // 2. Issuer creates and signs GVT credential for Prover
+        let gvt_credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (gvt_credential_pub_key, gvt_credential_priv_key, gvt_credential_key_correctness_proof) =
+            Issuer::new_credential_def(&gvt_credential_schema, &non_credential_schema).unwrap();
+
+        let gvt_credential_nonce = new_nonce().unwrap();
+
+        let (gvt_blinded_credential_secrets, gvt_credential_secrets_blinding_factors, gvt_blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&gvt_credential_pub_key,
+                                             &gvt_credential_key_correctness_proof,
+                                             &gvt_credential_values,
+                                             &gvt_credential_nonce).unwrap();
+
+        let gvt_credential_issuance_nonce = new_nonce().unwrap();
+
+        let (mut gvt_credential_signature, gvt_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                                      &gvt_blinded_credential_secrets,
+                                                                                                      &gvt_blinded_credential_secrets_correctness_proof,
+                                                                                                      &gvt_credential_nonce,
+                                                                                                      &gvt_credential_issuance_nonce,
+                                                                                                      &gvt_credential_values,
+                                                                                                      &gvt_credential_pub_key,
+                                                                                                      &gvt_credential_priv_key).unwrap();
+
+        // 3. Prover processes GVT credential
+        Prover::process_credential_signature(&mut gvt_credential_signature,
+                                             &gvt_credential_values,
+                                             &gvt_signature_correctness_proof,
+                                             &gvt_credential_secrets_blinding_factors,
+                                             &gvt_credential_pub_key,
+                                             &gvt_credential_issuance_nonce).unwrap();
+
+        // 4. Issuer creates and signs XYZ credential for Prover
+        let xyz_credential_schema = helpers::xyz_credential_schema();
+        let (xyz_credential_pub_key, xyz_credential_priv_key, xyz_credential_key_correctness_proof) =
+            Issuer::new_credential_def(&xyz_credential_schema, &non_credential_schema).unwrap();
+
+        let xyz_credential_nonce = new_nonce().unwrap();
+        let xyz_credential_values = helpers::xyz_credential_values(&master_secret);
+
+        let (xyz_blinded_credential_secrets, xyz_credential_secrets_blinding_factors, xyz_blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&xyz_credential_pub_key,
+                                             &xyz_credential_key_correctness_proof,
+                                             &xyz_credential_values,
+                                             &xyz_credential_nonce).unwrap();
+
+        let xyz_credential_issuance_nonce = new_nonce().unwrap();
+
+        let (mut xyz_credential_signature, xyz_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                                      &xyz_blinded_credential_secrets,
+                                                                                                      &xyz_blinded_credential_secrets_correctness_proof,
+                                                                                                      &xyz_credential_nonce,
+                                                                                                      &xyz_credential_issuance_nonce,
+                                                                                                      &xyz_credential_values,
+                                                                                                      &xyz_credential_pub_key,
+                                                                                                      &xyz_credential_priv_key).unwrap();
+
+        // 5. Prover processes XYZ credential
+        Prover::process_credential_signature(&mut xyz_credential_signature,
+                                             &xyz_credential_values,
+                                             &xyz_signature_correctness_proof,
+                                             &xyz_credential_secrets_blinding_factors,
+                                             &xyz_credential_pub_key,
+                                             &xyz_credential_issuance_nonce).unwrap();
+        // 6. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 7. Verifier creates proof request which contains two sub proof requests: GVT and XYZ
+        let gvt_sub_proof_request = helpers::gvt_sub_proof_request();
+        let xyz_sub_proof_request = helpers::xyz_sub_proof_request();
+
+        // 8. Prover creates proof builder
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+
+        // 9. Prover adds GVT sub proof request
+        proof_builder.add_sub_proof_request(&gvt_sub_proof_request,
+                                            &gvt_credential_schema,
+                                            &non_credential_schema,
+                                            &gvt_credential_signature,
+                                            &gvt_credential_values,
+                                            GVT_KEY_ID,
+                                            &gvt_credential_pub_key).unwrap();
+
+        // 10. Prover adds XYZ sub proof request
+        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                            &xyz_credential_schema,
+                                            &non_credential_schema,
+                                            &xyz_credential_signature,
+                                            &xyz_credential_values,
+                                            XYZ_KEY_ID,
+                                            &xyz_credential_pub_key).unwrap();
+
+        // 11. Prover gets proof which contains sub proofs for GVT and XYZ sub proof requests
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof for GVT and XYZ sub proof requests
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
+                                             &gvt_credential_schema,
+                                             &non_credential_schema,
+                                             GVT_KEY_ID,
+                                             &gvt_credential_pub_key).unwrap();
+        proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
+                                             &xyz_credential_schema,
+                                             &non_credential_schema,
+                                             XYZ_KEY_ID,
+                                             &xyz_credential_pub_key).unwrap();
+
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_compact_proof_with_multiple_credentials() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Prover creates master secret
+        let master_secret = Prover::new_master_secret().unwrap();
+
+        let gvt_credential_values = helpers::gvt_credential_values(&master_secret);
+
+        // 2. Issuer creates and signs GVT credential for Prover
+        let gvt_credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (gvt_credential_pub_key, gvt_credential_priv_key, gvt_credential_key_correctness_proof) =
+            Issuer::new_credential_def(&gvt_credential_schema, &non_credential_schema).unwrap();
+
+        let gvt_credential_nonce = new_nonce().unwrap();
+
+        let (gvt_blinded_credential_secrets, gvt_credential_secrets_blinding_factors, gvt_blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&gvt_credential_pub_key,
+                                             &gvt_credential_key_correctness_proof,
+                                             &gvt_credential_values,
+                                             &gvt_credential_nonce).unwrap();
+
+        let gvt_credential_issuance_nonce = new_nonce().unwrap();
+
+        let (mut gvt_credential_signature, gvt_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                                      &gvt_blinded_credential_secrets,
+                                                                                                      &gvt_blinded_credential_secrets_correctness_proof,
+                                                                                                      &gvt_credential_nonce,
+                                                                                                      &gvt_credential_issuance_nonce,
+                                                                                                      &gvt_credential_values,
+                                                                                                      &gvt_credential_pub_key,
+                                                                                                      &gvt_credential_priv_key).unwrap();
+
+        // 3. Prover processes GVT credential
+        Prover::process_credential_signature(&mut gvt_credential_signature,
+                                             &gvt_credential_values,
+                                             &gvt_signature_correctness_proof,
+                                             &gvt_credential_secrets_blinding_factors,
+                                             &gvt_credential_pub_key,
+                                             &gvt_credential_issuance_nonce).unwrap();
+
+        // 4. Issuer creates and signs XYZ credential for Prover
+        let xyz_credential_schema = helpers::xyz_credential_schema();
+        let (xyz_credential_pub_key, xyz_credential_priv_key, xyz_credential_key_correctness_proof) =
+            Issuer::new_credential_def(&xyz_credential_schema, &non_credential_schema).unwrap();
+
+        let xyz_credential_nonce = new_nonce().unwrap();
+        let xyz_credential_values = helpers::xyz_credential_values(&master_secret);
+
+        let (xyz_blinded_credential_secrets, xyz_credential_secrets_blinding_factors, xyz_blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&xyz_credential_pub_key,
+                                             &xyz_credential_key_correctness_proof,
+                                             &xyz_credential_values,
+                                             &xyz_credential_nonce).unwrap();
+
+        let xyz_credential_issuance_nonce = new_nonce().unwrap();
+
+        let (mut xyz_credential_signature, xyz_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                                      &xyz_blinded_credential_secrets,
+                                                                                                      &xyz_blinded_credential_secrets_correctness_proof,
+                                                                                                      &xyz_credential_nonce,
+                                                                                                      &xyz_credential_issuance_nonce,
+                                                                                                      &xyz_credential_values,
+                                                                                                      &xyz_credential_pub_key,
+                                                                                                      &xyz_credential_priv_key).unwrap();
+
+        // 5. Prover processes XYZ credential
+        Prover::process_credential_signature(&mut xyz_credential_signature,
+                                             &xyz_credential_values,
+                                             &xyz_signature_correctness_proof,
+                                             &xyz_credential_secrets_blinding_factors,
+                                             &xyz_credential_pub_key,
+                                             &xyz_credential_issuance_nonce).unwrap();
+        // 6. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 7. Verifier creates proof request which contains two sub proof requests: GVT and XYZ
+        let gvt_sub_proof_request = helpers::gvt_sub_proof_request();
+        let xyz_sub_proof_request = helpers::xyz_sub_proof_request();
+
+        // 8. Prover creates proof builder
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+
+        // 9. Prover adds GVT sub proof request
+        proof_builder.add_sub_proof_request(&gvt_sub_proof_request,
+                                            &gvt_credential_schema,
+                                            &non_credential_schema,
+                                            &gvt_credential_signature,
+                                            &gvt_credential_values,
+                                            GVT_KEY_ID,
+                                            &gvt_credential_pub_key).unwrap();
+
+        // 10. Prover adds XYZ sub proof request
+        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                            &xyz_credential_schema,
+                                            &non_credential_schema,
+                                            &xyz_credential_signature,
+                                            &xyz_credential_values,
+                                            XYZ_KEY_ID,
+                                            &xyz_credential_pub_key).unwrap();
+
+        // 11. Prover gets proof which contains sub proofs for GVT and XYZ sub proof requests
+        let proof = proof_builder.finalize(&nonce).unwrap();
+        let proof_json = serde_json::to_string(&proof).unwrap();
+
+        // 12. Prover compacts proof: master_secret m-value is stored once for both sub proofs
+        let compact_proof = proof.compact().unwrap();
+        let compact_proof_json = serde_json::to_string(&compact_proof).unwrap();
+
+        assert!(compact_proof.is_compact());
+        assert!(compact_proof_json.len() < proof_json.len());
+
+        let compact_proof: indy_crypto::cl::Proof = serde_json::from_str(&compact_proof_json).unwrap();
+
+        // 13. Verifier verifies compact proof for GVT and XYZ sub proof requests
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
+                                             &gvt_credential_schema,
+                                             &non_credential_schema,
+                                             GVT_KEY_ID,
+                                             &gvt_credential_pub_key).unwrap();
+        proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
+                                             &xyz_credential_schema,
+                                             &non_credential_schema,
+                                             XYZ_KEY_ID,
+                                             &xyz_credential_pub_key).unwrap();
+
+        assert!(proof_verifier.verify(&compact_proof, &nonce).unwrap());
+
+        // 14. Expanded proof is equal to the original one
+        let expanded_proof = compact_proof.expand().unwrap();
+        assert!(!expanded_proof.is_compact());
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&proof_json).unwrap(), serde_json::to_value(&expanded_proof).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_attribute_equality_across_credentials() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        assert!(helpers::prove_and_verify_ssn_equality("123456789", "123456789"));
+    }
+
+    #[test]
+    fn anoncreds_works_for_attribute_equality_across_credentials_with_different_values() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        assert!(!helpers::prove_and_verify_ssn_equality("123456789", "987654321"));
+    }
+
+    #[test]
+    fn anoncreds_works_for_credentials_linked_by_custom_link_secret() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let link_secret = Prover::new_master_secret().unwrap();
+        assert!(helpers::prove_and_verify_link_secret_linkage(&link_secret, &link_secret));
+    }
+
+    #[test]
+    fn anoncreds_works_for_credentials_linked_by_custom_link_secret_with_different_values() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let first_link_secret = Prover::new_master_secret().unwrap();
+        let second_link_secret = Prover::new_master_secret().unwrap();
+        assert!(!helpers::prove_and_verify_link_secret_linkage(&first_link_secret, &second_link_secret));
+    }
+
+    #[test]
+    fn anoncreds_works_for_recomputed_challenge() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+
+        assert_eq!(proof.challenge(), &proof_verifier.recompute_challenge(&proof, &nonce).unwrap());
+        assert_ne!(proof.challenge(), &proof_verifier.recompute_challenge(&proof, &new_nonce().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_cleared_proof_builder() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let master_secret = Prover::new_master_secret().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let gvt_credential_schema = helpers::gvt_credential_schema();
+        let gvt_credential_values = helpers::gvt_credential_values(&master_secret);
+        let (gvt_credential_pub_key, gvt_credential_signature) =
+            helpers::issue_credential(&gvt_credential_schema, &non_credential_schema, &gvt_credential_values);
+
+        let xyz_credential_schema = helpers::xyz_credential_schema();
+        let xyz_credential_values = helpers::xyz_credential_values(&master_secret);
+        let (xyz_credential_pub_key, xyz_credential_signature) =
+            helpers::issue_credential(&xyz_credential_schema, &non_credential_schema, &xyz_credential_values);
+
+        let gvt_sub_proof_request = helpers::gvt_sub_proof_request();
+        let xyz_sub_proof_request = helpers::xyz_sub_proof_request();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+
+        // First proof is built for GVT credential
+        let gvt_nonce = new_nonce().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&gvt_sub_proof_request,
+                                            &gvt_credential_schema,
+                                            &non_credential_schema,
+                                            &gvt_credential_signature,
+                                            &gvt_credential_values,
+                                            KEY_ID,
+                                            &gvt_credential_pub_key).unwrap();
+        let gvt_proof = proof_builder.finalize(&gvt_nonce).unwrap();
+
+        // Second proof is built for XYZ credential by the same builder after clear
+        proof_builder.clear();
+
+        let xyz_nonce = new_nonce().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                            &xyz_credential_schema,
+                                            &non_credential_schema,
+                                            &xyz_credential_signature,
+                                            &xyz_credential_values,
+                                            KEY_ID,
+                                            &xyz_credential_pub_key).unwrap();
+        let xyz_proof = proof_builder.finalize(&xyz_nonce).unwrap();
+
+        let mut gvt_proof_verifier = Verifier::new_proof_verifier().unwrap();
+        gvt_proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
+                                                 &gvt_credential_schema,
+                                                 &non_credential_schema,
+                                                 KEY_ID,
+                                                 &gvt_credential_pub_key).unwrap();
+        assert!(gvt_proof_verifier.verify(&gvt_proof, &gvt_nonce).unwrap());
+
+        let mut xyz_proof_verifier = Verifier::new_proof_verifier().unwrap();
+        xyz_proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
+                                                 &xyz_credential_schema,
+                                                 &non_credential_schema,
+                                                 KEY_ID,
+                                                 &xyz_credential_pub_key).unwrap();
+        assert!(xyz_proof_verifier.verify(&xyz_proof, &xyz_nonce).unwrap());
+    }
+
+    #[cfg(feature = "proof_builder_snapshot")]
+    #[test]
+    fn anoncreds_works_for_resumed_proof_builder_snapshot() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+
+        let snapshot_json = proof_builder.to_snapshot_json().unwrap();
+
+        let nonce = new_nonce().unwrap();
+
+        let proof_builder = indy_crypto::cl::prover::ProofBuilder::from_snapshot_json(&snapshot_json).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // Second restore of the same snapshot would allow to finalize its init proofs with other nonce
+        let res = indy_crypto::cl::prover::ProofBuilder::from_snapshot_json(&snapshot_json);
+        assert_eq!(ErrorCode::CommonInvalidState, res.unwrap_err().to_error_code());
+    }
+
+    #[cfg(feature = "proof_builder_snapshot")]
+    #[test]
+    fn anoncreds_works_for_proof_builder_snapshot_of_other_version() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&helpers::gvt_sub_proof_request(),
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+
+        let snapshot_json = proof_builder.to_snapshot_json().unwrap();
+        assert!(snapshot_json.starts_with("{\"ver\":1,"));
+
+        let res = indy_crypto::cl::prover::ProofBuilder::from_snapshot_json(&snapshot_json.replacen("{\"ver\":1,", "{\"ver\":2,", 1));
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_sub_proofs_matched_by_key_id() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let master_secret = Prover::new_master_secret().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // Two issuers issue GVT and XYZ credentials with their own keys
+        let gvt_credential_schema = helpers::gvt_credential_schema();
+        let gvt_credential_values = helpers::gvt_credential_values(&master_secret);
+        let (gvt_credential_pub_key, gvt_credential_signature) =
+            helpers::issue_credential(&gvt_credential_schema, &non_credential_schema, &gvt_credential_values);
+
+        let xyz_credential_schema = helpers::xyz_credential_schema();
+        let xyz_credential_values = helpers::xyz_credential_values(&master_secret);
+        let (xyz_credential_pub_key, xyz_credential_signature) =
+            helpers::issue_credential(&xyz_credential_schema, &non_credential_schema, &xyz_credential_values);
+
+        let gvt_sub_proof_request = helpers::gvt_sub_proof_request();
+        let xyz_sub_proof_request = helpers::xyz_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&gvt_sub_proof_request,
+                                            &gvt_credential_schema,
+                                            &non_credential_schema,
+                                            &gvt_credential_signature,
+                                            &gvt_credential_values,
+                                            GVT_KEY_ID,
+                                            &gvt_credential_pub_key).unwrap();
+        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                            &xyz_credential_schema,
+                                            &non_credential_schema,
+                                            &xyz_credential_signature,
+                                            &xyz_credential_values,
+                                            XYZ_KEY_ID,
+                                            &xyz_credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        assert_eq!(vec![GVT_KEY_ID, XYZ_KEY_ID], proof.key_ids().map(|key_id| key_id.as_str()).collect::<Vec<&str>>());
+
+        // Verifier adds keys in its own order, sub proofs are matched to keys by key id
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
+                                             &xyz_credential_schema,
+                                             &non_credential_schema,
+                                             XYZ_KEY_ID,
+                                             &xyz_credential_pub_key).unwrap();
+        proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
+                                             &gvt_credential_schema,
+                                             &non_credential_schema,
+                                             GVT_KEY_ID,
+                                             &gvt_credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // Verifier doesn't know key of one of sub proofs
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
+                                             &gvt_credential_schema,
+                                             &non_credential_schema,
+                                             GVT_KEY_ID,
+                                             &gvt_credential_pub_key).unwrap();
+        proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
+                                             &xyz_credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &xyz_credential_pub_key).unwrap();
+        assert_eq!(ErrorCode::AnoncredsProofRejected, proof_verifier.verify(&proof, &nonce).unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_proof_bytes() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let proof_bytes = proof.to_bytes().unwrap();
+        assert!(proof_bytes.len() < serde_json::to_string(&proof).unwrap().len());
+
+        let restored_proof = Proof::from_bytes(&proof_bytes).unwrap();
+        assert_eq!(proof, restored_proof);
+
+        // Bytes of other layout version are rejected instead of being misread
+        let mut other_layout_bytes = proof_bytes.clone();
+        other_layout_bytes[0] += 1;
+        assert_eq!(ErrorCode::CommonInvalidStructure, Proof::from_bytes(&other_layout_bytes).unwrap_err().to_error_code());
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&restored_proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_prepared_public_key_reused_in_many_verifications() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        const VERIFICATIONS: u32 = 20;
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // Raw key: derived values are computed for each verifier
+        let mut raw_key_challenges = Vec::new();
+        for _ in 0..VERIFICATIONS {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 KEY_ID,
+                                                 &credential_pub_key).unwrap();
+            assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+            raw_key_challenges.push(proof_verifier.recompute_challenge(&proof, &nonce).unwrap());
+        }
+
+        // Prepared key: derived values and exponentiation tables are computed once and reused
+        let prepared_pub_key = PreparedPublicKey::new(&credential_pub_key).unwrap();
+        let mut prepared_key_challenges = Vec::new();
+        for _ in 0..VERIFICATIONS {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 KEY_ID,
+                                                 &prepared_pub_key).unwrap();
+            assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+            prepared_key_challenges.push(proof_verifier.recompute_challenge(&proof, &nonce).unwrap());
+        }
+
+        assert_eq!(raw_key_challenges, prepared_key_challenges);
+        assert!(prepared_key_challenges.iter().all(|challenge| challenge == proof.challenge()));
+    }
+
+    #[test]
+    fn anoncreds_works_for_malformed_predicate_proof() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        let malformed_proof = |path: &str, value: serde_json::Value| {
+            let mut proof_json = serde_json::to_value(&proof).unwrap();
+            *proof_json.pointer_mut(path).unwrap() = value;
+            serde_json::from_value::<Proof>(proof_json).unwrap()
+        };
+
+        // u value of four squares decomposition is too long
+        let too_long_u = BigNumber::from_dec("2").unwrap()
+            .exp(&BigNumber::from_dec("800").unwrap(), None).unwrap()
+            .to_dec().unwrap();
+        let proof_with_long_u = malformed_proof("/proofs/0/primary_proof/ne_proofs/0/u/0", serde_json::Value::String(too_long_u));
+        assert_eq!(ErrorCode::AnoncredsPredicateFailed, proof_verifier.verify(&proof_with_long_u, &nonce).unwrap_err().to_error_code());
+
+        // t value is out of range
+        let proof_with_zero_t = malformed_proof("/proofs/0/primary_proof/ne_proofs/0/t/DELTA", serde_json::Value::String("0".to_string()));
+        assert_eq!(ErrorCode::AnoncredsPredicateFailed, proof_verifier.verify(&proof_with_zero_t, &nonce).unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_c_list_not_correspond_to_tau_list() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        let tampered_proof = |tamper: &Fn(&mut Vec<serde_json::Value>)| {
+            let mut proof_json = serde_json::to_value(&proof).unwrap();
+            tamper(proof_json.pointer_mut("/aggregated_proof/c_list").unwrap().as_array_mut().unwrap());
+            serde_json::from_value::<Proof>(proof_json).unwrap()
+        };
+
+        // extra c_list element
+        let proof_with_extra_c = tampered_proof(&|c_list| {
+            let extra = c_list[0].clone();
+            c_list.push(extra)
+        });
+        match proof_verifier.verify(&proof_with_extra_c, &nonce) {
+            Err(IndyCryptoError::AnoncredsProofRejected(err)) => assert!(err.contains("c_list length")),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+
+        // c_list elements in wrong order
+        let proof_with_swapped_c = tampered_proof(&|c_list| c_list.swap(0, 1));
+        assert_eq!(ErrorCode::AnoncredsProofRejected, proof_verifier.verify(&proof_with_swapped_c, &nonce).unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_set_membership() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let allowed_heights = helpers::bn_list(&["165", "175", "185"]);
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_set_membership("height", &allowed_heights).unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let verify = |allowed_values: &[BigNumber], proof: &Proof| {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_set_membership("height", allowed_values).unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 KEY_ID,
+                                                 &credential_pub_key).unwrap();
+            proof_verifier.verify(proof, &nonce).unwrap()
+        };
+
+        assert!(verify(&allowed_heights[..], &proof));
+        assert!(verify(&allowed_heights[..], &Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap()));
+
+        // verifier expects another set
+        assert!(!verify(&helpers::bn_list(&["165", "180", "185"])[..], &proof));
+
+        // verifier doesn't expect set membership at all
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert_eq!(ErrorCode::AnoncredsProofRejected, proof_verifier.verify(&proof, &nonce).unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_set_membership_not_satisfied() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_set_membership("height", &helpers::bn_list(&["165", "185"])).unwrap();
+        let res = proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      KEY_ID,
+                                                      &credential_pub_key);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        // revealed attribute can't be proven to be in the set
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_set_membership("name", &helpers::bn_list(&["1139481716457488690172217916278103335"])).unwrap();
+        let res = proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      KEY_ID,
+                                                      &credential_pub_key);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_set_memberships_added_in_different_order() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let allowed_heights = helpers::bn_list(&["165", "175", "185"]);
+        let allowed_sexes = helpers::bn_list(&["5944657099558967239210949258394887428692050081607692519917050011144233115103"]);
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_set_membership("sex", &allowed_sexes).unwrap();
+        proof_builder.add_set_membership("height", &allowed_heights).unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // set membership proofs follow canonical order regardless of order of add_set_membership calls
+        let proof_json = serde_json::to_value(&proof).unwrap();
+        let set_membership_attrs = proof_json["proofs"][0]["primary_proof"]["set_membership_proofs"].as_array().unwrap().iter()
+            .map(|set_membership_proof| set_membership_proof["attr_name"].as_str().unwrap().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(vec!["height".to_string(), "sex".to_string()], set_membership_attrs);
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_set_membership("height", &allowed_heights).unwrap();
+        proof_verifier.add_set_membership("sex", &allowed_sexes).unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_sub_proof_requests_added_in_different_order() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let master_secret = Prover::new_master_secret().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let gvt_credential_schema = helpers::gvt_credential_schema();
+        let gvt_credential_values = helpers::gvt_credential_values(&master_secret);
+        let (gvt_credential_pub_key, gvt_credential_signature) =
+            helpers::issue_credential(&gvt_credential_schema, &non_credential_schema, &gvt_credential_values);
+        let gvt_sub_proof_request = helpers::gvt_sub_proof_request();
+
+        let xyz_credential_schema = helpers::xyz_credential_schema();
+        let xyz_credential_values = helpers::xyz_credential_values(&master_secret);
+        let (xyz_credential_pub_key, xyz_credential_signature) =
+            helpers::issue_credential(&xyz_credential_schema, &non_credential_schema, &xyz_credential_values);
+        let xyz_sub_proof_request = helpers::xyz_sub_proof_request();
+
+        let nonce = new_nonce().unwrap();
+
+        let prove = |order: &[&str]| {
+            let mut proof_builder = Prover::new_proof_builder().unwrap();
+            proof_builder.add_common_attribute("master_secret").unwrap();
+            for &key_id in order {
+                let (sub_proof_request, credential_schema, credential_signature, credential_values, credential_pub_key) = match key_id {
+                    GVT_KEY_ID => (&gvt_sub_proof_request, &gvt_credential_schema, &gvt_credential_signature, &gvt_credential_values, &gvt_credential_pub_key),
+                    _ => (&xyz_sub_proof_request, &xyz_credential_schema, &xyz_credential_signature, &xyz_credential_values, &xyz_credential_pub_key)
+                };
+                proof_builder.add_sub_proof_request(sub_proof_request,
+                                                    credential_schema,
+                                                    &non_credential_schema,
+                                                    credential_signature,
+                                                    credential_values,
+                                                    key_id,
+                                                    credential_pub_key).unwrap();
+            }
+            proof_builder.finalize(&nonce).unwrap()
+        };
+
+        let verify = |order: &[&str], proof: &Proof| {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_common_attribute("master_secret").unwrap();
+            for &key_id in order {
+                let (sub_proof_request, credential_schema, credential_pub_key) = match key_id {
+                    GVT_KEY_ID => (&gvt_sub_proof_request, &gvt_credential_schema, &gvt_credential_pub_key),
+                    _ => (&xyz_sub_proof_request, &xyz_credential_schema, &xyz_credential_pub_key)
+                };
+                proof_verifier.add_sub_proof_request(sub_proof_request,
+                                                     credential_schema,
+                                                     &non_credential_schema,
+                                                     key_id,
+                                                     credential_pub_key).unwrap();
+            }
+            proof_verifier.verify(proof, &nonce)
+        };
+
+        // sub proofs are ordered by index, so verifier has to add sub proof requests in the same order as prover
+        let gvt_xyz_proof = prove(&[GVT_KEY_ID, XYZ_KEY_ID]);
+        let xyz_gvt_proof = prove(&[XYZ_KEY_ID, GVT_KEY_ID]);
+
+        assert!(verify(&[GVT_KEY_ID, XYZ_KEY_ID], &gvt_xyz_proof).unwrap());
+        assert!(verify(&[XYZ_KEY_ID, GVT_KEY_ID], &xyz_gvt_proof).unwrap());
+
+        assert_eq!(ErrorCode::AnoncredsProofRejected, verify(&[XYZ_KEY_ID, GVT_KEY_ID], &gvt_xyz_proof).unwrap_err().to_error_code());
+        assert_eq!(ErrorCode::AnoncredsProofRejected, verify(&[GVT_KEY_ID, XYZ_KEY_ID], &xyz_gvt_proof).unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_eq_predicate() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::country_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::country_credential_values(&Prover::new_master_secret().unwrap(), "840");
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::country_sub_proof_request(840);
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // country is neither revealed nor proven by inequality proof
+        let proof_json = serde_json::to_value(&proof).unwrap();
+        let primary_proof_json = &proof_json["proofs"][0]["primary_proof"];
+        assert!(primary_proof_json["eq_proof"]["revealed_attrs"].get("country").is_none());
+        assert!(primary_proof_json["eq_proof"]["m"].get("country").is_none());
+        assert_eq!(0, primary_proof_json["ne_proofs"].as_array().unwrap().len());
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // verifier expecting other constant rejects the proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&helpers::country_sub_proof_request(826),
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(!proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_reading_proven_predicates() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&helpers::gvt_sub_proof_request(),
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&new_nonce().unwrap()).unwrap();
+
+        let predicates = proof.predicates();
+        assert_eq!(1, predicates.len());
+        assert_eq!(1, predicates[0].len());
+        assert_eq!("age", predicates[0][0].attr_name());
+        assert_eq!(&PredicateType::GE, predicates[0][0].p_type());
+        assert_eq!(18, predicates[0][0].value());
+    }
+
+    #[test]
+    fn anoncreds_works_for_proof_checked_against_another_request() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        Verifier::verify_matches_request(&proof, 0, &sub_proof_request, &credential_schema).unwrap();
+
+        // proof is valid, but answers less strict request
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 21).unwrap();
+        let another_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let res = Verifier::verify_matches_request(&proof, 0, &another_sub_proof_request, &credential_schema);
+        assert_eq!(ErrorCode::AnoncredsProofRejected, res.unwrap_err().to_error_code());
+
+        // proof doesn't reveal requested attribute
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_revealed_attr("sex").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let another_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let res = Verifier::verify_matches_request(&proof, 0, &another_sub_proof_request, &credential_schema);
+        assert_eq!(ErrorCode::AnoncredsProofRejected, res.unwrap_err().to_error_code());
+
+        // sub proof index is out of range
+        let res = Verifier::verify_matches_request(&proof, 1, &sub_proof_request, &credential_schema);
+        assert_eq!(ErrorCode::AnoncredsProofRejected, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_multiple_sub_proofs_checked_against_requests() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let master_secret = Prover::new_master_secret().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_public_attr("name").unwrap();
+        credential_schema_builder.add_attr("sex").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        credential_schema_builder.add_attr("height").unwrap();
+        let gvt_credential_schema = credential_schema_builder.finalize().unwrap();
+        let gvt_credential_values = helpers::gvt_credential_values(&master_secret);
+        let (gvt_credential_pub_key, gvt_credential_signature) =
+            helpers::issue_credential(&gvt_credential_schema, &non_credential_schema, &gvt_credential_values);
+
+        let xyz_credential_schema = helpers::xyz_credential_schema();
+        let xyz_credential_values = helpers::xyz_credential_values(&master_secret);
+        let (xyz_credential_pub_key, xyz_credential_signature) =
+            helpers::issue_credential(&xyz_credential_schema, &non_credential_schema, &xyz_credential_values);
+
+        let gvt_sub_proof_request = helpers::gvt_sub_proof_request();
+        let xyz_sub_proof_request = helpers::xyz_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&gvt_sub_proof_request,
+                                            &gvt_credential_schema,
+                                            &non_credential_schema,
+                                            &gvt_credential_signature,
+                                            &gvt_credential_values,
+                                            GVT_KEY_ID,
+                                            &gvt_credential_pub_key).unwrap();
+        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                            &xyz_credential_schema,
+                                            &non_credential_schema,
+                                            &xyz_credential_signature,
+                                            &xyz_credential_values,
+                                            XYZ_KEY_ID,
+                                            &xyz_credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        Verifier::verify_matches_request(&proof, 0, &gvt_sub_proof_request, &gvt_credential_schema).unwrap();
+        Verifier::verify_matches_request(&proof, 1, &xyz_sub_proof_request, &xyz_credential_schema).unwrap();
+
+        // each sub proof is checked against its own request only
+        let res = Verifier::verify_matches_request(&proof, 1, &gvt_sub_proof_request, &gvt_credential_schema);
+        assert_eq!(ErrorCode::AnoncredsProofRejected, res.unwrap_err().to_error_code());
+
+        // public attribute is expected to be revealed even if request omits it
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request_without_public_attr = sub_proof_request_builder.finalize().unwrap();
+
+        Verifier::verify_matches_request(&proof, 0, &sub_proof_request_without_public_attr, &gvt_credential_schema).unwrap();
+    }
+
+    #[test]
+    fn anoncreds_works_for_bitlength_predicate() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_bitlength_predicate("height", 31).unwrap();
+        sub_proof_request_builder.add_bitlength_predicate("age", 8).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_bitlength_predicate_not_satisfied() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        // height 175 doesn't fit in 7 bits
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_bitlength_predicate("height", 7).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        assert!(!Prover::can_satisfy(&sub_proof_request, &credential_schema, &credential_values).unwrap());
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        let res = proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      KEY_ID,
+                                                      &credential_pub_key);
+        assert_eq!(ErrorCode::AnoncredsPredicateNotSatisfied, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_eq_predicate_not_satisfied() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::country_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::country_credential_values(&Prover::new_master_secret().unwrap(), "826");
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::country_sub_proof_request(840);
+
+        assert!(!Prover::can_satisfy(&sub_proof_request, &credential_schema, &credential_values).unwrap());
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        let res = proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      KEY_ID,
+                                                      &credential_pub_key);
+        assert_eq!(ErrorCode::AnoncredsPredicateNotSatisfied, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_revealed_commitment() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+        let salt = Prover::new_commitment_salt().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_revealed_commitment("height", &salt).unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let restored_proof = Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+        assert_eq!(proof, restored_proof);
+
+        for proof in vec![proof, restored_proof] {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 KEY_ID,
+                                                 &credential_pub_key).unwrap();
+            assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+            let expected_commitment =
+                Verifier::calc_revealed_commitment(&credential_pub_key, &BigNumber::from_dec("175").unwrap(), &salt).unwrap();
+            assert_eq!(Some(&expected_commitment), proof.revealed_commitment(0, "height"));
+
+            let other_commitment =
+                Verifier::calc_revealed_commitment(&credential_pub_key, &BigNumber::from_dec("180").unwrap(), &salt).unwrap();
+            assert_ne!(Some(&other_commitment), proof.revealed_commitment(0, "height"));
+
+            assert!(proof.revealed_commitment(0, "sex").is_none());
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_revealed_commitment_of_revealed_attr() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_revealed_commitment("name", &Prover::new_commitment_salt().unwrap()).unwrap();
+        let res = proof_builder.add_sub_proof_request(&helpers::gvt_sub_proof_request(),
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      KEY_ID,
+                                                      &credential_pub_key);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_refreshed_signature_randomization() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, mut credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let credential_signature_json = serde_json::to_value(&credential_signature).unwrap();
 
-        let gvt_credential_values = helpers::gvt_credential_values(&master_secret);
+        Prover::refresh_signature_randomization(&mut credential_signature, &credential_pub_key).unwrap();
 
-        // 2. Issuer creates and signs GVT credential for Prover
-        let gvt_credential_schema = helpers::gvt_credential_schema();
-        let non_credential_schema = helpers::non_credential_schema();
-        let (gvt_credential_pub_key, gvt_credential_priv_key, gvt_credential_key_correctness_proof) =
-            Issuer::new_credential_def(&gvt_credential_schema, &non_credential_schema).unwrap();
+        let refreshed_credential_signature_json = serde_json::to_value(&credential_signature).unwrap();
+        assert_ne!(credential_signature_json["p_credential"]["v"], refreshed_credential_signature_json["p_credential"]["v"]);
+        assert_ne!(credential_signature_json["p_credential"]["a"], refreshed_credential_signature_json["p_credential"]["a"]);
 
-        let gvt_credential_nonce = new_nonce().unwrap();
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
 
-        let (gvt_blinded_credential_secrets, gvt_credential_secrets_blinding_factors, gvt_blinded_credential_secrets_correctness_proof) =
-            Prover::blind_credential_secrets(&gvt_credential_pub_key,
-                                             &gvt_credential_key_correctness_proof,
-                                             &gvt_credential_values,
-                                             &gvt_credential_nonce).unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
 
-        let gvt_credential_issuance_nonce = new_nonce().unwrap();
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             KEY_ID,
+                                             &credential_pub_key).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
 
-        let (mut gvt_credential_signature, gvt_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
-                                                                                                      &gvt_blinded_credential_secrets,
-                                                                                                      &gvt_blinded_credential_secrets_correctness_proof,
-                                                                                                      &gvt_credential_nonce,
-                                                                                                      &gvt_credential_issuance_nonce,
-                                                                                                      &gvt_credential_values,
-                                                                                                      &gvt_credential_pub_key,
-                                                                                                      &gvt_credential_priv_key).unwrap();
+    #[test]
+    fn prover_verify_credential_works() {
+        IndyCryptoDefaultLogger::init(None).ok();
 
-        // 3. Prover processes GVT credential
-        Prover::process_credential_signature(&mut gvt_credential_signature,
-                                             &gvt_credential_values,
-                                             &gvt_signature_correctness_proof,
-                                             &gvt_credential_secrets_blinding_factors,
-                                             &gvt_credential_pub_key,
-                                             &gvt_credential_issuance_nonce).unwrap();
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
 
-        // 4. Issuer creates and signs XYZ credential for Prover
-        let xyz_credential_schema = helpers::xyz_credential_schema();
-        let (xyz_credential_pub_key, xyz_credential_priv_key, xyz_credential_key_correctness_proof) =
-            Issuer::new_credential_def(&xyz_credential_schema, &non_credential_schema).unwrap();
+        assert!(Prover::verify_credential(&credential_signature, &credential_values, &credential_pub_key).unwrap());
+    }
 
-        let xyz_credential_nonce = new_nonce().unwrap();
-        let xyz_credential_values = helpers::xyz_credential_values(&master_secret);
+    #[test]
+    fn prover_verify_credential_works_for_tampered_attr_value() {
+        IndyCryptoDefaultLogger::init(None).ok();
 
-        let (xyz_blinded_credential_secrets, xyz_credential_secrets_blinding_factors, xyz_blinded_credential_secrets_correctness_proof) =
-            Prover::blind_credential_secrets(&xyz_credential_pub_key,
-                                             &xyz_credential_key_correctness_proof,
-                                             &xyz_credential_values,
-                                             &xyz_credential_nonce).unwrap();
+        let master_secret = Prover::new_master_secret().unwrap();
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&master_secret);
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
 
-        let xyz_credential_issuance_nonce = new_nonce().unwrap();
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_known("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("age", "18").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let tampered_credential_values = credential_values_builder.finalize().unwrap();
 
-        let (mut xyz_credential_signature, xyz_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
-                                                                                                      &xyz_blinded_credential_secrets,
-                                                                                                      &xyz_blinded_credential_secrets_correctness_proof,
-                                                                                                      &xyz_credential_nonce,
-                                                                                                      &xyz_credential_issuance_nonce,
-                                                                                                      &xyz_credential_values,
-                                                                                                      &xyz_credential_pub_key,
-                                                                                                      &xyz_credential_priv_key).unwrap();
+        assert!(!Prover::verify_credential(&credential_signature, &tampered_credential_values, &credential_pub_key).unwrap());
+    }
 
-        // 5. Prover processes XYZ credential
-        Prover::process_credential_signature(&mut xyz_credential_signature,
-                                             &xyz_credential_values,
-                                             &xyz_signature_correctness_proof,
-                                             &xyz_credential_secrets_blinding_factors,
-                                             &xyz_credential_pub_key,
-                                             &xyz_credential_issuance_nonce).unwrap();
-        // 6. Verifier creates nonce
+    #[test]
+    fn anoncreds_works_for_predicate_security_levels() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
         let nonce = new_nonce().unwrap();
 
-        // 7. Verifier creates proof request which contains two sub proof requests: GVT and XYZ
-        let gvt_sub_proof_request = helpers::gvt_sub_proof_request();
-        let xyz_sub_proof_request = helpers::xyz_sub_proof_request();
+        let prove_and_verify = |prover_level: u32, verifier_level: u32| {
+            let mut proof_builder = Prover::new_proof_builder().unwrap();
+            proof_builder.set_predicate_security(prover_level).unwrap();
+            proof_builder.add_common_attribute("master_secret").unwrap();
+            proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                &credential_schema,
+                                                &non_credential_schema,
+                                                &credential_signature,
+                                                &credential_values,
+                                                KEY_ID,
+                                                &credential_pub_key).unwrap();
+            let proof = proof_builder.finalize(&nonce).unwrap();
+
+            // level is written to the proof, so changing it must break verification
+            let mut proof_json = serde_json::to_value(&proof).unwrap();
+            proof_json["predicate_security"] = serde_json::Value::from(if prover_level == 1 { 2 } else { 1 });
+            let tampered_proof: Proof = serde_json::from_value(proof_json).unwrap();
+
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.set_min_predicate_security(verifier_level).unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 KEY_ID,
+                                                 &credential_pub_key).unwrap();
+            assert!(!proof_verifier.verify(&tampered_proof, &nonce).unwrap_or(false));
+            proof_verifier.verify(&proof, &nonce).unwrap()
+        };
+
+        assert!(prove_and_verify(1, 1));
+        assert!(prove_and_verify(2, 1));
+        assert!(prove_and_verify(2, 2));
+        assert!(!prove_and_verify(1, 2));
 
-        // 8. Prover creates proof builder
         let mut proof_builder = Prover::new_proof_builder().unwrap();
-        proof_builder.add_common_attribute("master_secret").unwrap();
+        assert!(proof_builder.set_predicate_security(3).is_err());
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        assert!(proof_verifier.set_min_predicate_security(0).is_err());
+        assert!(proof_verifier.set_min_predicate_security(3).is_err());
+    }
 
-        // 9. Prover adds GVT sub proof request
-        proof_builder.add_sub_proof_request(&gvt_sub_proof_request,
-                                            &gvt_credential_schema,
-                                            &non_credential_schema,
-                                            &gvt_credential_signature,
-                                            &gvt_credential_values,
-                                            &gvt_credential_pub_key).unwrap();
+    #[test]
+    fn anoncreds_works_for_required_revealed_attrs() {
+        IndyCryptoDefaultLogger::init(None).ok();
 
-        // 10. Prover adds XYZ sub proof request
-        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
-                                            &xyz_credential_schema,
-                                            &non_credential_schema,
-                                            &xyz_credential_signature,
-                                            &xyz_credential_values,
-                                            &xyz_credential_pub_key).unwrap();
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let (credential_pub_key, credential_signature) =
+            helpers::issue_credential(&credential_schema, &non_credential_schema, &credential_values);
 
-        // 11. Prover gets proof which contains sub proofs for GVT and XYZ sub proof requests
-        let proof = proof_builder.finalize(&nonce).unwrap();
+        // GVT sub proof request reveals only `name`, `age` is proved by predicate
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
 
-        // 12. Verifier verifies proof for GVT and XYZ sub proof requests
-        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
-        proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
-                                             &gvt_credential_schema,
-                                             &non_credential_schema,
-                                             &gvt_credential_pub_key).unwrap();
-        proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
-                                             &xyz_credential_schema,
-                                             &non_credential_schema,
-                                             &xyz_credential_pub_key).unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            KEY_ID,
+                                            &credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
 
-        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+        let verify = |required_revealed_attrs: &[&str]| {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.require_revealed(required_revealed_attrs).unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 KEY_ID,
+                                                 &credential_pub_key).unwrap();
+            proof_verifier.verify(&proof, &nonce).unwrap()
+        };
+
+        assert!(verify(&["name"]));
+        assert!(!verify(&["name", "age"]));
+        assert!(!verify(&["sex"]));
     }
 
     #[test]
@@ -421,6 +2071,7 @@ mod test {
                                             &non_credential_schema,
                                             &credential_signature,
                                             &credential_values,
+                                            KEY_ID,
                                             &credential_pub_key).unwrap();
         let proof = proof_builder.finalize(&nonce).unwrap();
 
@@ -429,6 +2080,7 @@ mod test {
         proof_verifier.add_sub_proof_request(&sub_proof_request,
                                              &credential_schema,
                                              &non_credential_schema,
+                                             KEY_ID,
                                              &credential_pub_key).unwrap();
         assert_eq!(false, proof_verifier.verify(&proof, &nonce).unwrap());
     }
@@ -494,6 +2146,7 @@ mod test {
                                             &non_credential_schema,
                                             &credential_signature,
                                             &credential_values,
+                                            KEY_ID,
                                             &credential_pub_key).unwrap();
 
 
@@ -504,6 +2157,7 @@ mod test {
         proof_verifier.add_sub_proof_request(&sub_proof_request,
                                              &credential_schema,
                                              &non_credential_schema,
+                                             KEY_ID,
                                              &credential_pub_key).unwrap();
         assert_eq!(false, proof_verifier.verify(&proof, &nonce).unwrap());
     }
@@ -567,6 +2221,7 @@ mod test {
                                             &non_credential_schema,
                                             &credential_signature,
                                             &credential_values,
+                                            KEY_ID,
                                             &credential_pub_key).unwrap();
 
         let proof = proof_builder.finalize(&nonce_for_proof_creation).unwrap();
@@ -578,6 +2233,7 @@ mod test {
         proof_verifier.add_sub_proof_request(&sub_proof_request,
                                              &credential_schema,
                                              &non_credential_schema,
+                                             KEY_ID,
                                              &credential_pub_key).unwrap();
         assert_eq!(false, proof_verifier.verify(&proof, &nonce_for_proof_verification).unwrap());
     }
@@ -640,6 +2296,7 @@ mod test {
                                             &non_credential_schema,
                                             &credential_signature,
                                             &credential_values,
+                                            KEY_ID,
                                             &credential_pub_key).unwrap();
         let proof = proof_builder.finalize(&nonce).unwrap();
 
@@ -652,6 +2309,7 @@ mod test {
         proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
                                              &xyz_credential_schema,
                                              &non_credential_schema,
+                                             XYZ_KEY_ID,
                                              &xyz_credential_pub_key).unwrap();
         let res = proof_verifier.verify(&proof, &nonce);
         assert_eq!(ErrorCode::AnoncredsProofRejected, res.unwrap_err().to_error_code());
@@ -773,6 +2431,7 @@ mod test {
                                                       &non_credential_schema,
                                                       &credential_signature,
                                                       &credential_values,
+                                                      KEY_ID,
                                                       &credential_pub_key);
 
         assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
@@ -836,6 +2495,7 @@ mod test {
                                                       &non_credential_schema,
                                                       &credential_signature,
                                                       &credential_values,
+                                                      KEY_ID,
                                                       &credential_pub_key);
         assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
     }
@@ -900,6 +2560,7 @@ mod test {
                                                       &non_credential_schema,
                                                       &credential_signature,
                                                       &credential_values,
+                                                      KEY_ID,
                                                       &credential_pub_key);
         assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
     }
@@ -965,8 +2626,9 @@ mod test {
                                                       &non_credential_schema,
                                                       &credential_signature,
                                                       &credential_values,
+                                                      KEY_ID,
                                                       &credential_pub_key);
-        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+        assert_eq!(ErrorCode::AnoncredsPredicateNotSatisfied, res.unwrap_err().to_error_code());
     }
 
     #[test]
@@ -989,6 +2651,7 @@ mod test {
         let res = proof_verifier.add_sub_proof_request(&sub_proof_request,
                                                        &xyz_credential_schema,
                                                        &non_credential_schema,
+                                                       KEY_ID,
                                                        &credential_pub_key);
         assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
     }
@@ -1194,7 +2857,7 @@ mod test {
                                                        &credential_secrets_blinding_factors,
                                                        &credential_pub_key,
                                                        &credential_issuance_nonce);
-        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+        assert_eq!(ErrorCode::AnoncredsCredentialSignatureInvalid, res.unwrap_err().to_error_code());
     }
 
     #[test]
@@ -1253,7 +2916,7 @@ mod test {
                                                        &credential_secrets_blinding_factors,
                                                        &credential_pub_key,
                                                        &credential_issuance_nonce);
-        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+        assert_eq!(ErrorCode::AnoncredsCredentialSignatureInvalid, res.unwrap_err().to_error_code());
     }
 
     #[test]
@@ -1304,7 +2967,7 @@ mod test {
                                                        &credential_secrets_blinding_factors,
                                                        &credential_pub_key,
                                                        &credential_issuance_nonce);
-        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+        assert_eq!(ErrorCode::AnoncredsCredentialSignatureInvalid, res.unwrap_err().to_error_code());
     }
 
     #[test]
@@ -1353,7 +3016,7 @@ mod test {
                                                        &credential_secrets_blinding_factors,
                                                        &credential_pub_key,
                                                        &other_nonce);
-        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+        assert_eq!(ErrorCode::AnoncredsCredentialSignatureInvalid, res.unwrap_err().to_error_code());
     }
 }
 
@@ -1408,11 +3071,210 @@ mod helpers {
         gvt_sub_proof_request_builder.finalize().unwrap()
     }
 
+    pub fn bn_list(values: &[&str]) -> Vec<BigNumber> {
+        values.iter().map(|value| BigNumber::from_dec(value).unwrap()).collect()
+    }
+
     pub fn xyz_sub_proof_request() -> SubProofRequest {
         let mut xyz_sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
         xyz_sub_proof_request_builder.add_revealed_attr("status").unwrap();
         xyz_sub_proof_request_builder.add_predicate("period", "GE", 4).unwrap();
         xyz_sub_proof_request_builder.finalize().unwrap()
     }
-}
 
+    pub fn country_credential_schema() -> CredentialSchema {
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.add_attr("country").unwrap();
+        credential_schema_builder.finalize().unwrap()
+    }
+
+    pub fn country_credential_values(master_secret: &MasterSecret, country: &str) -> CredentialValues {
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_known("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("country", country).unwrap();
+        credential_values_builder.finalize().unwrap()
+    }
+
+    pub fn country_sub_proof_request(country: i32) -> SubProofRequest {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("country", "EQ", country).unwrap();
+        sub_proof_request_builder.finalize().unwrap()
+    }
+
+    pub fn ssn_credential_schema(attr: &str) -> CredentialSchema {
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr(attr).unwrap();
+        credential_schema_builder.add_attr("ssn").unwrap();
+        credential_schema_builder.finalize().unwrap()
+    }
+
+    pub fn ssn_credential_values(master_secret: &MasterSecret, attr: &str, ssn: &str) -> CredentialValues {
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_known("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_dec_known(attr, "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("ssn", ssn).unwrap();
+        credential_values_builder.finalize().unwrap()
+    }
+
+    pub fn issue_credential(credential_schema: &CredentialSchema,
+                            non_credential_schema: &NonCredentialSchema,
+                            credential_values: &CredentialValues) -> (CredentialPublicKey, CredentialSignature) {
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(credential_schema, non_credential_schema).unwrap();
+
+        let credential_nonce = new_nonce().unwrap();
+
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             credential_values,
+                                             &credential_nonce).unwrap();
+
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce).unwrap();
+
+        (credential_pub_key, credential_signature)
+    }
+
+    pub fn ssn_sub_proof_request(attr: &str) -> SubProofRequest {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr(attr).unwrap();
+        sub_proof_request_builder.finalize().unwrap()
+    }
+
+    pub fn prove_and_verify_ssn_equality(first_ssn: &str, second_ssn: &str) -> bool {
+        let master_secret = Prover::new_master_secret().unwrap();
+        let non_credential_schema = non_credential_schema();
+        let (first_key_id, second_key_id) = ("NcYxiDXkpYi6ov5FcYDi1e:3:CL:name", "NcYxiDXkpYi6ov5FcYDi1e:3:CL:status");
+
+        let first_credential_schema = ssn_credential_schema("name");
+        let first_credential_values = ssn_credential_values(&master_secret, "name", first_ssn);
+        let (first_credential_pub_key, first_credential_signature) =
+            issue_credential(&first_credential_schema, &non_credential_schema, &first_credential_values);
+        let first_sub_proof_request = ssn_sub_proof_request("name");
+
+        let second_credential_schema = ssn_credential_schema("status");
+        let second_credential_values = ssn_credential_values(&master_secret, "status", second_ssn);
+        let (second_credential_pub_key, second_credential_signature) =
+            issue_credential(&second_credential_schema, &non_credential_schema, &second_credential_values);
+        let second_sub_proof_request = ssn_sub_proof_request("status");
+
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_attribute_equality(0, 1, "ssn").unwrap();
+        proof_builder.add_sub_proof_request(&first_sub_proof_request,
+                                            &first_credential_schema,
+                                            &non_credential_schema,
+                                            &first_credential_signature,
+                                            &first_credential_values,
+                                            first_key_id,
+                                            &first_credential_pub_key).unwrap();
+        proof_builder.add_sub_proof_request(&second_sub_proof_request,
+                                            &second_credential_schema,
+                                            &non_credential_schema,
+                                            &second_credential_signature,
+                                            &second_credential_values,
+                                            second_key_id,
+                                            &second_credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_attribute_equality(0, 1, "ssn").unwrap();
+        proof_verifier.add_sub_proof_request(&first_sub_proof_request,
+                                             &first_credential_schema,
+                                             &non_credential_schema,
+                                             first_key_id,
+                                             &first_credential_pub_key).unwrap();
+        proof_verifier.add_sub_proof_request(&second_sub_proof_request,
+                                             &second_credential_schema,
+                                             &non_credential_schema,
+                                             second_key_id,
+                                             &second_credential_pub_key).unwrap();
+        proof_verifier.verify(&proof, &nonce).unwrap()
+    }
+
+    pub fn link_secret_non_credential_schema() -> NonCredentialSchema {
+        let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+        non_credential_schema_builder.add_attr("link_secret").unwrap();
+        non_credential_schema_builder.finalize().unwrap()
+    }
+
+    pub fn link_secret_credential_values(link_secret: &MasterSecret, name: &str) -> CredentialValues {
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_known("link_secret", &link_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_dec_known(name, "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("ssn", "123456789").unwrap();
+        credential_values_builder.finalize().unwrap()
+    }
+
+    pub fn prove_and_verify_link_secret_linkage(first_link_secret: &MasterSecret, second_link_secret: &MasterSecret) -> bool {
+        let non_credential_schema = link_secret_non_credential_schema();
+        let (first_key_id, second_key_id) = ("NcYxiDXkpYi6ov5FcYDi1e:3:CL:name", "NcYxiDXkpYi6ov5FcYDi1e:3:CL:status");
+
+        let first_credential_schema = ssn_credential_schema("name");
+        let first_credential_values = link_secret_credential_values(first_link_secret, "name");
+        let (first_credential_pub_key, first_credential_signature) =
+            issue_credential(&first_credential_schema, &non_credential_schema, &first_credential_values);
+        let first_sub_proof_request = ssn_sub_proof_request("name");
+
+        let second_credential_schema = ssn_credential_schema("status");
+        let second_credential_values = link_secret_credential_values(second_link_secret, "status");
+        let (second_credential_pub_key, second_credential_signature) =
+            issue_credential(&second_credential_schema, &non_credential_schema, &second_credential_values);
+        let second_sub_proof_request = ssn_sub_proof_request("status");
+
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("link_secret").unwrap();
+        proof_builder.add_sub_proof_request(&first_sub_proof_request,
+                                            &first_credential_schema,
+                                            &non_credential_schema,
+                                            &first_credential_signature,
+                                            &first_credential_values,
+                                            first_key_id,
+                                            &first_credential_pub_key).unwrap();
+        proof_builder.add_sub_proof_request(&second_sub_proof_request,
+                                            &second_credential_schema,
+                                            &non_credential_schema,
+                                            &second_credential_signature,
+                                            &second_credential_values,
+                                            second_key_id,
+                                            &second_credential_pub_key).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_common_attribute("link_secret").unwrap();
+        proof_verifier.add_sub_proof_request(&first_sub_proof_request,
+                                             &first_credential_schema,
+                                             &non_credential_schema,
+                                             first_key_id,
+                                             &first_credential_pub_key).unwrap();
+        proof_verifier.add_sub_proof_request(&second_sub_proof_request,
+                                             &second_credential_schema,
+                                             &non_credential_schema,
+                                             second_key_id,
+                                             &second_credential_pub_key).unwrap();
+        proof_verifier.verify(&proof, &nonce).unwrap()
+    }
+}
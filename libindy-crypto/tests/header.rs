@@ -0,0 +1,32 @@
+// Needs C compiler, so it's run only with `header` feature: `cargo test --features header`.
+#![cfg(feature = "header")]
+
+extern crate indy_crypto;
+
+use indy_crypto::errors::ErrorCode;
+
+use std::env;
+use std::process::Command;
+
+/// Compiles stub C consumer against `include/*.h` to check that generated header is valid C
+/// and its `ErrorCode` values correspond to the library ones.
+/// C compiler is taken from `CC` environment variable (`cc` by default).
+#[test]
+fn c_header_compiles_against_stub() {
+    let compiler = env::var("CC").unwrap_or("cc".to_string());
+
+    let output = Command::new(&compiler)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-fsyntax-only")
+        .arg("-Iinclude")
+        .arg(format!("-DEXPECTED_SUCCESS={}", ErrorCode::Success as usize))
+        .arg(format!("-DEXPECTED_ANONCREDS_PREDICATE_NOT_SATISFIED={}", ErrorCode::AnoncredsPredicateNotSatisfied as usize))
+        .arg("tests/header/stub.c")
+        .output()
+        .expect(&format!("Unable to run C compiler {:?}", compiler));
+
+    assert!(output.status.success(), "C header check failed:\n{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,45 @@
+// Stub consumer of the library C headers. It is only compiled (see tests/header.rs),
+// so mismatch of declarations with expected signatures or error codes fails the build.
+
+#include "crypto.h"
+
+#define STATIC_CHECK(name, cond) typedef char static_check_##name[(cond) ? 1 : -1]
+
+STATIC_CHECK(success_code, Success == EXPECTED_SUCCESS);
+STATIC_CHECK(last_code, AnoncredsPredicateNotSatisfied == EXPECTED_ANONCREDS_PREDICATE_NOT_SATISFIED);
+STATIC_CHECK(error_code_size, sizeof(ErrorCode) == sizeof(void *));
+STATIC_CHECK(crypto_error_size, sizeof(crypto_error_t) == sizeof(ErrorCode));
+
+static bool stub_enabled(const void *context, uint32_t level, const char *target) {
+    (void) context; (void) level; (void) target;
+    return true;
+}
+
+static void stub_log(const void *context, uint32_t level, const char *target, const char *message,
+                     const char *module_path, const char *file, uint32_t line) {
+    (void) context; (void) level; (void) target; (void) message; (void) module_path; (void) file; (void) line;
+}
+
+ErrorCode stub_consumer(void) {
+    ErrorCode (*free_string)(const char *) = cl_free_string;
+    ErrorCode (*new_nonce)(const void **) = cl_new_nonce;
+    ErrorCode (*nonce_to_json)(const void *, const char **) = cl_nonce_to_json;
+    ErrorCode (*proof_from_bytes)(const uint8_t *, uintptr_t, const void **) = cl_proof_from_bytes;
+
+    const void *nonce = NULL;
+    const char *nonce_json = NULL;
+
+    ErrorCode res = new_nonce(&nonce);
+    if (res != Success) {
+        return res;
+    }
+
+    res = nonce_to_json(nonce, &nonce_json);
+    if (res == Success) {
+        res = free_string(nonce_json);
+    }
+
+    (void) proof_from_bytes;
+
+    return indy_crypto_set_logger(NULL, stub_enabled, stub_log, NULL);
+}